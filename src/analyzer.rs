@@ -3,17 +3,19 @@
 //! This module handles cross-file analysis including finding
 //! files that import a given file.
 
-use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use crate::parser::{analyze_file, FileAnalysis};
+use crate::cache::{analyze_cached, parse_cache, ParseCache};
+use crate::parser::ImportInfo;
+use crate::resolver::ImportResolver;
 use crate::utils::fs::walk_source_files;
 
 /// Check if an import path matches the target module name,
 /// using the appropriate separator for each language.
 ///
 /// - Rust (`.rs`): `::` separator
-/// - Python (`.py`): `.` separator
+/// - Python, Java, Kotlin, C#, Swift (`.py`, `.java`, `.kt`, `.cs`, `.swift`): `.` separator
+/// - Zig, Ruby (`.zig`, `.rb`): `/` separator, with the file extension ignored
 /// - JS/TS and others: `/` separator
 ///
 /// # Arguments
@@ -24,83 +26,191 @@ use crate::utils::fs::walk_source_files;
 /// # Returns
 /// `true` if any segment of the import path equals `target_module`
 fn path_matches_import(import_path: &str, target_module: &str, file_ext: &str) -> bool {
+    import_segments(import_path, file_ext).contains(&target_module)
+}
+
+/// Split an import path into the names it can refer to a file by
+///
+/// Uses the separator of the importing file's language (see
+/// [`path_matches_import`]).
+pub(crate) fn import_segments<'a>(import_path: &'a str, file_ext: &str) -> Vec<&'a str> {
     let separator = match file_ext {
         "rs" => "::",
-        "py" => ".",
+        "py" | "java" | "kt" | "cs" | "swift" => ".",
         _ => "/", // JS/TS and others
     };
-    let parts: Vec<&str> = import_path.split(separator).collect();
-    parts.iter().any(|&p| p == target_module)
+    let parts = import_path.split(separator);
+    if matches!(file_ext, "zig" | "rb") {
+        // `@import("codegen/emit.zig")` and `require "auth.rb"` may name the
+        // file with its extension
+        let suffix = format!(".{}", file_ext);
+        return parts
+            .map(|p| p.strip_suffix(suffix.as_str()).unwrap_or(p))
+            .collect();
+    }
+    parts.collect()
 }
 
-/// Find files that import the given file
+/// Name that importers use to refer to `file_path`.
+///
+/// Go imports whole packages, so a `.go` file is referred to by its
+/// directory name. Swift imports modules, so a `.swift` file under a
+/// SwiftPM `Sources/<Module>/` directory is referred to by its module name.
+/// Other languages use the file stem.
+pub(crate) fn import_target_name(file_path: &Path) -> &str {
+    let name = match file_path.extension().and_then(|e| e.to_str()) {
+        Some("go") => file_path.parent().and_then(|p| p.file_name()),
+        Some("swift") => swift_module_name(file_path).or_else(|| file_path.file_stem()),
+        _ => file_path.file_stem(),
+    };
+    name.and_then(|s| s.to_str()).unwrap_or("")
+}
+
+/// SwiftPM module of a file: the directory directly below `Sources/`
+fn swift_module_name(file_path: &Path) -> Option<&std::ffi::OsStr> {
+    let mut components = file_path.parent()?.components();
+    components.find(|c| c.as_os_str() == "Sources")?;
+    components.next().map(|c| c.as_os_str())
+}
+
+/// Find files that import the given file, resolving C/C++ includes
+/// against additional include directories.
 ///
 /// Walks the directory tree starting from `base_path` and finds
 /// all files that contain imports matching the target file name.
-/// Uses an internal cache to avoid re-parsing files.
+/// Files are parsed through the shared parse cache.
 ///
 /// # Arguments
 /// * `file_path` - The file to search for importers of
 /// * `base_path` - The root directory to search in
+/// * `include_dirs` - Include directories (relative ones are taken from `base_path`)
 ///
 /// # Returns
 /// A vector of file paths that import the target file
-pub fn find_importers(file_path: &Path, base_path: &Path) -> Vec<String> {
-    let target_name = file_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("");
-
-    if target_name.is_empty() {
-        return Vec::new();
-    }
+pub fn find_importers_with_include_dirs(
+    file_path: &Path,
+    base_path: &Path,
+    include_dirs: &[PathBuf],
+) -> Vec<String> {
+    let resolver = ImportResolver::new(base_path).with_include_dirs(include_dirs);
+    find_importers_with_resolver(file_path, base_path, &resolver)
+}
 
-    let mut cache = HashMap::new();
-    find_importers_inner(file_path, base_path, target_name, &mut cache)
+/// Find files that import the given file, resolving imports with `resolver`
+///
+/// # Arguments
+/// * `file_path` - The file to search for importers of
+/// * `base_path` - The root directory to search in
+/// * `resolver` - Resolver configured for the project at `base_path`
+///
+/// # Returns
+/// A vector of file paths that import the target file
+pub(crate) fn find_importers_with_resolver(
+    file_path: &Path,
+    base_path: &Path,
+    resolver: &ImportResolver,
+) -> Vec<String> {
+    find_importers_cached(file_path, base_path, resolver, parse_cache())
 }
 
-/// Find files that import the given file, with an externally provided cache.
+/// Find files that import the given file, parsing through `cache`
 ///
-/// This allows callers to share a parse cache across multiple calls.
+/// Files unchanged since they were cached are not parsed again.
 ///
 /// # Arguments
 /// * `file_path` - The file to search for importers of
 /// * `base_path` - The root directory to search in
-/// * `cache` - A mutable reference to a cache of previously parsed files
+/// * `resolver` - Resolver configured for the project at `base_path`
+/// * `cache` - Parse cache shared with other analyzers
 ///
 /// # Returns
 /// A vector of file paths that import the target file
-#[allow(dead_code)]
-pub fn find_importers_cached(
+pub(crate) fn find_importers_cached(
     file_path: &Path,
     base_path: &Path,
-    cache: &mut HashMap<PathBuf, FileAnalysis>,
+    resolver: &ImportResolver,
+    cache: &ParseCache,
 ) -> Vec<String> {
-    let target_name = file_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("");
+    let target_name = import_target_name(file_path);
 
     if target_name.is_empty() {
         return Vec::new();
     }
 
-    find_importers_inner(file_path, base_path, target_name, cache)
+    find_importers_inner(file_path, base_path, target_name, resolver, cache)
 }
 
 /// All source file extensions we want to scan for imports
-const IMPORT_SCAN_EXTENSIONS: &[&str] = &[
+pub(crate) const IMPORT_SCAN_EXTENSIONS: &[&str] = &[
     "rs", "ts", "tsx", "js", "jsx", "py", "go", "java", "cpp", "c", "h", "hpp", "cs", "rb",
-    "swift", "kt",
+    "swift", "kt", "zig",
 ];
 
+/// C/C++ extensions whose includes are only ever resolved, never matched by name
+pub(crate) const C_FAMILY_EXTENSIONS: &[&str] = &["c", "h", "cpp", "hpp"];
+
+/// Namespaces or packages declared by a C#, Java or Kotlin target file
+///
+/// C# `using` directives and Java/Kotlin wildcard imports name namespaces
+/// rather than files, so such targets are matched through the namespaces
+/// they declare.
+fn target_namespaces(file_path: &Path) -> Vec<String> {
+    if !matches!(
+        file_path.extension().and_then(|e| e.to_str()),
+        Some("cs" | "java" | "kt")
+    ) {
+        return Vec::new();
+    }
+    analyze_cached(file_path)
+        .map(|analysis| analysis.namespaces)
+        .unwrap_or_default()
+}
+
+/// Check whether a `using` directive refers to one of the target namespaces
+///
+/// `using static Ns.Type;` names a type inside the namespace, so paths
+/// below a namespace match as well.
+pub(crate) fn using_matches_namespace(import: &ImportInfo, namespaces: &[String]) -> bool {
+    namespaces.iter().any(|ns| {
+        import.module_path == *ns
+            || import
+                .module_path
+                .strip_prefix(ns.as_str())
+                .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+/// Check whether a Java/Kotlin wildcard import names one of the target packages
+///
+/// `import com.acme.auth.*` brings in every type of the package, so it
+/// matches any file declaring `package com.acme.auth`.
+fn wildcard_matches_package(import: &ImportInfo, namespaces: &[String]) -> bool {
+    import.items.iter().any(|i| i == "*") && namespaces.contains(&import.module_path)
+}
+
+/// Compare two paths by their canonical form (falls back to plain equality)
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Scan `base_path` for files importing `file_path`
+///
+/// Imports that resolve to project files count only when they resolve to
+/// the target. Imports that resolve to nothing (external packages, unknown
+/// layouts, languages without a resolver) fall back to matching
+/// `target_name` against the import path, except C/C++ includes.
 fn find_importers_inner(
     file_path: &Path,
     base_path: &Path,
     target_name: &str,
-    cache: &mut HashMap<PathBuf, FileAnalysis>,
+    resolver: &ImportResolver,
+    cache: &ParseCache,
 ) -> Vec<String> {
     let mut importers = Vec::new();
+    let namespaces = target_namespaces(file_path);
 
     let source_files = walk_source_files(base_path, IMPORT_SCAN_EXTENSIONS);
 
@@ -116,21 +226,34 @@ fn find_importers_inner(
             .and_then(|e| e.to_str())
             .unwrap_or("");
 
-        // Use cached analysis if available, otherwise parse and cache
-        let analysis = if let Some(cached) = cache.get(&path) {
-            Some(cached)
-        } else if let Ok(parsed) = analyze_file(&path) {
-            cache.insert(path.clone(), parsed);
-            cache.get(&path)
-        } else {
-            None
-        };
+        // Files unchanged since the last scan are not parsed again
+        if let Ok(analysis) = cache.analyze(&path) {
+            if !namespaces.is_empty() && file_ext == "cs" {
+                if analysis
+                    .imports
+                    .iter()
+                    .any(|import| using_matches_namespace(import, &namespaces))
+                {
+                    if let Some(p) = path.to_str() {
+                        importers.push(p.to_string());
+                    }
+                }
+                continue;
+            }
 
-        if let Some(analysis) = analysis {
             for import in &analysis.imports {
-                if path_matches_import(&import.module_path, target_name, file_ext)
-                    || import.items.iter().any(|i| i == target_name)
-                {
+                let resolved = resolver.resolve(import, &path);
+                let is_match = if !resolved.is_empty() {
+                    resolved.iter().any(|file| same_file(file, file_path))
+                } else {
+                    // Includes name a file, so an unresolved include is never a match
+                    !C_FAMILY_EXTENSIONS.contains(&file_ext)
+                        && (path_matches_import(&import.module_path, target_name, file_ext)
+                            || import.items.iter().any(|i| i == target_name)
+                            || (matches!(file_ext, "java" | "kt")
+                                && wildcard_matches_package(import, &namespaces)))
+                };
+                if is_match {
                     if let Some(p) = path.to_str() {
                         importers.push(p.to_string());
                     }
@@ -146,9 +269,14 @@ fn find_importers_inner(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::ImportKind;
     use std::path::PathBuf;
     use tempfile::tempdir;
 
+    fn find_importers(file_path: &Path, base_path: &Path) -> Vec<String> {
+        find_importers_with_include_dirs(file_path, base_path, &[])
+    }
+
     #[test]
     fn test_find_importers_empty_target() {
         let dir = tempdir().unwrap();
@@ -169,11 +297,12 @@ mod tests {
     fn test_find_importers_cached_populates_cache() {
         let dir = tempdir().unwrap();
         let file_path = PathBuf::from("target.rs");
-        let mut cache = HashMap::new();
+        let cache = ParseCache::new();
 
         // Call with an empty directory — cache should remain empty
         // since there are no source files to parse
-        let result = find_importers_cached(&file_path, dir.path(), &mut cache);
+        let resolver = ImportResolver::new(dir.path());
+        let result = find_importers_cached(&file_path, dir.path(), &resolver, &cache);
         assert!(result.is_empty());
         assert!(cache.is_empty());
     }
@@ -184,26 +313,26 @@ mod tests {
         let file_path = PathBuf::from("target.rs");
 
         // Pre-populate the cache with a synthetic FileAnalysis
-        let mut cache = HashMap::new();
+        let cache = ParseCache::new();
         let synthetic_path = dir.path().join("other.rs");
         // Create the file so it's found during directory walk
         std::fs::write(&synthetic_path, "// empty").unwrap();
 
-        use crate::parser::{FileAnalysis, ImportInfo};
-        let analysis = FileAnalysis {
-            imports: vec![ImportInfo {
-                module_path: "crate::target".to_string(),
-                items: vec![],
-            }],
-            exports: vec![],
-            language: "rust".to_string(),
-        };
-        cache.insert(synthetic_path.clone(), analysis);
+        use crate::parser::{FileAnalysis, ImportKind};
+        let mut analysis = FileAnalysis::empty("rust");
+        analysis.imports.push(ImportInfo {
+            module_path: "crate::target".to_string(),
+            items: vec![],
+            kind: ImportKind::Static,
+        });
+        cache.insert(&synthetic_path, analysis).unwrap();
 
-        let result = find_importers_cached(&file_path, dir.path(), &mut cache);
+        let resolver = ImportResolver::new(dir.path());
+        let result = find_importers_cached(&file_path, dir.path(), &resolver, &cache);
         // The cached analysis contains an import of "target", so it should match
         assert_eq!(result.len(), 1);
         assert!(result[0].contains("other.rs"));
+        assert_eq!(cache.len(), 1);
     }
 
     #[test]
@@ -232,4 +361,211 @@ mod tests {
         assert!(path_matches_import("./lib/config", "config", "js"));
         assert!(path_matches_import("./lib/config", "config", "jsx"));
     }
+
+    #[test]
+    fn test_path_matches_import_jvm() {
+        assert!(path_matches_import("com.acme.auth", "auth", "java"));
+        assert!(path_matches_import("com.acme.auth", "acme", "kt"));
+        assert!(!path_matches_import("com.acme.auth", "com.acme", "java"));
+    }
+
+    #[test]
+    fn test_path_matches_import_go() {
+        assert!(path_matches_import("github.com/acme/app/auth", "auth", "go"));
+        assert!(!path_matches_import("github.com/acme/app/auth", "app/auth", "go"));
+    }
+
+    #[test]
+    fn test_using_matches_namespace() {
+        let using = |path: &str| ImportInfo {
+            module_path: path.to_string(),
+            items: vec![],
+            kind: ImportKind::Static,
+        };
+        let namespaces = vec!["Acme.Auth".to_string()];
+        assert!(using_matches_namespace(&using("Acme.Auth"), &namespaces));
+        assert!(using_matches_namespace(&using("Acme.Auth.TokenService"), &namespaces));
+        assert!(!using_matches_namespace(&using("Acme.Authz"), &namespaces));
+        assert!(!using_matches_namespace(&using("Acme"), &namespaces));
+    }
+
+    #[test]
+    fn test_path_matches_import_zig() {
+        assert!(path_matches_import("codegen/emit.zig", "emit", "zig"));
+        assert!(path_matches_import("std", "std", "zig"));
+        assert!(!path_matches_import("codegen/emit.zig", "emit.zig", "zig"));
+        assert!(!path_matches_import("emitter.zig", "emit", "zig"));
+    }
+
+    #[test]
+    fn test_path_matches_import_ruby_and_swift() {
+        assert!(path_matches_import("../lib/auth", "auth", "rb"));
+        assert!(path_matches_import("lib/auth.rb", "auth", "rb"));
+        assert!(path_matches_import("UIKit.UIView", "UIKit", "swift"));
+        assert!(!path_matches_import("Networking", "Network", "swift"));
+    }
+
+    #[test]
+    fn test_import_target_name() {
+        assert_eq!(import_target_name(Path::new("src/auth/token.go")), "auth");
+        assert_eq!(import_target_name(Path::new("src/auth/token.rs")), "token");
+        assert_eq!(
+            import_target_name(Path::new("Sources/Networking/HTTP/Client.swift")),
+            "Networking"
+        );
+        assert_eq!(import_target_name(Path::new("App/Client.swift")), "Client");
+    }
+
+    #[test]
+    fn test_find_importers_rust_resolves_mod_tree() {
+        let dir = tempdir().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir_all(src.join("auth")).unwrap();
+        std::fs::create_dir_all(src.join("billing")).unwrap();
+        std::fs::write(src.join("lib.rs"), "mod auth;\nmod billing;\n").unwrap();
+        let target = src.join("auth/mod.rs");
+        std::fs::write(&target, "pub struct Session;\n").unwrap();
+        std::fs::write(src.join("billing/mod.rs"), "pub struct Invoice;\n").unwrap();
+        std::fs::write(src.join("billing/invoice.rs"), "use crate::auth::Session;\n").unwrap();
+        std::fs::write(src.join("billing/report.rs"), "use super::Invoice;\n").unwrap();
+
+        // Both targets are `mod.rs`; only the path through the mod tree decides
+        let result = find_importers(&target, dir.path());
+        assert_eq!(result.len(), 1);
+        assert!(result[0].ends_with("invoice.rs"));
+    }
+
+    #[cfg(feature = "lang-python")]
+    #[test]
+    fn test_find_importers_python_same_named_modules() {
+        let dir = tempdir().unwrap();
+        for pkg in ["orders", "users"] {
+            std::fs::create_dir_all(dir.path().join(pkg)).unwrap();
+            std::fs::write(dir.path().join(pkg).join("__init__.py"), "").unwrap();
+            std::fs::write(dir.path().join(pkg).join("utils.py"), "").unwrap();
+        }
+        let target = dir.path().join("orders/utils.py");
+        std::fs::write(dir.path().join("orders/views.py"), "from .utils import total\n").unwrap();
+        std::fs::write(dir.path().join("users/views.py"), "from users.utils import slug\n").unwrap();
+
+        let result = find_importers(&target, dir.path());
+        assert_eq!(result.len(), 1);
+        assert!(result[0].ends_with("orders/views.py"));
+    }
+
+    #[cfg(feature = "lang-cpp")]
+    #[test]
+    fn test_find_importers_c_header() {
+        let dir = tempdir().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir_all(dir.path().join("include")).unwrap();
+        std::fs::create_dir_all(&src).unwrap();
+        let header = dir.path().join("include/config.h");
+        std::fs::write(&header, "int config_load(void);\n").unwrap();
+        std::fs::write(src.join("main.c"), "#include \"config.h\"\n").unwrap();
+        // Same file name, different directory: must not count as an includer
+        std::fs::write(src.join("config.h"), "").unwrap();
+        std::fs::write(src.join("other.c"), "#include \"config.h\"\n").unwrap();
+
+        // Without include dirs the quoted include resolves to src/config.h
+        assert!(find_importers(&header, dir.path()).is_empty());
+
+        let result =
+            find_importers_with_include_dirs(&header, dir.path(), &[PathBuf::from("include")]);
+        assert_eq!(result.len(), 0, "local src/config.h shadows include/config.h");
+
+        std::fs::remove_file(src.join("config.h")).unwrap();
+        let mut result =
+            find_importers_with_include_dirs(&header, dir.path(), &[PathBuf::from("include")]);
+        result.sort();
+        assert_eq!(result.len(), 2);
+        assert!(result[0].ends_with("main.c"));
+        assert!(result[1].ends_with("other.c"));
+    }
+
+    #[cfg(feature = "lang-java")]
+    #[test]
+    fn test_find_importers_java_class() {
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("TokenStore.java");
+        std::fs::write(&target, "package com.acme.auth;\n\npublic class TokenStore {}\n").unwrap();
+        std::fs::write(
+            dir.path().join("Login.java"),
+            "package com.acme.web;\n\nimport com.acme.auth.TokenStore;\n\npublic class Login {}\n",
+        )
+        .unwrap();
+
+        let result = find_importers(&target, dir.path());
+        assert_eq!(result.len(), 1);
+        assert!(result[0].ends_with("Login.java"));
+    }
+
+    #[cfg(feature = "lang-java")]
+    #[test]
+    fn test_find_importers_java_wildcard() {
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("TokenStore.java");
+        std::fs::write(&target, "package com.acme.auth;\n\npublic class TokenStore {}\n").unwrap();
+        std::fs::write(
+            dir.path().join("Login.java"),
+            "package com.acme.web;\n\nimport com.acme.auth.*;\n\npublic class Login {}\n",
+        )
+        .unwrap();
+        // Wildcard import of a different package
+        std::fs::write(
+            dir.path().join("Report.java"),
+            "package com.acme.web;\n\nimport com.acme.billing.*;\n\npublic class Report {}\n",
+        )
+        .unwrap();
+
+        let result = find_importers(&target, dir.path());
+        assert_eq!(result.len(), 1);
+        assert!(result[0].ends_with("Login.java"));
+    }
+
+    #[cfg(feature = "lang-go")]
+    #[test]
+    fn test_find_importers_go_package() {
+        let dir = tempdir().unwrap();
+        let pkg = dir.path().join("auth");
+        std::fs::create_dir(&pkg).unwrap();
+        let target = pkg.join("token.go");
+        std::fs::write(&target, "package auth\n\nfunc Issue() {}\n").unwrap();
+        std::fs::write(
+            dir.path().join("main.go"),
+            "package main\n\nimport \"example.com/app/auth\"\n",
+        )
+        .unwrap();
+
+        let result = find_importers(&target, dir.path());
+        assert_eq!(result.len(), 1);
+        assert!(result[0].ends_with("main.go"));
+    }
+
+    #[cfg(feature = "lang-csharp")]
+    #[test]
+    fn test_find_importers_csharp_namespace() {
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("TokenService.cs");
+        std::fs::write(
+            &target,
+            "namespace Acme.Auth;\n\npublic class TokenService {}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("LoginController.cs"),
+            "using Acme.Auth;\n\npublic class LoginController {}\n",
+        )
+        .unwrap();
+        // Mentions the file stem but not the namespace
+        std::fs::write(
+            dir.path().join("Unrelated.cs"),
+            "using Vendor.TokenService;\n\npublic class Unrelated {}\n",
+        )
+        .unwrap();
+
+        let result = find_importers(&target, dir.path());
+        assert_eq!(result.len(), 1);
+        assert!(result[0].ends_with("LoginController.cs"));
+    }
 }
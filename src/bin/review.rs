@@ -6,17 +6,180 @@
 //!   review --diff           - Review git diff (staged or unstaged)
 //!   review --hook           - Pre-commit hook mode (review staged diff)
 //!   review --hook-install   - Install git pre-commit hook
+//!   review --fix --errors <log> [file] - Propose a unified diff that fixes build errors
+//!   review graph --target <dir> - Print the project dependency graph
+//!   review snapshot --target <dir> - Print an architecture snapshot (JSON)
+//!   review drift --target <dir>    - Show architecture drift since a commit or snapshot
 
 use ai_code_review::{
     build_analyze_prompt, build_discovery_prompt, build_find_shared_prompt,
-    build_investigate_prompt, gather_raw_context, generate_module_tree,
-    shared_finder::find_shared_candidates,
-    walk_source_files, Backend, CodeReviewer, PromptType, ANALYZE_PROMPT, DISCOVERY_PROMPT,
-    FIND_SHARED_PROMPT, INVESTIGATE_PROMPT, QDD_PROMPT, SOURCE_EXTENSIONS,
+    build_investigate_prompt, call_ai, format_rule_violations, gather_raw_context, generate_module_tree,
+    shared_finder::{
+        find_shared_candidates_many_with_options, SharedFormat, SharedKind,
+        SharedOptions,
+    },
+    default_usage_path, get_working_diff, parse_cache, repo_root, walk_source_files, Backend, BudgetAction, CallPolicy, CodeReviewError,
+    ArchitectureDrift, ArchitectureRules, ArchitectureSnapshot, CodeReviewer, DependencyGraph, DriftThresholds, Finding, GraphFormat, GraphLevel, ImportResolver, Investigator, MetricThresholds, Patch, PromptType, ReviewSeverity, ReviewSummary, RuleViolation, SpendBudget, UsageLimits, ANALYZE_PROMPT, DISCOVERY_PROMPT, FIND_SHARED_PROMPT, INVESTIGATE_PROMPT, QDD_PROMPT,
+    RULES_FILE_NAME, SOURCE_EXTENSIONS,
 };
-use cli_ai_analyzer::{prompt as ai_prompt, AnalyzeOptions};
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// AI call settings shared by every CLI mode
+struct AiSettings {
+    backend: Backend,
+    model: Option<String>,
+    policy: CallPolicy,
+    limits: Option<Arc<UsageLimits>>,
+    /// Architecture rules from `--rules` (otherwise discovered per directory)
+    rules: Option<ArchitectureRules>,
+    /// Measure per-function metrics (`--metrics`)
+    metrics: bool,
+}
+
+impl AiSettings {
+    /// Send a prompt using the configured backend, model and call policy
+    fn call(&self, prompt: &str) -> Result<String, CodeReviewError> {
+        let model = self.model.as_deref();
+        match self.limits {
+            Some(ref limits) => limits.call(prompt, self.backend, model, &self.policy),
+            None => call_ai(prompt, self.backend, model, &self.policy),
+        }
+        .map(|o| o.response)
+    }
+
+    /// Send a prompt already downgraded to the quick review
+    fn call_degraded(&self, prompt: &str) -> Result<String, CodeReviewError> {
+        let model = self.model.as_deref();
+        match self.limits {
+            Some(ref limits) => limits.call_degraded(prompt, self.backend, model, &self.policy),
+            None => call_ai(prompt, self.backend, model, &self.policy),
+        }
+        .map(|o| o.response)
+    }
+
+    /// Action to take if the spend budget is exhausted right now
+    fn exhausted_action(&self) -> Option<BudgetAction> {
+        self.limits
+            .as_ref()
+            .filter(|l| l.is_exhausted())
+            .map(|l| l.exhausted_action())
+    }
+
+    /// Create a reviewer for `dir` with these settings (exits on error)
+    fn reviewer(&self, dir: &Path, prompt_type: PromptType, context_enabled: bool) -> CodeReviewer {
+        let reviewer = match CodeReviewer::new(dir) {
+            Ok(r) => r
+                .with_backend(self.backend)
+                .with_prompt_type(prompt_type)
+                .with_context(context_enabled)
+                .with_call_policy(self.policy.clone()),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        };
+        let reviewer = match self.limits {
+            Some(ref limits) => reviewer.with_limits(Arc::clone(limits)),
+            None => reviewer,
+        };
+        let reviewer = match self.rules_for(dir) {
+            Some(rules) => reviewer.with_architecture_rules(rules),
+            None => reviewer,
+        };
+        let reviewer = if self.metrics {
+            reviewer.with_metric_thresholds(Some(MetricThresholds::default()))
+        } else {
+            reviewer
+        };
+        match self.model {
+            Some(ref m) => reviewer.with_model(m),
+            None => reviewer,
+        }
+    }
+
+    /// Create an investigator with these settings
+    fn investigator(&self) -> Investigator {
+        let investigator = Investigator::new()
+            .with_backend(self.backend)
+            .with_call_policy(self.policy.clone());
+        let investigator = match self.limits {
+            Some(ref limits) => investigator.with_limits(Arc::clone(limits)),
+            None => investigator,
+        };
+        match self.model {
+            Some(ref m) => investigator.with_model(m),
+            None => investigator,
+        }
+    }
+
+    /// Rules given with `--rules`, else the rules file in `dir` (exits on a broken file)
+    fn rules_for(&self, dir: &Path) -> Option<ArchitectureRules> {
+        if self.rules.is_some() {
+            return self.rules.clone();
+        }
+        match ArchitectureRules::discover(dir) {
+            Ok(rules) => rules,
+            Err(e) => {
+                eprintln!("Error: invalid {}: {}", RULES_FILE_NAME, e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Parse the numeric value following `flag` (exits on error)
+fn parse_number<T: FromStr>(args: &[String], i: usize, flag: &str) -> T {
+    match args.get(i).and_then(|v| v.parse::<T>().ok()) {
+        Some(n) => n,
+        None => {
+            eprintln!("Error: {} requires a number", flag);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Parse a backend name (unknown names fall back to Gemini)
+fn parse_backend(name: &str) -> Backend {
+    match name.to_lowercase().as_str() {
+        "claude" => Backend::Claude,
+        "codex" => Backend::Codex,
+        _ => Backend::Gemini,
+    }
+}
+
+/// Parse a shared code kind name (`same_file_name`, `similar_content`, ...)
+fn parse_shared_kind(name: &str) -> Option<SharedKind> {
+    [
+        SharedKind::SameFileName,
+        SharedKind::SameExport,
+        SharedKind::SameConstant,
+        SharedKind::SimilarContent,
+    ]
+    .into_iter()
+    .find(|kind| kind.name() == name.to_lowercase())
+}
+
+fn parse_shared_format(name: &str) -> Option<SharedFormat> {
+    match name {
+        "json" => Some(SharedFormat::Json),
+        "md" | "markdown" => Some(SharedFormat::Markdown),
+        "csv" => Some(SharedFormat::Csv),
+        _ => None,
+    }
+}
+
+fn parse_graph_format(name: &str) -> Option<GraphFormat> {
+    match name {
+        "dot" => Some(GraphFormat::Dot),
+        "mermaid" => Some(GraphFormat::Mermaid),
+        "json" => Some(GraphFormat::Json),
+        _ => None,
+    }
+}
 
 /// Read REVIEW_EXTRA_CONTEXT env var (file path) and append its content to the prompt.
 fn append_extra_context(prompt: &mut String) {
@@ -55,9 +218,34 @@ fn main() {
     let mut prompt_type = PromptType::Default;
     let mut mode = Mode::File(PathBuf::new());
     let mut context_enabled = false;
+    let mut metrics = false;
     let mut goal: Option<String> = None;
     let mut question: Option<String> = None;
     let mut target: Option<PathBuf> = None;
+    let mut policy = CallPolicy::default();
+    let mut rpm: u32 = 0;
+    let mut max_concurrent: usize = 0;
+    let mut session_budget: Option<u64> = None;
+    let mut daily_budget: Option<u64> = None;
+    let mut budget_file: Option<PathBuf> = None;
+    let mut on_exhausted = BudgetAction::default();
+    let mut format: Option<String> = None;
+    let mut shared_config: Option<PathBuf> = None;
+    let mut min_similarity: Option<f64> = None;
+    let mut shared_kinds: Option<Vec<SharedKind>> = None;
+    let mut max_results: Option<usize> = None;
+    let mut ignore: Vec<String> = Vec::new();
+    let mut graph_level = GraphLevel::default();
+    let mut rules_file: Option<PathBuf> = None;
+    let mut rev: Option<String> = None;
+    let mut out: Option<PathBuf> = None;
+    let mut drift_from: Option<String> = None;
+    let mut drift_to: Option<String> = None;
+    let mut drift_staged = false;
+    let mut fix = false;
+    let mut errors_file: Option<String> = None;
+    let mut references: Vec<PathBuf> = Vec::new();
+    let mut apply = false;
 
     let mut i = 1;
     while i < args.len() {
@@ -65,11 +253,7 @@ fn main() {
             "--backend" => {
                 i += 1;
                 if i < args.len() {
-                    backend = match args[i].to_lowercase().as_str() {
-                        "claude" => Backend::Claude,
-                        "codex" => Backend::Codex,
-                        _ => Backend::Gemini,
-                    };
+                    backend = parse_backend(&args[i]);
                 }
             }
             "--model" => {
@@ -118,6 +302,124 @@ fn main() {
             "--hook-install" => {
                 mode = Mode::HookInstall;
             }
+            "graph" if i == 1 => {
+                mode = Mode::Graph;
+            }
+            "snapshot" if i == 1 => {
+                mode = Mode::Snapshot;
+            }
+            "drift" if i == 1 => {
+                mode = Mode::Drift;
+            }
+            "--rev" => {
+                i += 1;
+                if i < args.len() {
+                    rev = Some(args[i].clone());
+                }
+            }
+            "--out" => {
+                i += 1;
+                if i < args.len() {
+                    out = Some(PathBuf::from(&args[i]));
+                }
+            }
+            "--from" => {
+                i += 1;
+                if i < args.len() {
+                    drift_from = Some(args[i].clone());
+                }
+            }
+            "--to" => {
+                i += 1;
+                if i < args.len() {
+                    drift_to = Some(args[i].clone());
+                }
+            }
+            "--staged" => {
+                drift_staged = true;
+            }
+            "--format" => {
+                i += 1;
+                if i < args.len() {
+                    format = Some(args[i].to_lowercase());
+                }
+            }
+            "--shared-config" => {
+                i += 1;
+                if i < args.len() {
+                    shared_config = Some(PathBuf::from(&args[i]));
+                } else {
+                    eprintln!("Error: --shared-config requires a path");
+                    std::process::exit(1);
+                }
+            }
+            "--min-similarity" => {
+                i += 1;
+                min_similarity = Some(parse_number::<f64>(&args, i, "--min-similarity"));
+            }
+            "--kinds" => {
+                i += 1;
+                let names = args.get(i).map(String::as_str).unwrap_or("");
+                let kinds: Vec<SharedKind> = names
+                    .split(',')
+                    .filter(|name| !name.trim().is_empty())
+                    .map(|name| {
+                        parse_shared_kind(name.trim()).unwrap_or_else(|| {
+                            eprintln!("Error: unknown kind for --kinds: {}", name);
+                            std::process::exit(1);
+                        })
+                    })
+                    .collect();
+                // An empty list would silently mean "all kinds"
+                if kinds.is_empty() {
+                    eprintln!("Error: --kinds requires at least one kind (same_file_name, same_export, same_constant, similar_content)");
+                    std::process::exit(1);
+                }
+                shared_kinds = Some(kinds);
+            }
+            "--max-results" => {
+                i += 1;
+                max_results = Some(parse_number::<usize>(&args, i, "--max-results"));
+            }
+            "--ignore" => {
+                i += 1;
+                if i < args.len() {
+                    ignore.push(args[i].clone());
+                }
+            }
+            "--fix" => {
+                fix = true;
+            }
+            "--errors" => {
+                i += 1;
+                if i < args.len() {
+                    errors_file = Some(args[i].clone());
+                } else {
+                    eprintln!("Error: --errors requires a path (or - for stdin)");
+                    std::process::exit(1);
+                }
+            }
+            "--reference" => {
+                i += 1;
+                if i < args.len() {
+                    references.push(PathBuf::from(&args[i]));
+                }
+            }
+            "--apply" => {
+                apply = true;
+            }
+            "--modules" => {
+                graph_level = GraphLevel::Modules;
+            }
+            "--rules" => {
+                i += 1;
+                if i < args.len() {
+                    rules_file = Some(PathBuf::from(&args[i]));
+                } else {
+                    eprintln!("Error: --rules requires a path");
+                    std::process::exit(1);
+                }
+            }
             "--discover" => {
                 mode = Mode::Discover(String::new()); // placeholder, goal filled later
             }
@@ -144,14 +446,16 @@ fn main() {
                 }
             }
             "--find-shared" => {
-                i += 1;
-                if i + 1 < args.len() {
-                    mode = Mode::FindShared(PathBuf::from(&args[i]), PathBuf::from(&args[i + 1]));
+                let mut dirs = Vec::new();
+                while i + 1 < args.len() && !args[i + 1].starts_with("--") {
                     i += 1;
-                } else {
-                    eprintln!("Error: --find-shared requires two directory paths");
+                    dirs.push(PathBuf::from(&args[i]));
+                }
+                if dirs.len() < 2 {
+                    eprintln!("Error: --find-shared requires two or more directory paths");
                     std::process::exit(1);
                 }
+                mode = Mode::FindShared(dirs);
             }
             "--goal" => {
                 i += 1;
@@ -162,6 +466,61 @@ fn main() {
             "--context" => {
                 context_enabled = true;
             }
+            "--metrics" => {
+                metrics = true;
+            }
+            "--timeout" => {
+                i += 1;
+                policy.timeout = match parse_number::<u64>(&args, i, "--timeout") {
+                    0 => None,
+                    secs => Some(Duration::from_secs(secs)),
+                };
+            }
+            "--retries" => {
+                i += 1;
+                policy.max_retries = parse_number(&args, i, "--retries");
+            }
+            "--rpm" => {
+                i += 1;
+                rpm = parse_number(&args, i, "--rpm");
+            }
+            "--max-concurrent" => {
+                i += 1;
+                max_concurrent = parse_number(&args, i, "--max-concurrent");
+            }
+            "--session-budget" => {
+                i += 1;
+                session_budget = Some(parse_number(&args, i, "--session-budget"));
+            }
+            "--daily-budget" => {
+                i += 1;
+                daily_budget = Some(parse_number(&args, i, "--daily-budget"));
+            }
+            "--budget-file" => {
+                i += 1;
+                if i < args.len() {
+                    budget_file = Some(PathBuf::from(&args[i]));
+                }
+            }
+            "--on-budget-exhausted" => {
+                i += 1;
+                if i < args.len() {
+                    on_exhausted = match args[i].to_lowercase().as_str() {
+                        "skip" => BudgetAction::Skip,
+                        _ => BudgetAction::Quick,
+                    };
+                }
+            }
+            "--fallback" => {
+                i += 1;
+                if i < args.len() {
+                    policy.fallback_backends = args[i]
+                        .split(',')
+                        .filter(|b| !b.trim().is_empty())
+                        .map(|b| parse_backend(b.trim()))
+                        .collect();
+                }
+            }
             "--target" => {
                 i += 1;
                 if i < args.len() {
@@ -179,7 +538,60 @@ fn main() {
         i += 1;
     }
 
-    if matches!(mode, Mode::Diff | Mode::Qdd | Mode::Discover(_) | Mode::Hook | Mode::HookInstall) && target.is_none() {
+    // --fix works on the given file, or on the working diff of --target
+    let mut error_log = String::new();
+    if fix {
+        let file = match mode {
+            Mode::File(path) if !path.as_os_str().is_empty() => Some(path),
+            _ => None,
+        };
+        if file.is_none() && target.is_none() {
+            eprintln!("Error: --fix requires a file or --target <path>");
+            std::process::exit(1);
+        }
+        error_log = match errors_file.as_deref() {
+            Some("-") => {
+                let mut log = String::new();
+                std::io::Read::read_to_string(&mut std::io::stdin(), &mut log).map(|_| log)
+            }
+            Some(path) => std::fs::read_to_string(path),
+            None => {
+                eprintln!("Error: --fix requires --errors <log> (or - for stdin)");
+                std::process::exit(1);
+            }
+        }
+        .unwrap_or_else(|e| {
+            eprintln!("Error: failed to read build errors: {}", e);
+            std::process::exit(1);
+        });
+        mode = Mode::Fix(file);
+    }
+
+    let graph_format = match (&mode, format.as_deref()) {
+        (Mode::Graph, Some(name)) => parse_graph_format(name).unwrap_or_else(|| {
+            eprintln!("Error: unknown --format for graph: {} (dot, mermaid, json)", name);
+            std::process::exit(1);
+        }),
+        _ => GraphFormat::default(),
+    };
+    let shared_format = match (&mode, format.as_deref()) {
+        (Mode::FindShared(_), Some(name)) => Some(parse_shared_format(name).unwrap_or_else(|| {
+            eprintln!("Error: unknown --format for --find-shared: {} (json, md, csv)", name);
+            std::process::exit(1);
+        })),
+        _ => None,
+    };
+    if format.is_some() && !matches!(mode, Mode::Graph | Mode::FindShared(_)) {
+        eprintln!("Error: --format is only supported by graph and --find-shared");
+        std::process::exit(1);
+    }
+
+    if drift_staged && drift_to.is_some() {
+        eprintln!("Error: --staged and --to cannot be used together");
+        std::process::exit(1);
+    }
+
+    if matches!(mode, Mode::Diff | Mode::Qdd | Mode::Discover(_) | Mode::Hook | Mode::HookInstall | Mode::Graph | Mode::Snapshot | Mode::Drift) && target.is_none() {
         eprintln!("Error: This mode requires --target <path>");
         std::process::exit(1);
     }
@@ -218,21 +630,82 @@ fn main() {
         }
     }
 
+    // Daily usage is persisted so separate invocations share one budget
+    let limits = (rpm > 0 || max_concurrent > 0 || session_budget.is_some() || daily_budget.is_some())
+        .then(|| {
+            let state_path = budget_file.or_else(|| daily_budget.map(|_| default_usage_path()));
+            let budget = SpendBudget::new(session_budget, daily_budget, state_path).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            Arc::new(
+                UsageLimits::new()
+                    .with_rate_limit(rpm, max_concurrent)
+                    .with_budget(budget)
+                    .on_exhausted(on_exhausted),
+            )
+        });
+
+    let rules = rules_file.map(|path| match ArchitectureRules::load(&path) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("Error: failed to load rules {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    });
+
+    let mut shared_options = match shared_config {
+        Some(path) => SharedOptions::load(&path).unwrap_or_else(|e| {
+            eprintln!("Error: failed to load {}: {}", path.display(), e);
+            std::process::exit(1);
+        }),
+        None => SharedOptions::default(),
+    };
+    if let Some(min) = min_similarity {
+        shared_options.min_similarity = min;
+    }
+    if let Some(kinds) = shared_kinds {
+        shared_options.kinds = kinds;
+    }
+    if max_results.is_some() {
+        shared_options.max_results = max_results;
+    }
+    shared_options.ignore.extend(ignore);
+
+    let ai = AiSettings {
+        backend,
+        model,
+        policy,
+        limits,
+        rules,
+        metrics,
+    };
+
+    // Files parsed by earlier runs on the same project are not parsed again
+    let cache_root = match &mode {
+        Mode::Dir(dir) => Some(dir.clone()),
+        _ => target.clone(),
+    };
+    if let Some(root) = &cache_root {
+        if let Err(e) = parse_cache().load(root) {
+            eprintln!("[cache] ignoring parse cache: {}", e);
+        }
+    }
+
     match mode {
         Mode::File(path) => {
             if path.as_os_str().is_empty() {
                 eprintln!("Error: No file specified");
                 std::process::exit(1);
             }
-            review_file(&path, backend, model.clone(), prompt_type, context_enabled);
+            review_file(&path, &ai, prompt_type, context_enabled);
         }
         Mode::Dir(dir) => {
-            review_directory(&dir, backend, model.clone(), prompt_type, context_enabled);
+            review_directory(&dir, &ai, prompt_type, context_enabled);
         }
         Mode::Diff => {
             review_diff(
-                backend,
-                model.clone(),
+                &ai,
                 prompt_type,
                 context_enabled,
                 target
@@ -243,26 +716,35 @@ fn main() {
         Mode::Discover(goal) => {
             discover_architecture(
                 &goal,
-                backend,
-                model.clone(),
+                &ai,
                 target
                     .as_deref()
                     .expect("target required for discover mode"),
             );
         }
         Mode::Analyze(path) => {
-            analyze_with_ai(&path, backend, model.clone());
+            analyze_with_ai(&path, &ai);
         }
         Mode::Investigate(dir, question) => {
-            investigate_codebase(&dir, &question, backend, model.clone());
+            investigate_codebase(&dir, &question, &ai);
+        }
+        Mode::FindShared(dirs) => {
+            find_shared_modules(&dirs, &shared_options, shared_format, &ai);
         }
-        Mode::FindShared(path_a, path_b) => {
-            find_shared_modules(&path_a, &path_b, backend, model.clone());
+        Mode::Fix(file) => {
+            fix_build_errors(
+                file.as_deref(),
+                target.as_deref(),
+                &error_log,
+                &references,
+                out.as_deref(),
+                apply,
+                &ai,
+            );
         }
         Mode::Qdd => {
             run_qdd(
-                backend,
-                model.clone(),
+                &ai,
                 target
                     .as_deref()
                     .expect("target required for qdd mode"),
@@ -270,8 +752,7 @@ fn main() {
         }
         Mode::Hook => {
             run_hook(
-                backend,
-                model.clone(),
+                &ai,
                 prompt_type,
                 context_enabled,
                 target
@@ -286,11 +767,45 @@ fn main() {
                     .expect("target required for hook-install mode"),
             );
         }
+        Mode::Graph => {
+            print_dependency_graph(
+                target
+                    .as_deref()
+                    .expect("target required for graph mode"),
+                graph_format,
+                graph_level,
+            );
+        }
+        Mode::Snapshot => {
+            write_snapshot(
+                target
+                    .as_deref()
+                    .expect("target required for snapshot mode"),
+                rev.as_deref(),
+                out.as_deref(),
+            );
+        }
+        Mode::Drift => {
+            print_drift(
+                target
+                    .as_deref()
+                    .expect("target required for drift mode"),
+                drift_from.as_deref().unwrap_or("HEAD"),
+                drift_to.as_deref(),
+                drift_staged,
+            );
+        }
+    }
+
+    if let Some(root) = &cache_root {
+        if let Err(e) = parse_cache().save(root) {
+            eprintln!("[cache] failed to save parse cache: {}", e);
+        }
     }
 }
 
 fn print_usage() {
-    println!("Usage: review <file|--dir <dir>|--diff|--qdd|--discover|--analyze|--investigate|--hook>");
+    println!("Usage: review <file|--dir <dir>|--diff|--qdd|--discover|--analyze|--investigate|--fix|--hook>");
     println!("  <file>         Review a single file");
     println!("  --dir <dir>    Review all source files in directory");
     println!("  --diff         Review git diff (changed files)");
@@ -298,18 +813,49 @@ fn print_usage() {
     println!("  --discover     Discovery mode (requires --goal)");
     println!("  --analyze <f>  Analyze file with AI (no AST parsing, AI does the work)");
     println!("  --investigate <dir>  Cross-file investigation (requires --question)");
+    println!("  --fix          Propose a unified diff fixing --errors for <file> or the diff of --target");
     println!("  --hook         Pre-commit hook mode (review staged diff)");
     println!("  --hook-install Install git pre-commit hook");
-    println!("  --find-shared <dirA> <dirB>  Find shared/duplicated code between two projects");
+    println!("  --find-shared <dirA> <dirB> [dirC ...]  Find shared/duplicated code between projects");
+    println!("  graph          Print the dependency graph of --target (DOT by default)");
+    println!("  snapshot       Print an architecture snapshot of --target as JSON");
+    println!("  drift          Show architecture drift of --target (default: HEAD -> working tree)");
     println!();
     println!("Options:");
     println!("  -h, --help                Show this help");
     println!("  --backend <gemini|claude> AI backend (default: gemini)");
+    println!("  --timeout <secs>          Per-call AI timeout (default: 300, 0 = none)");
+    println!("  --retries <n>             Retries for timeouts/rate limits (default: 2)");
+    println!("  --fallback <b1,b2>        Fallback backends tried when the primary fails");
+    println!("  --rpm <n>                 Max AI requests per minute (default: unlimited)");
+    println!("  --max-concurrent <n>      Max concurrent AI calls (default: unlimited)");
+    println!("  --session-budget <tokens> Estimated token budget for this run");
+    println!("  --daily-budget <tokens>   Estimated token budget per day (persisted)");
+    println!("  --budget-file <path>      Usage file (default: ~/.ai-code-review/usage.json)");
+    println!("  --on-budget-exhausted <quick|skip>  Degrade to quick review or skip (default: quick)");
     println!("  --prompt <default|quick|security|architecture|holistic|principles|discovery|analyze|multi|qdd>");
     println!("  --context                 Enable project context (module tree, dependencies)");
+    println!("  --metrics                 Measure functions (length, nesting, complexity, params); breaches become findings");
     println!("  --goal <text>             Project goal for discovery mode");
     println!("  --question <text>         Investigation question for --investigate mode");
-    println!("  --target <path>           Target repo/dir (required for --diff/--qdd/--discover/--hook/--hook-install)");
+    println!("  --target <path>           Target repo/dir (required for --diff/--qdd/--discover/--hook/--hook-install/graph/snapshot/drift)");
+    println!("  --errors <file|->         Build log for --fix (- reads stdin)");
+    println!("  --reference <dir>         Reference codebase searched for --fix (repeatable)");
+    println!("  --apply                   Apply the --fix patch to the working tree");
+    println!("  --format <dot|mermaid|json>  Output format for graph (default: dot)");
+    println!("  --format <json|md|csv>    Print --find-shared results in this format (skips the AI analysis)");
+    println!("  --shared-config <file>    --find-shared options (JSON: min_similarity, kinds, max_results, ignore, common_symbols, common_identifiers)");
+    println!("  --min-similarity <0-1>    Drop --find-shared candidates below this similarity");
+    println!("  --kinds <k1,k2>           same_file_name, same_export, same_constant, similar_content");
+    println!("  --max-results <n>         Keep at most n candidates (clusters for 3+ projects)");
+    println!("  --ignore <glob>           Skip matching paths in --find-shared (repeatable, e.g. tests/**, *.generated.ts)");
+    println!("  --modules                 Roll the graph up to modules (directories)");
+    println!("  --rev <rev>               Commit to snapshot (default: working tree)");
+    println!("  --out <file>              Write the snapshot or --fix patch to a file instead of stdout");
+    println!("  --from <rev|file>         Drift baseline: commit or saved snapshot (default: HEAD)");
+    println!("  --to <rev|file>           Drift target: commit or saved snapshot (default: working tree)");
+    println!("  --staged                  Drift target: the staged files (for pre-commit hooks)");
+    println!("  --rules <file>            Architecture rules (JSON; default: <dir>/{})", RULES_FILE_NAME);
 }
 
 enum Mode {
@@ -322,25 +868,24 @@ enum Mode {
     Qdd,                           // QDD mode - generate questions from diff/file
     Hook,                          // Pre-commit hook mode
     HookInstall,                   // Install git pre-commit hook
-    FindShared(PathBuf, PathBuf),  // (path_a, path_b)
+    FindShared(Vec<PathBuf>),      // two or more project roots
+    Fix(Option<PathBuf>),          // file to fix (otherwise the working diff of --target)
+    Graph,                         // Print the dependency graph of --target
+    Snapshot,                      // Print an architecture snapshot of --target
+    Drift,                         // Show architecture drift of --target
 }
 
-fn review_file(path: &Path, backend: Backend, model: Option<String>, prompt_type: PromptType, context_enabled: bool) {
+fn review_file(path: &Path, ai: &AiSettings, prompt_type: PromptType, context_enabled: bool) {
+    // Absolute, so paths below the project root are found relative to it
+    let path = &path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
     let parent = path.parent().unwrap_or(std::path::Path::new("."));
-    let mut reviewer = match CodeReviewer::new(parent) {
-        Ok(r) => r.with_backend(backend).with_prompt_type(prompt_type).with_context(context_enabled),
-        Err(e) => {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-    };
-    if let Some(ref m) = model {
-        reviewer = reviewer.with_model(m);
-    }
+    let root = project_root(parent);
+    let reviewer = with_graph(ai.reviewer(&root, prompt_type, context_enabled), &root, context_enabled);
 
     match reviewer.review_file(path) {
         Ok(result) => {
             println!("## Review: {}\n", result.name);
+            print_findings(&result.findings);
             println!("{}", result.review);
             print_default_next_steps();
         }
@@ -351,17 +896,27 @@ fn review_file(path: &Path, backend: Backend, model: Option<String>, prompt_type
     }
 }
 
-fn review_directory(dir: &Path, backend: Backend, model: Option<String>, prompt_type: PromptType, context_enabled: bool) {
-    let mut reviewer = match CodeReviewer::new(dir) {
-        Ok(r) => r.with_backend(backend).with_prompt_type(prompt_type).with_context(context_enabled),
-        Err(e) => {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
+/// Project root for a single-file review
+///
+/// Rule paths are relative to the directory holding the rules file, so that
+/// is the nearest ancestor with one (up to the repository root), else the
+/// repository root, else `dir` itself.
+fn project_root(dir: &Path) -> PathBuf {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    let repo = repo_root(&dir);
+    for ancestor in dir.ancestors() {
+        if ancestor.join(RULES_FILE_NAME).is_file() {
+            return ancestor.to_path_buf();
+        }
+        if repo.as_deref() == Some(ancestor) {
+            break;
         }
-    };
-    if let Some(ref m) = model {
-        reviewer = reviewer.with_model(m);
     }
+    repo.unwrap_or(dir)
+}
+
+fn review_directory(dir: &Path, ai: &AiSettings, prompt_type: PromptType, context_enabled: bool) {
+    let reviewer = with_graph(ai.reviewer(dir, prompt_type, context_enabled), dir, context_enabled);
 
     // Find source files
     let files = find_modified_files(dir, SOURCE_EXTENSIONS);
@@ -371,25 +926,29 @@ fn review_directory(dir: &Path, backend: Backend, model: Option<String>, prompt_
         return;
     }
 
+    let mut summary = ReviewSummary::new();
     for file in files {
         match reviewer.review_file(&file) {
             Ok(result) => {
                 println!("## Review: {}\n", result.name);
+                print_findings(&result.findings);
                 println!("{}\n", result.review);
                 print_default_next_steps();
                 println!("---\n");
+                summary.add(result);
             }
             Err(e) => {
                 eprintln!("Review failed for {:?}: {}", file, e);
             }
         }
     }
+    print_usage_summary(&summary);
 }
 
 #[cfg(target_os = "windows")]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
-fn review_diff(backend: Backend, model: Option<String>, prompt_type: PromptType, context_enabled: bool, target: &Path) {
+fn review_diff(ai: &AiSettings, prompt_type: PromptType, context_enabled: bool, target: &Path) {
     let cwd = target.to_path_buf();
 
     // Get changed files from git (relative to cwd)
@@ -422,17 +981,16 @@ fn review_diff(backend: Backend, model: Option<String>, prompt_type: PromptType,
         return;
     }
 
-    let mut reviewer = match CodeReviewer::new(&cwd) {
-        Ok(r) => r.with_backend(backend).with_prompt_type(prompt_type).with_context(context_enabled),
-        Err(e) => {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-    };
-    if let Some(ref m) = model {
-        reviewer = reviewer.with_model(m);
+    // Architecture drift is shown once and handed to every file's review
+    let drift = drift_since_head(&cwd);
+    if !drift.is_empty() {
+        println!("{}", drift.to_prompt_string());
+        println!("---\n");
     }
+    let reviewer = with_graph(ai.reviewer(&cwd, prompt_type, context_enabled), &cwd, context_enabled)
+        .with_architecture_drift(drift);
 
+    let mut summary = ReviewSummary::new();
     for file in changed_files {
         if !file.exists() {
             continue;
@@ -440,15 +998,139 @@ fn review_diff(backend: Backend, model: Option<String>, prompt_type: PromptType,
         match reviewer.review_file(&file) {
             Ok(result) => {
                 println!("## Review: {}\n", result.name);
+                print_findings(&result.findings);
                 println!("{}\n", result.review);
                 print_default_next_steps();
                 println!("---\n");
+                summary.add(result);
             }
             Err(e) => {
                 eprintln!("Review failed for {:?}: {}", file, e);
             }
         }
     }
+    print_usage_summary(&summary);
+}
+
+/// Build the dependency graph once when several files are reviewed with context
+fn with_graph(reviewer: CodeReviewer, dir: &Path, context_enabled: bool) -> CodeReviewer {
+    if context_enabled {
+        reviewer.with_dependency_graph(DependencyGraph::build(dir))
+    } else {
+        reviewer
+    }
+}
+
+/// Print the dependency graph (stdout) and its cycles (stderr)
+fn print_dependency_graph(target: &Path, format: GraphFormat, level: GraphLevel) {
+    let graph = DependencyGraph::build(target);
+    print!("{}", graph.render(format, level));
+
+    eprintln!("[graph] {} files", graph.len());
+    for cycle in graph.cycles() {
+        let files: Vec<String> = cycle.iter().map(|f| graph.display_path(f)).collect();
+        eprintln!("[cycle] {}", files.join(" -> "));
+    }
+    for cycle in graph.module_cycles() {
+        eprintln!("[module cycle] {}", cycle.join(" -> "));
+    }
+}
+
+/// Print (or save) an architecture snapshot of `target` at `rev` or the working tree
+fn write_snapshot(target: &Path, rev: Option<&str>, out: Option<&Path>) {
+    let snapshot = match rev {
+        Some(rev) => snapshot_or_exit(ArchitectureSnapshot::capture_at(target, rev)),
+        None => ArchitectureSnapshot::capture(target),
+    };
+    let result = match out {
+        Some(path) => snapshot.save(path).map(|_| eprintln!("[snapshot] {}", path.display())),
+        None => snapshot.to_json().map(|json| println!("{}", json)),
+    };
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Print the architecture drift between two commits or saved snapshots
+fn print_drift(target: &Path, from: &str, to: Option<&str>, staged: bool) {
+    let before = load_snapshot(target, from);
+    let after = match to {
+        Some(to) => load_snapshot(target, to),
+        None if staged => snapshot_or_exit(ArchitectureSnapshot::capture_staged(target)),
+        None => ArchitectureSnapshot::capture(target),
+    };
+    let drift = before.drift_to(&after, &DriftThresholds::default());
+    if drift.is_empty() {
+        println!("No architecture drift");
+    } else {
+        print!("{}", drift.to_prompt_string());
+    }
+}
+
+/// A saved snapshot file, or a snapshot of the commit `spec`
+fn load_snapshot(target: &Path, spec: &str) -> ArchitectureSnapshot {
+    let path = Path::new(spec);
+    if path.is_file() {
+        snapshot_or_exit(ArchitectureSnapshot::load(path))
+    } else {
+        snapshot_or_exit(ArchitectureSnapshot::capture_at(target, spec))
+    }
+}
+
+fn snapshot_or_exit(snapshot: Result<ArchitectureSnapshot, CodeReviewError>) -> ArchitectureSnapshot {
+    snapshot.unwrap_or_else(|e| {
+        eprintln!("Error: failed to take snapshot: {}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Architecture drift of the working tree since HEAD
+///
+/// Empty outside a git repository or before the first commit.
+fn drift_since_head(target: &Path) -> ArchitectureDrift {
+    ArchitectureDrift::since(target, "HEAD", &DriftThresholds::default()).unwrap_or_default()
+}
+
+/// Architecture drift of the staged files since HEAD, i.e. of the commit being made
+///
+/// Empty outside a git repository or before the first commit.
+fn staged_drift(target: &Path) -> ArchitectureDrift {
+    ArchitectureDrift::staged(target, &DriftThresholds::default()).unwrap_or_default()
+}
+
+/// Print deterministic findings ahead of the AI review
+fn print_findings(findings: &[Finding]) {
+    if findings.is_empty() {
+        return;
+    }
+    println!("### Findings\n");
+    for finding in findings {
+        println!("- [{:?}] {}: {}", finding.severity, finding.check, finding.message);
+    }
+    println!();
+}
+
+/// Print token and latency totals per prompt type (stderr)
+fn print_usage_summary(summary: &ReviewSummary) {
+    if summary.totals.reviews == 0 {
+        return;
+    }
+    eprintln!("=== Usage ===");
+    for (prompt_type, stats) in &summary.by_prompt_type {
+        eprintln!(
+            "{}: {} review(s), ~{} prompt tokens (avg {}), avg {} ms",
+            prompt_type,
+            stats.reviews,
+            stats.prompt_tokens,
+            stats.avg_prompt_tokens(),
+            stats.avg_latency_ms()
+        );
+    }
+    eprintln!(
+        "total: ~{} prompt tokens, {} ms",
+        summary.totals.prompt_tokens, summary.totals.latency_ms
+    );
 }
 
 fn print_default_next_steps() {
@@ -530,7 +1212,7 @@ fn find_files(dir: &Path, extensions: &[&str]) -> Vec<PathBuf> {
     result
 }
 
-fn analyze_with_ai(file_path: &Path, backend: Backend, model: Option<String>) {
+fn analyze_with_ai(file_path: &Path, ai: &AiSettings) {
     if !file_path.exists() {
         eprintln!("Error: File not found: {:?}", file_path);
         std::process::exit(1);
@@ -562,15 +1244,10 @@ fn analyze_with_ai(file_path: &Path, backend: Backend, model: Option<String>) {
     // Build prompt and call AI
     let mut prompt = build_analyze_prompt(ANALYZE_PROMPT, &context);
     append_extra_context(&mut prompt);
-    let options = if let Some(ref m) = model {
-        AnalyzeOptions::with_model(m).with_backend(backend)
-    } else {
-        AnalyzeOptions::default().with_backend(backend)
-    };
 
     println!("## Analyze: {}\n", file_name);
 
-    match ai_prompt(&prompt, options) {
+    match ai.call(&prompt) {
         Ok(response) => {
             println!("{}", response);
         }
@@ -581,7 +1258,7 @@ fn analyze_with_ai(file_path: &Path, backend: Backend, model: Option<String>) {
     }
 }
 
-fn investigate_codebase(dir: &Path, question: &str, backend: Backend, model: Option<String>) {
+fn investigate_codebase(dir: &Path, question: &str, ai: &AiSettings) {
     if !dir.exists() {
         eprintln!("Error: Directory not found: {:?}", dir);
         std::process::exit(1);
@@ -620,13 +1297,8 @@ fn investigate_codebase(dir: &Path, question: &str, backend: Backend, model: Opt
 
     let mut prompt = build_investigate_prompt(INVESTIGATE_PROMPT, question, &context);
     append_extra_context(&mut prompt);
-    let options = if let Some(ref m) = model {
-        AnalyzeOptions::with_model(m).with_backend(backend)
-    } else {
-        AnalyzeOptions::default().with_backend(backend)
-    };
 
-    match ai_prompt(&prompt, options) {
+    match ai.call(&prompt) {
         Ok(response) => {
             println!("{}", response);
         }
@@ -637,7 +1309,93 @@ fn investigate_codebase(dir: &Path, question: &str, backend: Backend, model: Opt
     }
 }
 
-fn discover_architecture(goal: &str, backend: Backend, model: Option<String>, target: &Path) {
+fn fix_build_errors(
+    file: Option<&Path>,
+    target: Option<&Path>,
+    error_log: &str,
+    references: &[PathBuf],
+    out: Option<&Path>,
+    apply: bool,
+    ai: &AiSettings,
+) {
+    // The patch paths are relative to `base`
+    let (base, files, diff) = match file {
+        Some(file) => {
+            let parent = file
+                .parent()
+                .filter(|p| !p.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            let base = target.filter(|t| file.starts_with(t)).unwrap_or(parent);
+            let relative = file.strip_prefix(base).unwrap_or(file).to_path_buf();
+            (base.to_path_buf(), vec![relative], None)
+        }
+        None => {
+            let base = target.expect("target required for fix mode without a file");
+            let Some(diff) = get_working_diff(base) else {
+                eprintln!("Error: no changes against HEAD in {}", base.display());
+                std::process::exit(1);
+            };
+            let files = match Patch::parse(&diff) {
+                Ok(patch) => patch
+                    .files
+                    .iter()
+                    .filter_map(|f| f.new_path.as_ref().map(PathBuf::from))
+                    .collect(),
+                Err(e) => {
+                    eprintln!("Error: failed to read the working diff: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            (base.to_path_buf(), files, Some(diff))
+        }
+    };
+
+    let investigator = references
+        .iter()
+        .fold(ai.investigator(), |investigator, r| investigator.with_reference(r));
+
+    eprintln!("=== Fix ===");
+    eprintln!("Base: {}", base.display());
+    for f in &files {
+        eprintln!("File: {}", f.display());
+    }
+    eprintln!();
+
+    let patch = match investigator.fix(&base, &files, diff.as_deref(), error_log) {
+        Ok(patch) => patch,
+        Err(e) => {
+            eprintln!("Fix failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match out {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, patch.to_string()) {
+                eprintln!("Error: failed to write {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+            eprintln!("[fix] patch written to {}", path.display());
+        }
+        None => print!("{}", patch),
+    }
+
+    if apply {
+        match patch.apply(&base) {
+            Ok(changed) => {
+                for path in changed {
+                    eprintln!("[fix] applied: {}", path.display());
+                }
+            }
+            Err(e) => {
+                eprintln!("Error: failed to apply the patch: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+fn discover_architecture(goal: &str, ai: &AiSettings, target: &Path) {
     let cwd = target.to_path_buf();
 
     // Find src directory or use current directory
@@ -673,18 +1431,12 @@ fn discover_architecture(goal: &str, backend: Backend, model: Option<String>, ta
     let mut prompt = build_discovery_prompt(DISCOVERY_PROMPT, goal, &full_structure);
     append_extra_context(&mut prompt);
 
-    // Call AI
-    let options = if let Some(ref m) = model {
-        AnalyzeOptions::with_model(m).with_backend(backend)
-    } else {
-        AnalyzeOptions::default().with_backend(backend)
-    };
-
     println!("## Discovery: {}\n", goal);
     println!("現在の構造:\n```\n{}\n```\n", full_structure);
     println!("---\n");
 
-    match ai_prompt(&prompt, options) {
+    // Call AI
+    match ai.call(&prompt) {
         Ok(response) => {
             println!("{}", response);
         }
@@ -696,50 +1448,57 @@ fn discover_architecture(goal: &str, backend: Backend, model: Option<String>, ta
 }
 
 
-fn find_shared_modules(path_a: &Path, path_b: &Path, backend: Backend, model: Option<String>) {
-    if !path_a.exists() {
-        eprintln!("Error: Directory not found: {:?}", path_a);
-        std::process::exit(1);
-    }
-    if !path_b.exists() {
-        eprintln!("Error: Directory not found: {:?}", path_b);
-        std::process::exit(1);
+fn find_shared_modules(
+    dirs: &[PathBuf],
+    options: &SharedOptions,
+    format: Option<SharedFormat>,
+    ai: &AiSettings,
+) {
+    for dir in dirs {
+        if !dir.exists() {
+            eprintln!("Error: Directory not found: {:?}", dir);
+            std::process::exit(1);
+        }
     }
 
     eprintln!("=== Shared Code Discovery ===");
-    eprintln!("Project A: {}", path_a.display());
-    eprintln!("Project B: {}", path_b.display());
+    for (i, dir) in dirs.iter().enumerate() {
+        eprintln!("Project {}: {}", (b'A' + (i % 26) as u8) as char, dir.display());
+    }
     eprintln!();
 
     // Phase 1: Static analysis
-    let report = find_shared_candidates(path_a, path_b);
-
+    let roots: Vec<&Path> = dirs.iter().map(PathBuf::as_path).collect();
+    let report = find_shared_candidates_many_with_options(&roots, options);
     eprintln!(
-        "Scanned: {} files (A) + {} files (B)",
-        report.files_scanned_a, report.files_scanned_b
+        "Scanned: {} files in {} projects",
+        report.files_scanned.iter().sum::<usize>(),
+        report.projects.len()
     );
-    eprintln!("Found {} candidates\n", report.candidates.len());
-
-    if report.candidates.is_empty() {
+    eprintln!(
+        "Found {} clusters, {} extraction targets\n",
+        report.clusters.len(),
+        report.plan.len()
+    );
+    if let Some(format) = format {
+        println!("{}", report.render(format));
+        return;
+    }
+    if report.clusters.is_empty() {
         println!("共有候補は見つかりませんでした。");
         return;
     }
+    let report_text = report.to_prompt_string();
 
     // Print static analysis results
-    let report_text = report.to_prompt_string();
     println!("{}", report_text);
 
     // Phase 2: AI analysis
     eprintln!("--- AI Analysis ---\n");
     let mut prompt = build_find_shared_prompt(FIND_SHARED_PROMPT, &report_text);
     append_extra_context(&mut prompt);
-    let options = if let Some(ref m) = model {
-        AnalyzeOptions::with_model(m).with_backend(backend)
-    } else {
-        AnalyzeOptions::default().with_backend(backend)
-    };
 
-    match ai_prompt(&prompt, options) {
+    match ai.call(&prompt) {
         Ok(response) => {
             println!("{}", response);
         }
@@ -750,7 +1509,7 @@ fn find_shared_modules(path_a: &Path, path_b: &Path, backend: Backend, model: Op
     }
 }
 
-fn run_qdd(backend: Backend, model: Option<String>, target: &Path) {
+fn run_qdd(ai: &AiSettings, target: &Path) {
     let cwd = target.to_path_buf();
 
     // Get diff (staged + unstaged)
@@ -786,12 +1545,7 @@ fn run_qdd(backend: Backend, model: Option<String>, target: &Path) {
         .replace("{content}", &diff);
     append_extra_context(&mut prompt);
 
-    let options = if let Some(ref m) = model {
-        AnalyzeOptions::with_model(m).with_backend(backend)
-    } else {
-        AnalyzeOptions::default().with_backend(backend)
-    };
-    match ai_prompt(&prompt, options) {
+    match ai.call(&prompt) {
         Ok(response) => {
             println!("{}", response);
 
@@ -808,9 +1562,30 @@ fn run_qdd(backend: Backend, model: Option<String>, target: &Path) {
     }
 }
 
-fn run_hook(backend: Backend, model: Option<String>, prompt_type: PromptType, context_enabled: bool, target: &Path) {
+fn run_hook(ai: &AiSettings, prompt_type: PromptType, context_enabled: bool, target: &Path) {
     let cwd = target.to_path_buf();
 
+    // Architecture rules are checked on every staged file, whatever the AI
+    // says and whatever the budget allows
+    let violations = check_staged_rules(ai, &cwd);
+    for violation in &violations {
+        eprintln!("[rule] {}", violation.message());
+    }
+
+    // Out of budget: never block the commit, just review less (or not at all)
+    let (prompt_type, context_enabled, degraded) = match ai.exhausted_action() {
+        Some(BudgetAction::Skip) => {
+            eprintln!("⚠ Spend budget exhausted — skipping review.");
+            enforce_rules(&violations);
+            return;
+        }
+        Some(BudgetAction::Quick) => {
+            eprintln!("⚠ Spend budget exhausted — using quick review.");
+            (PromptType::Quick, false, true)
+        }
+        None => (prompt_type, context_enabled, false),
+    };
+
     // Get staged diff
     let diff = {
         let mut cmd = Command::new("git");
@@ -823,12 +1598,14 @@ fn run_hook(backend: Backend, model: Option<String>, prompt_type: PromptType, co
         match cmd.output() {
             Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout).to_string(),
             _ => {
+                enforce_rules(&violations);
                 return;
             }
         }
     };
 
     if diff.trim().is_empty() {
+        enforce_rules(&violations);
         return;
     }
 
@@ -840,6 +1617,7 @@ fn run_hook(backend: Backend, model: Option<String>, prompt_type: PromptType, co
         );
         eprintln!("  Tip: use pre-commit-review.py for large diffs (Gemini sensitive-data scan).");
         eprintln!("  Or commit with smaller, focused changesets.");
+        enforce_rules(&violations);
         return;
     }
 
@@ -884,14 +1662,24 @@ fn run_hook(backend: Backend, model: Option<String>, prompt_type: PromptType, co
             )
         }
     };
+    if !violations.is_empty() {
+        prompt.push_str("\n\n");
+        prompt.push_str(&format_rule_violations(&violations));
+    }
+    let drift = staged_drift(&cwd);
+    if !drift.is_empty() {
+        eprintln!("{}", drift.to_prompt_string());
+        prompt.push_str("\n\n");
+        prompt.push_str(&drift.to_prompt_string());
+    }
     append_extra_context(&mut prompt);
 
-    let options = if let Some(ref m) = model {
-        AnalyzeOptions::with_model(m).with_backend(backend)
+    let result = if degraded {
+        ai.call_degraded(&prompt)
     } else {
-        AnalyzeOptions::default().with_backend(backend)
+        ai.call(&prompt)
     };
-    match ai_prompt(&prompt, options) {
+    match result {
         Ok(review) => {
             eprintln!("{}\n", review);
             eprintln!("=== Review Complete ===\n");
@@ -906,6 +1694,64 @@ fn run_hook(backend: Backend, model: Option<String>, prompt_type: PromptType, co
             // Don't block on errors
         }
     }
+    enforce_rules(&violations);
+}
+
+/// Architecture rule violations in the staged files of `cwd`
+///
+/// `--relative` lists paths relative to `cwd` (and only files below it),
+/// so they join onto `cwd` even when it is not the repository root.
+fn check_staged_rules(ai: &AiSettings, cwd: &Path) -> Vec<RuleViolation> {
+    let Some(rules) = ai.rules_for(cwd) else {
+        return Vec::new();
+    };
+    let output = {
+        let mut cmd = Command::new("git");
+        cmd.args(["diff", "--cached", "--name-only", "--relative", "--diff-filter=d"]);
+        cmd.current_dir(cwd);
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            cmd.creation_flags(CREATE_NO_WINDOW);
+        }
+        cmd.output()
+    };
+    let Ok(output) = output else {
+        return Vec::new();
+    };
+    let resolver = ImportResolver::new(cwd);
+    // Check the staged blobs, not the working tree, which may hold unstaged edits
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|l| !l.is_empty())
+        .filter_map(|l| Some((cwd.join(l), staged_blob(cwd, l)?)))
+        .flat_map(|(file, source)| rules.check_source(&file, &source, cwd, &resolver))
+        .collect()
+}
+
+/// Content of a staged file (`path` relative to `cwd`)
+fn staged_blob(cwd: &Path, path: &str) -> Option<String> {
+    let mut cmd = Command::new("git");
+    cmd.args(["show", &format!(":./{}", path)]);
+    cmd.current_dir(cwd);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = cmd.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+/// Block the commit when a rule violation is configured as an error
+fn enforce_rules(violations: &[RuleViolation]) {
+    if violations.iter().any(|v| v.severity == ReviewSeverity::Error) {
+        eprintln!("[BLOCKED] Architecture rule violations found. Fix before committing.");
+        std::process::exit(1);
+    }
 }
 
 fn install_hook(target: &Path) {
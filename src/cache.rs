@@ -0,0 +1,361 @@
+//! Persistent parse cache
+//!
+//! Every analyzer needs the imports and exports of many files, and parsing
+//! them is the slow part of context gathering. `ParseCache` keeps each
+//! file's `FileAnalysis` keyed by path and validated by modification time,
+//! size and content hash, so a file is parsed again only when it changed.
+//!
+//! The process-wide cache ([`parse_cache`]) is shared by the dependency
+//! graph, importer search, context gathering, rule checks and the shared
+//! module finder. It can be saved per project and loaded by the next run.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::parser::{analyze_source, enabled_languages, FileAnalysis};
+use crate::utils::fs::{data_dir, write_atomic};
+use crate::utils::hash::fnv1a;
+
+/// Version of the on-disk format; files with another version are ignored
+const CACHE_FORMAT: u32 = 2;
+
+/// Modification times closer than this to the last check are not trusted
+///
+/// File systems record modification times with coarse granularity, so a
+/// file rewritten right after it was read can keep the same time and size.
+/// Such entries are verified by content hash instead (the "racy git" rule).
+const RACY_WINDOW_NANOS: u64 = 2_000_000_000;
+
+/// Cached analysis of one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    /// Modification time (nanoseconds since the Unix epoch)
+    modified: u64,
+    /// File size in bytes
+    size: u64,
+    /// Hash of the file content
+    hash: u64,
+    /// When the entry was last validated (nanoseconds since the Unix epoch)
+    checked: u64,
+    analysis: FileAnalysis,
+}
+
+impl CacheEntry {
+    /// Whether the file may have changed without its stamp changing
+    fn is_racy(&self) -> bool {
+        self.checked < self.modified.saturating_add(RACY_WINDOW_NANOS)
+    }
+}
+
+/// Cache file of one project, keyed by path relative to the project root
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    format: u32,
+    /// Crate version that wrote the cache; parsers change between versions
+    version: String,
+    /// Languages the writing build could parse; files of other languages
+    /// were cached as empty analyses
+    #[serde(default)]
+    languages: Vec<String>,
+    entries: HashMap<String, CacheEntry>,
+}
+
+/// Analyses of parsed files, reused until the file changes
+#[derive(Debug, Default)]
+pub struct ParseCache {
+    entries: Mutex<HashMap<PathBuf, CacheEntry>>,
+    /// Whether entries changed since the last save
+    dirty: AtomicBool,
+}
+
+impl ParseCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Analysis of `path`, parsing the file only if it changed
+    pub fn analyze(&self, path: &Path) -> Result<FileAnalysis> {
+        let metadata = fs::metadata(path)?;
+        let modified = nanos_since_epoch(metadata.modified()?);
+        let size = metadata.len();
+        if let Some(entry) = self.lock().get(path) {
+            if entry.modified == modified && entry.size == size && !entry.is_racy() {
+                return Ok(entry.analysis.clone());
+            }
+        }
+
+        // The stamp changed (or cannot be trusted): compare content
+        let source = fs::read_to_string(path)?;
+        let hash = fnv1a(source.as_bytes());
+        let checked = nanos_since_epoch(SystemTime::now());
+        if let Some(entry) = self.lock().get_mut(path).filter(|entry| entry.hash == hash) {
+            entry.modified = modified;
+            entry.size = size;
+            entry.checked = checked;
+            self.dirty.store(true, Ordering::Relaxed);
+            return Ok(entry.analysis.clone());
+        }
+
+        let analysis = analyze_source(path, &source)?;
+        let entry = CacheEntry {
+            modified,
+            size,
+            hash,
+            checked,
+            analysis: analysis.clone(),
+        };
+        self.lock().insert(path.to_path_buf(), entry);
+        self.dirty.store(true, Ordering::Relaxed);
+        Ok(analysis)
+    }
+
+    /// Whether `path` has an analysis that [`ParseCache::analyze`] can return
+    /// without reading the file
+    pub fn is_fresh(&self, path: &Path) -> bool {
+        let Ok(metadata) = fs::metadata(path) else {
+            return false;
+        };
+        let Ok(modified) = metadata.modified() else {
+            return false;
+        };
+        let modified = nanos_since_epoch(modified);
+        self.lock().get(path).is_some_and(|entry| {
+            entry.modified == modified && entry.size == metadata.len() && !entry.is_racy()
+        })
+    }
+
+    /// Forget the analysis of `path` (e.g. after the watcher saw it change)
+    pub fn invalidate(&self, path: &Path) {
+        if self.lock().remove(path).is_some() {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Number of cached files
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Check if nothing is cached
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Load the saved cache of the project at `base_path`
+    ///
+    /// Entries already in memory are kept. Returns the number of entries
+    /// loaded; a missing or outdated cache file (another crate version or
+    /// another set of `lang-*` features) loads nothing, and an
+    /// unreadable one is deleted so the next save rebuilds it.
+    pub fn load(&self, base_path: &Path) -> Result<usize> {
+        self.load_from(&parse_cache_path(base_path), base_path)
+    }
+
+    /// Save the entries under `base_path` for the next run
+    ///
+    /// Does nothing when no entry changed since the last load or save.
+    pub fn save(&self, base_path: &Path) -> Result<()> {
+        self.save_to(&parse_cache_path(base_path), base_path)
+    }
+
+    fn load_from(&self, cache_path: &Path, base_path: &Path) -> Result<usize> {
+        let Ok(json) = fs::read_to_string(cache_path) else {
+            return Ok(0);
+        };
+        let file: CacheFile = match serde_json::from_str(&json) {
+            Ok(file) => file,
+            Err(e) => {
+                log::warn!("Discarding corrupt parse cache {}: {}", cache_path.display(), e);
+                let _ = fs::remove_file(cache_path);
+                return Ok(0);
+            }
+        };
+        if file.format != CACHE_FORMAT
+            || file.version != env!("CARGO_PKG_VERSION")
+            || file.languages != enabled_languages()
+        {
+            return Ok(0);
+        }
+        let mut entries = self.lock();
+        let mut loaded = 0;
+        for (relative, entry) in file.entries {
+            entries.entry(base_path.join(relative)).or_insert_with(|| {
+                loaded += 1;
+                entry
+            });
+        }
+        Ok(loaded)
+    }
+
+    fn save_to(&self, cache_path: &Path, base_path: &Path) -> Result<()> {
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+        let entries = self
+            .lock()
+            .iter()
+            .filter(|(path, _)| path.is_file())
+            .filter_map(|(path, entry)| {
+                let relative = path.strip_prefix(base_path).ok()?;
+                Some((relative.to_string_lossy().replace('\\', "/"), entry.clone()))
+            })
+            .collect();
+        let file = CacheFile {
+            format: CACHE_FORMAT,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            languages: enabled_languages().into_iter().map(String::from).collect(),
+            entries,
+        };
+        // A reader never sees a half-written cache
+        write_atomic(cache_path, serde_json::to_string(&file)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Cache `analysis` for `path` as if the file had been parsed now
+    #[cfg(test)]
+    pub(crate) fn insert(&self, path: &Path, analysis: FileAnalysis) -> Result<()> {
+        let metadata = fs::metadata(path)?;
+        let entry = CacheEntry {
+            modified: nanos_since_epoch(metadata.modified()?),
+            size: metadata.len(),
+            hash: fnv1a(&fs::read(path)?),
+            checked: nanos_since_epoch(SystemTime::now()),
+            analysis,
+        };
+        self.lock().insert(path.to_path_buf(), entry);
+        Ok(())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<PathBuf, CacheEntry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The parse cache shared by all analyzers in this process
+pub fn parse_cache() -> &'static ParseCache {
+    static CACHE: OnceLock<ParseCache> = OnceLock::new();
+    CACHE.get_or_init(ParseCache::new)
+}
+
+/// Analyze a file through the shared parse cache
+pub(crate) fn analyze_cached(path: &Path) -> Result<FileAnalysis> {
+    parse_cache().analyze(path)
+}
+
+/// Where the parse cache of the project at `base_path` is saved
+/// (`~/.ai-code-review/cache/<project>-<hash>.json`)
+pub fn parse_cache_path(base_path: &Path) -> PathBuf {
+    let base = base_path.canonicalize().unwrap_or_else(|_| base_path.to_path_buf());
+    let name = base
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "root".to_string());
+    let hash = fnv1a(base.to_string_lossy().as_bytes());
+    data_dir().join("cache").join(format!("{}-{:016x}.json", name, hash))
+}
+
+fn nanos_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "lang-rust")]
+    use tempfile::tempdir;
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_analyze_detects_changes() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        fs::write(&file, "mod aaa;\n").unwrap();
+
+        let cache = ParseCache::new();
+        assert_eq!(cache.analyze(&file).unwrap().modules, vec!["aaa"]);
+        assert_eq!(cache.len(), 1);
+
+        // Same size, rewritten within the timestamp granularity
+        fs::write(&file, "mod bbb;\n").unwrap();
+        assert_eq!(cache.analyze(&file).unwrap().modules, vec!["bbb"]);
+
+        cache.invalidate(&file);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_is_fresh() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        fs::write(&file, "mod aaa;\n").unwrap();
+        let old = SystemTime::now() - std::time::Duration::from_secs(60);
+        fs::File::options().write(true).open(&file).unwrap().set_modified(old).unwrap();
+
+        let cache = ParseCache::new();
+        assert!(!cache.is_fresh(&file));
+        cache.analyze(&file).unwrap();
+        assert!(cache.is_fresh(&file));
+
+        fs::write(&file, "mod bbb;\n").unwrap();
+        assert!(!cache.is_fresh(&file));
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_save_and_load() {
+        let dir = tempdir().unwrap();
+        let project = dir.path().join("project");
+        fs::create_dir_all(project.join("src")).unwrap();
+        let file = project.join("src").join("lib.rs");
+        fs::write(&file, "pub mod api;\n").unwrap();
+        let cache_path = dir.path().join("cache.json");
+
+        let cache = ParseCache::new();
+        cache.analyze(&file).unwrap();
+        cache.save_to(&cache_path, &project).unwrap();
+        let saved = fs::read_to_string(&cache_path).unwrap();
+        assert!(saved.contains("\"src/lib.rs\""));
+
+        let restored = ParseCache::new();
+        assert_eq!(restored.load_from(&cache_path, &project).unwrap(), 1);
+        assert_eq!(restored.analyze(&file).unwrap().modules, vec!["api"]);
+
+        // Caches written by another version are ignored
+        fs::write(&cache_path, saved.replace(env!("CARGO_PKG_VERSION"), "0.0.0-old")).unwrap();
+        assert_eq!(ParseCache::new().load_from(&cache_path, &project).unwrap(), 0);
+
+        // So are caches written by a build with other languages enabled
+        let other = saved.replace("\"languages\":[", "\"languages\":[\"cobol\",");
+        assert_ne!(other, saved);
+        fs::write(&cache_path, other).unwrap();
+        assert_eq!(ParseCache::new().load_from(&cache_path, &project).unwrap(), 0);
+
+        // A corrupt cache is discarded and rebuilt by the next save
+        fs::write(&cache_path, &saved[..saved.len() / 2]).unwrap();
+        let rebuilt = ParseCache::new();
+        assert_eq!(rebuilt.load_from(&cache_path, &project).unwrap(), 0);
+        assert!(!cache_path.exists());
+        rebuilt.analyze(&file).unwrap();
+        rebuilt.save_to(&cache_path, &project).unwrap();
+        assert_eq!(ParseCache::new().load_from(&cache_path, &project).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_parse_cache_path_per_project() {
+        let a = parse_cache_path(Path::new("/work/a/app"));
+        let b = parse_cache_path(Path::new("/work/b/app"));
+        assert_ne!(a, b);
+        assert!(a.file_name().unwrap().to_string_lossy().starts_with("app-"));
+    }
+}
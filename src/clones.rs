@@ -0,0 +1,340 @@
+//! Function-level clone detection
+//!
+//! Each function is turned into a token stream with identifiers and
+//! literals abstracted, the k-grams of that stream are hashed, and
+//! winnowing keeps a small, position-independent set of fingerprints.
+//! Functions of two projects that share most of their fingerprints are
+//! reported as clones:
+//!
+//! - Type-1: identical apart from whitespace and comments
+//! - Type-2: identical apart from identifiers and literals
+//! - Type-3: similar, with statements added, removed or changed
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::parser::symbols::{function_tokens, supports_symbols, Token};
+use crate::utils::hash::{fnv1a, fnv1a_extend};
+
+/// Tokens per hashed k-gram
+const KGRAM: usize = 5;
+
+/// Winnowing window (in k-grams); any match of `KGRAM + WINDOW - 1` tokens
+/// shares at least one fingerprint
+const WINDOW: usize = 4;
+
+/// Functions with fewer tokens are too small to be worth extracting
+const MIN_CLONE_TOKENS: usize = 40;
+
+/// Functions with fewer lines are too small to be worth extracting
+const MIN_CLONE_LINES: usize = 5;
+
+/// Minimum fingerprint overlap (Jaccard) for a Type-3 clone
+const MIN_CLONE_SIMILARITY: f64 = 0.6;
+
+/// Fingerprints shared by more functions are boilerplate and ignored
+const MAX_POSTINGS: usize = 100;
+
+/// Clones reported per function
+const MAX_MATCHES_PER_FUNCTION: usize = 3;
+
+/// How far two cloned functions differ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloneType {
+    /// Identical apart from whitespace and comments
+    Exact,
+    /// Identical apart from identifiers and literals
+    Renamed,
+    /// Similar, with some statements changed
+    Gapped,
+}
+
+impl std::fmt::Display for CloneType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CloneType::Exact => write!(f, "Type-1クローン（完全一致）"),
+            CloneType::Renamed => write!(f, "Type-2クローン（識別子・リテラルのみ異なる）"),
+            CloneType::Gapped => write!(f, "Type-3クローン（一部変更あり）"),
+        }
+    }
+}
+
+/// A function of project A cloned in project B
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CloneMatch {
+    pub clone_type: CloneType,
+    /// Function name in project A
+    pub name_a: String,
+    /// Function name in project B
+    pub name_b: String,
+    /// Line range in project A (1-based, inclusive)
+    pub lines_a: (usize, usize),
+    /// Line range in project B (1-based, inclusive)
+    pub lines_b: (usize, usize),
+    /// Fingerprint overlap 0.0-1.0 (1.0 for Type-1 and Type-2)
+    pub similarity: f64,
+}
+
+/// A clone together with the files it was found in
+#[derive(Debug, Clone)]
+pub(crate) struct ClonePair {
+    /// Path in project A (relative to project root)
+    pub path_a: String,
+    /// Path in project B (relative to project root)
+    pub path_b: String,
+    pub clone: CloneMatch,
+}
+
+/// A function prepared for comparison
+#[derive(Debug, Clone)]
+pub(crate) struct FunctionUnit {
+    /// Path relative to the project root
+    pub path: String,
+    pub name: String,
+    pub lines: (usize, usize),
+    /// Languages whose token streams are comparable
+    family: &'static str,
+    /// Hash of the raw token stream
+    exact_hash: u64,
+    /// Hash of the normalized token stream
+    renamed_hash: u64,
+    fingerprints: HashSet<u64>,
+}
+
+/// Collect the functions of `files` that are large enough to be clones
+pub(crate) fn collect_functions(root: &Path, files: &[PathBuf]) -> Vec<FunctionUnit> {
+    let mut units = Vec::new();
+    for file in files {
+        let Some(family) = language_family(file) else {
+            continue;
+        };
+        let Ok(source) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        let Some(functions) = function_tokens(file, &source) else {
+            continue;
+        };
+        let rel = file.strip_prefix(root).unwrap_or(file).display().to_string();
+        for function in functions {
+            let symbol = function.symbol;
+            let tokens = function.tokens;
+            if tokens.len() < MIN_CLONE_TOKENS
+                || symbol.end_line + 1 - symbol.start_line < MIN_CLONE_LINES
+            {
+                continue;
+            }
+            units.push(FunctionUnit {
+                path: rel.clone(),
+                name: symbol.name,
+                lines: (symbol.start_line, symbol.end_line),
+                family,
+                exact_hash: stream_hash(tokens.iter().map(|t| t.text.as_str())),
+                renamed_hash: stream_hash(tokens.iter().map(|t| t.normalized.as_str())),
+                fingerprints: winnow(&tokens),
+            });
+        }
+    }
+    units
+}
+
+/// Find the functions of `units_a` cloned in `units_b`
+pub(crate) fn match_clones(units_a: &[FunctionUnit], units_b: &[FunctionUnit]) -> Vec<ClonePair> {
+    let mut postings: HashMap<(&str, u64), Vec<usize>> = HashMap::new();
+    for (i, unit) in units_b.iter().enumerate() {
+        for fingerprint in &unit.fingerprints {
+            postings.entry((unit.family, *fingerprint)).or_default().push(i);
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for unit_a in units_a {
+        let mut shared: HashMap<usize, usize> = HashMap::new();
+        for fingerprint in &unit_a.fingerprints {
+            let Some(list) = postings.get(&(unit_a.family, *fingerprint)) else {
+                continue;
+            };
+            if list.len() > MAX_POSTINGS {
+                continue;
+            }
+            for &i in list {
+                *shared.entry(i).or_default() += 1;
+            }
+        }
+
+        let mut matches: Vec<(usize, CloneMatch)> = shared
+            .into_iter()
+            .filter_map(|(i, count)| Some((i, classify(unit_a, &units_b[i], count)?)))
+            .collect();
+        matches.sort_by(|(_, a), (_, b)| {
+            b.similarity
+                .partial_cmp(&a.similarity)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.clone_type.cmp(&b.clone_type))
+        });
+        matches.truncate(MAX_MATCHES_PER_FUNCTION);
+
+        pairs.extend(matches.into_iter().map(|(i, clone)| ClonePair {
+            path_a: unit_a.path.clone(),
+            path_b: units_b[i].path.clone(),
+            clone,
+        }));
+    }
+    pairs
+}
+
+/// Decide whether two functions sharing `shared` fingerprints are clones
+fn classify(a: &FunctionUnit, b: &FunctionUnit, shared: usize) -> Option<CloneMatch> {
+    let (clone_type, similarity) = if a.exact_hash == b.exact_hash {
+        (CloneType::Exact, 1.0)
+    } else if a.renamed_hash == b.renamed_hash {
+        (CloneType::Renamed, 1.0)
+    } else {
+        let union = a.fingerprints.len() + b.fingerprints.len() - shared;
+        let similarity = shared as f64 / union.max(1) as f64;
+        if similarity < MIN_CLONE_SIMILARITY {
+            return None;
+        }
+        (CloneType::Gapped, similarity)
+    };
+    Some(CloneMatch {
+        clone_type,
+        name_a: a.name.clone(),
+        name_b: b.name.clone(),
+        lines_a: a.lines,
+        lines_b: b.lines,
+        similarity,
+    })
+}
+
+/// Group of languages whose normalized token streams can be compared
+fn language_family(path: &Path) -> Option<&'static str> {
+    if !supports_symbols(path) {
+        return None;
+    }
+    match path.extension().and_then(|e| e.to_str())? {
+        "rs" => Some("rust"),
+        "ts" | "tsx" | "js" | "jsx" => Some("typescript"),
+        "py" => Some("python"),
+        _ => None,
+    }
+}
+
+/// Hash of a whole token stream
+fn stream_hash<'a>(tokens: impl Iterator<Item = &'a str>) -> u64 {
+    tokens.fold(fnv1a(b""), |hash, token| {
+        fnv1a_extend(fnv1a_extend(hash, token.as_bytes()), b"\0")
+    })
+}
+
+/// Winnowed fingerprints of the normalized token stream
+///
+/// Keeps the minimum k-gram hash of every window of `WINDOW` consecutive
+/// k-grams (the rightmost one on ties), so the fingerprints do not depend
+/// on where the code sits in the function.
+fn winnow(tokens: &[Token]) -> HashSet<u64> {
+    let hashes: Vec<u64> = tokens
+        .windows(KGRAM)
+        .map(|gram| stream_hash(gram.iter().map(|t| t.normalized.as_str())))
+        .collect();
+    if hashes.len() <= WINDOW {
+        return hashes.iter().min().copied().into_iter().collect();
+    }
+    hashes
+        .windows(WINDOW)
+        .filter_map(|window| {
+            window
+                .iter()
+                .enumerate()
+                .min_by(|(i, a), (j, b)| a.cmp(b).then(j.cmp(i)))
+                .map(|(_, hash)| *hash)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[cfg(feature = "lang-rust")]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const ORIGINAL: &str = r#"
+pub fn total_weight(items: &[Item], factor: f64) -> f64 {
+    let mut total = 0.0;
+    for item in items {
+        if item.weight > 10.0 {
+            total += item.weight * factor;
+        } else {
+            total += item.weight;
+        }
+    }
+    total
+}
+"#;
+
+    fn units(source: &str) -> (tempfile::TempDir, Vec<FunctionUnit>) {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("calc.rs");
+        std::fs::write(&file, source).unwrap();
+        let units = collect_functions(dir.path(), &[file]);
+        (dir, units)
+    }
+
+    fn clone_type(a: &str, b: &str) -> Option<CloneType> {
+        let (_dir_a, units_a) = units(a);
+        let (_dir_b, units_b) = units(b);
+        match_clones(&units_a, &units_b)
+            .first()
+            .map(|pair| pair.clone.clone_type)
+    }
+
+    #[test]
+    fn test_exact_clone() {
+        let reformatted = format!("// copied\n{}", ORIGINAL.replace("    ", "  "));
+        assert_eq!(clone_type(ORIGINAL, &reformatted), Some(CloneType::Exact));
+    }
+
+    #[test]
+    fn test_renamed_clone() {
+        let renamed = ORIGINAL
+            .replace("total_weight", "sum_mass")
+            .replace("total", "acc")
+            .replace("10.0", "25.0");
+        let (_dir_a, units_a) = units(ORIGINAL);
+        let (_dir_b, units_b) = units(&renamed);
+        let pairs = match_clones(&units_a, &units_b);
+        assert_eq!(pairs.len(), 1);
+        let clone = &pairs[0].clone;
+        assert_eq!(clone.clone_type, CloneType::Renamed);
+        assert_eq!(clone.name_a, "total_weight");
+        assert_eq!(clone.name_b, "sum_mass");
+        assert_eq!(clone.lines_a, (2, 12));
+        assert_eq!(pairs[0].path_b, "calc.rs");
+    }
+
+    #[test]
+    fn test_gapped_clone() {
+        let gapped = ORIGINAL.replace(
+            "    total\n",
+            "    log::debug!(\"total: {}\", total);\n    total\n",
+        );
+        assert_eq!(clone_type(ORIGINAL, &gapped), Some(CloneType::Gapped));
+    }
+
+    #[test]
+    fn test_unrelated_functions() {
+        let other = r#"
+pub fn parse_header(line: &str) -> Option<(String, String)> {
+    let (key, value) = line.split_once(':')?;
+    let key = key.trim().to_lowercase();
+    if key.is_empty() {
+        return None;
+    }
+    Some((key, value.trim().to_string()))
+}
+"#;
+        assert_eq!(clone_type(ORIGINAL, other), None);
+    }
+}
@@ -5,13 +5,17 @@
 
 use std::fs;
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::analyzer::find_importers;
+use crate::analyzer::find_importers_with_resolver;
+use crate::cache::analyze_cached;
+use crate::coupling::{unbalanced_coupling, CouplingEdge};
 use crate::error::Result;
 use crate::git::get_cochanged_files;
+use crate::graph::DependencyGraph;
 use crate::modules::{generate_module_tree, get_sibling_files};
-use crate::parser::analyze_file;
+use crate::parser::{FileAnalysis, ImportKind, Visibility, SYSTEM_INCLUDE};
+use crate::resolver::ImportResolver;
 use crate::utils::fs::is_source_file;
 
 /// Candidate filenames to search for project README files
@@ -42,10 +46,20 @@ pub struct RelatedFile {
 pub struct DependencyInfo {
     /// Modules/files this file imports
     pub imports: Vec<String>,
+    /// Project files the imports resolve to (relative to the project root)
+    pub import_files: Vec<String>,
     /// Files that import this file
     pub imported_by: Vec<String>,
+    /// Items this file exports, with non-public visibility noted
+    /// (e.g. `parse (pub(crate))`)
+    pub exports: Vec<String>,
+    /// Child modules declared by this file (Rust `mod foo;`)
+    pub modules: Vec<String>,
 }
 
+/// Maximum number of unbalanced coupling edges included in a context
+const MAX_COUPLING_EDGES: usize = 5;
+
 /// Complete project context for a file
 #[derive(Debug, Clone)]
 pub struct ProjectContext {
@@ -59,6 +73,9 @@ pub struct ProjectContext {
     pub sibling_files: Vec<String>,
     /// Project requirements and documentation context
     pub requirements: RequirementsContext,
+    /// Worst unbalanced coupling edges, those touching this file first
+    /// (filled by [`gather_coupling_context`] for architecture reviews)
+    pub coupling: Vec<CouplingEdge>,
 }
 
 impl ProjectContext {
@@ -70,6 +87,7 @@ impl ProjectContext {
             dependencies: DependencyInfo::default(),
             sibling_files: Vec::new(),
             requirements: RequirementsContext::default(),
+            coupling: Vec::new(),
         }
     }
 
@@ -81,13 +99,52 @@ impl ProjectContext {
         crate::prompt::format_project_context(self)
     }
 
+    /// Names of the sections that carry information (for usage accounting)
+    pub fn section_names(&self) -> Vec<&'static str> {
+        let mut sections = Vec::new();
+        if self.requirements.description.is_some() {
+            sections.push("description");
+        }
+        if self.requirements.readme_summary.is_some() {
+            sections.push("readme");
+        }
+        if self.requirements.module_docs.is_some() {
+            sections.push("module_docs");
+        }
+        if !self.module_tree.is_empty() {
+            sections.push("module_tree");
+        }
+        if !self.related_files.is_empty() {
+            sections.push("related_files");
+        }
+        if !self.dependencies.imports.is_empty()
+            || !self.dependencies.import_files.is_empty()
+            || !self.dependencies.imported_by.is_empty()
+        {
+            sections.push("dependencies");
+        }
+        if !self.dependencies.exports.is_empty() || !self.dependencies.modules.is_empty() {
+            sections.push("public_api");
+        }
+        if !self.sibling_files.is_empty() {
+            sections.push("sibling_files");
+        }
+        if !self.coupling.is_empty() {
+            sections.push("coupling");
+        }
+        sections
+    }
+
     /// Check if the context has any useful information
     pub fn is_empty(&self) -> bool {
         self.module_tree.is_empty()
             && self.related_files.is_empty()
             && self.dependencies.imports.is_empty()
             && self.dependencies.imported_by.is_empty()
+            && self.dependencies.exports.is_empty()
+            && self.dependencies.modules.is_empty()
             && self.sibling_files.is_empty()
+            && self.coupling.is_empty()
             && self.requirements.description.is_none()
             && self.requirements.readme_summary.is_none()
             && self.requirements.module_docs.is_none()
@@ -181,6 +238,147 @@ pub fn gather_requirements(base_path: &Path) -> RequirementsContext {
 /// # Returns
 /// A `ProjectContext` containing all gathered information
 pub fn gather_context(file_path: &Path, base_path: &Path, lookback: usize) -> Result<ProjectContext> {
+    gather_context_with_include_dirs(file_path, base_path, lookback, &[])
+}
+
+/// Gather context, resolving C/C++ includes against extra include directories
+///
+/// Same as [`gather_context`], but quoted and angle includes are also looked
+/// up in `include_dirs` (relative ones are taken from `base_path`) when
+/// listing the files that include `file_path`.
+pub fn gather_context_with_include_dirs(
+    file_path: &Path,
+    base_path: &Path,
+    lookback: usize,
+    include_dirs: &[PathBuf],
+) -> Result<ProjectContext> {
+    let resolver = ImportResolver::new(base_path).with_include_dirs(include_dirs);
+    gather_context_with_resolver(file_path, base_path, lookback, &resolver)
+}
+
+/// Gather context, resolving imports to project files with `resolver`
+///
+/// Same as [`gather_context`], but the files this file imports and the
+/// files importing it come from `resolver` (include directories, Python
+/// package roots, `tsconfig.json` paths).
+pub fn gather_context_with_resolver(
+    file_path: &Path,
+    base_path: &Path,
+    lookback: usize,
+    resolver: &ImportResolver,
+) -> Result<ProjectContext> {
+    let mut dependencies = DependencyInfo::default();
+    if let Ok(analysis) = analyze_cached(file_path) {
+        let mut import_files: Vec<String> = analysis
+            .imports
+            .iter()
+            .flat_map(|import| resolver.resolve(import, file_path))
+            .map(|file| {
+                file.strip_prefix(base_path)
+                    .unwrap_or(&file)
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        import_files.sort();
+        import_files.dedup();
+        dependencies = describe_analysis(analysis);
+        dependencies.import_files = import_files;
+    }
+
+    // Find files that import this file
+    dependencies.imported_by = find_importers_with_resolver(file_path, base_path, resolver);
+
+    Ok(assemble_context(file_path, base_path, lookback, dependencies))
+}
+
+/// Gather context, taking dependencies from a prebuilt project graph
+///
+/// Same as [`gather_context`], but imports and importers come from `graph`
+/// instead of re-walking and re-parsing the project for every file.
+pub fn gather_context_with_graph(
+    file_path: &Path,
+    base_path: &Path,
+    lookback: usize,
+    graph: &DependencyGraph,
+) -> Result<ProjectContext> {
+    let analysis = match graph.analysis(file_path) {
+        Some(analysis) => Some(analysis.clone()),
+        None => analyze_cached(file_path).ok(),
+    };
+    let mut dependencies = analysis.map(describe_analysis).unwrap_or_default();
+    dependencies.import_files = graph
+        .dependencies(file_path)
+        .iter()
+        .map(|file| graph.display_path(file))
+        .collect();
+    dependencies.imported_by = graph
+        .importers(file_path)
+        .iter()
+        .map(|file| graph.display_path(file))
+        .collect();
+
+    Ok(assemble_context(file_path, base_path, lookback, dependencies))
+}
+
+/// Top unbalanced coupling edges of the project, those touching
+/// `file_path` first
+///
+/// The project-wide pass runs once per graph (see
+/// [`crate::coupling::unbalanced_coupling`]); each file only filters it.
+pub fn gather_coupling_context(file_path: &Path, lookback: usize, graph: &DependencyGraph) -> Vec<CouplingEdge> {
+    let all = unbalanced_coupling(graph, lookback);
+    let file = graph.display_path(file_path);
+    let touching = all.iter().filter(|edge| edge.from == file || edge.to == file);
+    let others = all.iter().filter(|edge| edge.from != file && edge.to != file);
+    touching.chain(others).take(MAX_COUPLING_EDGES).cloned().collect()
+}
+
+/// Imports, exports and child modules of an analyzed file
+fn describe_analysis(analysis: FileAnalysis) -> DependencyInfo {
+    let imports = analysis
+        .imports
+        .iter()
+        .map(|i| {
+            let import = if i.items.iter().any(|item| item == SYSTEM_INCLUDE) {
+                format!("<{}>", i.module_path)
+            } else if i.items.is_empty() {
+                i.module_path.clone()
+            } else {
+                format!("{}::{{{}}}", i.module_path, i.items.join(", "))
+            };
+            match i.kind {
+                ImportKind::Static => import,
+                ImportKind::TypeOnly => format!("{} (type-only)", import),
+                ImportKind::Dynamic => format!("{} (dynamic)", import),
+                ImportKind::Optional => format!("{} (optional)", import),
+            }
+        })
+        .collect();
+    let exports = analysis
+        .exports
+        .iter()
+        .map(|name| match analysis.export_visibility(name) {
+            Visibility::Public => name.clone(),
+            visibility => format!("{} ({})", name, visibility.label()),
+        })
+        .collect();
+
+    DependencyInfo {
+        imports,
+        exports,
+        modules: analysis.modules,
+        ..DependencyInfo::default()
+    }
+}
+
+/// Combine dependency information with the rest of the project context
+fn assemble_context(
+    file_path: &Path,
+    base_path: &Path,
+    lookback: usize,
+    dependencies: DependencyInfo,
+) -> ProjectContext {
     // Get module tree
     let src_path = if base_path.join("src").exists() {
         base_path.join("src")
@@ -200,38 +398,20 @@ pub fn gather_context(file_path: &Path, base_path: &Path, lookback: usize) -> Re
         })
         .collect();
 
-    // Analyze imports/exports
-    let mut dependencies = DependencyInfo::default();
-    if let Ok(analysis) = analyze_file(file_path) {
-        dependencies.imports = analysis
-            .imports
-            .iter()
-            .map(|i| {
-                if i.items.is_empty() {
-                    i.module_path.clone()
-                } else {
-                    format!("{}::{{{}}}", i.module_path, i.items.join(", "))
-                }
-            })
-            .collect();
-    }
-
-    // Find files that import this file
-    dependencies.imported_by = find_importers(file_path, base_path);
-
     // Get sibling files
     let sibling_files = get_sibling_files(file_path);
 
     // Gather project requirements
     let requirements = gather_requirements(base_path);
 
-    Ok(ProjectContext {
+    ProjectContext {
         module_tree,
         related_files,
         dependencies,
         sibling_files,
         requirements,
-    })
+        coupling: Vec::new(),
+    }
 }
 
 /// Gather context with default settings
@@ -319,12 +499,14 @@ pub fn gather_raw_context(file_path: &Path, base_path: &Path, max_files: usize,
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::coupling::{CouplingDistance, IntegrationStrength};
     use std::path::PathBuf;
 
     #[test]
     fn test_empty_context() {
         let ctx = ProjectContext::empty();
         assert!(ctx.is_empty());
+        assert!(ctx.section_names().is_empty());
         assert!(ctx.to_prompt_string().is_empty() || ctx.to_prompt_string().trim().is_empty());
     }
 
@@ -338,17 +520,38 @@ mod tests {
             }],
             dependencies: DependencyInfo {
                 imports: vec!["std::path::Path".to_string()],
+                import_files: vec!["src/config.rs".to_string()],
                 imported_by: vec!["main.rs".to_string()],
+                exports: vec!["run".to_string(), "parse (pub(crate))".to_string()],
+                modules: vec!["config".to_string()],
             },
             sibling_files: vec!["other.rs".to_string()],
             requirements: RequirementsContext::default(),
+            coupling: vec![CouplingEdge {
+                from: "src/api/handler.rs".to_string(),
+                to: "src/main.rs".to_string(),
+                strength: IntegrationStrength::Intrusive,
+                distance: CouplingDistance::CrossModule,
+                churn: 7,
+                volatile: true,
+                unbalanced: true,
+            }],
         };
 
         let prompt = ctx.to_prompt_string();
         assert!(prompt.contains("プロジェクト構造"));
         assert!(prompt.contains("最近一緒に変更されたファイル"));
         assert!(prompt.contains("依存関係"));
+        assert!(prompt.contains("依存ファイル: src/config.rs"));
+        assert!(prompt.contains("公開API: run, parse (pub(crate))"));
+        assert!(prompt.contains("子モジュール: config"));
         assert!(prompt.contains("同じディレクトリのファイル"));
+        assert!(prompt.contains("## 結合バランス"));
+        assert!(prompt.contains("src/api/handler.rs → src/main.rs: intrusive / cross-module / 変更7回"));
+        assert_eq!(
+            ctx.section_names(),
+            vec!["module_tree", "related_files", "dependencies", "public_api", "sibling_files", "coupling"]
+        );
     }
 
     #[test]
@@ -391,6 +594,7 @@ mod tests {
                 readme_summary: Some("# Test README\n\nThis is a test.".to_string()),
                 module_docs: None,
             },
+            coupling: Vec::new(),
         };
 
         let prompt = ctx.to_prompt_string();
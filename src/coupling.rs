@@ -0,0 +1,391 @@
+//! Coupling balance metrics
+//!
+//! Estimates the three dimensions of balanced coupling for every edge of a
+//! [`DependencyGraph`]:
+//!
+//! - integration strength: how much of the imported file's internals the
+//!   importer relies on (public contract, whole model, or non-public items)
+//! - distance: how far apart the two files are (same module, sibling
+//!   modules, across the package, across packages)
+//! - volatility: how often the imported file changes (git churn)
+//!
+//! Strong coupling is fine over short distances and weak coupling over long
+//! ones. An edge that is strong *and* distant is unbalanced, and it hurts
+//! when the imported file is volatile.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+use crate::git::get_file_churn;
+use crate::graph::DependencyGraph;
+use crate::parser::Visibility;
+
+/// Manifests that mark the root of a package (crate, npm package, ...)
+const PACKAGE_MANIFESTS: &[&str] = &["Cargo.toml", "package.json", "pyproject.toml", "setup.py", "go.mod"];
+
+/// Minimum churn for a file to count as volatile
+const MIN_VOLATILE_CHURN: usize = 3;
+
+/// How much of the imported file an importer depends on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IntegrationStrength {
+    /// Only items that are part of the public API
+    Contract,
+    /// Everything the file exposes (`*` imports), or crate-internal items
+    Model,
+    /// Items restricted to an enclosing module (`pub(super)`) or private by
+    /// convention (`_name`)
+    Intrusive,
+}
+
+/// How far apart the importer and the imported file are
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CouplingDistance {
+    /// Same directory
+    SameModule,
+    /// Neighbouring directories (shared parent, or parent and child)
+    Sibling,
+    /// Different parts of the same package
+    CrossModule,
+    /// Different packages (crates, npm packages, ...)
+    CrossPackage,
+}
+
+impl IntegrationStrength {
+    /// Short label used when presenting metrics
+    pub fn label(&self) -> &'static str {
+        match self {
+            IntegrationStrength::Contract => "contract",
+            IntegrationStrength::Model => "model",
+            IntegrationStrength::Intrusive => "intrusive",
+        }
+    }
+
+    fn weight(&self) -> usize {
+        *self as usize + 1
+    }
+}
+
+impl CouplingDistance {
+    /// Short label used when presenting metrics
+    pub fn label(&self) -> &'static str {
+        match self {
+            CouplingDistance::SameModule => "same-module",
+            CouplingDistance::Sibling => "sibling",
+            CouplingDistance::CrossModule => "cross-module",
+            CouplingDistance::CrossPackage => "cross-package",
+        }
+    }
+
+    fn weight(&self) -> usize {
+        *self as usize + 1
+    }
+}
+
+/// Coupling metrics of one dependency edge
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CouplingEdge {
+    /// Importing file (relative to the project root)
+    pub from: String,
+    /// Imported file (relative to the project root)
+    pub to: String,
+    /// Integration strength of the imports along the edge
+    pub strength: IntegrationStrength,
+    /// Distance between the two files
+    pub distance: CouplingDistance,
+    /// Commits touching the imported file within the lookback window
+    pub churn: usize,
+    /// Whether the imported file counts as volatile
+    pub volatile: bool,
+    /// Strong and distant coupling to a volatile file
+    pub unbalanced: bool,
+}
+
+impl CouplingEdge {
+    /// Pain score used to rank edges: strength × distance × (1 + churn)
+    pub fn score(&self) -> usize {
+        self.strength.weight() * self.distance.weight() * (1 + self.churn)
+    }
+}
+
+/// Unbalanced edges of a graph and the churn lookback they were computed for
+///
+/// Held by [`DependencyGraph`] and cleared whenever its edges change, so
+/// reviews of many files share one coupling pass.
+#[derive(Debug, Default)]
+pub(crate) struct CouplingCache(Mutex<Option<(usize, Arc<Vec<CouplingEdge>>)>>);
+
+impl Clone for CouplingCache {
+    fn clone(&self) -> Self {
+        let cached = self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+        Self(Mutex::new(cached))
+    }
+}
+
+impl CouplingCache {
+    /// Drop the cached edges
+    pub(crate) fn clear(&mut self) {
+        *self.0.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+    }
+}
+
+/// Unbalanced coupling edges of the graph, worst first
+///
+/// Churn is read from git over the last `lookback` commits. The result is
+/// cached in the graph until its edges change.
+pub fn unbalanced_coupling(graph: &DependencyGraph, lookback: usize) -> Arc<Vec<CouplingEdge>> {
+    let mut cached = graph
+        .coupling_cache()
+        .0
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some((cached_lookback, edges)) = cached.as_ref() {
+        if *cached_lookback == lookback {
+            return Arc::clone(edges);
+        }
+    }
+    let churn = get_file_churn(graph.base_path(), lookback);
+    let edges: Arc<Vec<CouplingEdge>> = Arc::new(
+        analyze_coupling(graph, &churn)
+            .into_iter()
+            .filter(|edge| edge.unbalanced)
+            .collect(),
+    );
+    *cached = Some((lookback, Arc::clone(&edges)));
+    edges
+}
+
+/// Compute coupling metrics for every edge of the graph
+///
+/// `churn` maps files to the number of recent commits touching them (see
+/// `get_file_churn`). Without churn data every file is treated as volatile,
+/// so imbalance is judged on strength and distance alone.
+///
+/// Edges are returned with unbalanced ones first, each group ordered by
+/// descending [`CouplingEdge::score`].
+pub fn analyze_coupling(graph: &DependencyGraph, churn: &HashMap<PathBuf, usize>) -> Vec<CouplingEdge> {
+    let threshold = volatility_threshold(churn);
+    let mut edges: Vec<CouplingEdge> = graph
+        .edges()
+        .map(|(from, to)| {
+            let strength = integration_strength(graph, from, to);
+            let distance = coupling_distance(from, to);
+            let file_churn = churn.get(to).copied().unwrap_or(0);
+            let volatile = churn.is_empty() || file_churn >= threshold;
+            CouplingEdge {
+                from: graph.display_path(from),
+                to: graph.display_path(to),
+                strength,
+                distance,
+                churn: file_churn,
+                volatile,
+                unbalanced: volatile && is_unbalanced(strength, distance),
+            }
+        })
+        .collect();
+
+    edges.sort_by(|a, b| {
+        b.unbalanced
+            .cmp(&a.unbalanced)
+            .then_with(|| b.score().cmp(&a.score()))
+            .then_with(|| (&a.from, &a.to).cmp(&(&b.from, &b.to)))
+    });
+    edges
+}
+
+/// Strength and distance that pull in the same direction
+///
+/// Model coupling is too strong across the package; intrusive coupling is
+/// too strong anywhere outside its own module.
+fn is_unbalanced(strength: IntegrationStrength, distance: CouplingDistance) -> bool {
+    strength > IntegrationStrength::Contract && strength.weight() + distance.weight() >= 5
+}
+
+/// Strongest integration among the imports linking `from` to `to`
+fn integration_strength(graph: &DependencyGraph, from: &Path, to: &Path) -> IntegrationStrength {
+    let Some(target) = graph.analysis(to) else {
+        return IntegrationStrength::Contract;
+    };
+    graph
+        .edge_imports(from, to)
+        .into_iter()
+        .flat_map(|import| import.items.iter())
+        .filter(|item| !item.starts_with("as ") && *item != "self")
+        .map(|item| {
+            if item == "*" {
+                return IntegrationStrength::Model;
+            }
+            let name = item.rsplit("::").next().unwrap_or(item);
+            if name.starts_with('_') && !name.starts_with("__") {
+                return IntegrationStrength::Intrusive;
+            }
+            match target.export_visibility(name) {
+                Visibility::Restricted => IntegrationStrength::Intrusive,
+                Visibility::Crate => IntegrationStrength::Model,
+                Visibility::Public => IntegrationStrength::Contract,
+            }
+        })
+        .max()
+        .unwrap_or(IntegrationStrength::Contract)
+}
+
+/// Distance between two files, from their directories and package roots
+fn coupling_distance(from: &Path, to: &Path) -> CouplingDistance {
+    let (Some(from_dir), Some(to_dir)) = (from.parent(), to.parent()) else {
+        return CouplingDistance::SameModule;
+    };
+    if from_dir == to_dir {
+        return CouplingDistance::SameModule;
+    }
+    if package_root(from_dir) != package_root(to_dir) {
+        return CouplingDistance::CrossPackage;
+    }
+    let neighbours = from_dir.parent() == Some(to_dir)
+        || to_dir.parent() == Some(from_dir)
+        || from_dir.parent() == to_dir.parent();
+    if neighbours {
+        CouplingDistance::Sibling
+    } else {
+        CouplingDistance::CrossModule
+    }
+}
+
+/// Nearest directory at or above `dir` with a package manifest
+fn package_root(dir: &Path) -> Option<&Path> {
+    dir.ancestors()
+        .find(|ancestor| PACKAGE_MANIFESTS.iter().any(|m| ancestor.join(m).is_file()))
+}
+
+/// Churn at which a file counts as volatile: the upper quartile of the
+/// files that changed at all, and at least [`MIN_VOLATILE_CHURN`]
+fn volatility_threshold(churn: &HashMap<PathBuf, usize>) -> usize {
+    let mut counts: Vec<usize> = churn.values().copied().filter(|&c| c > 0).collect();
+    if counts.is_empty() {
+        return MIN_VOLATILE_CHURN;
+    }
+    counts.sort_unstable();
+    counts[counts.len() * 3 / 4].max(MIN_VOLATILE_CHURN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    /// Two crates in one workspace; files inside `core` reach into the
+    /// store at increasing distances
+    fn workspace() -> tempfile::TempDir {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, "core/Cargo.toml", "[package]\nname = \"core-lib\"\n");
+        write(root, "core/src/lib.rs", "pub mod store;\npub mod util;\nuse crate::store::*;\n");
+        write(root, "core/src/store.rs", "pub struct Store;\npub(crate) fn raw() {}\npub(super) fn hidden() {}\n");
+        write(root, "core/src/util.rs", "use crate::store::Store;\n");
+        write(root, "core/src/io/disk.rs", "use crate::store::raw;\n");
+        write(root, "core/src/io/net/socket.rs", "use crate::store::hidden;\n");
+        write(root, "app/Cargo.toml", "[package]\nname = \"app\"\n");
+        write(root, "app/src/main.rs", "use core_lib::store::Store;\n");
+        dir
+    }
+
+    fn edge<'a>(edges: &'a [CouplingEdge], from: &str, to: &str) -> &'a CouplingEdge {
+        edges
+            .iter()
+            .find(|e| e.from == from && e.to == to)
+            .unwrap_or_else(|| panic!("missing edge {} -> {}", from, to))
+    }
+
+    #[test]
+    fn test_strength_and_distance() {
+        let dir = workspace();
+        let graph = DependencyGraph::build(dir.path());
+        let edges = analyze_coupling(&graph, &HashMap::new());
+
+        let util = edge(&edges, "core/src/util.rs", "core/src/store.rs");
+        assert_eq!(util.strength, IntegrationStrength::Contract);
+        assert_eq!(util.distance, CouplingDistance::SameModule);
+
+        let glob = edge(&edges, "core/src/lib.rs", "core/src/store.rs");
+        assert_eq!(glob.strength, IntegrationStrength::Model);
+
+        let disk = edge(&edges, "core/src/io/disk.rs", "core/src/store.rs");
+        assert_eq!(disk.strength, IntegrationStrength::Model);
+        assert_eq!(disk.distance, CouplingDistance::Sibling);
+
+        let socket = edge(&edges, "core/src/io/net/socket.rs", "core/src/store.rs");
+        assert_eq!(socket.strength, IntegrationStrength::Intrusive);
+        assert_eq!(socket.distance, CouplingDistance::CrossModule);
+        assert!(socket.unbalanced);
+
+        // The other workspace crate is reached through its library name
+        let app = edge(&edges, "app/src/main.rs", "core/src/store.rs");
+        assert_eq!(app.strength, IntegrationStrength::Contract);
+        assert_eq!(app.distance, CouplingDistance::CrossPackage);
+
+        // Unbalanced edges come first
+        assert_eq!(edges[0].from, "core/src/io/net/socket.rs");
+    }
+
+    #[test]
+    fn test_low_volatility_balances_strong_coupling() {
+        let dir = workspace();
+        let graph = DependencyGraph::build(dir.path());
+        let store = graph.base_path().join("core/src/store.rs");
+        let mut churn = HashMap::new();
+        churn.insert(store, 1);
+        churn.insert(graph.base_path().join("core/src/util.rs"), 9);
+
+        let edges = analyze_coupling(&graph, &churn);
+        let socket = edge(&edges, "core/src/io/net/socket.rs", "core/src/store.rs");
+        assert!(!socket.volatile);
+        assert!(!socket.unbalanced);
+        assert_eq!(socket.churn, 1);
+    }
+
+    #[test]
+    fn test_unbalanced_coupling_is_cached_until_edges_change() {
+        let dir = workspace();
+        let mut graph = DependencyGraph::build(dir.path());
+
+        let first = unbalanced_coupling(&graph, 10);
+        assert!(first.iter().all(|edge| edge.unbalanced));
+        assert!(first.iter().any(|edge| edge.from == "core/src/io/net/socket.rs"));
+        assert!(Arc::ptr_eq(&first, &unbalanced_coupling(&graph, 10)));
+
+        let socket = graph.base_path().join("core/src/io/net/socket.rs");
+        fs::write(&socket, "use crate::store::Store;\n").unwrap();
+        graph.update_file(&socket);
+        let updated = unbalanced_coupling(&graph, 10);
+        assert!(!updated.iter().any(|edge| edge.from == "core/src/io/net/socket.rs"));
+    }
+
+    #[test]
+    fn test_is_unbalanced() {
+        use CouplingDistance::*;
+        use IntegrationStrength::*;
+        assert!(!is_unbalanced(Contract, CrossPackage));
+        assert!(!is_unbalanced(Model, Sibling));
+        assert!(is_unbalanced(Model, CrossModule));
+        assert!(!is_unbalanced(Intrusive, SameModule));
+        assert!(is_unbalanced(Intrusive, Sibling));
+    }
+
+    #[test]
+    fn test_volatility_threshold() {
+        let churn: HashMap<PathBuf, usize> = (1..=8).map(|i| (PathBuf::from(i.to_string()), i)).collect();
+        assert_eq!(volatility_threshold(&churn), 7);
+        assert_eq!(volatility_threshold(&HashMap::new()), MIN_VOLATILE_CHURN);
+    }
+}
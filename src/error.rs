@@ -1,6 +1,7 @@
 //! Error types for ai-code-review
 
 use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Result type for ai-code-review operations
@@ -52,4 +53,32 @@ pub enum CodeReviewError {
     /// Parse error (tree-sitter)
     #[error("Parse error: {0}")]
     ParseError(String),
+
+    /// AI call exceeded the configured timeout
+    #[error("AI call timed out after {0:?}")]
+    Timeout(Duration),
+
+    /// Backend rejected the call due to rate limiting or quota
+    #[error("Rate limited by backend: {0}")]
+    RateLimited(String),
+
+    /// Backend could not be reached
+    #[error("Backend unavailable: {0}")]
+    BackendUnavailable(String),
+
+    /// Backend CLI is not installed or not executable
+    #[error("Backend not installed: {0}")]
+    BackendNotInstalled(String),
+
+    /// Circuit breaker is open after repeated failures
+    #[error("Circuit breaker open, retry in {0:?}")]
+    CircuitOpen(Duration),
+
+    /// Spend budget is exhausted
+    #[error("Spend budget exhausted: {0}")]
+    BudgetExhausted(String),
+
+    /// Unified diff could not be parsed or does not apply
+    #[error("Patch error: {0}")]
+    PatchError(String),
 }
@@ -1,13 +1,28 @@
 //! Git integration for getting diffs
 
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::{CodeReviewError, Result};
 
 /// Create a `Command` for git with the given arguments.
 /// On Windows, sets `CREATE_NO_WINDOW` to suppress console popups.
+///
+/// Inside a git hook `GIT_DIR`, `GIT_WORK_TREE` and `GIT_INDEX_FILE` are
+/// set for the hook's repository. Every command here runs in an explicit
+/// directory instead, so they are cleared and git discovers the repository
+/// from that directory.
 fn new_git_command(args: &[&str]) -> Command {
     let mut cmd = Command::new("git");
     cmd.args(args);
+    cmd.env_remove("GIT_DIR")
+        .env_remove("GIT_WORK_TREE")
+        .env_remove("GIT_INDEX_FILE");
     #[cfg(target_os = "windows")]
     {
         use std::os::windows::process::CommandExt;
@@ -16,6 +31,18 @@ fn new_git_command(args: &[&str]) -> Command {
     cmd
 }
 
+/// Like [`new_git_command`], but keeps an inherited `GIT_INDEX_FILE`
+///
+/// `git commit -a` and `git commit <paths>` run the pre-commit hook against
+/// a temporary index; commands that read the staged changes must see it.
+fn staged_git_command(args: &[&str]) -> Command {
+    let mut cmd = new_git_command(args);
+    if let Some(index) = std::env::var_os("GIT_INDEX_FILE") {
+        cmd.env("GIT_INDEX_FILE", index);
+    }
+    cmd
+}
+
 /// Get git diff for a file (unstaged changes first, then staged)
 pub fn get_git_diff(file_path: &Path) -> Option<String> {
     let file_str = file_path.to_string_lossy();
@@ -33,7 +60,7 @@ pub fn get_git_diff(file_path: &Path) -> Option<String> {
         }
 
         // Try staged changes
-        let mut cmd2 = new_git_command(&["diff", "--cached", "--", &file_str]);
+        let mut cmd2 = staged_git_command(&["diff", "--cached", "--", &file_str]);
         cmd2.current_dir(parent);
 
         let output2 = cmd2.output().ok()?;
@@ -50,7 +77,7 @@ pub fn get_git_diff(file_path: &Path) -> Option<String> {
 
 /// Get the staged diff (all files) for pre-commit hook support
 pub fn get_staged_diff(repo_dir: &Path) -> Option<String> {
-    let mut cmd = new_git_command(&["diff", "--cached"]);
+    let mut cmd = staged_git_command(&["diff", "--cached"]);
     cmd.current_dir(repo_dir);
     let output = cmd.output().ok()?;
     if output.status.success() {
@@ -61,9 +88,24 @@ pub fn get_staged_diff(repo_dir: &Path) -> Option<String> {
     }
 }
 
+/// Get the working tree diff against HEAD (staged and unstaged) of `dir`
+///
+/// Paths are relative to `dir`, and changes outside `dir` are left out.
+pub fn get_working_diff(dir: &Path) -> Option<String> {
+    let mut cmd = new_git_command(&["diff", "--relative", "HEAD"]);
+    cmd.current_dir(dir);
+    let output = cmd.output().ok()?;
+    if output.status.success() {
+        let s = String::from_utf8_lossy(&output.stdout).to_string();
+        if s.trim().is_empty() { None } else { Some(s) }
+    } else {
+        None
+    }
+}
+
 /// Get the list of staged files as absolute paths
 pub fn get_staged_files(repo_dir: &Path) -> Vec<PathBuf> {
-    let mut cmd = new_git_command(&["diff", "--cached", "--name-only"]);
+    let mut cmd = staged_git_command(&["diff", "--cached", "--name-only"]);
     cmd.current_dir(repo_dir);
     match cmd.output() {
         Ok(o) if o.status.success() => {
@@ -89,8 +131,6 @@ pub fn get_staged_files(repo_dir: &Path) -> Vec<PathBuf> {
 /// # Returns
 /// A vector of (file_path, co_change_count) tuples, sorted by count descending
 pub fn get_cochanged_files(file_path: &Path, lookback: usize) -> Vec<(String, usize)> {
-    use std::collections::HashMap;
-
     let file_str = file_path.to_string_lossy();
     let parent = match file_path.parent() {
         Some(p) => p,
@@ -150,6 +190,214 @@ pub fn get_cochanged_files(file_path: &Path, lookback: usize) -> Vec<(String, us
     result
 }
 
+/// Count how many of the last N commits touched each file below `dir`
+///
+/// Used as a volatility estimate: files that change often are expensive to
+/// depend on. Returns an empty map outside a git repository.
+///
+/// # Arguments
+/// * `dir` - Directory whose files are counted (paths in the map are joined onto it)
+/// * `lookback` - Number of commits to look back
+pub fn get_file_churn(dir: &Path, lookback: usize) -> HashMap<PathBuf, usize> {
+    let mut churn = HashMap::new();
+
+    // --relative limits the log to `dir` and prints paths relative to it
+    let mut cmd = new_git_command(&[
+        "log",
+        "--format=",
+        "--name-only",
+        "--relative",
+        "-n",
+        &lookback.to_string(),
+    ]);
+    cmd.current_dir(dir);
+
+    let output = match cmd.output() {
+        Ok(o) if o.status.success() => o,
+        _ => return churn,
+    };
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let line = line.trim();
+        if !line.is_empty() {
+            *churn.entry(dir.join(line)).or_insert(0) += 1;
+        }
+    }
+    churn
+}
+
+/// Resolve a revision (branch, tag, `HEAD~1`, ...) to a full commit hash
+pub fn resolve_commit(dir: &Path, rev: &str) -> Option<String> {
+    let spec = format!("{}^{{commit}}", rev);
+    let mut cmd = new_git_command(&["rev-parse", "--verify", "--quiet", &spec]);
+    cmd.current_dir(dir);
+    let output = cmd.output().ok().filter(|o| o.status.success())?;
+    let hash = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!hash.is_empty()).then_some(hash)
+}
+
+/// Top-level directory of the repository containing `dir`
+pub fn repo_root(dir: &Path) -> Option<PathBuf> {
+    let mut cmd = new_git_command(&["rev-parse", "--show-toplevel"]);
+    cmd.current_dir(dir);
+    let output = cmd.output().ok().filter(|o| o.status.success())?;
+    let root = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!root.is_empty()).then(|| PathBuf::from(root))
+}
+
+/// Where the files of a [`TreeExport`] come from
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum TreeSource<'a> {
+    /// The tree of a commit
+    Commit(&'a str),
+    /// The index, i.e. what the next commit will contain
+    Index,
+}
+
+/// Files of a commit or of the index, written to a temporary directory that is removed on drop
+///
+/// Blobs are read straight from the object database (`ls-tree` / `ls-files -s`
+/// and `cat-file --batch`), so no worktree, index or lock file of the
+/// repository is touched — safe to use from inside a pre-commit hook.
+pub(crate) struct TreeExport {
+    root: PathBuf,
+    prefix: PathBuf,
+}
+
+impl TreeExport {
+    /// Export the whole tree of the repository containing `dir`
+    pub(crate) fn export(dir: &Path, source: TreeSource<'_>) -> Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let prefix = PathBuf::from(git_stdout(dir, new_git_command(&["rev-parse", "--show-prefix"]))?.trim());
+        let blobs = match source {
+            TreeSource::Commit(rev) => {
+                let listing = git_stdout(dir, new_git_command(&["ls-tree", "-r", "-z", "--full-tree", rev]))?;
+                parse_listing(&listing, |fields| match fields {
+                    [mode, "blob", oid] if mode.starts_with("100") => Some(*oid),
+                    _ => None,
+                })
+            }
+            TreeSource::Index => {
+                let listing = git_stdout(dir, staged_git_command(&["ls-files", "-s", "-z", "--full-name", "--", ":/"]))?;
+                parse_listing(&listing, |fields| match fields {
+                    [mode, oid, "0"] if mode.starts_with("100") => Some(*oid),
+                    _ => None,
+                })
+            }
+        };
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        let root = std::env::temp_dir().join(format!(
+            "ai-code-review-{}-{}-{}",
+            std::process::id(),
+            nanos,
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&root)?;
+        // From here on, drop removes the directory again on error
+        let export = Self { root, prefix };
+        export.write_blobs(dir, &blobs)?;
+        Ok(export)
+    }
+
+    /// Directory inside the export that corresponds to the `dir` it was created from
+    pub(crate) fn path(&self) -> PathBuf {
+        self.root.join(&self.prefix)
+    }
+
+    /// Write each `(oid, path)` blob below the export root
+    fn write_blobs(&self, dir: &Path, blobs: &[(String, String)]) -> Result<()> {
+        let mut cmd = new_git_command(&["cat-file", "--batch"]);
+        cmd.current_dir(dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        let mut child = cmd.spawn()?;
+
+        // Feed the ids from a thread so a full stdout pipe cannot deadlock us
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let ids: String = blobs.iter().map(|(oid, _)| format!("{}\n", oid)).collect();
+        let writer = std::thread::spawn(move || stdin.write_all(ids.as_bytes()));
+
+        let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        let mut result = Ok(());
+        for (_, path) in blobs {
+            // Header: "<oid> blob <size>", or "<oid> missing"
+            let mut header = String::new();
+            stdout.read_line(&mut header)?;
+            let size = match header.split_whitespace().collect::<Vec<_>>()[..] {
+                [_, "blob", size] => size.parse::<usize>().ok(),
+                _ => None,
+            };
+            let Some(size) = size else {
+                result = Err(CodeReviewError::GitError(format!("cannot read {}: {}", path, header.trim())));
+                break;
+            };
+            let mut content = vec![0; size + 1];
+            stdout.read_exact(&mut content)?;
+            content.truncate(size);
+
+            let target = self.root.join(path);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(target, content)?;
+        }
+
+        drop(stdout);
+        let _ = child.wait();
+        if let Ok(Err(e)) = writer.join() {
+            // A broken pipe after an early error is expected
+            if result.is_ok() {
+                result = Err(e.into());
+            }
+        }
+        result
+    }
+}
+
+impl Drop for TreeExport {
+    fn drop(&mut self) {
+        if fs::remove_dir_all(&self.root).is_err() {
+            log::warn!("Failed to remove exported tree {}", self.root.display());
+        }
+    }
+}
+
+/// Stdout of a git command run in `dir`, or a `GitError` with its stderr
+fn git_stdout(dir: &Path, mut cmd: Command) -> Result<String> {
+    cmd.current_dir(dir);
+    let output = cmd.output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(CodeReviewError::GitError(if stderr.is_empty() {
+            format!("git failed in {}", dir.display())
+        } else {
+            stderr
+        }));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// `(oid, path)` of the regular files in a NUL-separated `ls-tree` / `ls-files -s` listing
+///
+/// `blob` picks the object id from the whitespace-separated fields before
+/// the tab, or rejects the entry (symlinks, submodules, conflict stages).
+fn parse_listing<'a>(listing: &'a str, blob: impl Fn(&[&'a str]) -> Option<&'a str>) -> Vec<(String, String)> {
+    listing
+        .split('\0')
+        .filter_map(|entry| {
+            let (meta, path) = entry.split_once('\t')?;
+            let fields: Vec<&str> = meta.split_whitespace().collect();
+            blob(&fields).map(|oid| (oid.to_string(), path.to_string()))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,4 +411,30 @@ mod tests {
         // Result may be empty if not in a git repo, that's fine
         let _ = result;
     }
+
+    #[test]
+    fn test_get_file_churn_outside_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(get_file_churn(dir.path(), 10).is_empty());
+    }
+
+    #[test]
+    fn test_export_outside_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(resolve_commit(dir.path(), "HEAD").is_none());
+        assert!(matches!(
+            TreeExport::export(dir.path(), TreeSource::Commit("HEAD")),
+            Err(CodeReviewError::GitError(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_listing_keeps_regular_files() {
+        let tree = ["100644 blob aaa\tsrc/lib.rs", "120000 blob bbb\tlink", "160000 commit ccc\tvendor", ""].join("\0");
+        let blobs = parse_listing(&tree, |fields| match fields {
+            [mode, "blob", oid] if mode.starts_with("100") => Some(*oid),
+            _ => None,
+        });
+        assert_eq!(blobs, vec![("aaa".to_string(), "src/lib.rs".to_string())]);
+    }
 }
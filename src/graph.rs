@@ -0,0 +1,858 @@
+//! Project-wide dependency graph
+//!
+//! Built once per project: every source file is parsed a single time and
+//! its imports are resolved to project files. The graph answers importer,
+//! dependent and cycle queries without re-walking the tree, rolls files up
+//! into modules (directories), and can be kept current from watcher events
+//! with [`DependencyGraph::update_file`].
+//!
+//! Imports that resolve to project files (see [`ImportResolver`]) only link
+//! to those files. Unresolved imports, and languages without a resolver
+//! (Go, Java, C#, ...), are linked by package, namespace or file name, as
+//! `find_importers_with_resolver` does.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::analyzer::{
+    import_segments, import_target_name, using_matches_namespace, C_FAMILY_EXTENSIONS,
+    IMPORT_SCAN_EXTENSIONS,
+};
+use crate::cache::analyze_cached;
+use crate::coupling::CouplingCache;
+use crate::parser::{FileAnalysis, ImportInfo};
+use crate::resolver::{normalize, ImportResolver, RESOLVED_EXTENSIONS};
+use crate::utils::fs::walk_source_files;
+
+/// Output format for [`DependencyGraph::render`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    #[default]
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+    /// JSON with nodes, edges and cycles
+    Json,
+}
+
+/// Granularity of a rendered graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GraphLevel {
+    /// One node per file
+    #[default]
+    Files,
+    /// One node per module (directory), edges weighted by import count
+    Modules,
+}
+
+/// Aggregated dependency between two modules
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleEdge {
+    /// Importing module
+    pub from: String,
+    /// Imported module
+    pub to: String,
+    /// Number of file-level edges between the two modules
+    pub imports: usize,
+}
+
+/// Dependency graph of a project's source files
+#[derive(Debug, Clone)]
+pub struct DependencyGraph {
+    base_path: PathBuf,
+    resolver: ImportResolver,
+    /// Parsed analysis of every file in the graph
+    analyses: BTreeMap<PathBuf, FileAnalysis>,
+    /// Files each file imports
+    edges: BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+    /// Files importing each file
+    reverse: BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+    /// Name and namespace lookup for languages without a resolver
+    index: NameIndex,
+    /// Unbalanced coupling edges, computed on first use
+    coupling: CouplingCache,
+}
+
+/// Nodes, weighted edges and cycles of one rendering level
+#[derive(Serialize)]
+struct GraphView {
+    nodes: Vec<ViewNode>,
+    edges: Vec<ViewEdge>,
+    cycles: Vec<Vec<String>>,
+}
+
+#[derive(Serialize)]
+struct ViewNode {
+    id: String,
+    fan_in: usize,
+    fan_out: usize,
+}
+
+#[derive(Serialize)]
+struct ViewEdge {
+    from: String,
+    to: String,
+    weight: usize,
+}
+
+impl DependencyGraph {
+    /// Build the graph for the project at `base_path` with a default resolver
+    pub fn build(base_path: &Path) -> Self {
+        Self::build_with_resolver(base_path, ImportResolver::new(base_path))
+    }
+
+    /// Build the graph, resolving imports with `resolver`
+    pub fn build_with_resolver(base_path: &Path, resolver: ImportResolver) -> Self {
+        let base_path = base_path
+            .canonicalize()
+            .unwrap_or_else(|_| base_path.to_path_buf());
+        let analyses = walk_source_files(&base_path, IMPORT_SCAN_EXTENSIONS)
+            .par_iter()
+            .filter_map(|path| analyze_cached(path).ok().map(|a| (normalize(path), a)))
+            .collect();
+
+        let mut graph = Self {
+            base_path,
+            resolver,
+            analyses,
+            edges: BTreeMap::new(),
+            reverse: BTreeMap::new(),
+            index: NameIndex::default(),
+            coupling: CouplingCache::default(),
+        };
+        graph.relink_all();
+        graph
+    }
+
+    /// Re-parse a changed, created or deleted file and update its edges
+    ///
+    /// Adding or removing a file (or changing the namespaces it declares)
+    /// can change how other files' imports resolve, so every file is
+    /// re-linked then; otherwise only the changed file's edges are.
+    pub fn update_file(&mut self, path: &Path) {
+        let is_source = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| IMPORT_SCAN_EXTENSIONS.contains(&ext));
+        if !is_source {
+            return;
+        }
+        let key = self.key(path);
+
+        let analysis = if key.is_file() { analyze_cached(&key).ok() } else { None };
+        let previous = match analysis {
+            Some(analysis) => self.analyses.insert(key.clone(), analysis),
+            None => self.analyses.remove(&key),
+        };
+
+        let namespaces_changed = previous.as_ref().map(|a| &a.namespaces)
+            != self.analyses.get(&key).map(|a| &a.namespaces);
+        let membership_changed = previous.is_none() || !self.analyses.contains_key(&key);
+        if membership_changed || namespaces_changed {
+            self.relink_all();
+        } else {
+            let targets = self.link(&key);
+            self.set_edges(&key, targets);
+        }
+    }
+
+    /// Project root the graph was built for
+    pub fn base_path(&self) -> &Path {
+        &self.base_path
+    }
+
+    /// Number of files in the graph
+    pub fn len(&self) -> usize {
+        self.analyses.len()
+    }
+
+    /// Check whether the graph has no files
+    pub fn is_empty(&self) -> bool {
+        self.analyses.is_empty()
+    }
+
+    /// Files in the graph, in path order
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.analyses.keys().map(PathBuf::as_path)
+    }
+
+    /// Check whether `path` is part of the graph
+    pub fn contains(&self, path: &Path) -> bool {
+        self.analyses.contains_key(&self.key(path))
+    }
+
+    /// Parsed analysis of a file in the graph
+    pub fn analysis(&self, path: &Path) -> Option<&FileAnalysis> {
+        self.analyses.get(&self.key(path))
+    }
+
+    /// Files that `path` imports
+    pub fn dependencies(&self, path: &Path) -> Vec<PathBuf> {
+        Self::neighbours(&self.edges, &self.key(path))
+    }
+
+    /// Imports of `from` that link it to `to`
+    pub fn edge_imports(&self, from: &Path, to: &Path) -> Vec<&ImportInfo> {
+        let (from, to) = (self.key(from), self.key(to));
+        let Some(analysis) = self.analyses.get(&from) else {
+            return Vec::new();
+        };
+        analysis
+            .imports
+            .iter()
+            .filter(|import| self.link_import(&from, analysis, import).contains(&to))
+            .collect()
+    }
+
+    /// Files that import `path`
+    pub fn importers(&self, path: &Path) -> Vec<PathBuf> {
+        Self::neighbours(&self.reverse, &self.key(path))
+    }
+
+    /// Files that depend on `path` directly or through other files
+    pub fn transitive_dependents(&self, path: &Path) -> Vec<PathBuf> {
+        let start = self.key(path);
+        let mut seen = BTreeSet::new();
+        let mut queue = VecDeque::from([start.clone()]);
+        while let Some(file) = queue.pop_front() {
+            for importer in self.reverse.get(&file).into_iter().flatten() {
+                if *importer != start && seen.insert(importer.clone()) {
+                    queue.push_back(importer.clone());
+                }
+            }
+        }
+        seen.into_iter().collect()
+    }
+
+    /// Every file-level edge as `(importer, imported)`, in path order
+    pub fn edges(&self) -> impl Iterator<Item = (&Path, &Path)> {
+        self.edges
+            .iter()
+            .flat_map(|(from, targets)| targets.iter().map(move |to| (from.as_path(), to.as_path())))
+    }
+
+    /// Number of files importing `path`
+    pub fn fan_in(&self, path: &Path) -> usize {
+        self.reverse.get(&self.key(path)).map_or(0, BTreeSet::len)
+    }
+
+    /// Number of files `path` imports
+    pub fn fan_out(&self, path: &Path) -> usize {
+        self.edges.get(&self.key(path)).map_or(0, BTreeSet::len)
+    }
+
+    /// Import cycles: strongly connected components with more than one file,
+    /// each sorted by path (a file importing itself is not an edge)
+    pub fn cycles(&self) -> Vec<Vec<PathBuf>> {
+        let nodes: Vec<PathBuf> = self.analyses.keys().cloned().collect();
+        strongly_connected(&nodes, &self.edges)
+    }
+
+    /// Module (directory relative to the project root) a file belongs to
+    ///
+    /// Files directly in the project root belong to `.`.
+    pub fn module_of(&self, path: &Path) -> String {
+        let key = self.key(path);
+        let dir = key
+            .parent()
+            .and_then(|p| p.strip_prefix(&self.base_path).ok())
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default();
+        if dir.is_empty() {
+            ".".to_string()
+        } else {
+            dir
+        }
+    }
+
+    /// Dependencies between modules, with imports inside a module left out
+    pub fn module_edges(&self) -> Vec<ModuleEdge> {
+        let mut counts: BTreeMap<(String, String), usize> = BTreeMap::new();
+        for (from, targets) in &self.edges {
+            let from_module = self.module_of(from);
+            for to in targets {
+                let to_module = self.module_of(to);
+                if from_module != to_module {
+                    *counts.entry((from_module.clone(), to_module)).or_default() += 1;
+                }
+            }
+        }
+        counts
+            .into_iter()
+            .map(|((from, to), imports)| ModuleEdge { from, to, imports })
+            .collect()
+    }
+
+    /// Cycles between modules, a sign of broken layering
+    pub fn module_cycles(&self) -> Vec<Vec<String>> {
+        let modules: Vec<String> = self.modules().into_iter().collect();
+        let mut edges: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for edge in self.module_edges() {
+            edges.entry(edge.from).or_default().insert(edge.to);
+        }
+        strongly_connected(&modules, &edges)
+    }
+
+    /// Path of a file relative to the project root, for display
+    pub fn display_path(&self, path: &Path) -> String {
+        let key = self.key(path);
+        key.strip_prefix(&self.base_path)
+            .unwrap_or(&key)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// Render the graph at the given level in the given format
+    pub fn render(&self, format: GraphFormat, level: GraphLevel) -> String {
+        let view = match level {
+            GraphLevel::Files => self.file_view(),
+            GraphLevel::Modules => self.module_view(),
+        };
+        match format {
+            GraphFormat::Dot => render_dot(&view, level),
+            GraphFormat::Mermaid => render_mermaid(&view, level),
+            GraphFormat::Json => serde_json::to_string_pretty(&view).unwrap_or_default(),
+        }
+    }
+
+    /// Canonical key for a path (absolute, without `.`/`..`)
+    ///
+    /// Graph files are stored below the canonical project root, so the
+    /// lexical form usually matches; the filesystem is only consulted for
+    /// paths it does not (symlinks, a non-canonical root).
+    fn key(&self, path: &Path) -> PathBuf {
+        let lexical = if path.is_absolute() {
+            normalize(path)
+        } else {
+            normalize(&self.base_path.join(path))
+        };
+        if self.analyses.contains_key(&lexical) {
+            return lexical;
+        }
+        path.canonicalize().unwrap_or(lexical)
+    }
+
+    fn neighbours(map: &BTreeMap<PathBuf, BTreeSet<PathBuf>>, key: &Path) -> Vec<PathBuf> {
+        map.get(key).map(|set| set.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    pub(crate) fn coupling_cache(&self) -> &CouplingCache {
+        &self.coupling
+    }
+
+    fn modules(&self) -> BTreeSet<String> {
+        self.analyses.keys().map(|file| self.module_of(file)).collect()
+    }
+
+    /// Recompute every file's edges
+    fn relink_all(&mut self) {
+        self.coupling.clear();
+        self.index = NameIndex::new(&self.analyses);
+        let edges: BTreeMap<PathBuf, BTreeSet<PathBuf>> = self
+            .analyses
+            .keys()
+            .map(|file| (file.clone(), self.link(file)))
+            .collect();
+
+        self.reverse.clear();
+        for (from, targets) in &edges {
+            for to in targets {
+                self.reverse.entry(to.clone()).or_default().insert(from.clone());
+            }
+        }
+        self.edges = edges;
+    }
+
+    /// Replace the outgoing edges of one file
+    fn set_edges(&mut self, file: &Path, targets: BTreeSet<PathBuf>) {
+        self.coupling.clear();
+        if let Some(old) = self.edges.remove(file) {
+            for to in old {
+                if let Some(importers) = self.reverse.get_mut(&to) {
+                    importers.remove(file);
+                }
+            }
+        }
+        for to in &targets {
+            self.reverse.entry(to.clone()).or_default().insert(file.to_path_buf());
+        }
+        self.edges.insert(file.to_path_buf(), targets);
+    }
+
+    /// Files a file's imports link to
+    fn link(&self, file: &Path) -> BTreeSet<PathBuf> {
+        let Some(analysis) = self.analyses.get(file) else {
+            return BTreeSet::new();
+        };
+        let mut targets: BTreeSet<PathBuf> = analysis
+            .imports
+            .iter()
+            .flat_map(|import| self.link_import(file, analysis, import))
+            .collect();
+        targets.remove(file);
+        targets
+    }
+
+    /// Files a single import links to
+    fn link_import(&self, file: &Path, analysis: &FileAnalysis, import: &ImportInfo) -> BTreeSet<PathBuf> {
+        let ext = file.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let mut targets = BTreeSet::new();
+
+        // Resolved imports link only to their files; unresolved ones fall
+        // back to name matching, as in `find_importers_with_resolver`
+        if RESOLVED_EXTENSIONS.contains(&ext) {
+            let resolved = self.resolver.resolve(import, file);
+            if !resolved.is_empty() {
+                targets.extend(
+                    resolved
+                        .into_iter()
+                        .map(|target| self.key(&target))
+                        .filter(|target| self.analyses.contains_key(target)),
+                );
+                return targets;
+            }
+        }
+        if C_FAMILY_EXTENSIONS.contains(&ext) {
+            return targets;
+        }
+        if ext == "cs" {
+            for (namespace, files) in &self.index.namespaces {
+                if using_matches_namespace(import, std::slice::from_ref(namespace)) {
+                    targets.extend(files.iter().cloned());
+                }
+            }
+        }
+        // Wildcard imports bring in every file of the package
+        if matches!(ext, "java" | "kt") && import.items.iter().any(|i| i == "*") {
+            targets.extend(self.index.namespaces.get(&import.module_path).into_iter().flatten().cloned());
+        }
+        let names = import_segments(&import.module_path, ext)
+            .into_iter()
+            .chain(import.items.iter().map(String::as_str));
+        for name in names {
+            let candidates = self.index.names.get(name).into_iter().flatten();
+            targets.extend(candidates.filter(|target| {
+                let target_analysis = &self.analyses[*target];
+                // C# files with namespaces are only reachable by namespace
+                target_analysis.language == analysis.language
+                    && (target_analysis.language != "csharp" || target_analysis.namespaces.is_empty())
+            }).cloned());
+        }
+        targets
+    }
+
+    fn file_view(&self) -> GraphView {
+        let nodes = self
+            .analyses
+            .keys()
+            .map(|file| ViewNode {
+                id: self.display_path(file),
+                fan_in: self.fan_in(file),
+                fan_out: self.fan_out(file),
+            })
+            .collect();
+        let edges = self
+            .edges
+            .iter()
+            .flat_map(|(from, targets)| {
+                targets.iter().map(move |to| ViewEdge {
+                    from: self.display_path(from),
+                    to: self.display_path(to),
+                    weight: 1,
+                })
+            })
+            .collect();
+        let cycles = self
+            .cycles()
+            .into_iter()
+            .map(|cycle| cycle.iter().map(|file| self.display_path(file)).collect())
+            .collect();
+        GraphView { nodes, edges, cycles }
+    }
+
+    fn module_view(&self) -> GraphView {
+        let module_edges = self.module_edges();
+        let mut fan_in: HashMap<&str, usize> = HashMap::new();
+        let mut fan_out: HashMap<&str, usize> = HashMap::new();
+        for edge in &module_edges {
+            *fan_in.entry(edge.to.as_str()).or_default() += 1;
+            *fan_out.entry(edge.from.as_str()).or_default() += 1;
+        }
+        let nodes = self
+            .modules()
+            .into_iter()
+            .map(|module| ViewNode {
+                fan_in: fan_in.get(module.as_str()).copied().unwrap_or(0),
+                fan_out: fan_out.get(module.as_str()).copied().unwrap_or(0),
+                id: module,
+            })
+            .collect();
+        let edges = module_edges
+            .iter()
+            .map(|edge| ViewEdge {
+                from: edge.from.clone(),
+                to: edge.to.clone(),
+                weight: edge.imports,
+            })
+            .collect();
+        GraphView {
+            nodes,
+            edges,
+            cycles: self.module_cycles(),
+        }
+    }
+}
+
+/// Lookup tables for linking imports of unresolved languages
+#[derive(Debug, Clone, Default)]
+struct NameIndex {
+    /// Files by the name importers use for them (see `import_target_name`)
+    names: HashMap<String, Vec<PathBuf>>,
+    /// C# files by declared namespace, Java/Kotlin files by package
+    namespaces: BTreeMap<String, Vec<PathBuf>>,
+}
+
+impl NameIndex {
+    fn new(analyses: &BTreeMap<PathBuf, FileAnalysis>) -> Self {
+        let mut names: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        let mut namespaces: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+        for (file, analysis) in analyses {
+            let name = import_target_name(file);
+            if !name.is_empty() {
+                names.entry(name.to_string()).or_default().push(file.clone());
+            }
+            for namespace in &analysis.namespaces {
+                namespaces.entry(namespace.clone()).or_default().push(file.clone());
+            }
+        }
+        Self { names, namespaces }
+    }
+}
+
+/// Strongly connected components that form cycles (Tarjan's algorithm)
+///
+/// Iterative, so deep import chains cannot overflow the stack. Components
+/// are sorted internally and by their first node.
+fn strongly_connected<N: Ord + Clone>(nodes: &[N], edges: &BTreeMap<N, BTreeSet<N>>) -> Vec<Vec<N>> {
+    let position: BTreeMap<&N, usize> = nodes.iter().enumerate().map(|(i, n)| (n, i)).collect();
+    let adjacency: Vec<Vec<usize>> = nodes
+        .iter()
+        .map(|n| {
+            edges
+                .get(n)
+                .into_iter()
+                .flatten()
+                .filter_map(|to| position.get(to).copied())
+                .collect()
+        })
+        .collect();
+
+    let mut index = vec![usize::MAX; nodes.len()];
+    let mut lowlink = vec![0; nodes.len()];
+    let mut on_stack = vec![false; nodes.len()];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut components = Vec::new();
+
+    for start in 0..nodes.len() {
+        if index[start] != usize::MAX {
+            continue;
+        }
+        let mut calls = vec![(start, 0)];
+        index[start] = next_index;
+        lowlink[start] = next_index;
+        next_index += 1;
+        stack.push(start);
+        on_stack[start] = true;
+
+        while let Some(frame) = calls.last_mut() {
+            let v = frame.0;
+            if let Some(&w) = adjacency[v].get(frame.1) {
+                frame.1 += 1;
+                if index[w] == usize::MAX {
+                    index[w] = next_index;
+                    lowlink[w] = next_index;
+                    next_index += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    calls.push((w, 0));
+                } else if on_stack[w] {
+                    lowlink[v] = lowlink[v].min(index[w]);
+                }
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[v]);
+            }
+            if lowlink[v] == index[v] {
+                let mut component = Vec::new();
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component.push(nodes[w].clone());
+                    if w == v {
+                        break;
+                    }
+                }
+                if component.len() > 1 || adjacency[v].contains(&v) {
+                    component.sort();
+                    components.push(component);
+                }
+            }
+        }
+    }
+    components.sort();
+    components
+}
+
+/// Pairs of nodes whose edge lies on a cycle
+fn cycle_edges(view: &GraphView) -> BTreeSet<(&str, &str)> {
+    let mut in_cycle: HashMap<&str, usize> = HashMap::new();
+    for (i, cycle) in view.cycles.iter().enumerate() {
+        for node in cycle {
+            in_cycle.insert(node.as_str(), i);
+        }
+    }
+    view.edges
+        .iter()
+        .filter(|e| {
+            in_cycle.contains_key(e.from.as_str())
+                && in_cycle.get(e.from.as_str()) == in_cycle.get(e.to.as_str())
+        })
+        .map(|e| (e.from.as_str(), e.to.as_str()))
+        .collect()
+}
+
+fn render_dot(view: &GraphView, level: GraphLevel) -> String {
+    let cyclic = cycle_edges(view);
+    let mut out = String::from("digraph dependencies {\n    rankdir=LR;\n    node [shape=box];\n");
+    for node in &view.nodes {
+        let _ = writeln!(out, "    \"{}\";", node.id);
+    }
+    for edge in &view.edges {
+        let mut attrs = Vec::new();
+        if level == GraphLevel::Modules {
+            attrs.push(format!("label=\"{}\"", edge.weight));
+        }
+        if cyclic.contains(&(edge.from.as_str(), edge.to.as_str())) {
+            attrs.push("color=red".to_string());
+        }
+        let attrs = if attrs.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", attrs.join(", "))
+        };
+        let _ = writeln!(out, "    \"{}\" -> \"{}\"{};", edge.from, edge.to, attrs);
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn render_mermaid(view: &GraphView, level: GraphLevel) -> String {
+    let ids: HashMap<&str, usize> = view
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node.id.as_str(), i))
+        .collect();
+    let cyclic = cycle_edges(view);
+
+    let mut out = String::from("graph LR\n");
+    for (i, node) in view.nodes.iter().enumerate() {
+        let _ = writeln!(out, "    n{}[\"{}\"]", i, node.id);
+    }
+    let mut cyclic_links = Vec::new();
+    for (link, edge) in view.edges.iter().enumerate() {
+        let (Some(from), Some(to)) = (ids.get(edge.from.as_str()), ids.get(edge.to.as_str())) else {
+            continue;
+        };
+        if level == GraphLevel::Modules {
+            let _ = writeln!(out, "    n{} -->|{}| n{}", from, edge.weight, to);
+        } else {
+            let _ = writeln!(out, "    n{} --> n{}", from, to);
+        }
+        if cyclic.contains(&(edge.from.as_str(), edge.to.as_str())) {
+            cyclic_links.push(link.to_string());
+        }
+    }
+    if !cyclic_links.is_empty() {
+        let _ = writeln!(out, "    linkStyle {} stroke:red", cyclic_links.join(","));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    /// src/lib.rs -> api, db; api/handlers.rs -> db; db.rs <-> cache.rs
+    fn sample_project() -> tempfile::TempDir {
+        let dir = tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(src.join("api")).unwrap();
+        fs::write(src.join("lib.rs"), "mod api;\nmod db;\nmod cache;\nuse crate::api::handlers;\nuse crate::db::Pool;\n").unwrap();
+        fs::write(src.join("api/mod.rs"), "pub mod handlers;\n").unwrap();
+        fs::write(src.join("api/handlers.rs"), "use crate::db::Pool;\n").unwrap();
+        fs::write(src.join("db.rs"), "use crate::cache::Cache;\npub struct Pool;\n").unwrap();
+        fs::write(src.join("cache.rs"), "use crate::db::Pool;\npub struct Cache;\n").unwrap();
+        dir
+    }
+
+    fn names(graph: &DependencyGraph, files: &[PathBuf]) -> Vec<String> {
+        files.iter().map(|f| graph.display_path(f)).collect()
+    }
+
+    #[test]
+    fn test_importers_and_dependencies() {
+        let dir = sample_project();
+        let graph = DependencyGraph::build(dir.path());
+        let db = dir.path().join("src/db.rs");
+
+        assert_eq!(graph.len(), 5);
+        assert_eq!(
+            names(&graph, &graph.importers(&db)),
+            vec!["src/api/handlers.rs", "src/cache.rs", "src/lib.rs"]
+        );
+        assert_eq!(names(&graph, &graph.dependencies(&db)), vec!["src/cache.rs"]);
+        assert_eq!(graph.fan_in(&db), 3);
+        assert_eq!(graph.fan_out(&db), 1);
+    }
+
+    #[test]
+    fn test_transitive_dependents() {
+        let dir = sample_project();
+        let graph = DependencyGraph::build(dir.path());
+        let handlers = dir.path().join("src/api/handlers.rs");
+
+        assert_eq!(names(&graph, &graph.transitive_dependents(&handlers)), vec!["src/lib.rs"]);
+        assert_eq!(
+            names(&graph, &graph.transitive_dependents(&dir.path().join("src/cache.rs"))),
+            vec!["src/api/handlers.rs", "src/db.rs", "src/lib.rs"]
+        );
+    }
+
+    #[test]
+    fn test_cycles_and_module_rollup() {
+        let dir = sample_project();
+        let graph = DependencyGraph::build(dir.path());
+
+        let cycles = graph.cycles();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(names(&graph, &cycles[0]), vec!["src/cache.rs", "src/db.rs"]);
+
+        assert_eq!(graph.module_of(&dir.path().join("src/api/handlers.rs")), "src/api");
+        let edge = |from: &str, to: &str, imports| ModuleEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            imports,
+        };
+        // The crate root reaches into api (mod.rs and handlers.rs), and api
+        // back into the root's siblings
+        assert_eq!(
+            graph.module_edges(),
+            vec![edge("src", "src/api", 2), edge("src/api", "src", 1)]
+        );
+        assert_eq!(graph.module_cycles(), vec![vec!["src".to_string(), "src/api".to_string()]]);
+    }
+
+    #[test]
+    fn test_update_file_incrementally() {
+        let dir = sample_project();
+        let mut graph = DependencyGraph::build(dir.path());
+        let src = dir.path().join("src");
+
+        // Break the cycle
+        fs::write(src.join("cache.rs"), "pub struct Cache;\n").unwrap();
+        graph.update_file(&src.join("cache.rs"));
+        assert!(graph.cycles().is_empty());
+        assert_eq!(graph.fan_in(&src.join("db.rs")), 2);
+
+        // A new file and a deleted one
+        fs::write(src.join("api/auth.rs"), "use super::handlers;\n").unwrap();
+        graph.update_file(&src.join("api/auth.rs"));
+        assert_eq!(
+            names(&graph, &graph.importers(&src.join("api/handlers.rs"))),
+            vec!["src/api/auth.rs", "src/lib.rs"]
+        );
+
+        fs::remove_file(src.join("api/auth.rs")).unwrap();
+        graph.update_file(&src.join("api/auth.rs"));
+        assert_eq!(graph.len(), 5);
+        assert_eq!(graph.fan_in(&src.join("api/handlers.rs")), 1);
+    }
+
+    #[test]
+    fn test_unresolved_imports_fall_back_to_names() {
+        let dir = tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::create_dir_all(dir.path().join("tools")).unwrap();
+        fs::write(src.join("lib.rs"), "use helpers::format;\n").unwrap();
+        let helpers = dir.path().join("tools/helpers.rs");
+        fs::write(&helpers, "pub fn format() {}\n").unwrap();
+        let graph = DependencyGraph::build(dir.path());
+
+        // Same answer as the per-file scan
+        let scanned: Vec<PathBuf> = crate::analyzer::find_importers_with_include_dirs(&helpers, dir.path(), &[])
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(names(&graph, &graph.importers(&helpers)), vec!["src/lib.rs"]);
+        assert_eq!(names(&graph, &scanned), vec!["src/lib.rs"]);
+    }
+
+    #[cfg(feature = "lang-java")]
+    #[test]
+    fn test_java_wildcard_links_package() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("TokenStore.java"), "package com.acme.auth;\n\npublic class TokenStore {}\n").unwrap();
+        fs::write(
+            dir.path().join("Login.java"),
+            "package com.acme.web;\n\nimport com.acme.auth.*;\n\npublic class Login {}\n",
+        )
+        .unwrap();
+        let graph = DependencyGraph::build(dir.path());
+
+        assert_eq!(
+            names(&graph, &graph.dependencies(&dir.path().join("Login.java"))),
+            vec!["TokenStore.java"]
+        );
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let dir = sample_project();
+        let graph = DependencyGraph::build(dir.path());
+
+        let dot = graph.render(GraphFormat::Dot, GraphLevel::Files);
+        assert!(dot.starts_with("digraph dependencies {"));
+        assert!(dot.contains("\"src/db.rs\" -> \"src/cache.rs\" [color=red];"));
+        assert!(dot.contains("\"src/api/handlers.rs\" -> \"src/db.rs\";"));
+
+        let mermaid = graph.render(GraphFormat::Mermaid, GraphLevel::Modules);
+        assert!(mermaid.starts_with("graph LR\n"));
+        assert!(mermaid.contains("-->|2|"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&graph.render(GraphFormat::Json, GraphLevel::Files)).unwrap();
+        assert_eq!(json["nodes"].as_array().unwrap().len(), 5);
+        assert_eq!(json["cycles"][0][0], "src/cache.rs");
+    }
+
+    #[test]
+    fn test_strongly_connected_self_loop_and_chain() {
+        let nodes = vec![1, 2, 3];
+        let mut edges: BTreeMap<i32, BTreeSet<i32>> = BTreeMap::new();
+        edges.entry(1).or_default().insert(2);
+        edges.entry(3).or_default().insert(3);
+        assert_eq!(strongly_connected(&nodes, &edges), vec![vec![3]]);
+    }
+}
@@ -1,43 +1,158 @@
-//! Deep architectural investigation against reference codebases
+//! Build error investigation against reference codebases
+//!
+//! When a file fails to build, the quickest hint is often how an
+//! established codebase uses the same API. `Investigator` searches
+//! reference roots for the keywords of a build error (given by the caller
+//! or extracted from the log) and asks the AI to compare the failing file
+//! with those snippets, using a prompt template. [`Investigator::fix`]
+//! goes one step further and asks for a unified diff, which is only
+//! returned once it applies cleanly to the working tree.
 
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use cli_ai_analyzer::{analyze, AnalyzeOptions, Backend};
-use crate::error::Result;
-use walkdir::WalkDir;
-use std::sync::{Mutex, OnceLock};
+use std::sync::{Arc, Mutex};
 
-static SNIPPET_CACHE: OnceLock<Mutex<String>> = OnceLock::new();
+use cli_ai_analyzer::Backend;
 
+use crate::error::{CodeReviewError, Result};
+use crate::limits::UsageLimits;
+use crate::patch::Patch;
+use crate::policy::{call_ai, CallPolicy};
+use crate::prompt::{
+    build_fix_prompt, build_fix_retry_prompt, build_reference_investigation_prompt, FIX_PROMPT,
+    REFERENCE_INVESTIGATION_PROMPT,
+};
+use crate::utils::fs::{walk_source_files, SOURCE_EXTENSIONS};
+
+/// Default size of the reference snippets in the prompt (characters)
+pub const DEFAULT_SNIPPET_BUDGET: usize = 20_000;
+
+/// Lines kept before and after a keyword
+const SNIPPET_CONTEXT_LINES: usize = 12;
+
+/// Snippets kept per keyword, so one keyword cannot use the whole budget
+const MAX_SNIPPETS_PER_KEYWORD: usize = 3;
+
+/// Keywords extracted from one build log
+const MAX_AUTO_KEYWORDS: usize = 12;
+
+/// AI calls per fix: the first attempt plus one retry with the reason the
+/// previous diff did not apply
+const MAX_FIX_ATTEMPTS: usize = 2;
+
+/// Words of compiler messages that are never worth searching for
+const STOP_WORDS: &[&str] = &[
+    "error", "warning", "note", "help", "expected", "found", "type", "value", "function",
+    "struct", "field", "method", "member", "argument", "return", "cannot", "undefined",
+    "declared", "self", "Self", "const", "let", "var", "pub", "void", "bool", "char", "int",
+    "str", "usize", "isize", "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64", "f32", "f64",
+    "null", "true", "false", "anytype", "string", "number", "object", "any",
+];
+
+/// Snippets already gathered: (references, extensions, keywords) -> snippets
+type SnippetKey = (Vec<PathBuf>, Vec<String>, Vec<String>);
+
+/// Investigates build errors by comparing the failing file with reference codebases
 pub struct Investigator {
     references: Vec<PathBuf>,
+    extensions: Vec<String>,
+    keywords: Vec<String>,
+    auto_keywords: bool,
+    template: String,
+    fix_template: String,
+    snippet_budget: usize,
     backend: Backend,
     model: Option<String>,
-    keywords: Vec<String>,
+    policy: CallPolicy,
+    limits: Option<Arc<UsageLimits>>,
+    cache: Mutex<HashMap<SnippetKey, String>>,
+}
+
+impl Default for Investigator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Investigator {
+    /// Create an investigator without references
+    ///
+    /// Searches all source extensions, extracts keywords from build logs and
+    /// uses [`REFERENCE_INVESTIGATION_PROMPT`] and [`FIX_PROMPT`].
     pub fn new() -> Self {
         Self {
             references: Vec::new(),
+            extensions: SOURCE_EXTENSIONS.iter().map(|e| e.to_string()).collect(),
+            keywords: Vec::new(),
+            auto_keywords: true,
+            template: REFERENCE_INVESTIGATION_PROMPT.to_string(),
+            fix_template: FIX_PROMPT.to_string(),
+            snippet_budget: DEFAULT_SNIPPET_BUDGET,
             backend: Backend::default(),
             model: None,
-            keywords: vec![
-                "SetSwapChain".to_string(),
-                "ISwapChainPanelNative".to_string(),
-                "get_ActualWidth".to_string(),
-                "put_RequestedTheme".to_string(),
-                "HRESULT".to_string(),
-                "VTable".to_string(),
-            ],
+            policy: CallPolicy::default(),
+            limits: None,
+            cache: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Add a reference codebase root
     pub fn with_reference(mut self, path: impl Into<PathBuf>) -> Self {
         self.references.push(path.into());
         self
     }
 
+    /// Set the file extensions searched in references (without dot)
+    pub fn with_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.extensions = extensions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Add keywords searched for in addition to those of the build log
+    pub fn with_keywords<I, S>(mut self, keywords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.keywords.extend(keywords.into_iter().map(Into::into));
+        self
+    }
+
+    /// Enable or disable extracting keywords from build logs (default: enabled)
+    pub fn with_auto_keywords(mut self, enabled: bool) -> Self {
+        self.auto_keywords = enabled;
+        self
+    }
+
+    /// Set the prompt template
+    ///
+    /// Placeholders: `{file_name}`, `{language}`, `{content}`, `{errors}`,
+    /// `{keywords}` and `{references}`.
+    pub fn with_template(mut self, template: impl Into<String>) -> Self {
+        self.template = template.into();
+        self
+    }
+
+    /// Set the prompt template of [`Investigator::fix`]
+    ///
+    /// Placeholders: `{files}`, `{diff}`, `{errors}`, `{keywords}` and
+    /// `{references}`.
+    pub fn with_fix_template(mut self, template: impl Into<String>) -> Self {
+        self.fix_template = template.into();
+        self
+    }
+
+    /// Set the maximum size of the reference snippets (characters)
+    pub fn with_snippet_budget(mut self, budget: usize) -> Self {
+        self.snippet_budget = budget;
+        self
+    }
+
     pub fn with_backend(mut self, backend: Backend) -> Self {
         self.backend = backend;
         self
@@ -48,87 +163,397 @@ impl Investigator {
         self
     }
 
-    fn gather_snippets(&self) -> String {
-        let cache = SNIPPET_CACHE.get_or_init(|| Mutex::new(String::new()));
-        let mut locked_cache = cache.lock().unwrap();
-        
-        if !locked_cache.is_empty() {
-            return locked_cache.clone();
+    /// Set timeout, retries and fallback backends for the AI call
+    pub fn with_call_policy(mut self, policy: CallPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Share a rate limiter and spend budget with other callers
+    pub fn with_limits(mut self, limits: Arc<UsageLimits>) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Keywords searched for a build log: the configured ones, then the
+    /// extracted ones
+    pub fn keywords_for(&self, error_log: &str) -> Vec<String> {
+        let mut keywords = self.keywords.clone();
+        if self.auto_keywords {
+            for keyword in extract_keywords(error_log) {
+                if !keywords.contains(&keyword) {
+                    keywords.push(keyword);
+                }
+            }
+        }
+        keywords
+    }
+
+    /// Snippets of the references around `keywords`, cached per
+    /// references, extensions and keywords
+    pub fn gather_snippets(&self, keywords: &[String]) -> String {
+        let key = (self.references.clone(), self.extensions.clone(), keywords.to_vec());
+        if let Some(snippets) = self.lock_cache().get(&key) {
+            return snippets.clone();
+        }
+        let snippets = self.search_references(keywords);
+        self.lock_cache().insert(key, snippets.clone());
+        snippets
+    }
+
+    /// Build the investigation prompt for a failing file
+    pub fn build_prompt(&self, target_file: &Path, error_log: &str) -> Result<String> {
+        let content = fs::read_to_string(target_file)?;
+        let file_name = target_file
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let language = target_file
+            .extension()
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let keywords = self.keywords_for(error_log);
+        let snippets = self.gather_snippets(&keywords);
+
+        Ok(build_reference_investigation_prompt(
+            &self.template,
+            &file_name,
+            &language,
+            &content,
+            error_log,
+            &keywords.join(", "),
+            &snippets,
+        ))
+    }
+
+    /// Ask the AI why `target_file` fails to build with `error_log`
+    pub fn investigate(&self, target_file: &Path, error_log: &str) -> Result<String> {
+        let prompt = self.build_prompt(target_file, error_log)?;
+        self.call(&prompt)
+    }
+
+    /// Build the fix prompt for failing files
+    ///
+    /// `files` are absolute or relative to `base`; the prompt shows them
+    /// relative to `base`, which is where the returned diff applies.
+    pub fn build_fix_prompt(
+        &self,
+        base: &Path,
+        files: &[PathBuf],
+        diff: Option<&str>,
+        error_log: &str,
+    ) -> Result<String> {
+        let mut sections = String::new();
+        for file in files {
+            let path = base.join(file);
+            let content = fs::read_to_string(&path)?;
+            let relative = path.strip_prefix(base).unwrap_or(&path);
+            let language = path
+                .extension()
+                .map(|e| e.to_string_lossy().to_string())
+                .unwrap_or_default();
+            sections.push_str(&format!(
+                "\n### {}\n\n```{}\n{}\n```\n",
+                relative.to_string_lossy().replace('\\', "/"),
+                language,
+                content.trim_end()
+            ));
+        }
+        let keywords = self.keywords_for(error_log);
+        let snippets = self.gather_snippets(&keywords);
+
+        Ok(build_fix_prompt(
+            &self.fix_template,
+            &sections,
+            diff.unwrap_or_default(),
+            error_log,
+            &keywords.join(", "),
+            &snippets,
+        ))
+    }
+
+    /// Ask the AI for a unified diff that fixes `error_log`
+    ///
+    /// The diff is parsed and validated against the files under `base`;
+    /// if it does not apply, the AI is asked once more with the reason.
+    /// The returned patch has corrected hunk headers and is not applied.
+    pub fn fix(
+        &self,
+        base: &Path,
+        files: &[PathBuf],
+        diff: Option<&str>,
+        error_log: &str,
+    ) -> Result<Patch> {
+        self.fix_with(base, files, diff, error_log, |prompt| self.call(prompt))
+    }
+
+    fn fix_with(
+        &self,
+        base: &Path,
+        files: &[PathBuf],
+        diff: Option<&str>,
+        error_log: &str,
+        mut call: impl FnMut(&str) -> Result<String>,
+    ) -> Result<Patch> {
+        let initial = self.build_fix_prompt(base, files, diff, error_log)?;
+        let mut prompt = initial.clone();
+        let mut last_error = None;
+        for _ in 0..MAX_FIX_ATTEMPTS {
+            let response = call(&prompt)?;
+            match Patch::parse(&response).and_then(|patch| patch.validate(base)) {
+                Ok(patch) => return Ok(patch),
+                Err(e) => {
+                    prompt = build_fix_retry_prompt(&initial, &e.to_string(), &response);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| CodeReviewError::PatchError("no attempts".to_string())))
+    }
+
+    fn call(&self, prompt: &str) -> Result<String> {
+        let model = self.model.as_deref();
+        match self.limits {
+            Some(ref limits) => limits.call(prompt, self.backend, model, &self.policy),
+            None => call_ai(prompt, self.backend, model, &self.policy),
         }
+        .map(|outcome| outcome.response)
+    }
 
-        let mut snippets = String::new();
-        for ref_path in &self.references {
-            if !ref_path.exists() { continue; }
-            
-            let walker = WalkDir::new(ref_path).max_depth(5).into_iter();
-            for entry_res in walker {
-                let entry = match entry_res {
-                    Ok(e) => e,
-                    Err(_) => continue,
+    fn search_references(&self, keywords: &[String]) -> String {
+        if keywords.is_empty() {
+            return String::new();
+        }
+        let extensions: Vec<&str> = self.extensions.iter().map(String::as_str).collect();
+
+        // Snippets per keyword, so every keyword gets a share of the budget
+        let mut found: Vec<Vec<String>> = vec![Vec::new(); keywords.len()];
+        for root in self.references.iter().filter(|r| r.exists()) {
+            let mut files = walk_source_files(root, &extensions);
+            files.sort();
+            for file in files {
+                if found.iter().all(|s| s.len() >= MAX_SNIPPETS_PER_KEYWORD) {
+                    break;
+                }
+                let Ok(content) = fs::read_to_string(&file) else {
+                    continue;
                 };
-                let path = entry.path();
-                if path.is_file() && (path.extension().map_or(false, |ext| ext == "cpp" || ext == "h" || ext == "rs" || ext == "zig")) {
-                    if let Ok(content) = fs::read_to_string(path) {
-                        for kw in &self.keywords {
-                            if content.contains(kw) {
-                                snippets.push_str(&format!("\n--- Snippet from {:?} (keyword: {}) ---\n", path, kw));
-                                if let Some(pos) = content.find(kw) {
-                                    let start = if pos > 500 { pos - 500 } else { 0 };
-                                    let end = if pos + 500 < content.len() { pos + 500 } else { content.len() };
-                                    snippets.push_str(&content[start..end]);
-                                    snippets.push_str("\n");
-                                }
-                                break;
-                            }
+                let display = display_path(root, &file);
+                for (keyword, snippets) in keywords.iter().zip(found.iter_mut()) {
+                    if snippets.len() < MAX_SNIPPETS_PER_KEYWORD {
+                        if let Some(snippet) = snippet_around(&content, keyword, &display) {
+                            snippets.push(snippet);
                         }
                     }
                 }
-                if snippets.len() > 20000 { break; } 
             }
         }
-        *locked_cache = snippets.clone();
-        snippets
+
+        // Take the first snippet of every keyword, then the second, ...
+        let mut out = String::new();
+        for round in 0..MAX_SNIPPETS_PER_KEYWORD {
+            for snippets in &found {
+                let Some(snippet) = snippets.get(round) else {
+                    continue;
+                };
+                // Skip an oversize snippet, smaller ones may still fit
+                if out.len() + snippet.len() > self.snippet_budget {
+                    continue;
+                }
+                out.push_str(snippet);
+            }
+        }
+        out
     }
 
-    pub fn investigate(&self, target_file: &Path, error_log: &str) -> Result<String> {
-        let target_content = fs::read_to_string(target_file)?;
-        let target_name = target_file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
-
-        let ref_context = self.gather_snippets();
-
-        let prompt = format!(
-            r#"ARCHITECTURAL INVESTIGATION & FIX GENERATION
-You are an expert Windows System Engineer specializing in WinRT, DirectX, and Zig.
-
-TARGET FILE ({}):
-```zig
-{}
-```
-
-BUILD ERRORS:
-```
-{}
-```
-
-REFERENCE SNIPPETS FROM ESTABLISHED CODEBASES:
-{}
-
-TASK:
-1. Compare target logic with references.
-2. Fix `emit.zig` so generated code compiles and matches Ghostty expectations (naming, return types).
-3. Output ONLY the raw `emit.zig` content.
-"#,
-            target_name, target_content, error_log, ref_context
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, HashMap<SnippetKey, String>> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Identifiers in a build log worth searching for in reference code
+///
+/// Quoted names (`` `x` ``, `'x'`, `"x"`) are taken first-come, plus bare
+/// words shaped like code identifiers (`snake_case`, `camelCase`,
+/// `PascalCase`, `ALL_CAPS`). Paths, error codes and generic compiler
+/// words are skipped.
+pub fn extract_keywords(error_log: &str) -> Vec<String> {
+    let mut keywords: Vec<String> = Vec::new();
+    for line in error_log.lines() {
+        for word in line.split_whitespace() {
+            if word.contains(['/', '\\']) {
+                continue;
+            }
+            let quoted = word.starts_with(['`', '\'', '"']) || word.ends_with(['`', '\'', '"']);
+            for token in word.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+                if (quoted || looks_like_identifier(token))
+                    && is_keyword_candidate(token)
+                    && !keywords.iter().any(|k| k == token)
+                {
+                    keywords.push(token.to_string());
+                }
+            }
+        }
+    }
+    keywords.truncate(MAX_AUTO_KEYWORDS);
+    keywords
+}
+
+/// Whether a token is long and specific enough to search for
+fn is_keyword_candidate(token: &str) -> bool {
+    token.len() >= 3
+        && !token.starts_with(|c: char| c.is_ascii_digit())
+        && !STOP_WORDS.iter().any(|s| s.eq_ignore_ascii_case(token))
+}
+
+/// Whether a bare word is shaped like a code identifier rather than prose
+fn looks_like_identifier(word: &str) -> bool {
+    let letters = word.chars().filter(|c| c.is_alphabetic()).count();
+    let upper = word.chars().filter(|c| c.is_uppercase()).count();
+    let inner_upper = word.chars().skip(1).any(|c| c.is_uppercase());
+    let lower = word.chars().any(|c| c.is_lowercase());
+    (word.contains('_') && letters >= 2)
+        || (inner_upper && lower)
+        || (!lower && upper >= 3 && word.len() >= 4)
+}
+
+/// The lines around the first occurrence of `keyword`, with a header
+fn snippet_around(content: &str, keyword: &str, display: &str) -> Option<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let hit = lines.iter().position(|line| line.contains(keyword))?;
+    let start = hit.saturating_sub(SNIPPET_CONTEXT_LINES);
+    let end = (hit + SNIPPET_CONTEXT_LINES + 1).min(lines.len());
+    Some(format!(
+        "\n--- {}:{} (keyword: {}) ---\n{}\n",
+        display,
+        hit + 1,
+        keyword,
+        lines[start..end].join("\n")
+    ))
+}
+
+/// `<root name>/<path relative to root>`
+fn display_path(root: &Path, file: &Path) -> String {
+    let name = root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let relative = file.strip_prefix(root).unwrap_or(file);
+    format!("{}/{}", name, relative.to_string_lossy().replace('\\', "/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_extract_keywords() {
+        let log = "src/emit.zig:42:17: error: no field named 'put_RequestedTheme' in struct 'IFrameworkElement'\n\
+                   src/emit.zig:50:9: error: expected type 'HRESULT', found 'i32'\n\
+                   error[E0425]: cannot find function `load_config` in this scope";
+        assert_eq!(
+            extract_keywords(log),
+            vec!["put_RequestedTheme", "IFrameworkElement", "HRESULT", "load_config"]
         );
+    }
+
+    #[test]
+    fn test_gather_snippets_per_references() {
+        let dir = tempdir().unwrap();
+        let ghostty = dir.path().join("ghostty");
+        let other = dir.path().join("other");
+        fs::create_dir_all(&ghostty).unwrap();
+        fs::create_dir_all(&other).unwrap();
+        fs::write(ghostty.join("panel.zig"), "const x = 1;\nfn init() { SetSwapChain(panel); }\n").unwrap();
+        fs::write(ghostty.join("notes.txt"), "SetSwapChain").unwrap();
+        fs::write(other.join("panel.cpp"), "void f() { SetSwapChain(p); }\n").unwrap();
+
+        let keywords = vec!["SetSwapChain".to_string()];
+        let investigator = Investigator::new().with_reference(&ghostty);
+        let snippets = investigator.gather_snippets(&keywords);
+        assert!(snippets.contains("--- ghostty/panel.zig:2 (keyword: SetSwapChain) ---"));
+        assert!(!snippets.contains("notes.txt"));
+
+        // Another instance with other references does not see the first cache
+        let other_snippets = Investigator::new().with_reference(&other).gather_snippets(&keywords);
+        assert!(other_snippets.contains("other/panel.cpp:1"));
+        assert!(!other_snippets.contains("ghostty"));
+
+        let cpp_only = Investigator::new()
+            .with_reference(&ghostty)
+            .with_extensions(["cpp"])
+            .gather_snippets(&keywords);
+        assert!(cpp_only.is_empty());
+    }
+
+    #[test]
+    fn test_oversize_snippet_does_not_end_search() {
+        let dir = tempdir().unwrap();
+        let long_line = format!("fn huge() {{ {} }}\n", "x".repeat(400));
+        fs::write(dir.path().join("a.rs"), long_line).unwrap();
+        fs::write(dir.path().join("b.rs"), "fn small() {}\n").unwrap();
+
+        let keywords = vec!["huge".to_string(), "small".to_string()];
+        let snippets = Investigator::new()
+            .with_reference(dir.path())
+            .with_snippet_budget(200)
+            .gather_snippets(&keywords);
+        assert!(!snippets.contains("keyword: huge"));
+        assert!(snippets.contains("keyword: small"));
+    }
+
+    #[test]
+    fn test_build_prompt_uses_template() {
+        let dir = tempdir().unwrap();
+        let reference = dir.path().join("reference");
+        fs::create_dir_all(&reference).unwrap();
+        fs::write(reference.join("app.rs"), "fn main() { load_config(); }\n").unwrap();
+        let target = dir.path().join("main.rs");
+        fs::write(&target, "fn main() { load_cfg(); }\n").unwrap();
+
+        let investigator = Investigator::new()
+            .with_reference(&reference)
+            .with_keywords(["main"])
+            .with_template("{file_name}|{language}|{keywords}|{errors}\n{references}");
+        let prompt = investigator
+            .build_prompt(&target, "error[E0425]: cannot find function `load_config`")
+            .unwrap();
+        assert!(prompt.starts_with("main.rs|rs|main, load_config|error[E0425]"));
+        assert!(prompt.contains("reference/app.rs:1 (keyword: load_config)"));
+    }
+
+    #[test]
+    fn test_fix_retries_with_validation_error() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "fn main() {\n    load_cfg();\n}\n").unwrap();
 
-        let options = if let Some(ref m) = self.model {
-            AnalyzeOptions::with_model(m).with_backend(self.backend)
-        } else {
-            AnalyzeOptions::default().with_backend(self.backend)
-        };
+        let responses = [
+            "```diff\n--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,3 +1,3 @@\n fn main() {\n-    load_settings();\n+    load_config();\n }\n```",
+            "```diff\n--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,3 +1,3 @@\n fn main() {\n-    load_cfg();\n+    load_config();\n }\n```",
+        ];
+        let mut prompts = Vec::new();
+        let patch = Investigator::new()
+            .with_auto_keywords(false)
+            .with_fix_template("{files}|{diff}|{errors}")
+            .fix_with(
+                dir.path(),
+                &[PathBuf::from("src/main.rs")],
+                None,
+                "error[E0425]: cannot find function `load_cfg`",
+                |prompt| {
+                    prompts.push(prompt.to_string());
+                    Ok(responses[prompts.len() - 1].to_string())
+                },
+            )
+            .unwrap();
 
-        let res = analyze(&prompt, &Vec::<PathBuf>::new(), options)
-            .map_err(|e| crate::error::CodeReviewError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
-        Ok(res)
+        assert_eq!(patch.paths(), vec!["src/main.rs"]);
+        assert!(prompts[0].contains("### src/main.rs\n\n```rs\nfn main() {"));
+        assert!(prompts[0].contains("|（なし）|error[E0425]"));
+        assert!(prompts[1].contains("does not apply: expected `load_settings();`"));
+        // Nothing is written until the caller applies the patch
+        assert!(fs::read_to_string(dir.path().join("src/main.rs")).unwrap().contains("load_cfg"));
     }
 }
@@ -15,6 +15,8 @@
 //! - **Git Integration**: Reviews git diffs when available
 //! - **Customizable Prompts**: Japanese prompts for architecture, security, quick reviews
 //! - **Debouncing**: Prevents excessive API calls on rapid saves
+//! - **Call Policy**: Timeouts, retries with backoff, fallback backends, circuit breaker
+//! - **Usage Limits**: Shared rate limiter and per-session/per-day token budget
 //!
 //! ## Example
 //!
@@ -59,15 +61,28 @@
 //! ```
 
 mod analyzer;
+mod cache;
+mod clones;
 mod context;
+mod coupling;
 mod error;
 mod git;
+mod graph;
+mod investigator;
+mod limits;
+mod metrics;
 mod modules;
 mod parser;
+mod patch;
+mod policy;
 mod prompt;
 mod result;
+mod resolver;
 mod reviewer;
+mod rules;
 pub mod shared_finder;
+mod snapshot;
+mod symbols;
 mod utils;
 
 /// Re-export of `Backend` from `cli_ai_analyzer` for convenience.
@@ -82,21 +97,48 @@ mod utils;
 /// ```rust,ignore
 /// use ai_code_review::{CodeReviewer, Backend};
 /// ```
+pub use analyzer::find_importers_with_include_dirs;
+pub use cache::{parse_cache, parse_cache_path, ParseCache};
 pub use cli_ai_analyzer::Backend;
+pub use clones::{CloneMatch, CloneType};
 pub use context::{
-    gather_context, gather_context_default, gather_raw_context, gather_requirements, ProjectContext,
-    RawContext,
+    gather_context, gather_context_default, gather_coupling_context, gather_context_with_graph,
+    gather_context_with_include_dirs, gather_context_with_resolver, gather_raw_context,
+    gather_requirements, ProjectContext, RawContext,
 };
+pub use coupling::{
+    analyze_coupling, unbalanced_coupling, CouplingDistance, CouplingEdge, IntegrationStrength,
+};
+pub use error::CodeReviewError;
+pub use git::{get_file_churn, get_working_diff, repo_root, resolve_commit};
+pub use graph::{DependencyGraph, GraphFormat, GraphLevel, ModuleEdge};
+pub use investigator::{extract_keywords, Investigator, DEFAULT_SNIPPET_BUDGET};
+pub use limits::{
+    default_usage_path, estimate_tokens, BudgetAction, RateLimiter, SpendBudget, UsageLimits,
+};
+pub use metrics::{analyze_metrics, FileMetrics, FunctionMetrics, MetricThresholds, METRICS_CHECK};
 pub use modules::generate_module_tree;
+pub use patch::{FilePatch, Hunk, HunkLine, Patch};
+pub use policy::{call_ai, CallOutcome, CallPolicy, CircuitBreaker};
 pub use prompt::{
-    build_analyze_prompt, build_discovery_prompt, build_find_shared_prompt,
-    build_investigate_prompt, build_prompt, build_prompt_with_context, PromptType,
+    build_analyze_prompt, build_discovery_prompt, build_find_shared_prompt, build_fix_prompt,
+    build_fix_retry_prompt, build_investigate_prompt, build_prompt, build_prompt_with_context, build_reference_investigation_prompt, format_architecture_drift, format_metrics, format_rule_violations, format_symbol_context, PromptType,
     ANALYZE_PROMPT, ARCHITECTURE_REVIEW_PROMPT, ARCHITECTURE_REVIEW_WITH_CONTEXT_PROMPT,
-    DEFAULT_REVIEW_PROMPT, DISCOVERY_PROMPT, FIND_SHARED_PROMPT, INVESTIGATE_PROMPT,
-    PRINCIPLES_REVIEW_PROMPT, QDD_PROMPT, QUICK_REVIEW_PROMPT, SECURITY_REVIEW_PROMPT,
+    DEFAULT_REVIEW_PROMPT, DISCOVERY_PROMPT, FIND_SHARED_PROMPT, FIX_PROMPT, FIX_RETRY_PROMPT,
+    INVESTIGATE_PROMPT, PRINCIPLES_REVIEW_PROMPT, QDD_PROMPT, QUICK_REVIEW_PROMPT, REFERENCE_INVESTIGATION_PROMPT,
+    SECURITY_REVIEW_PROMPT,
 };
-pub use result::{ReviewResult, ReviewSeverity, ReviewSummary};
+pub use result::{Finding, PromptTypeStats, ReviewMetrics, ReviewResult, ReviewSeverity, ReviewSummary};
+pub use resolver::ImportResolver;
 pub use reviewer::{CodeReviewer, ReviewConfig, perform_multi_review};
+pub use rules::{ArchitectureRule, ArchitectureRules, RuleKind, RuleViolation, RULES_FILE_NAME};
+pub use snapshot::{
+    ArchitectureDrift, ArchitectureSnapshot, DriftThresholds, ModuleGrowth, ModuleSnapshot,
+};
+pub use symbols::{
+    changed_lines, gather_symbol_context, gather_symbol_context_with_graph, CallSite,
+    SymbolContext, SymbolDefinition, DEFAULT_SYMBOL_BUDGET,
+};
 pub use utils::fs::{walk_source_files, SOURCE_EXTENSIONS};
 
 #[cfg(test)]
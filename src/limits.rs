@@ -0,0 +1,571 @@
+//! Rate limiting and spend budget for AI calls
+//!
+//! A single `UsageLimits` instance is shared (via `Arc`) by every entry
+//! point — watch mode, `perform_multi_review` and the CLI — so concurrent
+//! reviews draw from one request rate and one token budget. Daily usage is
+//! persisted to a JSON file so separate processes see the same total.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use cli_ai_analyzer::Backend;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CodeReviewError, Result};
+use crate::policy::{call_ai_gated, CallOutcome, CallPolicy};
+use crate::utils::fs::{data_dir, lock_exclusive, write_atomic};
+
+/// Window used for the requests-per-minute limit
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Default file name for persisted usage
+const USAGE_FILE_NAME: &str = "usage.json";
+
+/// Estimate the token count of a text.
+///
+/// ASCII text averages ~4 characters per token; CJK and other non-ASCII
+/// characters are counted as one token each.
+pub fn estimate_tokens(text: &str) -> u64 {
+    let (ascii, other) = text
+        .chars()
+        .fold((0u64, 0u64), |(a, o), c| if c.is_ascii() { (a + 1, o) } else { (a, o + 1) });
+    ascii.div_ceil(4) + other
+}
+
+/// Internal limiter state
+struct LimiterState {
+    in_flight: usize,
+    recent: VecDeque<Instant>,
+}
+
+/// Blocking limiter for requests per minute and concurrent calls
+pub struct RateLimiter {
+    requests_per_minute: u32,
+    max_concurrent: usize,
+    state: Mutex<LimiterState>,
+    released: Condvar,
+}
+
+/// Permit for one in-flight call; releases its slot on drop
+pub struct RatePermit<'a> {
+    limiter: &'a RateLimiter,
+}
+
+impl Drop for RatePermit<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.limiter.state.lock() {
+            state.in_flight = state.in_flight.saturating_sub(1);
+        }
+        self.limiter.released.notify_all();
+    }
+}
+
+impl RateLimiter {
+    /// Create a limiter (0 disables the respective limit)
+    pub fn new(requests_per_minute: u32, max_concurrent: usize) -> Self {
+        Self {
+            requests_per_minute,
+            max_concurrent,
+            state: Mutex::new(LimiterState {
+                in_flight: 0,
+                recent: VecDeque::new(),
+            }),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Block until a call may start, then reserve a slot
+    pub fn acquire(&self) -> Result<RatePermit<'_>> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|e| CodeReviewError::LockError(e.to_string()))?;
+        loop {
+            let now = Instant::now();
+            while state
+                .recent
+                .front()
+                .is_some_and(|t| now.duration_since(*t) >= RATE_WINDOW)
+            {
+                state.recent.pop_front();
+            }
+
+            let concurrency_ok = self.max_concurrent == 0 || state.in_flight < self.max_concurrent;
+            let rate_ok = self.requests_per_minute == 0
+                || state.recent.len() < self.requests_per_minute as usize;
+
+            if concurrency_ok && rate_ok {
+                state.in_flight += 1;
+                state.recent.push_back(now);
+                return Ok(RatePermit { limiter: self });
+            }
+
+            // Wake on a released slot, or when the oldest request leaves the window
+            let wait = if rate_ok {
+                RATE_WINDOW
+            } else {
+                state
+                    .recent
+                    .front()
+                    .map(|t| RATE_WINDOW.saturating_sub(now.duration_since(*t)))
+                    .unwrap_or(RATE_WINDOW)
+            };
+            state = self
+                .released
+                .wait_timeout(state, wait)
+                .map_err(|e| CodeReviewError::LockError(e.to_string()))?
+                .0;
+        }
+    }
+}
+
+/// Daily usage record persisted on disk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// Local date (YYYY-MM-DD) the counters belong to
+    pub date: String,
+    /// Estimated tokens spent (prompt + response)
+    pub tokens: u64,
+    /// Number of AI calls
+    pub requests: u64,
+}
+
+impl UsageRecord {
+    fn today() -> Self {
+        Self {
+            date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            tokens: 0,
+            requests: 0,
+        }
+    }
+
+    /// Load from disk, resetting the counters if the file is missing or
+    /// from another day
+    ///
+    /// A file that cannot be read or parsed is an `IoError` naming the
+    /// file: silently starting from 0 would let a corrupt file lift the
+    /// budget, and it is not a spent budget either.
+    fn load(path: &Path) -> Result<Self> {
+        let today = Self::today();
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(today),
+            Err(e) => {
+                return Err(std::io::Error::new(
+                    e.kind(),
+                    format!("cannot read usage file {}: {}", path.display(), e),
+                )
+                .into())
+            }
+        };
+        let record: UsageRecord = serde_json::from_str(&content).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("usage file {} is corrupt ({}); fix or delete it", path.display(), e),
+            )
+        })?;
+        Ok(if record.date == today.date { record } else { today })
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        write_atomic(path, serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Internal budget state
+struct BudgetState {
+    session_tokens: u64,
+    daily: UsageRecord,
+}
+
+/// Token budget per session and per day
+pub struct SpendBudget {
+    session_limit: Option<u64>,
+    daily_limit: Option<u64>,
+    state_path: Option<PathBuf>,
+    state: Mutex<BudgetState>,
+}
+
+impl SpendBudget {
+    /// Create a budget (`None` = unlimited). Daily usage is persisted to
+    /// `state_path` when given, otherwise it only lives for this process.
+    ///
+    /// Fails if the usage file exists but cannot be read.
+    pub fn new(
+        session_limit: Option<u64>,
+        daily_limit: Option<u64>,
+        state_path: Option<PathBuf>,
+    ) -> Result<Self> {
+        let daily = match state_path {
+            Some(ref path) => UsageRecord::load(path)?,
+            None => UsageRecord::today(),
+        };
+        Ok(Self {
+            session_limit,
+            daily_limit,
+            state_path,
+            state: Mutex::new(BudgetState {
+                session_tokens: 0,
+                daily,
+            }),
+        })
+    }
+
+    /// Check whether either limit has been reached.
+    ///
+    /// Re-reads the persisted daily usage so spend from other processes
+    /// counts. A usage file that has become unreadable is not a spent
+    /// budget: it is logged here and fails the next call instead.
+    pub fn is_exhausted(&self) -> bool {
+        self.check().unwrap_or_else(|e| {
+            log::warn!("{}", e);
+            false
+        })
+    }
+
+    /// Like [`SpendBudget::is_exhausted`], but fails if the usage file
+    /// cannot be read
+    fn check(&self) -> Result<bool> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|e| CodeReviewError::LockError(e.to_string()))?;
+        if let Some(ref path) = self.state_path {
+            state.daily = UsageRecord::load(path)?;
+        } else if state.daily.date != UsageRecord::today().date {
+            state.daily = UsageRecord::today();
+        }
+        Ok(self.session_limit.is_some_and(|l| state.session_tokens >= l)
+            || self.daily_limit.is_some_and(|l| state.daily.tokens >= l))
+    }
+
+    /// Record spent tokens and persist the daily total
+    ///
+    /// The usage file is updated under a file lock, so concurrent processes
+    /// (e.g. parallel hook runs) do not lose each other's spend.
+    pub fn record(&self, tokens: u64) -> Result<()> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|e| CodeReviewError::LockError(e.to_string()))?;
+        state.session_tokens += tokens;
+        let _file_lock = self.state_path.as_deref().map(lock_exclusive).transpose()?;
+        // Merge with whatever other processes wrote since we last looked
+        let mut daily = match self.state_path {
+            Some(ref path) => UsageRecord::load(path)?,
+            None if state.daily.date == UsageRecord::today().date => state.daily.clone(),
+            None => UsageRecord::today(),
+        };
+        daily.tokens += tokens;
+        daily.requests += 1;
+        if let Some(ref path) = self.state_path {
+            daily.save(path)?;
+        }
+        state.daily = daily;
+        Ok(())
+    }
+
+    /// Tokens spent in this session
+    pub fn session_tokens(&self) -> u64 {
+        self.state.lock().map(|s| s.session_tokens).unwrap_or(0)
+    }
+
+    /// Tokens spent today (as of the last check or record)
+    pub fn daily_tokens(&self) -> u64 {
+        self.state.lock().map(|s| s.daily.tokens).unwrap_or(0)
+    }
+}
+
+/// What to do when the spend budget is exhausted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BudgetAction {
+    /// Keep reviewing with the quick prompt
+    #[default]
+    Quick,
+    /// Skip reviews entirely
+    Skip,
+}
+
+/// Shared rate limiter and spend budget for all AI calls
+pub struct UsageLimits {
+    rate: Option<RateLimiter>,
+    budget: Option<SpendBudget>,
+    on_exhausted: BudgetAction,
+}
+
+impl Default for UsageLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UsageLimits {
+    /// Create limits with no rate limit and no budget
+    pub fn new() -> Self {
+        Self {
+            rate: None,
+            budget: None,
+            on_exhausted: BudgetAction::default(),
+        }
+    }
+
+    /// Limit requests per minute and concurrent calls (0 = unlimited)
+    pub fn with_rate_limit(mut self, requests_per_minute: u32, max_concurrent: usize) -> Self {
+        self.rate = Some(RateLimiter::new(requests_per_minute, max_concurrent));
+        self
+    }
+
+    /// Set the token budget
+    pub fn with_budget(mut self, budget: SpendBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Set the behavior when the budget is exhausted
+    pub fn on_exhausted(mut self, action: BudgetAction) -> Self {
+        self.on_exhausted = action;
+        self
+    }
+
+    /// Action to take when the budget is exhausted
+    pub fn exhausted_action(&self) -> BudgetAction {
+        self.on_exhausted
+    }
+
+    /// Check whether the spend budget is exhausted
+    pub fn is_exhausted(&self) -> bool {
+        self.budget.as_ref().is_some_and(|b| b.is_exhausted())
+    }
+
+    /// Access the spend budget, if configured
+    pub fn budget(&self) -> Option<&SpendBudget> {
+        self.budget.as_ref()
+    }
+
+    /// Send a prompt through the rate limiter and record its token spend.
+    ///
+    /// Fails with `CodeReviewError::BudgetExhausted` when the budget is
+    /// already used up, and with `IoError` when the usage file cannot be
+    /// read (whatever the exhausted action). Degrading to a cheaper prompt is the caller's job,
+    /// since only the caller knows which prompt it is sending.
+    pub fn call(
+        &self,
+        prompt: &str,
+        backend: Backend,
+        model: Option<&str>,
+        policy: &CallPolicy,
+    ) -> Result<CallOutcome> {
+        self.call_inner(prompt, backend, model, policy, false)
+    }
+
+    /// Send a prompt that was already degraded to the quick prompt.
+    ///
+    /// Allowed past an exhausted budget when the action is `BudgetAction::Quick`;
+    /// spend is still recorded.
+    pub fn call_degraded(
+        &self,
+        prompt: &str,
+        backend: Backend,
+        model: Option<&str>,
+        policy: &CallPolicy,
+    ) -> Result<CallOutcome> {
+        self.call_inner(prompt, backend, model, policy, self.on_exhausted == BudgetAction::Quick)
+    }
+
+    fn call_inner(
+        &self,
+        prompt: &str,
+        backend: Backend,
+        model: Option<&str>,
+        policy: &CallPolicy,
+        allow_exhausted: bool,
+    ) -> Result<CallOutcome> {
+        if let Some(ref budget) = self.budget {
+            // A broken usage file is an error, not a spent budget
+            let exhausted = budget.check()?;
+            if !allow_exhausted && exhausted {
+                return Err(CodeReviewError::BudgetExhausted(format!(
+                    "session {} / today {} tokens",
+                    budget.session_tokens(),
+                    budget.daily_tokens()
+                )));
+            }
+        }
+
+        // One permit per attempt, so retries and fallbacks are rate limited too
+        let outcome = call_ai_gated(prompt, backend, model, policy, || {
+            self.rate.as_ref().map(|r| r.acquire()).transpose()
+        })?;
+
+        if let Some(ref budget) = self.budget {
+            let tokens = estimate_tokens(prompt) + estimate_tokens(&outcome.response);
+            if let Err(e) = budget.record(tokens) {
+                log::warn!("Failed to persist usage: {}", e);
+            }
+        }
+        Ok(outcome)
+    }
+}
+
+/// Default location of the persisted usage file (`~/.ai-code-review/usage.json`)
+pub fn default_usage_path() -> PathBuf {
+    data_dir().join(USAGE_FILE_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        assert_eq!(estimate_tokens("設計"), 2);
+    }
+
+    #[test]
+    fn test_rate_limiter_caps_concurrency() {
+        let limiter = Arc::new(RateLimiter::new(0, 2));
+        let peak = Arc::new(Mutex::new((0usize, 0usize)));
+
+        let handles: Vec<_> = (0..6)
+            .map(|_| {
+                let limiter = Arc::clone(&limiter);
+                let peak = Arc::clone(&peak);
+                thread::spawn(move || {
+                    let _permit = limiter.acquire().unwrap();
+                    {
+                        let mut p = peak.lock().unwrap();
+                        p.0 += 1;
+                        p.1 = p.1.max(p.0);
+                    }
+                    thread::sleep(Duration::from_millis(20));
+                    peak.lock().unwrap().0 -= 1;
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert!(peak.lock().unwrap().1 <= 2);
+    }
+
+    #[test]
+    fn test_rate_limiter_releases_on_drop() {
+        let limiter = RateLimiter::new(0, 1);
+        drop(limiter.acquire().unwrap());
+        // Would block forever if the slot were not released
+        let _again = limiter.acquire().unwrap();
+    }
+
+    #[test]
+    fn test_session_budget_exhaustion() {
+        let budget = SpendBudget::new(Some(100), None, None).unwrap();
+        assert!(!budget.is_exhausted());
+        budget.record(60).unwrap();
+        assert!(!budget.is_exhausted());
+        budget.record(60).unwrap();
+        assert!(budget.is_exhausted());
+        assert_eq!(budget.session_tokens(), 120);
+    }
+
+    #[test]
+    fn test_daily_budget_persists_across_instances() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("usage.json");
+
+        let first = SpendBudget::new(None, Some(100), Some(path.clone())).unwrap();
+        first.record(80).unwrap();
+
+        // A second process (new instance) sees the persisted usage
+        let second = SpendBudget::new(None, Some(100), Some(path.clone())).unwrap();
+        assert_eq!(second.daily_tokens(), 80);
+        assert!(!second.is_exhausted());
+        second.record(30).unwrap();
+        assert!(first.is_exhausted());
+    }
+
+    #[test]
+    fn test_stale_usage_file_is_reset() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("usage.json");
+        let old = UsageRecord {
+            date: "2000-01-01".to_string(),
+            tokens: 1_000_000,
+            requests: 10,
+        };
+        old.save(&path).unwrap();
+
+        let budget = SpendBudget::new(None, Some(100), Some(path)).unwrap();
+        assert_eq!(budget.daily_tokens(), 0);
+        assert!(!budget.is_exhausted());
+    }
+
+    #[test]
+    fn test_corrupt_usage_file_is_an_error() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("usage.json");
+        fs::write(&path, "{ \"date\": ").unwrap();
+        assert!(SpendBudget::new(None, Some(100), Some(path.clone())).is_err());
+
+        // Corrupted after start: fail closed instead of resetting to 0
+        fs::remove_file(&path).unwrap();
+        let budget = SpendBudget::new(None, Some(100), Some(path.clone())).unwrap();
+        fs::write(&path, "garbage").unwrap();
+        assert!(!budget.is_exhausted());
+        assert!(budget.record(1).is_err());
+
+        // Calls fail with the file named, not as an exhausted budget
+        let limits = UsageLimits::new()
+            .with_budget(budget)
+            .on_exhausted(BudgetAction::Quick);
+        for result in [
+            limits.call("prompt", Backend::default(), None, &CallPolicy::default()),
+            limits.call_degraded("prompt", Backend::default(), None, &CallPolicy::default()),
+        ] {
+            match result {
+                Err(CodeReviewError::IoError(e)) => assert!(e.to_string().contains("usage.json")),
+                other => panic!("expected an IoError, got {:?}", other.map(|o| o.response)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_concurrent_records_are_not_lost() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("usage.json");
+        // Separate instances stand in for separate processes
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let path = path.clone();
+                thread::spawn(move || {
+                    let budget = SpendBudget::new(None, None, Some(path)).unwrap();
+                    for _ in 0..5 {
+                        budget.record(1).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        let record = UsageRecord::load(&path).unwrap();
+        assert_eq!(record.tokens, 40);
+        assert_eq!(record.requests, 40);
+    }
+
+    #[test]
+    fn test_call_rejected_when_exhausted() {
+        let limits = UsageLimits::new().with_budget(SpendBudget::new(Some(0), None, None).unwrap());
+        let result = limits.call("prompt", Backend::default(), None, &CallPolicy::default());
+        assert!(matches!(result, Err(CodeReviewError::BudgetExhausted(_))));
+    }
+}
@@ -0,0 +1,416 @@
+//! Per-function code metrics
+//!
+//! Walks the tree-sitter AST of Rust, TypeScript/JavaScript and Python
+//! files and measures each named function: length, deepest nesting of
+//! control flow, cyclomatic complexity and parameter count. Breaches of
+//! [`MetricThresholds`] become deterministic findings, so the AI does not
+//! have to estimate sizes from a diff.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Node, Parser};
+
+use crate::parser::symbols::{grammar_for, MetricKinds};
+use crate::prompt::format_metrics;
+use crate::result::{Finding, ReviewSeverity};
+
+/// Check name of findings produced by metric thresholds
+pub const METRICS_CHECK: &str = "metrics";
+
+/// Measurements of one function or method
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FunctionMetrics {
+    /// Function name (methods by their own name)
+    pub name: String,
+    /// First line (1-based)
+    pub line: usize,
+    /// Length in lines, signature included
+    pub lines: usize,
+    /// Deepest nesting of control-flow blocks inside the body
+    pub nesting: usize,
+    /// Cyclomatic complexity (1 + decision points)
+    pub complexity: usize,
+    /// Number of parameters, `self` excluded
+    pub params: usize,
+}
+
+impl FunctionMetrics {
+    /// Last line (1-based, inclusive)
+    pub fn end_line(&self) -> usize {
+        self.line + self.lines.saturating_sub(1)
+    }
+
+    /// Descriptions of the thresholds this function exceeds
+    pub fn breaches(&self, thresholds: &MetricThresholds) -> Vec<String> {
+        let mut breaches = Vec::new();
+        if self.lines > thresholds.function_lines {
+            breaches.push(format!("{} lines (limit {})", self.lines, thresholds.function_lines));
+        }
+        if self.nesting > thresholds.nesting {
+            breaches.push(format!("nesting depth {} (limit {})", self.nesting, thresholds.nesting));
+        }
+        if self.complexity > thresholds.complexity {
+            breaches.push(format!("cyclomatic complexity {} (limit {})", self.complexity, thresholds.complexity));
+        }
+        if self.params > thresholds.params {
+            breaches.push(format!("{} parameters (limit {})", self.params, thresholds.params));
+        }
+        breaches
+    }
+}
+
+/// Measurements of one source file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FileMetrics {
+    /// Functions measured, in source order
+    pub functions: Vec<FunctionMetrics>,
+    /// Named functions and methods in the file
+    pub function_count: usize,
+    /// Type definitions in the file (structs, enums, traits, classes, ...)
+    pub type_count: usize,
+    /// Lines in the file
+    pub lines: usize,
+}
+
+impl FileMetrics {
+    /// Functions and types defined in the file
+    pub fn items(&self) -> usize {
+        self.function_count + self.type_count
+    }
+
+    /// Keep only the functions spanning any of `lines` (file counts are kept)
+    pub fn touching(&self, lines: &BTreeSet<usize>) -> FileMetrics {
+        FileMetrics {
+            functions: self
+                .functions
+                .iter()
+                .filter(|f| lines.range(f.line..=f.end_line()).next().is_some())
+                .cloned()
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Threshold breaches as findings
+    pub fn findings(&self, thresholds: &MetricThresholds) -> Vec<Finding> {
+        let mut findings: Vec<Finding> = self
+            .functions
+            .iter()
+            .filter_map(|f| {
+                let breaches = f.breaches(thresholds);
+                (!breaches.is_empty()).then(|| Finding {
+                    check: METRICS_CHECK.to_string(),
+                    severity: ReviewSeverity::Warning,
+                    message: format!("`{}` has {}", f.name, breaches.join(", ")),
+                    line: Some(f.line),
+                })
+            })
+            .collect();
+        if self.items() > thresholds.file_items {
+            findings.push(Finding {
+                check: METRICS_CHECK.to_string(),
+                severity: ReviewSeverity::Warning,
+                message: format!(
+                    "file defines {} items ({} functions, {} types; limit {})",
+                    self.items(),
+                    self.function_count,
+                    self.type_count,
+                    thresholds.file_items
+                ),
+                line: None,
+            });
+        }
+        findings
+    }
+
+    /// Format as a compact table for the prompt
+    pub fn to_prompt_string(&self, thresholds: &MetricThresholds) -> String {
+        format_metrics(self, thresholds)
+    }
+}
+
+/// Limits above which a metric is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricThresholds {
+    /// Maximum function length in lines
+    pub function_lines: usize,
+    /// Maximum nesting depth of control flow
+    pub nesting: usize,
+    /// Maximum cyclomatic complexity
+    pub complexity: usize,
+    /// Maximum parameter count
+    pub params: usize,
+    /// Maximum functions plus types per file
+    pub file_items: usize,
+}
+
+impl Default for MetricThresholds {
+    fn default() -> Self {
+        Self {
+            function_lines: 50,
+            nesting: 4,
+            complexity: 10,
+            params: 5,
+            file_items: 40,
+        }
+    }
+}
+
+/// Measure the functions of a source file
+///
+/// Returns `None` for unsupported languages and unparsable input.
+pub fn analyze_metrics(path: &Path, source: &str) -> Option<FileMetrics> {
+    let grammar = grammar_for(path)?;
+    let mut parser = Parser::new();
+    parser.set_language(&grammar.language).ok()?;
+    let tree = parser.parse(source, None)?;
+
+    let mut walk = Walk {
+        grammar: &grammar.metrics,
+        source,
+        metrics: FileMetrics {
+            lines: source.lines().count(),
+            ..FileMetrics::default()
+        },
+        decisions: Vec::new(),
+    };
+    walk.visit(tree.root_node(), None, 0);
+
+    let Walk { mut metrics, decisions, .. } = walk;
+    for (function, decisions) in metrics.functions.iter_mut().zip(decisions) {
+        function.complexity = 1 + decisions.max(0) as usize;
+    }
+    metrics.function_count = metrics.functions.len();
+    Some(metrics)
+}
+
+/// State of one AST walk
+struct Walk<'a> {
+    grammar: &'a MetricKinds,
+    source: &'a str,
+    metrics: FileMetrics,
+    /// Decision points per function (parallel to `metrics.functions`)
+    decisions: Vec<i64>,
+}
+
+impl Walk<'_> {
+    /// Visit `node` inside function `current` at nesting `depth`
+    fn visit(&mut self, node: Node, current: Option<usize>, depth: usize) {
+        let kind = node.kind();
+        let grammar = self.grammar;
+
+        if grammar.functions.contains(&kind) {
+            if let Some(name) = self.function_name(node) {
+                let index = self.metrics.functions.len();
+                self.metrics.functions.push(FunctionMetrics {
+                    name,
+                    line: node.start_position().row + 1,
+                    lines: node.end_position().row - node.start_position().row + 1,
+                    nesting: 0,
+                    complexity: 1,
+                    params: self.param_count(node),
+                });
+                self.decisions.push(0);
+                self.visit_children(node, Some(index), 0);
+                return;
+            }
+        }
+        if grammar.types.contains(&kind) {
+            self.metrics.type_count += 1;
+        }
+
+        let mut depth = depth;
+        if let Some(index) = current {
+            if grammar.branches.contains(&kind) {
+                self.decisions[index] += 1;
+            }
+            if grammar.multiway.contains(&kind) {
+                self.decisions[index] -= 1;
+            }
+            if grammar.binary.contains(&kind) && self.is_logical(node) {
+                self.decisions[index] += 1;
+            }
+            // `else if` continues a chain rather than nesting
+            let chained = node.parent().is_some_and(|p| p.kind() == "else_clause");
+            if grammar.nesting.contains(&kind) && !chained {
+                depth += 1;
+                let function = &mut self.metrics.functions[index];
+                function.nesting = function.nesting.max(depth);
+            }
+        }
+        self.visit_children(node, current, depth);
+    }
+
+    fn visit_children(&mut self, node: Node, current: Option<usize>, depth: usize) {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit(child, current, depth);
+        }
+    }
+
+    /// Name of a function node; arrow functions and closures take the
+    /// name of the variable or field they are assigned to
+    fn function_name(&self, node: Node) -> Option<String> {
+        let name = node.child_by_field_name("name").or_else(|| {
+            let parent = node.parent()?;
+            match parent.kind() {
+                "variable_declarator" | "public_field_definition" => parent.child_by_field_name("name"),
+                "let_declaration" => parent.child_by_field_name("pattern"),
+                _ => None,
+            }
+        })?;
+        matches!(name.kind(), "identifier" | "property_identifier" | "type_identifier")
+            .then(|| self.source[name.byte_range()].to_string())
+    }
+
+    fn param_count(&self, node: Node) -> usize {
+        let Some(params) = node
+            .child_by_field_name("parameters")
+            .or_else(|| node.child_by_field_name("parameter"))
+        else {
+            return 0;
+        };
+        if params.kind() == "identifier" {
+            return 1;
+        }
+        let mut cursor = params.walk();
+        params
+            .named_children(&mut cursor)
+            .filter(|p| !self.grammar.non_params.contains(&p.kind()))
+            .enumerate()
+            // Python methods take `self` / `cls` explicitly
+            .filter(|(i, p)| !(*i == 0 && matches!(&self.source[p.byte_range()], "self" | "cls")))
+            .count()
+    }
+
+    fn is_logical(&self, node: Node) -> bool {
+        node.child_by_field_name("operator")
+            .is_some_and(|op| self.grammar.logical_operators.contains(&&self.source[op.byte_range()]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_rust_metrics() {
+        let source = r#"
+struct Config;
+
+impl Config {
+    fn check(&self, a: u32, b: u32) -> bool {
+        if a > 0 && b > 0 {
+            for i in 0..a {
+                match i {
+                    0 => return true,
+                    1 => {}
+                    _ => {}
+                }
+            }
+        } else if a == b {
+            return false;
+        }
+        true
+    }
+}
+
+fn simple() {}
+"#;
+        let metrics = analyze_metrics(Path::new("lib.rs"), source).unwrap();
+        assert_eq!((metrics.function_count, metrics.type_count), (2, 1));
+
+        let check = &metrics.functions[0];
+        assert_eq!(check.name, "check");
+        assert_eq!((check.line, check.lines), (5, 14));
+        assert_eq!(check.params, 2);
+        // if, &&, for, 3 arms - 1, else if
+        assert_eq!(check.complexity, 7);
+        // if > for > match
+        assert_eq!(check.nesting, 3);
+
+        let simple = &metrics.functions[1];
+        assert_eq!((simple.complexity, simple.nesting, simple.params), (1, 0, 0));
+    }
+
+    #[test]
+    #[cfg(feature = "lang-typescript")]
+    fn test_typescript_metrics() {
+        let source = r#"export const load = async (id: number, force?: boolean) => {
+  const items = list.filter((x) => x.ok || force);
+  return items.length > 0 ? items[0] : null;
+};
+class Store {
+  save(user: User) {
+    try { write(user); } catch (e) { if (e) { throw e; } }
+  }
+}
+"#;
+        let metrics = analyze_metrics(Path::new("store.ts"), source).unwrap();
+        let summary: Vec<(&str, usize, usize, usize)> = metrics
+            .functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.params, f.complexity, f.nesting))
+            .collect();
+        // The inline callback counts toward `load`
+        assert_eq!(summary, vec![("load", 2, 3, 0), ("save", 1, 3, 2)]);
+        assert_eq!(metrics.type_count, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-python")]
+    fn test_python_metrics() {
+        let source = "class Repo:\n    def get(self, key, default=None):\n        if key and default:\n            return default\n        elif key:\n            return [k for k in key if k]\n        return None\n";
+        let metrics = analyze_metrics(Path::new("repo.py"), source).unwrap();
+        let get = &metrics.functions[0];
+        assert_eq!((get.name.as_str(), get.params, get.lines), ("get", 2, 6));
+        // if, and, elif, comprehension if
+        assert_eq!(get.complexity, 5);
+        assert_eq!(get.nesting, 1);
+    }
+
+    #[test]
+    fn test_findings_and_touching() {
+        let long = FunctionMetrics {
+            name: "process".to_string(),
+            line: 10,
+            lines: 80,
+            nesting: 2,
+            complexity: 14,
+            params: 3,
+        };
+        let short = FunctionMetrics {
+            name: "helper".to_string(),
+            line: 100,
+            lines: 5,
+            nesting: 1,
+            complexity: 1,
+            params: 7,
+        };
+        let metrics = FileMetrics {
+            functions: vec![long, short],
+            function_count: 40,
+            type_count: 2,
+            lines: 900,
+        };
+        let thresholds = MetricThresholds::default();
+
+        let findings = metrics.findings(&thresholds);
+        assert_eq!(findings.len(), 3);
+        assert_eq!(
+            findings[0].message,
+            "`process` has 80 lines (limit 50), cyclomatic complexity 14 (limit 10)"
+        );
+        assert_eq!(findings[0].line, Some(10));
+        assert_eq!(findings[1].message, "`helper` has 7 parameters (limit 5)");
+        assert!(findings[2].message.starts_with("file defines 42 items"));
+
+        let touched = metrics.touching(&BTreeSet::from([50]));
+        assert_eq!(touched.functions.len(), 1);
+        assert_eq!(touched.function_count, 40);
+    }
+}
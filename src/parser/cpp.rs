@@ -0,0 +1,308 @@
+//! C and C++ source code parsing with tree-sitter
+//!
+//! Uses the C++ grammar for both languages. Supports:
+//! - `#include "util.h"` (quoted include, no items)
+//! - `#include <vector>` (angle include, stored with the `<system>` item)
+//! - includes nested in header guards and other `#if`/`#ifdef` blocks
+//!
+//! Exports are only collected for headers: non-static function
+//! declarations and inline definitions, classes/structs/unions/enums with a
+//! body, typedefs/aliases and namespaces. Namespaces, `extern "C"` blocks and
+//! templates are descended into.
+
+use std::collections::HashMap;
+
+use tree_sitter::Parser;
+
+use crate::error::{CodeReviewError, Result};
+use super::{FileAnalysis, ImportInfo, ImportKind, SYSTEM_INCLUDE};
+
+/// Analyze C/C++ source code to extract includes and (for headers) exports
+pub(crate) fn analyze_cpp(source: &str, is_header: bool) -> Result<FileAnalysis> {
+    let mut parser = Parser::new();
+    let language = tree_sitter_cpp::LANGUAGE;
+    parser
+        .set_language(&language.into())
+        .map_err(|e| CodeReviewError::ParseError(format!("Failed to set C++ language: {}", e)))?;
+
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| CodeReviewError::ParseError("Failed to parse C/C++ source".to_string()))?;
+
+    let mut imports = Vec::new();
+    let mut exports = Vec::new();
+
+    collect_items(tree.root_node(), source, is_header, &mut imports, &mut exports);
+
+    Ok(FileAnalysis {
+        imports,
+        exports,
+        namespaces: Vec::new(),
+        modules: Vec::new(),
+        reexports: Vec::new(),
+        visibility: HashMap::new(),
+        language: "cpp".to_string(),
+    })
+}
+
+/// Walk a container node (translation unit, namespace body, `#ifdef` block, ...)
+fn collect_items(
+    node: tree_sitter::Node,
+    source: &str,
+    is_header: bool,
+    imports: &mut Vec<ImportInfo>,
+    exports: &mut Vec<String>,
+) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "preproc_include" => {
+                if let Some(import) = extract_include(child, source) {
+                    imports.push(import);
+                }
+            }
+            // Header guards and conditional blocks can hold anything
+            "preproc_ifdef" | "preproc_if" | "preproc_else" | "preproc_elif" => {
+                collect_items(child, source, is_header, imports, exports);
+            }
+            "namespace_definition" if is_header => {
+                if let Some(name) = field_text(child, "name", source) {
+                    exports.push(name.to_string());
+                }
+                if let Some(body) = child.child_by_field_name("body") {
+                    collect_items(body, source, is_header, imports, exports);
+                }
+            }
+            // extern "C" { ... } or extern "C" void f();
+            "linkage_specification" if is_header => {
+                if let Some(body) = child.child_by_field_name("body") {
+                    if body.kind() == "declaration_list" {
+                        collect_items(body, source, is_header, imports, exports);
+                    } else {
+                        collect_declaration(body, source, exports);
+                    }
+                }
+            }
+            "template_declaration" if is_header => {
+                collect_items(child, source, is_header, imports, exports);
+            }
+            "class_specifier" | "struct_specifier" | "union_specifier" | "enum_specifier"
+                if is_header =>
+            {
+                push_type_name(child, source, exports);
+            }
+            "declaration" | "function_definition" | "type_definition" | "alias_declaration"
+                if is_header =>
+            {
+                collect_declaration(child, source, exports);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Extract include info from a preproc_include node
+///
+/// tree-sitter-cpp preproc_include structure:
+/// - preproc_include
+///   - "#include"
+///   - path: string_literal (`"util.h"`) | system_lib_string (`<vector>`)
+fn extract_include(node: tree_sitter::Node, source: &str) -> Option<ImportInfo> {
+    let path = node.child_by_field_name("path")?;
+    let text = path.utf8_text(source.as_bytes()).ok()?;
+
+    let (module_path, items) = match path.kind() {
+        "string_literal" => (text.trim_matches('"'), Vec::new()),
+        "system_lib_string" => (
+            text.trim_start_matches('<').trim_end_matches('>'),
+            vec![SYSTEM_INCLUDE.to_string()],
+        ),
+        // Macro includes (`#include CONFIG_HEADER`) cannot be resolved statically
+        _ => return None,
+    };
+
+    if module_path.is_empty() {
+        return None;
+    }
+
+    Some(ImportInfo {
+        module_path: module_path.to_string(),
+        items,
+        kind: ImportKind::Static,
+    })
+}
+
+/// Record exported names from a declaration, definition, typedef or alias
+fn collect_declaration(node: tree_sitter::Node, source: &str, exports: &mut Vec<String>) {
+    match node.kind() {
+        "alias_declaration" => {
+            if let Some(name) = field_text(node, "name", source) {
+                exports.push(name.to_string());
+            }
+        }
+        "type_definition" => {
+            if let Some(name) = field_text(node, "declarator", source) {
+                exports.push(name.to_string());
+            }
+        }
+        "declaration" | "function_definition" => {
+            if is_static(node, source) {
+                return;
+            }
+            // `struct Config { ... } config;` still declares the type
+            if let Some(ty) = node.child_by_field_name("type") {
+                push_type_name(ty, source, exports);
+            }
+            if let Some(name) = node
+                .child_by_field_name("declarator")
+                .and_then(|d| function_name(d, source))
+            {
+                exports.push(name);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Record the name of a class/struct/union/enum that has a body
+///
+/// Forward declarations and elaborated type uses (`struct Foo *p`) have no
+/// body and are skipped.
+fn push_type_name(node: tree_sitter::Node, source: &str, exports: &mut Vec<String>) {
+    let is_type = matches!(
+        node.kind(),
+        "class_specifier" | "struct_specifier" | "union_specifier" | "enum_specifier"
+    );
+    if !is_type || node.child_by_field_name("body").is_none() {
+        return;
+    }
+    if let Some(name) = field_text(node, "name", source) {
+        exports.push(name.to_string());
+    }
+}
+
+/// Find the function name inside a declarator, if it declares a function
+///
+/// Descends through pointer/reference declarators to the function_declarator;
+/// qualified names (`ns::Foo::bar`) are reduced to their last segment.
+fn function_name(node: tree_sitter::Node, source: &str) -> Option<String> {
+    match node.kind() {
+        "function_declarator" => {
+            let name = field_text(node, "declarator", source)?;
+            let short = name.rsplit("::").next().unwrap_or(name);
+            Some(short.to_string())
+        }
+        "pointer_declarator" => function_name(node.child_by_field_name("declarator")?, source),
+        "reference_declarator" => function_name(node.named_child(0)?, source),
+        _ => None,
+    }
+}
+
+/// Check for a `static` storage class specifier
+fn is_static(node: tree_sitter::Node, source: &str) -> bool {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.kind() == "storage_class_specifier"
+            && child.utf8_text(source.as_bytes()) == Ok("static")
+        {
+            return true;
+        }
+    }
+    false
+}
+
+/// Text of a named field on a node
+fn field_text<'a>(node: tree_sitter::Node, field: &str, source: &'a str) -> Option<&'a str> {
+    node.child_by_field_name(field)?
+        .utf8_text(source.as_bytes())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_cpp_header() {
+        let source = r#"
+#include "util.h"
+#include <vector>
+
+int add(int a, int b);
+static int helper(void);
+class Widget {};
+"#;
+        let result = analyze_cpp(source, true).unwrap();
+        assert_eq!(result.language, "cpp");
+        assert_eq!(result.imports.len(), 2);
+        assert!(result.exports.contains(&"add".to_string()));
+        assert!(result.exports.contains(&"Widget".to_string()));
+        assert!(!result.exports.contains(&"helper".to_string()));
+    }
+
+    #[test]
+    fn test_extract_quoted_and_angle_includes() {
+        let source = "#include \"net/socket.h\"\n#include <stdio.h>\n";
+        let result = analyze_cpp(source, false).unwrap();
+        assert_eq!(result.imports[0].module_path, "net/socket.h");
+        assert!(result.imports[0].items.is_empty());
+        assert_eq!(result.imports[1].module_path, "stdio.h");
+        assert_eq!(result.imports[1].items, vec![SYSTEM_INCLUDE]);
+    }
+
+    #[test]
+    fn test_includes_inside_header_guard() {
+        let source = r#"
+#ifndef CONFIG_H
+#define CONFIG_H
+#include "types.h"
+
+typedef struct { int port; } Config;
+Config *config_load(const char *path);
+
+#endif
+"#;
+        let result = analyze_cpp(source, true).unwrap();
+        assert_eq!(result.imports.len(), 1);
+        assert_eq!(result.imports[0].module_path, "types.h");
+        assert!(result.exports.contains(&"Config".to_string()));
+        assert!(result.exports.contains(&"config_load".to_string()));
+    }
+
+    #[test]
+    fn test_namespaces_templates_and_extern_c() {
+        let source = r#"
+namespace geo {
+    struct Point { double x, y; };
+    double distance(const Point &a, const Point &b);
+    template <typename T> class Grid {};
+}
+
+extern "C" {
+    void c_api_init(void);
+}
+"#;
+        let result = analyze_cpp(source, true).unwrap();
+        for name in ["geo", "Point", "distance", "Grid", "c_api_init"] {
+            assert!(result.exports.contains(&name.to_string()), "missing {}", name);
+        }
+    }
+
+    #[test]
+    fn test_forward_declarations_and_variables_not_exported() {
+        let source = r#"
+class Forward;
+extern int counter;
+"#;
+        let result = analyze_cpp(source, true).unwrap();
+        assert!(result.exports.is_empty());
+    }
+
+    #[test]
+    fn test_source_files_have_no_exports() {
+        let source = "#include \"widget.h\"\n\nint add(int a, int b) { return a + b; }\n";
+        let result = analyze_cpp(source, false).unwrap();
+        assert_eq!(result.imports.len(), 1);
+        assert!(result.exports.is_empty());
+    }
+}
@@ -0,0 +1,333 @@
+//! C# source code parsing with tree-sitter
+//!
+//! Supports:
+//! - `using System.Text;` (namespace import)
+//! - `using static System.Math;` (static import, stored as `*`)
+//! - `using Json = Newtonsoft.Json;` (alias, stored as `as Json`)
+//! - `global using System.Linq;`
+//! - block-scoped and file-scoped namespaces (recorded in `namespaces`)
+//!
+//! Exports are `public` types and the `public` members of those types
+//! (as `Type.Member`). Interface members are public unless marked otherwise.
+
+use std::collections::HashMap;
+
+use tree_sitter::Parser;
+
+use crate::error::{CodeReviewError, Result};
+use super::{FileAnalysis, ImportInfo, ImportKind};
+
+/// Type declarations whose members are collected
+const TYPE_DECLARATIONS: &[&str] = &[
+    "class_declaration",
+    "struct_declaration",
+    "interface_declaration",
+    "record_declaration",
+    "enum_declaration",
+    "delegate_declaration",
+];
+
+/// Collected imports, exports and namespaces
+#[derive(Default)]
+struct CsharpItems {
+    imports: Vec<ImportInfo>,
+    exports: Vec<String>,
+    namespaces: Vec<String>,
+}
+
+/// Analyze C# source code to extract using directives, namespaces and exports
+pub(crate) fn analyze_csharp(source: &str) -> Result<FileAnalysis> {
+    let mut parser = Parser::new();
+    let language = tree_sitter_c_sharp::LANGUAGE;
+    parser
+        .set_language(&language.into())
+        .map_err(|e| CodeReviewError::ParseError(format!("Failed to set C# language: {}", e)))?;
+
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| CodeReviewError::ParseError("Failed to parse C# source".to_string()))?;
+
+    let mut items = CsharpItems::default();
+    collect_declarations(tree.root_node(), source, "", &mut items);
+
+    Ok(FileAnalysis {
+        imports: items.imports,
+        exports: items.exports,
+        namespaces: items.namespaces,
+        modules: Vec::new(),
+        reexports: Vec::new(),
+        visibility: HashMap::new(),
+        language: "csharp".to_string(),
+    })
+}
+
+/// Walk the compilation unit or a namespace body
+///
+/// `namespace` is the enclosing namespace name, used to qualify nested ones.
+fn collect_declarations(node: tree_sitter::Node, source: &str, namespace: &str, items: &mut CsharpItems) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "using_directive" => {
+                if let Some(import) = extract_using(child, source) {
+                    items.imports.push(import);
+                }
+            }
+            "namespace_declaration" => {
+                if let Some(name) = qualified_namespace(child, source, namespace) {
+                    items.namespaces.push(name.clone());
+                    if let Some(body) = child.child_by_field_name("body") {
+                        collect_declarations(body, source, &name, items);
+                    }
+                }
+            }
+            // `namespace Acme.Auth;` applies to the rest of the file, whose
+            // declarations stay siblings of this node
+            "file_scoped_namespace_declaration" => {
+                if let Some(name) = qualified_namespace(child, source, namespace) {
+                    items.namespaces.push(name);
+                }
+            }
+            kind if TYPE_DECLARATIONS.contains(&kind) => {
+                collect_type(child, source, &mut items.exports);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Extract import info from a using_directive node
+///
+/// tree-sitter-c-sharp using_directive structure:
+/// - using_directive
+///   - "global" (optional)
+///   - "using"
+///   - "static" (optional)
+///   - name: identifier (alias, optional) followed by "="
+///   - qualified_name | identifier (the imported namespace or type)
+fn extract_using(node: tree_sitter::Node, source: &str) -> Option<ImportInfo> {
+    let alias = node.child_by_field_name("name");
+    let mut is_static = false;
+    let mut target = None;
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "static" => is_static = true,
+            "qualified_name" | "identifier" | "generic_name" | "alias_qualified_name" => {
+                if alias.is_some_and(|a| a.id() == child.id()) {
+                    continue;
+                }
+                target = child.utf8_text(source.as_bytes()).ok();
+            }
+            _ => {}
+        }
+    }
+
+    let module_path = target?.to_string();
+    let items = if let Some(alias) = alias.and_then(|a| a.utf8_text(source.as_bytes()).ok()) {
+        vec![format!("as {}", alias)]
+    } else if is_static {
+        vec!["*".to_string()]
+    } else {
+        Vec::new()
+    };
+
+    Some(ImportInfo { module_path, items, kind: ImportKind::Static })
+}
+
+/// Full name of a namespace declaration, qualified by its enclosing namespace
+fn qualified_namespace(node: tree_sitter::Node, source: &str, enclosing: &str) -> Option<String> {
+    let name = node
+        .child_by_field_name("name")?
+        .utf8_text(source.as_bytes())
+        .ok()?;
+    Some(if enclosing.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", enclosing, name)
+    })
+}
+
+/// Record a public type and its public members
+fn collect_type(node: tree_sitter::Node, source: &str, exports: &mut Vec<String>) {
+    if !has_modifier(node, source, "public") {
+        return;
+    }
+    let Some(type_name) = node
+        .child_by_field_name("name")
+        .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+    else {
+        return;
+    };
+    exports.push(type_name.to_string());
+
+    let in_interface = node.kind() == "interface_declaration";
+    let Some(body) = node.child_by_field_name("body") else {
+        return;
+    };
+    let mut cursor = body.walk();
+    for member in body.children(&mut cursor) {
+        let is_public = has_modifier(member, source, "public")
+            || (in_interface && !has_modifier(member, source, "private"));
+        if !is_public {
+            continue;
+        }
+        for name in member_names(member, source) {
+            exports.push(format!("{}.{}", type_name, name));
+        }
+    }
+}
+
+/// Names declared by a type member (fields can declare several)
+fn member_names<'a>(member: tree_sitter::Node, source: &'a str) -> Vec<&'a str> {
+    match member.kind() {
+        "method_declaration" | "property_declaration" | "event_declaration" => member
+            .child_by_field_name("name")
+            .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+            .into_iter()
+            .collect(),
+        "field_declaration" | "event_field_declaration" => {
+            let mut names = Vec::new();
+            let mut cursor = member.walk();
+            for child in member.children(&mut cursor) {
+                if child.kind() != "variable_declaration" {
+                    continue;
+                }
+                let mut decl_cursor = child.walk();
+                for declarator in child.children(&mut decl_cursor) {
+                    if let Some(name) = declarator
+                        .child_by_field_name("name")
+                        .filter(|_| declarator.kind() == "variable_declarator")
+                        .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+                    {
+                        names.push(name);
+                    }
+                }
+            }
+            names
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Check whether a declaration has the given modifier keyword
+fn has_modifier(node: tree_sitter::Node, source: &str, modifier: &str) -> bool {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.kind() == "modifier" && child.utf8_text(source.as_bytes()) == Ok(modifier) {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_csharp() {
+        let source = r#"
+using System;
+
+namespace Acme.Auth
+{
+    public class TokenService
+    {
+        public string Issue() { return ""; }
+        private void Rotate() {}
+    }
+
+    internal class Cache {}
+}
+"#;
+        let result = analyze_csharp(source).unwrap();
+        assert_eq!(result.language, "csharp");
+        assert_eq!(result.imports.len(), 1);
+        assert_eq!(result.namespaces, vec!["Acme.Auth"]);
+        assert!(result.exports.contains(&"TokenService".to_string()));
+        assert!(result.exports.contains(&"TokenService.Issue".to_string()));
+        assert!(!result.exports.contains(&"TokenService.Rotate".to_string()));
+        assert!(!result.exports.contains(&"Cache".to_string()));
+    }
+
+    #[test]
+    fn test_extract_csharp_using_forms() {
+        let source = r#"
+using System.Text;
+using static System.Math;
+using Json = Newtonsoft.Json;
+global using System.Linq;
+"#;
+        let result = analyze_csharp(source).unwrap();
+        assert_eq!(result.imports.len(), 4);
+        assert_eq!(result.imports[0].module_path, "System.Text");
+        assert!(result.imports[0].items.is_empty());
+        assert_eq!(result.imports[1].module_path, "System.Math");
+        assert_eq!(result.imports[1].items, vec!["*"]);
+        assert_eq!(result.imports[2].module_path, "Newtonsoft.Json");
+        assert_eq!(result.imports[2].items, vec!["as Json"]);
+        assert_eq!(result.imports[3].module_path, "System.Linq");
+    }
+
+    #[test]
+    fn test_file_scoped_and_nested_namespaces() {
+        let file_scoped = "namespace Acme.Billing;\n\npublic record Invoice(int Id);\n";
+        let result = analyze_csharp(file_scoped).unwrap();
+        assert_eq!(result.namespaces, vec!["Acme.Billing"]);
+        assert!(result.exports.contains(&"Invoice".to_string()));
+
+        let nested = "namespace Acme { namespace Core { public struct Id {} } }";
+        let result = analyze_csharp(nested).unwrap();
+        assert_eq!(result.namespaces, vec!["Acme", "Acme.Core"]);
+        assert!(result.exports.contains(&"Id".to_string()));
+    }
+
+    #[test]
+    fn test_csharp_interface_and_field_members() {
+        let source = r#"
+public interface IRepository
+{
+    void Save();
+}
+
+public class Settings
+{
+    public int Port, Timeout;
+    public string Name { get; set; }
+    int hidden;
+}
+"#;
+        let result = analyze_csharp(source).unwrap();
+        for name in [
+            "IRepository",
+            "IRepository.Save",
+            "Settings.Port",
+            "Settings.Timeout",
+            "Settings.Name",
+        ] {
+            assert!(result.exports.contains(&name.to_string()), "missing {}", name);
+        }
+        assert!(!result.exports.contains(&"Settings.hidden".to_string()));
+    }
+
+    #[test]
+    fn test_usings_inside_namespace() {
+        let source = "namespace Acme { using System.IO; }";
+        let result = analyze_csharp(source).unwrap();
+        assert_eq!(result.imports.len(), 1);
+        assert_eq!(result.imports[0].module_path, "System.IO");
+    }
+
+    #[test]
+    fn test_grammar_abi_is_supported() {
+        let version = tree_sitter::Language::from(tree_sitter_c_sharp::LANGUAGE).version();
+        assert!(
+            (tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION..=tree_sitter::LANGUAGE_VERSION)
+                .contains(&version),
+            "grammar ABI {} is not supported by tree-sitter",
+            version
+        );
+    }
+}
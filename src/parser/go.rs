@@ -0,0 +1,271 @@
+//! Go source code parsing with tree-sitter
+//!
+//! Supports:
+//! - `import "fmt"` (single import)
+//! - `import ( "fmt"; "os" )` (grouped imports)
+//! - `import f "fmt"` (aliased import, stored as `as f`)
+//! - `import . "fmt"` (dot import, stored as `*`)
+//! - `import _ "net/http/pprof"` (blank import, stored as `_`)
+//!
+//! Exports are top-level functions, methods, types, constants and variables
+//! whose names start with an uppercase letter.
+
+use std::collections::HashMap;
+
+use tree_sitter::Parser;
+
+use crate::error::{CodeReviewError, Result};
+use super::{FileAnalysis, ImportInfo, ImportKind};
+
+/// Analyze Go source code to extract imports and exports
+pub(crate) fn analyze_go(source: &str) -> Result<FileAnalysis> {
+    let mut parser = Parser::new();
+    let language = tree_sitter_go::LANGUAGE;
+    parser
+        .set_language(&language.into())
+        .map_err(|e| CodeReviewError::ParseError(format!("Failed to set Go language: {}", e)))?;
+
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| CodeReviewError::ParseError("Failed to parse Go source".to_string()))?;
+
+    let mut imports = Vec::new();
+    let mut exports = Vec::new();
+
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+
+    for node in root.children(&mut cursor) {
+        match node.kind() {
+            "import_declaration" => {
+                collect_import_specs(node, source, &mut imports);
+            }
+            "function_declaration" | "method_declaration" => {
+                if let Some(name) = field_text(node, "name", source) {
+                    push_exported(&mut exports, name);
+                }
+            }
+            "type_declaration" | "const_declaration" | "var_declaration" => {
+                collect_declared_names(node, source, &mut exports);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(FileAnalysis {
+        imports,
+        exports,
+        namespaces: Vec::new(),
+        modules: Vec::new(),
+        reexports: Vec::new(),
+        visibility: HashMap::new(),
+        language: "go".to_string(),
+    })
+}
+
+/// Collect `import_spec` nodes from an import declaration (single or grouped)
+fn collect_import_specs(node: tree_sitter::Node, source: &str, imports: &mut Vec<ImportInfo>) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "import_spec" => {
+                if let Some(import) = extract_import_spec(child, source) {
+                    imports.push(import);
+                }
+            }
+            "import_spec_list" => {
+                collect_import_specs(child, source, imports);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Extract import info from an import_spec node
+///
+/// tree-sitter-go import_spec structure:
+/// - import_spec
+///   - name (optional): package_identifier | dot | blank_identifier
+///   - path: interpreted_string_literal | raw_string_literal
+fn extract_import_spec(node: tree_sitter::Node, source: &str) -> Option<ImportInfo> {
+    let path = field_text(node, "path", source)?;
+    let module_path = path.trim_matches(|c| c == '"' || c == '`').to_string();
+    if module_path.is_empty() {
+        return None;
+    }
+
+    let items = match node.child_by_field_name("name") {
+        Some(name) => match name.kind() {
+            "dot" => vec!["*".to_string()],
+            "blank_identifier" => vec!["_".to_string()],
+            _ => {
+                let alias = name.utf8_text(source.as_bytes()).ok()?;
+                vec![format!("as {}", alias)]
+            }
+        },
+        None => Vec::new(),
+    };
+
+    Some(ImportInfo { module_path, items, kind: ImportKind::Static })
+}
+
+/// Collect names from type/const/var declarations (including grouped forms)
+fn collect_declared_names(node: tree_sitter::Node, source: &str, exports: &mut Vec<String>) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "type_spec" | "type_alias" => {
+                if let Some(name) = field_text(child, "name", source) {
+                    push_exported(exports, name);
+                }
+            }
+            // `const A, B = 1, 2` has several `name` fields
+            "const_spec" | "var_spec" => {
+                let mut spec_cursor = child.walk();
+                for name in child.children_by_field_name("name", &mut spec_cursor) {
+                    if let Ok(text) = name.utf8_text(source.as_bytes()) {
+                        push_exported(exports, text);
+                    }
+                }
+            }
+            "var_spec_list" => {
+                collect_declared_names(child, source, exports);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Text of a named field on a node
+fn field_text<'a>(node: tree_sitter::Node, field: &str, source: &'a str) -> Option<&'a str> {
+    node.child_by_field_name(field)?
+        .utf8_text(source.as_bytes())
+        .ok()
+}
+
+/// Record a name as an export if Go considers it exported (uppercase first letter)
+fn push_exported(exports: &mut Vec<String>, name: &str) {
+    if name.chars().next().is_some_and(char::is_uppercase) {
+        exports.push(name.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_go() {
+        let source = r#"
+package auth
+
+import "fmt"
+
+func Login() {}
+func logout() {}
+type Token struct{}
+"#;
+        let result = analyze_go(source).unwrap();
+        assert_eq!(result.language, "go");
+        assert_eq!(result.imports.len(), 1);
+        assert!(result.exports.contains(&"Login".to_string()));
+        assert!(result.exports.contains(&"Token".to_string()));
+        assert!(!result.exports.contains(&"logout".to_string()));
+    }
+
+    #[test]
+    fn test_extract_go_single_import() {
+        let source = "package main\n\nimport \"net/http\"\n";
+        let result = analyze_go(source).unwrap();
+        assert_eq!(result.imports.len(), 1);
+        assert_eq!(result.imports[0].module_path, "net/http");
+        assert!(result.imports[0].items.is_empty());
+    }
+
+    #[test]
+    fn test_extract_go_grouped_imports() {
+        let source = r#"
+package main
+
+import (
+    "fmt"
+    "github.com/acme/app/internal/auth"
+)
+"#;
+        let result = analyze_go(source).unwrap();
+        let paths: Vec<&str> = result.imports.iter().map(|i| i.module_path.as_str()).collect();
+        assert_eq!(paths, vec!["fmt", "github.com/acme/app/internal/auth"]);
+    }
+
+    #[test]
+    fn test_extract_go_aliased_import() {
+        let source = "package main\n\nimport f \"fmt\"\n";
+        let result = analyze_go(source).unwrap();
+        assert_eq!(result.imports[0].module_path, "fmt");
+        assert_eq!(result.imports[0].items, vec!["as f"]);
+    }
+
+    #[test]
+    fn test_extract_go_dot_and_blank_imports() {
+        let source = r#"
+package main
+
+import (
+    . "math"
+    _ "net/http/pprof"
+)
+"#;
+        let result = analyze_go(source).unwrap();
+        assert_eq!(result.imports.len(), 2);
+        assert_eq!(result.imports[0].module_path, "math");
+        assert_eq!(result.imports[0].items, vec!["*"]);
+        assert_eq!(result.imports[1].module_path, "net/http/pprof");
+        assert_eq!(result.imports[1].items, vec!["_"]);
+    }
+
+    #[test]
+    fn test_extract_go_raw_string_import() {
+        let source = "package main\n\nimport `os`\n";
+        let result = analyze_go(source).unwrap();
+        assert_eq!(result.imports[0].module_path, "os");
+    }
+
+    #[test]
+    fn test_go_exported_methods_and_declarations() {
+        let source = r#"
+package store
+
+type (
+    Store struct{}
+    cache map[string]string
+)
+
+type ID = string
+
+const MaxSize, minSize = 10, 1
+
+var (
+    Default = Store{}
+    counter int
+)
+
+func (s *Store) Get(key string) string { return "" }
+func (s *Store) evict() {}
+"#;
+        let result = analyze_go(source).unwrap();
+        for name in ["Store", "ID", "MaxSize", "Default", "Get"] {
+            assert!(result.exports.contains(&name.to_string()), "missing {}", name);
+        }
+        for name in ["cache", "minSize", "counter", "evict"] {
+            assert!(!result.exports.contains(&name.to_string()), "unexpected {}", name);
+        }
+    }
+
+    #[test]
+    fn test_go_no_imports_or_exports() {
+        let source = "package main\n\nfunc main() {}\n";
+        let result = analyze_go(source).unwrap();
+        assert!(result.imports.is_empty());
+        assert!(result.exports.is_empty());
+    }
+}
@@ -0,0 +1,225 @@
+//! Java source code parsing with tree-sitter
+//!
+//! Supports:
+//! - `import java.util.List;` (single-type import)
+//! - `import java.util.*;` (wildcard import, stored as `*`)
+//! - `import static org.junit.Assert.assertEquals;` (static import)
+//! - `import static org.junit.Assert.*;` (static wildcard import)
+//!
+//! Exports are top-level `public` classes, interfaces, enums, records and
+//! annotation types. The `package` declaration is recorded in `namespaces`
+//! so wildcard imports can be matched to the files of that package.
+
+use std::collections::HashMap;
+
+use tree_sitter::Parser;
+
+use crate::error::{CodeReviewError, Result};
+use super::{FileAnalysis, ImportInfo, ImportKind};
+
+/// Analyze Java source code to extract imports and exports
+pub(crate) fn analyze_java(source: &str) -> Result<FileAnalysis> {
+    let mut parser = Parser::new();
+    let language = tree_sitter_java::LANGUAGE;
+    parser
+        .set_language(&language.into())
+        .map_err(|e| CodeReviewError::ParseError(format!("Failed to set Java language: {}", e)))?;
+
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| CodeReviewError::ParseError("Failed to parse Java source".to_string()))?;
+
+    let mut imports = Vec::new();
+    let mut exports = Vec::new();
+    let mut namespaces = Vec::new();
+
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+
+    for node in root.children(&mut cursor) {
+        match node.kind() {
+            "package_declaration" => {
+                if let Some(name) = extract_package_name(node, source) {
+                    namespaces.push(name.to_string());
+                }
+            }
+            "import_declaration" => {
+                if let Some(import) = extract_java_import(node, source) {
+                    imports.push(import);
+                }
+            }
+            "class_declaration"
+            | "interface_declaration"
+            | "enum_declaration"
+            | "record_declaration"
+            | "annotation_type_declaration" => {
+                if let Some(name) = node
+                    .child_by_field_name("name")
+                    .filter(|_| is_public(node))
+                    .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+                {
+                    exports.push(name.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(FileAnalysis {
+        imports,
+        exports,
+        namespaces,
+        modules: Vec::new(),
+        reexports: Vec::new(),
+        visibility: HashMap::new(),
+        language: "java".to_string(),
+    })
+}
+
+/// Extract import info from an import_declaration node
+///
+/// tree-sitter-java import_declaration structure:
+/// - import_declaration
+///   - "import" keyword
+///   - "static" keyword (optional)
+///   - scoped_identifier | identifier (qualified name)
+///   - "." and asterisk (optional, for wildcard imports)
+///
+/// The last segment of a non-wildcard import is the imported item; for
+/// wildcard imports the whole qualified name is the module path.
+fn extract_java_import(node: tree_sitter::Node, source: &str) -> Option<ImportInfo> {
+    let mut qualified = None;
+    let mut wildcard = false;
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "scoped_identifier" | "identifier" => {
+                qualified = child.utf8_text(source.as_bytes()).ok();
+            }
+            "asterisk" => {
+                wildcard = true;
+            }
+            _ => {}
+        }
+    }
+
+    let qualified = qualified?;
+    if wildcard {
+        return Some(ImportInfo {
+            module_path: qualified.to_string(),
+            items: vec!["*".to_string()],
+            kind: ImportKind::Static,
+        });
+    }
+
+    let (module_path, item) = qualified.rsplit_once('.').unwrap_or(("", qualified));
+    Some(ImportInfo {
+        module_path: module_path.to_string(),
+        items: vec![item.to_string()],
+        kind: ImportKind::Static,
+    })
+}
+
+/// Qualified name of a package_declaration node (annotations are skipped)
+fn extract_package_name<'a>(node: tree_sitter::Node, source: &'a str) -> Option<&'a str> {
+    let mut cursor = node.walk();
+    let name = node
+        .named_children(&mut cursor)
+        .find(|child| matches!(child.kind(), "scoped_identifier" | "identifier"))?;
+    name.utf8_text(source.as_bytes()).ok()
+}
+
+/// Check whether a declaration carries the `public` modifier
+fn is_public(node: tree_sitter::Node) -> bool {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.kind() == "modifiers" {
+            let mut mod_cursor = child.walk();
+            if child.children(&mut mod_cursor).any(|m| m.kind() == "public") {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_java() {
+        let source = r#"
+package com.acme.auth;
+
+import java.util.List;
+
+public class AuthService {}
+class Helper {}
+"#;
+        let result = analyze_java(source).unwrap();
+        assert_eq!(result.language, "java");
+        assert_eq!(result.namespaces, vec!["com.acme.auth"]);
+        assert_eq!(result.imports.len(), 1);
+        assert!(result.exports.contains(&"AuthService".to_string()));
+        assert!(!result.exports.contains(&"Helper".to_string()));
+    }
+
+    #[test]
+    fn test_extract_java_single_type_import() {
+        let source = "import com.acme.auth.TokenStore;";
+        let result = analyze_java(source).unwrap();
+        assert_eq!(result.imports[0].module_path, "com.acme.auth");
+        assert_eq!(result.imports[0].items, vec!["TokenStore"]);
+    }
+
+    #[test]
+    fn test_extract_java_wildcard_import() {
+        let source = "import java.util.*;";
+        let result = analyze_java(source).unwrap();
+        assert_eq!(result.imports[0].module_path, "java.util");
+        assert_eq!(result.imports[0].items, vec!["*"]);
+    }
+
+    #[test]
+    fn test_extract_java_static_imports() {
+        let source = r#"
+import static org.junit.Assert.assertEquals;
+import static org.junit.Assert.*;
+"#;
+        let result = analyze_java(source).unwrap();
+        assert_eq!(result.imports.len(), 2);
+        assert_eq!(result.imports[0].module_path, "org.junit.Assert");
+        assert_eq!(result.imports[0].items, vec!["assertEquals"]);
+        assert_eq!(result.imports[1].module_path, "org.junit.Assert");
+        assert_eq!(result.imports[1].items, vec!["*"]);
+    }
+
+    #[test]
+    fn test_java_public_type_kinds() {
+        let source = r#"
+public interface Repository {}
+public enum Role { ADMIN, USER }
+public record Point(int x, int y) {}
+public @interface Audited {}
+final class Internal {}
+"#;
+        let result = analyze_java(source).unwrap();
+        for name in ["Repository", "Role", "Point", "Audited"] {
+            assert!(result.exports.contains(&name.to_string()), "missing {}", name);
+        }
+        assert!(!result.exports.contains(&"Internal".to_string()));
+    }
+
+    #[test]
+    fn test_java_nested_types_not_exported() {
+        let source = r#"
+public class Outer {
+    public static class Inner {}
+}
+"#;
+        let result = analyze_java(source).unwrap();
+        assert_eq!(result.exports, vec!["Outer"]);
+    }
+}
@@ -0,0 +1,271 @@
+//! Kotlin source code parsing with tree-sitter
+//!
+//! Supports:
+//! - `import com.acme.auth.TokenStore` (single import)
+//! - `import com.acme.auth.*` (wildcard import, stored as `*`)
+//! - `import com.acme.auth.TokenStore as Store` (aliased import, stored as `TokenStore`)
+//!
+//! Kotlin declarations are public unless marked otherwise, so exports are
+//! top-level classes, interfaces, objects, functions and properties without
+//! a `private`, `internal` or `protected` modifier. The `package` header is
+//! recorded in `namespaces`.
+
+use std::collections::HashMap;
+
+use tree_sitter::Parser;
+
+use crate::error::{CodeReviewError, Result};
+use super::{FileAnalysis, ImportInfo, ImportKind};
+
+/// Visibility modifiers that hide a top-level declaration from other modules
+const NON_PUBLIC_MODIFIERS: &[&str] = &["private", "internal", "protected"];
+
+/// Analyze Kotlin source code to extract imports and exports
+pub(crate) fn analyze_kotlin(source: &str) -> Result<FileAnalysis> {
+    let mut parser = Parser::new();
+    let language = tree_sitter_kotlin_ng::LANGUAGE;
+    parser
+        .set_language(&language.into())
+        .map_err(|e| CodeReviewError::ParseError(format!("Failed to set Kotlin language: {}", e)))?;
+
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| CodeReviewError::ParseError("Failed to parse Kotlin source".to_string()))?;
+
+    let mut imports = Vec::new();
+    let mut exports = Vec::new();
+    let mut namespaces = Vec::new();
+
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+
+    for node in root.children(&mut cursor) {
+        match node.kind() {
+            "package_header" => {
+                namespaces.extend(extract_package_name(node, source));
+            }
+            // Grouped headers (older grammars) or single import nodes
+            "import_list" | "import_header" | "import" => {
+                collect_kotlin_imports(node, source, &mut imports);
+            }
+            "class_declaration" | "object_declaration" | "function_declaration"
+            | "property_declaration" | "type_alias" => {
+                if let Some(name) = extract_declaration_name(node, source)
+                    .filter(|_| is_exported(node, source))
+                {
+                    exports.push(name);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(FileAnalysis {
+        imports,
+        exports,
+        namespaces,
+        modules: Vec::new(),
+        reexports: Vec::new(),
+        visibility: HashMap::new(),
+        language: "kotlin".to_string(),
+    })
+}
+
+/// Collect imports from an import node, descending into import lists
+fn collect_kotlin_imports(node: tree_sitter::Node, source: &str, imports: &mut Vec<ImportInfo>) {
+    if node.kind() == "import_list" {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            collect_kotlin_imports(child, source, imports);
+        }
+        return;
+    }
+    if let Some(import) = extract_kotlin_import(node, source) {
+        imports.push(import);
+    }
+}
+
+/// Extract import info from an import node
+///
+/// tree-sitter-kotlin-ng import structure:
+/// - import
+///   - "import" keyword
+///   - qualified_identifier (dotted name, one identifier per segment)
+///   - "." and "*" (optional, for wildcard imports)
+///   - "as" and identifier (optional alias)
+///
+/// `import a.b.C` becomes module `a.b` with item `C`; `import a.b.*` becomes
+/// module `a.b` with item `*`. An alias only renames the item locally, so
+/// the original item is recorded.
+fn extract_kotlin_import(node: tree_sitter::Node, source: &str) -> Option<ImportInfo> {
+    let mut segments = None;
+    let mut wildcard = false;
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            // The alias is a later identifier, so only the first one is the path
+            "qualified_identifier" | "identifier" if segments.is_none() => {
+                segments = Some(identifier_segments(child, source));
+            }
+            "*" | "wildcard_import" => {
+                wildcard = true;
+            }
+            _ => {}
+        }
+    }
+
+    let mut segments = segments.filter(|s| !s.is_empty())?;
+    if wildcard {
+        return Some(ImportInfo {
+            module_path: segments.join("."),
+            items: vec!["*".to_string()],
+            kind: ImportKind::Static,
+        });
+    }
+
+    let item = segments.pop()?;
+    Some(ImportInfo {
+        module_path: segments.join("."),
+        items: vec![item.to_string()],
+        kind: ImportKind::Static,
+    })
+}
+
+/// Qualified name of a package_header node
+fn extract_package_name(node: tree_sitter::Node, source: &str) -> Option<String> {
+    let mut cursor = node.walk();
+    let name = node
+        .named_children(&mut cursor)
+        .find(|child| matches!(child.kind(), "qualified_identifier" | "identifier"))?;
+    let segments = identifier_segments(name, source);
+    (!segments.is_empty()).then(|| segments.join("."))
+}
+
+/// Segments of a (possibly qualified) identifier, one per identifier leaf
+fn identifier_segments<'a>(node: tree_sitter::Node, source: &'a str) -> Vec<&'a str> {
+    if node.named_child_count() == 0 {
+        return node.utf8_text(source.as_bytes()).ok().into_iter().collect();
+    }
+    let mut segments = Vec::new();
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        segments.extend(identifier_segments(child, source));
+    }
+    segments
+}
+
+/// Check that a declaration has no non-public visibility modifier
+fn is_exported(node: tree_sitter::Node, source: &str) -> bool {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.kind() != "modifiers" {
+            continue;
+        }
+        let mut modifier_cursor = child.walk();
+        for modifier in child.children(&mut modifier_cursor) {
+            if modifier.kind() == "visibility_modifier"
+                && modifier
+                    .utf8_text(source.as_bytes())
+                    .is_ok_and(|text| NON_PUBLIC_MODIFIERS.contains(&text))
+            {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Find the declared name of a class, object, function, property or type alias
+fn extract_declaration_name(node: tree_sitter::Node, source: &str) -> Option<String> {
+    if let Some(name) = node.child_by_field_name("name") {
+        return name.utf8_text(source.as_bytes()).ok().map(String::from);
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "type_identifier" | "simple_identifier" | "identifier" => {
+                return child.utf8_text(source.as_bytes()).ok().map(String::from);
+            }
+            // `val name: Type = ...` nests the name in a variable declaration
+            "variable_declaration" => {
+                return extract_declaration_name(child, source);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_kotlin() {
+        let source = r#"
+package com.acme.auth
+
+import com.acme.core.Clock
+
+class AuthService
+private class Helper
+fun login() {}
+internal fun reset() {}
+"#;
+        let result = analyze_kotlin(source).unwrap();
+        assert_eq!(result.language, "kotlin");
+        assert_eq!(result.namespaces, vec!["com.acme.auth"]);
+        assert_eq!(result.imports.len(), 1);
+        assert_eq!(result.imports[0].module_path, "com.acme.core");
+        assert_eq!(result.imports[0].items, vec!["Clock"]);
+        assert!(result.exports.contains(&"AuthService".to_string()));
+        assert!(result.exports.contains(&"login".to_string()));
+        assert!(!result.exports.contains(&"Helper".to_string()));
+        assert!(!result.exports.contains(&"reset".to_string()));
+    }
+
+    #[test]
+    fn test_extract_kotlin_imports() {
+        let source = r#"
+import com.acme.auth.TokenStore
+import com.acme.util.*
+import com.acme.auth.Session as AuthSession
+"#;
+        let result = analyze_kotlin(source).unwrap();
+        assert_eq!(result.imports.len(), 3);
+        assert_eq!(result.imports[0].module_path, "com.acme.auth");
+        assert_eq!(result.imports[0].items, vec!["TokenStore"]);
+        assert_eq!(result.imports[1].module_path, "com.acme.util");
+        assert_eq!(result.imports[1].items, vec!["*"]);
+        assert_eq!(result.imports[2].module_path, "com.acme.auth");
+        assert_eq!(result.imports[2].items, vec!["Session"]);
+    }
+
+    #[test]
+    fn test_kotlin_objects_interfaces_and_properties() {
+        let source = r#"
+object Registry
+interface Repository
+val DEFAULT_TIMEOUT: Int = 30
+private val cache = mutableMapOf<String, String>()
+"#;
+        let result = analyze_kotlin(source).unwrap();
+        for name in ["Registry", "Repository", "DEFAULT_TIMEOUT"] {
+            assert!(result.exports.contains(&name.to_string()), "missing {}", name);
+        }
+        assert!(!result.exports.contains(&"cache".to_string()));
+    }
+
+    #[test]
+    fn test_grammar_abi_is_supported() {
+        let version = tree_sitter::Language::from(tree_sitter_kotlin_ng::LANGUAGE).version();
+        assert!(
+            (tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION..=tree_sitter::LANGUAGE_VERSION)
+                .contains(&version),
+            "grammar ABI {} is not supported by tree-sitter",
+            version
+        );
+    }
+}
@@ -1,28 +1,97 @@
 //! AST parsing with tree-sitter for import/export extraction
 
-use std::fs;
+use std::collections::HashMap;
 use std::path::Path;
 
+use serde::{Deserialize, Serialize};
+
 use crate::error::Result;
 
+#[cfg(feature = "lang-cpp")]
+pub(crate) mod cpp;
+#[cfg(feature = "lang-csharp")]
+pub(crate) mod csharp;
+#[cfg(feature = "lang-go")]
+pub(crate) mod go;
+#[cfg(feature = "lang-java")]
+pub(crate) mod java;
+#[cfg(feature = "lang-kotlin")]
+pub(crate) mod kotlin;
 #[cfg(feature = "lang-python")]
 pub(crate) mod python;
+#[cfg(feature = "lang-ruby")]
+pub(crate) mod ruby;
 #[cfg(feature = "lang-rust")]
 pub(crate) mod rust;
+#[cfg(feature = "lang-swift")]
+pub(crate) mod swift;
+pub(crate) mod symbols;
 #[cfg(feature = "lang-typescript")]
 pub(crate) mod typescript;
+#[cfg(feature = "lang-zig")]
+pub(crate) mod zig;
 
 /// Information about an import statement
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportInfo {
     /// Module path (e.g., "crate::services::auth")
     pub module_path: String,
     /// Imported items (e.g., ["AuthService", "Token"])
     pub items: Vec<String>,
+    /// How the import is loaded
+    pub kind: ImportKind,
+}
+
+/// How an import is loaded at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum ImportKind {
+    /// Loaded unconditionally when the file is loaded (`import`, `use`, `require()`)
+    #[default]
+    Static,
+    /// Only used for type checking and erased at runtime (`import type`)
+    TypeOnly,
+    /// Loaded on demand (`import()`)
+    Dynamic,
+    /// Loaded only on some code paths (Python imports in `try`/`if` blocks
+    /// or function bodies)
+    Optional,
+}
+
+/// Item marker for C/C++ angle-bracket includes (`#include <vector>`)
+///
+/// Quoted includes carry no items; angle includes carry this marker so
+/// include resolution can skip the including file's directory.
+pub(crate) const SYSTEM_INCLUDE: &str = "<system>";
+
+/// Visibility level of an exported item
+///
+/// Ordered from narrowest to widest, so the effective visibility of a
+/// nested item is the minimum of its own and its enclosing module's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub enum Visibility {
+    /// Visible to an ancestor module only (`pub(super)`, `pub(in path)`, `pub(self)`)
+    Restricted,
+    /// Visible within the crate (`pub(crate)`)
+    Crate,
+    /// Part of the public API (`pub`, `#[macro_export]`, or any export in
+    /// languages without visibility levels)
+    #[default]
+    Public,
+}
+
+impl Visibility {
+    /// Short label used when presenting exports
+    pub fn label(&self) -> &'static str {
+        match self {
+            Visibility::Restricted => "pub(restricted)",
+            Visibility::Crate => "pub(crate)",
+            Visibility::Public => "pub",
+        }
+    }
 }
 
 /// Analysis result for a single file
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileAnalysis {
     /// Import statements found
     pub imports: Vec<ImportInfo>,
@@ -30,6 +99,15 @@ pub struct FileAnalysis {
     /// Currently populated by parsers and used in tests; read access planned for future features.
     #[allow(dead_code)]
     pub exports: Vec<String>,
+    /// Namespaces or packages declared by the file (C#, Java, Kotlin), used to
+    /// map `using` directives and wildcard imports to files
+    pub namespaces: Vec<String>,
+    /// Out-of-line module declarations (Rust `mod foo;`), nested ones as `a::b`
+    pub modules: Vec<String>,
+    /// Imports that are re-exported (Rust `pub use`); also listed in `imports`
+    pub reexports: Vec<ImportInfo>,
+    /// Visibility per export name; exports missing here are public
+    pub visibility: HashMap<String, Visibility>,
     /// Detected language
     /// Currently populated by parsers and used in tests; read access planned for future features.
     #[allow(dead_code)]
@@ -42,29 +120,81 @@ impl FileAnalysis {
         Self {
             imports: Vec::new(),
             exports: Vec::new(),
+            namespaces: Vec::new(),
+            modules: Vec::new(),
+            reexports: Vec::new(),
+            visibility: HashMap::new(),
             language: language.to_string(),
         }
     }
+
+    /// Visibility of an exported name (public unless recorded otherwise)
+    pub fn export_visibility(&self, name: &str) -> Visibility {
+        self.visibility.get(name).copied().unwrap_or_default()
+    }
+}
+
+/// Languages this build can parse (the enabled `lang-*` features)
+pub(crate) fn enabled_languages() -> Vec<&'static str> {
+    [
+        ("rust", cfg!(feature = "lang-rust")),
+        ("typescript", cfg!(feature = "lang-typescript")),
+        ("python", cfg!(feature = "lang-python")),
+        ("go", cfg!(feature = "lang-go")),
+        ("cpp", cfg!(feature = "lang-cpp")),
+        ("csharp", cfg!(feature = "lang-csharp")),
+        ("java", cfg!(feature = "lang-java")),
+        ("kotlin", cfg!(feature = "lang-kotlin")),
+        ("ruby", cfg!(feature = "lang-ruby")),
+        ("swift", cfg!(feature = "lang-swift")),
+        ("zig", cfg!(feature = "lang-zig")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
 }
 
-/// Analyze a source file to extract imports and exports
-pub fn analyze_file(file_path: &Path) -> Result<FileAnalysis> {
+/// Analyze the source of a file to extract imports and exports
+///
+/// The language is picked from the extension of `file_path`. Callers read
+/// files through [`crate::cache::ParseCache`], which skips unchanged files.
+pub(crate) fn analyze_source(file_path: &Path, source: &str) -> Result<FileAnalysis> {
     let ext = file_path
         .extension()
         .and_then(|e| e.to_str())
         .unwrap_or("");
 
-    let source = fs::read_to_string(file_path)?;
-
     match ext {
         #[cfg(feature = "lang-rust")]
-        "rs" => rust::analyze_rust(&source),
+        "rs" => rust::analyze_rust(source),
         #[cfg(feature = "lang-typescript")]
-        "ts" | "tsx" => typescript::analyze_typescript(&source),
+        "ts" => typescript::analyze_typescript(source),
         #[cfg(feature = "lang-typescript")]
-        "js" | "jsx" => typescript::analyze_typescript(&source),
+        "tsx" | "js" | "jsx" => typescript::analyze_tsx(source),
+        #[cfg(feature = "lang-python")]
+        "py" if file_path.file_name().is_some_and(|n| n == "__init__.py") => {
+            python::analyze_python_init(source)
+        }
         #[cfg(feature = "lang-python")]
-        "py" => python::analyze_python(&source),
+        "py" => python::analyze_python(source),
+        #[cfg(feature = "lang-go")]
+        "go" => go::analyze_go(source),
+        #[cfg(feature = "lang-cpp")]
+        "h" | "hpp" => cpp::analyze_cpp(source, true),
+        #[cfg(feature = "lang-cpp")]
+        "c" | "cpp" => cpp::analyze_cpp(source, false),
+        #[cfg(feature = "lang-csharp")]
+        "cs" => csharp::analyze_csharp(source),
+        #[cfg(feature = "lang-java")]
+        "java" => java::analyze_java(source),
+        #[cfg(feature = "lang-kotlin")]
+        "kt" => kotlin::analyze_kotlin(source),
+        #[cfg(feature = "lang-ruby")]
+        "rb" => ruby::analyze_ruby(source),
+        #[cfg(feature = "lang-swift")]
+        "swift" => swift::analyze_swift(source),
+        #[cfg(feature = "lang-zig")]
+        "zig" => zig::analyze_zig(source),
         _ => Ok(FileAnalysis::empty("unknown")),
     }
 }
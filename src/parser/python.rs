@@ -8,14 +8,37 @@
 //! - `from module import item1, item2` (from import)
 //! - `from module import *` (wildcard import)
 //! - `from module import item as alias` (aliased from import)
+//! - imports nested in `if TYPE_CHECKING:` (type-only), other `if`/`try`
+//!   blocks and function bodies (optional)
+//!
+//! Exports are `__all__` when it is defined. Otherwise they are the public
+//! (non-underscore) top-level functions, classes and assigned names, plus
+//! relative from-imports re-exported by a package `__init__.py`.
+
+use std::collections::HashMap;
 
 use tree_sitter::Parser;
 
 use crate::error::{CodeReviewError, Result};
-use super::{FileAnalysis, ImportInfo};
+use super::{FileAnalysis, ImportInfo, ImportKind};
+
+/// Name of the `if` guard whose block only runs under a type checker
+const TYPE_CHECKING: &str = "TYPE_CHECKING";
 
 /// Analyze Python source code to extract imports and exports
 pub(crate) fn analyze_python(source: &str) -> Result<FileAnalysis> {
+    analyze(source, false)
+}
+
+/// Analyze a package `__init__.py`
+///
+/// Relative from-imports (`from . import x`, `from .models import User`)
+/// re-export the imported names as the package's API.
+pub(crate) fn analyze_python_init(source: &str) -> Result<FileAnalysis> {
+    analyze(source, true)
+}
+
+fn analyze(source: &str, is_package_init: bool) -> Result<FileAnalysis> {
     let mut parser = Parser::new();
     let language = tree_sitter_python::LANGUAGE;
     parser
@@ -28,42 +51,209 @@ pub(crate) fn analyze_python(source: &str) -> Result<FileAnalysis> {
 
     let mut imports = Vec::new();
     let mut exports = Vec::new();
+    let mut dunder_all: Option<Vec<String>> = None;
 
     let root = tree.root_node();
-    let mut cursor = root.walk();
+    collect_imports(root, source, ImportKind::Static, &mut imports);
 
+    let mut cursor = root.walk();
     for node in root.children(&mut cursor) {
         match node.kind() {
-            "import_statement" => {
-                // import module or import module as alias
-                let mut import_infos = extract_import_statement(node, source);
-                imports.append(&mut import_infos);
-            }
-            "import_from_statement" => {
-                // from module import item1, item2
-                if let Some(import) = extract_import_from_statement(node, source) {
-                    imports.push(import);
-                }
-            }
             "function_definition" | "class_definition" => {
                 if let Some(name) = extract_python_def(node, source) {
-                    // Python doesn't have explicit exports; collect all top-level defs
-                    if !name.starts_with('_') {
-                        exports.push(name);
-                    }
+                    exports.push(name);
+                }
+            }
+            // @decorator\ndef name(): ...
+            "decorated_definition" => {
+                if let Some(name) = node
+                    .child_by_field_name("definition")
+                    .and_then(|def| extract_python_def(def, source))
+                {
+                    exports.push(name);
                 }
             }
+            "expression_statement" => {
+                collect_assignment(node, source, &mut exports, &mut dunder_all);
+            }
             _ => {}
         }
     }
 
+    let mut reexports = Vec::new();
+    if is_package_init {
+        for import in &imports {
+            if import.kind == ImportKind::Static && import.module_path.starts_with('.') {
+                exports.extend(import.items.iter().filter(|i| *i != "*").cloned());
+                reexports.push(import.clone());
+            }
+        }
+    }
+
+    // `__all__` is the module's declared API; otherwise every public name
+    let exports = match dunder_all {
+        Some(names) => names,
+        None => exports.into_iter().filter(|name| !name.starts_with('_')).collect(),
+    };
+
     Ok(FileAnalysis {
         imports,
         exports,
+        namespaces: Vec::new(),
+        modules: Vec::new(),
+        reexports,
+        visibility: HashMap::new(),
         language: "python".to_string(),
     })
 }
 
+/// Collect imports anywhere below `node`, classified by the enclosing blocks
+///
+/// - `if TYPE_CHECKING:` bodies are type-only
+/// - other `if` branches, `try` statements and function bodies are optional
+/// - module and class bodies keep the enclosing kind
+fn collect_imports(
+    node: tree_sitter::Node,
+    source: &str,
+    kind: ImportKind,
+    imports: &mut Vec<ImportInfo>,
+) {
+    match node.kind() {
+        "import_statement" => {
+            for mut import in extract_import_statement(node, source) {
+                import.kind = kind;
+                imports.push(import);
+            }
+            return;
+        }
+        "import_from_statement" => {
+            if let Some(mut import) = extract_import_from_statement(node, source) {
+                import.kind = kind;
+                imports.push(import);
+            }
+            return;
+        }
+        "if_statement" => {
+            let is_type_checking = node
+                .child_by_field_name("condition")
+                .and_then(|c| c.utf8_text(source.as_bytes()).ok())
+                .is_some_and(|text| text.split('.').next_back() == Some(TYPE_CHECKING));
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                let is_guarded_body = node
+                    .child_by_field_name("consequence")
+                    .is_some_and(|body| body.id() == child.id());
+                let branch_kind = if is_type_checking && is_guarded_body {
+                    ImportKind::TypeOnly
+                } else {
+                    ImportKind::Optional
+                };
+                collect_imports(child, source, nested_kind(kind, branch_kind), imports);
+            }
+            return;
+        }
+        "try_statement" | "function_definition" => {
+            let nested = nested_kind(kind, ImportKind::Optional);
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                collect_imports(child, source, nested, imports);
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_imports(child, source, kind, imports);
+    }
+}
+
+/// Kind of an import inside a block of kind `inner` nested in `outer`
+///
+/// Type-only wins over optional, which wins over static.
+fn nested_kind(outer: ImportKind, inner: ImportKind) -> ImportKind {
+    match (outer, inner) {
+        (ImportKind::TypeOnly, _) | (_, ImportKind::TypeOnly) => ImportKind::TypeOnly,
+        (ImportKind::Static, inner) => inner,
+        (outer, _) => outer,
+    }
+}
+
+/// Record names bound by a top-level assignment, or the `__all__` list
+///
+/// `__all__ = [...]` sets the declared API; `__all__ += [...]` extends it.
+fn collect_assignment(
+    node: tree_sitter::Node,
+    source: &str,
+    names: &mut Vec<String>,
+    dunder_all: &mut Option<Vec<String>>,
+) {
+    let Some(assignment) = node
+        .named_child(0)
+        .filter(|n| matches!(n.kind(), "assignment" | "augmented_assignment"))
+    else {
+        return;
+    };
+    let Some(left) = assignment.child_by_field_name("left") else {
+        return;
+    };
+
+    if left.utf8_text(source.as_bytes()) == Ok("__all__") {
+        if let Some(right) = assignment.child_by_field_name("right") {
+            let listed = string_elements(right, source);
+            match dunder_all {
+                Some(all) if assignment.kind() == "augmented_assignment" => all.extend(listed),
+                _ => *dunder_all = Some(listed),
+            }
+        }
+        return;
+    }
+    if assignment.kind() == "augmented_assignment" {
+        return;
+    }
+
+    // x = ..., x: int = ..., a, b = ...
+    match left.kind() {
+        "identifier" => {
+            if let Ok(text) = left.utf8_text(source.as_bytes()) {
+                names.push(text.to_string());
+            }
+        }
+        "pattern_list" | "tuple_pattern" => {
+            let mut cursor = left.walk();
+            for target in left.named_children(&mut cursor) {
+                if target.kind() == "identifier" {
+                    if let Ok(text) = target.utf8_text(source.as_bytes()) {
+                        names.push(text.to_string());
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// String literals in a list or tuple (`["a", "b"]`)
+fn string_elements(node: tree_sitter::Node, source: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut cursor = node.walk();
+    for element in node.named_children(&mut cursor) {
+        if element.kind() != "string" {
+            continue;
+        }
+        let mut string_cursor = element.walk();
+        for part in element.named_children(&mut string_cursor) {
+            if part.kind() == "string_content" {
+                if let Ok(text) = part.utf8_text(source.as_bytes()) {
+                    strings.push(text.to_string());
+                }
+            }
+        }
+    }
+    strings
+}
+
 /// Extract import info from import_statement node
 ///
 /// tree-sitter-python import_statement structure:
@@ -91,6 +281,7 @@ fn extract_import_statement(node: tree_sitter::Node, source: &str) -> Vec<Import
                 imports.push(ImportInfo {
                     module_path,
                     items: Vec::new(),
+                    kind: ImportKind::Static,
                 });
             }
             "aliased_import" => {
@@ -158,7 +349,7 @@ fn extract_import_from_statement(node: tree_sitter::Node, source: &str) -> Optio
         return None;
     }
 
-    Some(ImportInfo { module_path, items })
+    Some(ImportInfo { module_path, items, kind: ImportKind::Static })
 }
 
 /// Extract dotted_name as a string (e.g., "os.path" from dotted_name node)
@@ -237,7 +428,7 @@ fn extract_aliased_import(node: tree_sitter::Node, source: &str) -> Option<Impor
         Vec::new()
     };
 
-    Some(ImportInfo { module_path, items })
+    Some(ImportInfo { module_path, items, kind: ImportKind::Static })
 }
 
 /// Extract item name from aliased_import in from-import statement
@@ -511,4 +702,90 @@ from module import *
         assert_eq!(result.imports[0].module_path, "xml.etree");
         assert_eq!(result.imports[0].items, vec!["ElementTree"]);
     }
+
+    #[test]
+    fn test_conditional_imports() {
+        let source = r#"
+from typing import TYPE_CHECKING
+import typing
+
+if TYPE_CHECKING:
+    from .models import User
+else:
+    User = None
+
+if typing.TYPE_CHECKING:
+    import pandas
+
+try:
+    import ujson as json
+except ImportError:
+    import json
+
+def load():
+    import yaml
+    return yaml
+"#;
+        let result = analyze_python(source).unwrap();
+        let kind_of = |path: &str| result.imports.iter().find(|i| i.module_path == path).unwrap().kind;
+        assert_eq!(kind_of("typing"), ImportKind::Static);
+        assert_eq!(kind_of(".models"), ImportKind::TypeOnly);
+        assert_eq!(kind_of("pandas"), ImportKind::TypeOnly);
+        assert_eq!(kind_of("ujson"), ImportKind::Optional);
+        assert_eq!(kind_of("json"), ImportKind::Optional);
+        assert_eq!(kind_of("yaml"), ImportKind::Optional);
+    }
+
+    #[test]
+    fn test_top_level_assignments_exported() {
+        let source = r#"
+MAX_RETRIES = 3
+timeout: float = 1.5
+host, port = "localhost", 8080
+_cache = {}
+
+@dataclass
+class Config:
+    debug: bool = False
+"#;
+        let result = analyze_python(source).unwrap();
+        for name in ["MAX_RETRIES", "timeout", "host", "port", "Config"] {
+            assert!(result.exports.contains(&name.to_string()), "missing {}", name);
+        }
+        assert!(!result.exports.contains(&"_cache".to_string()));
+        assert!(!result.exports.contains(&"debug".to_string()));
+    }
+
+    #[test]
+    fn test_dunder_all_takes_precedence() {
+        let source = r#"
+__all__ = ["connect", "_internal"]
+__all__ += ("Session",)
+
+def connect(): pass
+def disconnect(): pass
+def _internal(): pass
+class Session: pass
+"#;
+        let result = analyze_python(source).unwrap();
+        assert_eq!(result.exports, vec!["connect", "_internal", "Session"]);
+    }
+
+    #[test]
+    fn test_package_init_reexports() {
+        let source = r#"
+from . import models
+from .client import Client as HttpClient
+from .utils import *
+import logging
+"#;
+        let result = analyze_python_init(source).unwrap();
+        assert_eq!(result.reexports.len(), 3);
+        assert_eq!(result.exports, vec!["models", "HttpClient"]);
+
+        // The same imports in a plain module are not re-exports
+        let result = analyze_python(source).unwrap();
+        assert!(result.reexports.is_empty());
+        assert!(result.exports.is_empty());
+    }
 }
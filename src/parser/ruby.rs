@@ -0,0 +1,176 @@
+//! Ruby source code parsing with tree-sitter
+//!
+//! Supports:
+//! - `require "json"` (library or load-path require)
+//! - `require_relative "../lib/auth"` (file-relative require)
+//! - `require("net/http")` (parenthesized form)
+//!
+//! Requires are collected wherever they appear, since conditional and
+//! method-local requires are still dependencies. Requires with
+//! interpolated paths cannot be resolved statically and are skipped.
+//!
+//! Exports are the top-level `module` and `class` definitions, with
+//! scoped names (`Acme::Auth`) kept as written.
+
+use std::collections::HashMap;
+
+use tree_sitter::Parser;
+
+use crate::error::{CodeReviewError, Result};
+use super::{FileAnalysis, ImportInfo, ImportKind};
+
+/// Methods that load another file
+const REQUIRE_METHODS: &[&str] = &["require", "require_relative"];
+
+/// Analyze Ruby source code to extract requires and exports
+pub(crate) fn analyze_ruby(source: &str) -> Result<FileAnalysis> {
+    let mut parser = Parser::new();
+    let language = tree_sitter_ruby::LANGUAGE;
+    parser
+        .set_language(&language.into())
+        .map_err(|e| CodeReviewError::ParseError(format!("Failed to set Ruby language: {}", e)))?;
+
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| CodeReviewError::ParseError("Failed to parse Ruby source".to_string()))?;
+
+    let root = tree.root_node();
+
+    let mut imports = Vec::new();
+    collect_requires(root, source, &mut imports);
+
+    let mut exports = Vec::new();
+    let mut cursor = root.walk();
+    for node in root.children(&mut cursor) {
+        if matches!(node.kind(), "module" | "class") {
+            if let Some(name) = node
+                .child_by_field_name("name")
+                .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+            {
+                exports.push(name.to_string());
+            }
+        }
+    }
+
+    Ok(FileAnalysis {
+        imports,
+        exports,
+        namespaces: Vec::new(),
+        modules: Vec::new(),
+        reexports: Vec::new(),
+        visibility: HashMap::new(),
+        language: "ruby".to_string(),
+    })
+}
+
+/// Collect `require`/`require_relative` calls anywhere below `node`
+fn collect_requires(node: tree_sitter::Node, source: &str, imports: &mut Vec<ImportInfo>) {
+    if node.kind() == "call" {
+        if let Some(import) = extract_require(node, source) {
+            imports.push(import);
+            return;
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_requires(child, source, imports);
+    }
+}
+
+/// Extract import info from a require call
+///
+/// tree-sitter-ruby call structure:
+/// - call
+///   - method: identifier (`require` / `require_relative`)
+///   - arguments: argument_list
+///     - string
+///       - string_content
+fn extract_require(node: tree_sitter::Node, source: &str) -> Option<ImportInfo> {
+    // `Kernel.require` or `obj.require` is not a plain require
+    if node.child_by_field_name("receiver").is_some() {
+        return None;
+    }
+    let method = node
+        .child_by_field_name("method")?
+        .utf8_text(source.as_bytes())
+        .ok()?;
+    if !REQUIRE_METHODS.contains(&method) {
+        return None;
+    }
+
+    let arguments = node.child_by_field_name("arguments")?;
+    let string = arguments.named_child(0).filter(|n| n.kind() == "string")?;
+
+    // Only plain string literals: a single string_content child
+    if string.named_child_count() != 1 {
+        return None;
+    }
+    let content = string.named_child(0).filter(|n| n.kind() == "string_content")?;
+    let module_path = content.utf8_text(source.as_bytes()).ok()?;
+
+    Some(ImportInfo {
+        module_path: module_path.to_string(),
+        items: Vec::new(),
+        kind: ImportKind::Static,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_ruby() {
+        let source = r#"
+require "json"
+require_relative "../lib/auth"
+
+module Billing
+end
+
+class Invoice
+end
+"#;
+        let result = analyze_ruby(source).unwrap();
+        assert_eq!(result.language, "ruby");
+        let paths: Vec<&str> = result.imports.iter().map(|i| i.module_path.as_str()).collect();
+        assert_eq!(paths, vec!["json", "../lib/auth"]);
+        assert_eq!(result.exports, vec!["Billing", "Invoice"]);
+    }
+
+    #[test]
+    fn test_extract_ruby_require_forms() {
+        let source = r#"
+require("net/http")
+require 'set'
+
+def load_plugins
+  require_relative "plugins/registry"
+end
+
+Kernel.require "ignored"
+require "plugins/#{name}"
+"#;
+        let result = analyze_ruby(source).unwrap();
+        let paths: Vec<&str> = result.imports.iter().map(|i| i.module_path.as_str()).collect();
+        assert_eq!(paths, vec!["net/http", "set", "plugins/registry"]);
+    }
+
+    #[test]
+    fn test_ruby_scoped_and_nested_definitions() {
+        let source = r#"
+class Acme::TokenStore < Store
+  class Entry
+  end
+end
+
+module Acme
+  module Auth
+  end
+end
+"#;
+        let result = analyze_ruby(source).unwrap();
+        assert_eq!(result.exports, vec!["Acme::TokenStore", "Acme"]);
+    }
+}
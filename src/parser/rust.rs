@@ -1,9 +1,33 @@
 //! Rust source code parsing with tree-sitter
+//!
+//! Supports:
+//! - `use` declarations (paths, lists, globs, aliases)
+//! - `pub use` re-exports (recorded in `reexports` and exported by name)
+//! - `mod foo;` declarations (recorded in `modules`)
+//! - items inside inline `mod name { ... }` blocks (exported as `name::Item`)
+//! - `#[macro_export] macro_rules!` definitions
+//!
+//! Exports are items with a visibility modifier; `pub(crate)` and narrower
+//! levels are recorded in `visibility`. `#[cfg(test)]` modules are skipped.
 
 use tree_sitter::Parser;
 
 use crate::error::{CodeReviewError, Result};
-use super::{FileAnalysis, ImportInfo};
+use super::{FileAnalysis, ImportInfo, ImportKind, Visibility};
+
+/// Item kinds recorded as exports when they carry a visibility modifier
+const EXPORTABLE_ITEMS: &[&str] = &[
+    "function_item",
+    "struct_item",
+    "enum_item",
+    "union_item",
+    "type_item",
+    "const_item",
+    "static_item",
+    "trait_item",
+    "impl_item",
+    "mod_item",
+];
 
 /// Analyze Rust source code to extract imports and exports
 pub(crate) fn analyze_rust(source: &str) -> Result<FileAnalysis> {
@@ -17,37 +41,113 @@ pub(crate) fn analyze_rust(source: &str) -> Result<FileAnalysis> {
         .parse(source, None)
         .ok_or_else(|| CodeReviewError::ParseError("Failed to parse Rust source".to_string()))?;
 
-    let mut imports = Vec::new();
-    let mut exports = Vec::new();
+    let mut analysis = FileAnalysis::empty("rust");
+    collect_items(tree.root_node(), source, "", Some(Visibility::Public), &mut analysis);
 
-    let root = tree.root_node();
-    let mut cursor = root.walk();
+    Ok(analysis)
+}
 
-    // Traverse top-level nodes
-    for node in root.children(&mut cursor) {
-        match node.kind() {
+/// Walk the items of a source file or inline `mod {}` block
+///
+/// `prefix` qualifies names declared inside inline modules (`inner::`).
+/// `cap` is the widest visibility an item here can have: items inside a
+/// `pub(crate) mod` are at most crate-visible, and items inside a private
+/// inline module (`None`) are not exported at all. `#[cfg(test)]` modules
+/// are skipped entirely.
+fn collect_items(
+    node: tree_sitter::Node,
+    source: &str,
+    prefix: &str,
+    cap: Option<Visibility>,
+    analysis: &mut FileAnalysis,
+) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
             "use_declaration" => {
-                if let Some(import) = extract_rust_use(node, source) {
-                    imports.push(import);
+                let Some(import) = extract_rust_use(child, source) else {
+                    continue;
+                };
+                if let Some(visibility) = exported_visibility(child, source, cap) {
+                    for name in reexported_names(&import) {
+                        push_export(analysis, format!("{}{}", prefix, name), visibility);
+                    }
+                    analysis.reexports.push(import.clone());
                 }
+                analysis.imports.push(import);
             }
-            "function_item" | "struct_item" | "enum_item" | "type_item" | "const_item"
-            | "static_item" | "trait_item" | "impl_item" => {
-                if is_public(node, source) {
-                    if let Some(name) = extract_item_name(node, source) {
-                        exports.push(name);
+            "mod_item" => {
+                if has_attribute(child, source, "cfg(test)") {
+                    continue;
+                }
+                let Some(name) = extract_item_name(child, source) else {
+                    continue;
+                };
+                let path = format!("{}{}", prefix, name);
+                let visibility = exported_visibility(child, source, cap);
+                if let Some(visibility) = visibility {
+                    push_export(analysis, path.clone(), visibility);
+                }
+                match child.child_by_field_name("body") {
+                    Some(body) => {
+                        let prefix = format!("{}::", path);
+                        collect_items(body, source, &prefix, visibility, analysis);
+                    }
+                    // `mod foo;` pulls in another file
+                    None => analysis.modules.push(path),
+                }
+            }
+            // `macro_rules!` is only visible outside the crate with
+            // `#[macro_export]`, which places it at the crate root
+            "macro_definition" => {
+                if !has_attribute(child, source, "macro_export") {
+                    continue;
+                }
+                if let Some(name) = child
+                    .child_by_field_name("name")
+                    .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+                {
+                    push_export(analysis, name.to_string(), Visibility::Public);
+                }
+            }
+            kind if EXPORTABLE_ITEMS.contains(&kind) => {
+                if let Some(visibility) = exported_visibility(child, source, cap) {
+                    if let Some(name) = extract_item_name(child, source) {
+                        push_export(analysis, format!("{}{}", prefix, name), visibility);
                     }
                 }
             }
             _ => {}
         }
     }
+}
 
-    Ok(FileAnalysis {
-        imports,
-        exports,
-        language: "rust".to_string(),
-    })
+/// Record an export and, unless it is public, its visibility
+fn push_export(analysis: &mut FileAnalysis, name: String, visibility: Visibility) {
+    if visibility != Visibility::Public {
+        analysis.visibility.insert(name.clone(), visibility);
+    }
+    analysis.exports.push(name);
+}
+
+/// Names made visible by a re-exporting `use`
+///
+/// Globs cannot be named; `self` re-exports the module itself and nested
+/// paths (`sub::Item`) re-export their last segment.
+fn reexported_names(import: &ImportInfo) -> Vec<String> {
+    import
+        .items
+        .iter()
+        .filter(|item| item.as_str() != "*")
+        .filter_map(|item| {
+            let name = if item == "self" {
+                import.module_path.rsplit("::").next()?
+            } else {
+                item.rsplit("::").next()?
+            };
+            (!name.is_empty()).then(|| name.to_string())
+        })
+        .collect()
 }
 
 fn extract_rust_use(node: tree_sitter::Node, source: &str) -> Option<ImportInfo> {
@@ -78,6 +178,7 @@ fn extract_rust_use(node: tree_sitter::Node, source: &str) -> Option<ImportInfo>
                 return Some(ImportInfo {
                     module_path: String::new(),
                     items: vec![name.to_string()],
+                    kind: ImportKind::Static,
                 });
             }
             // use crate; or use self; or use super;
@@ -86,6 +187,7 @@ fn extract_rust_use(node: tree_sitter::Node, source: &str) -> Option<ImportInfo>
                 return Some(ImportInfo {
                     module_path: name.to_string(),
                     items: Vec::new(),
+                    kind: ImportKind::Static,
                 });
             }
             // use {Item1, Item2}; (use_list at top level)
@@ -94,6 +196,7 @@ fn extract_rust_use(node: tree_sitter::Node, source: &str) -> Option<ImportInfo>
                 return Some(ImportInfo {
                     module_path: String::new(),
                     items,
+                    kind: ImportKind::Static,
                 });
             }
             _ => {}
@@ -120,6 +223,7 @@ fn extract_from_scoped_identifier(node: tree_sitter::Node, source: &str) -> Opti
     Some(ImportInfo {
         module_path,
         items: vec![item],
+        kind: ImportKind::Static,
     })
 }
 
@@ -165,7 +269,7 @@ fn extract_from_scoped_use_list(node: tree_sitter::Node, source: &str) -> Option
     }
 
     let module_path = module_parts.join("::");
-    Some(ImportInfo { module_path, items })
+    Some(ImportInfo { module_path, items, kind: ImportKind::Static })
 }
 
 /// Extract items from use_list (e.g., {Read, Write, self})
@@ -225,6 +329,7 @@ fn extract_from_use_wildcard(node: tree_sitter::Node, source: &str) -> Option<Im
     Some(ImportInfo {
         module_path,
         items: vec!["*".to_string()],
+        kind: ImportKind::Static,
     })
 }
 
@@ -279,6 +384,7 @@ fn extract_from_use_as_clause(node: tree_sitter::Node, source: &str) -> Option<I
     Some(ImportInfo {
         module_path,
         items: vec![imported_name],
+        kind: ImportKind::Static,
     })
 }
 
@@ -309,12 +415,49 @@ fn extract_alias_from_use_as_clause(node: tree_sitter::Node, source: &str) -> Op
     None
 }
 
-fn is_public(node: tree_sitter::Node, _source: &str) -> bool {
+/// Effective visibility of an item, or `None` if it is not exported
+///
+/// The item's own modifier is narrowed by the enclosing module's `cap`.
+fn exported_visibility(
+    node: tree_sitter::Node,
+    source: &str,
+    cap: Option<Visibility>,
+) -> Option<Visibility> {
+    Some(item_visibility(node, source)?.min(cap?))
+}
+
+/// Visibility written on an item itself, or `None` without a modifier
+pub(crate) fn item_visibility(node: tree_sitter::Node, source: &str) -> Option<Visibility> {
     let mut cursor = node.walk();
-    for child in node.children(&mut cursor) {
-        if child.kind() == "visibility_modifier" {
-            return true;
+    let modifier = node
+        .children(&mut cursor)
+        .find(|child| child.kind() == "visibility_modifier")?;
+    Some(match modifier.utf8_text(source.as_bytes()).ok()? {
+        "pub" => Visibility::Public,
+        "pub(crate)" | "crate" => Visibility::Crate,
+        _ => Visibility::Restricted,
+    })
+}
+
+/// Check whether an item is preceded by an outer attribute containing `name`
+///
+/// Attributes and doc comments are siblings that precede the item, so walk
+/// backwards over them.
+fn has_attribute(node: tree_sitter::Node, source: &str, name: &str) -> bool {
+    let mut sibling = node.prev_sibling();
+    while let Some(prev) = sibling {
+        match prev.kind() {
+            "attribute_item" => {
+                let text = prev.utf8_text(source.as_bytes()).unwrap_or("");
+                let compact: String = text.split_whitespace().collect();
+                if compact.contains(name) {
+                    return true;
+                }
+            }
+            "line_comment" | "block_comment" => {}
+            _ => return false,
         }
+        sibling = prev.prev_sibling();
     }
     false
 }
@@ -433,4 +576,108 @@ fn private() {}
         assert!(result.exports.contains(&"DocEnum".to_string()));
         assert!(!result.exports.contains(&"private".to_string()));
     }
+
+    #[test]
+    fn test_rust_mod_declarations() {
+        let source = r#"
+mod config;
+pub mod parser;
+
+pub mod api {
+    pub mod v1;
+}
+
+#[cfg(test)]
+mod tests;
+"#;
+        let result = analyze_rust(source).unwrap();
+        assert_eq!(result.modules, vec!["config", "parser", "api::v1"]);
+        assert!(result.exports.contains(&"parser".to_string()));
+        assert!(result.exports.contains(&"api".to_string()));
+        assert!(result.exports.contains(&"api::v1".to_string()));
+        assert!(!result.exports.contains(&"config".to_string()));
+    }
+
+    #[test]
+    fn test_rust_pub_use_reexports() {
+        let source = r#"
+pub use crate::error::{CodeReviewError, Result};
+pub use self::inner::Client as HttpClient;
+pub(crate) use crate::utils::*;
+use std::path::Path;
+"#;
+        let result = analyze_rust(source).unwrap();
+        assert_eq!(result.imports.len(), 4);
+        assert_eq!(result.reexports.len(), 3);
+        assert_eq!(result.reexports[0].module_path, "crate::error");
+        for name in ["CodeReviewError", "Result", "HttpClient"] {
+            assert!(result.exports.contains(&name.to_string()), "missing {}", name);
+        }
+        assert!(!result.exports.contains(&"Path".to_string()));
+        assert!(!result.exports.contains(&"*".to_string()));
+    }
+
+    #[test]
+    fn test_rust_visibility_levels() {
+        let source = r#"
+pub fn api() {}
+pub(crate) fn internal() {}
+pub(super) struct Parent;
+pub(in crate::parser) enum Scoped { A }
+
+pub(crate) mod helpers {
+    pub fn widened() {}
+}
+"#;
+        let result = analyze_rust(source).unwrap();
+        assert_eq!(result.export_visibility("api"), Visibility::Public);
+        assert_eq!(result.export_visibility("internal"), Visibility::Crate);
+        assert_eq!(result.export_visibility("Parent"), Visibility::Restricted);
+        assert_eq!(result.export_visibility("Scoped"), Visibility::Restricted);
+        // `pub` inside a `pub(crate)` module is only crate-visible
+        assert_eq!(result.export_visibility("helpers::widened"), Visibility::Crate);
+        assert!(!result.visibility.contains_key("api"));
+    }
+
+    #[test]
+    fn test_rust_inline_module_items() {
+        let source = r#"
+pub mod shapes {
+    pub struct Circle;
+    fn area() {}
+}
+
+mod private {
+    pub fn hidden() {}
+}
+
+#[cfg(test)]
+mod tests {
+    pub fn helper() {}
+}
+"#;
+        let result = analyze_rust(source).unwrap();
+        assert!(result.exports.contains(&"shapes::Circle".to_string()));
+        assert!(!result.exports.contains(&"shapes::area".to_string()));
+        assert!(!result.exports.iter().any(|e| e.contains("hidden")));
+        assert!(!result.exports.iter().any(|e| e.contains("helper")));
+    }
+
+    #[test]
+    fn test_rust_macro_rules_exports() {
+        let source = r#"
+/// Builds a map
+#[macro_export]
+macro_rules! hashmap {
+    () => {};
+}
+
+macro_rules! local_only {
+    () => {};
+}
+"#;
+        let result = analyze_rust(source).unwrap();
+        assert_eq!(result.exports, vec!["hashmap"]);
+        assert_eq!(result.export_visibility("hashmap"), Visibility::Public);
+    }
 }
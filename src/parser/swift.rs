@@ -0,0 +1,206 @@
+//! Swift source code parsing with tree-sitter
+//!
+//! Supports:
+//! - `import Foundation` (module import)
+//! - `import UIKit.UIView` (submodule import)
+//! - `import struct Networking.Request` (declaration import, kind dropped)
+//! - `@testable import App` (attributes ignored)
+//!
+//! Exports are top-level classes, structs, enums, actors, protocols,
+//! functions, properties and type aliases declared `public` or `open`.
+//! Extensions add no new names and are skipped.
+
+use std::collections::HashMap;
+
+use tree_sitter::Parser;
+
+use crate::error::{CodeReviewError, Result};
+use super::{FileAnalysis, ImportInfo, ImportKind};
+
+/// Visibility modifiers that make a declaration part of the module's API
+const EXPORTED_VISIBILITY: &[&str] = &["public", "open"];
+
+/// Analyze Swift source code to extract imports and exports
+pub(crate) fn analyze_swift(source: &str) -> Result<FileAnalysis> {
+    let mut parser = Parser::new();
+    let language = tree_sitter_swift::LANGUAGE;
+    parser
+        .set_language(&language.into())
+        .map_err(|e| CodeReviewError::ParseError(format!("Failed to set Swift language: {}", e)))?;
+
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| CodeReviewError::ParseError("Failed to parse Swift source".to_string()))?;
+
+    let mut imports = Vec::new();
+    let mut exports = Vec::new();
+
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+
+    for node in root.children(&mut cursor) {
+        match node.kind() {
+            "import_declaration" => {
+                if let Some(import) = extract_import(node, source) {
+                    imports.push(import);
+                }
+            }
+            "class_declaration" | "protocol_declaration" | "function_declaration"
+            | "typealias_declaration" | "property_declaration" => {
+                if let Some(name) = declaration_name(node, source)
+                    .filter(|_| is_exported(node, source))
+                {
+                    exports.push(name.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(FileAnalysis {
+        imports,
+        exports,
+        namespaces: Vec::new(),
+        modules: Vec::new(),
+        reexports: Vec::new(),
+        visibility: HashMap::new(),
+        language: "swift".to_string(),
+    })
+}
+
+/// Extract import info from an import_declaration node
+///
+/// tree-sitter-swift import_declaration structure:
+/// - import_declaration
+///   - modifiers (optional, e.g. `@testable`)
+///   - "import"
+///   - "struct" | "class" | "func" | ... (optional import kind)
+///   - identifier
+///     - simple_identifier (one per dotted segment)
+fn extract_import(node: tree_sitter::Node, source: &str) -> Option<ImportInfo> {
+    let mut cursor = node.walk();
+    let path = node
+        .named_children(&mut cursor)
+        .find(|child| child.kind() == "identifier")?;
+
+    let mut path_cursor = path.walk();
+    let segments: Vec<&str> = path
+        .named_children(&mut path_cursor)
+        .filter(|segment| segment.kind() == "simple_identifier")
+        .filter_map(|segment| segment.utf8_text(source.as_bytes()).ok())
+        .collect();
+    if segments.is_empty() {
+        return None;
+    }
+
+    Some(ImportInfo {
+        module_path: segments.join("."),
+        items: Vec::new(),
+        kind: ImportKind::Static,
+    })
+}
+
+/// Declared name of a top-level declaration
+///
+/// Extensions reuse an existing type name, so they yield nothing.
+fn declaration_name<'a>(node: tree_sitter::Node, source: &'a str) -> Option<&'a str> {
+    let is_extension = node
+        .child_by_field_name("declaration_kind")
+        .is_some_and(|kind| kind.kind() == "extension");
+    if is_extension {
+        return None;
+    }
+    node.child_by_field_name("name")?
+        .utf8_text(source.as_bytes())
+        .ok()
+}
+
+/// Check for a `public` or `open` visibility modifier
+fn is_exported(node: tree_sitter::Node, source: &str) -> bool {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.kind() != "modifiers" {
+            continue;
+        }
+        let mut modifier_cursor = child.walk();
+        for modifier in child.children(&mut modifier_cursor) {
+            if modifier.kind() == "visibility_modifier"
+                && modifier
+                    .utf8_text(source.as_bytes())
+                    .is_ok_and(|text| EXPORTED_VISIBILITY.contains(&text))
+            {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_swift() {
+        let source = r#"
+import Foundation
+
+public class Client {}
+open class BaseController {}
+internal struct Cache {}
+public func connect() {}
+func helper() {}
+"#;
+        let result = analyze_swift(source).unwrap();
+        assert_eq!(result.language, "swift");
+        assert_eq!(result.imports.len(), 1);
+        assert_eq!(result.imports[0].module_path, "Foundation");
+        for name in ["Client", "BaseController", "connect"] {
+            assert!(result.exports.contains(&name.to_string()), "missing {}", name);
+        }
+        for name in ["Cache", "helper"] {
+            assert!(!result.exports.contains(&name.to_string()), "unexpected {}", name);
+        }
+    }
+
+    #[test]
+    fn test_extract_swift_import_forms() {
+        let source = r#"
+import UIKit.UIView
+import struct Networking.Request
+@testable import App
+"#;
+        let result = analyze_swift(source).unwrap();
+        let paths: Vec<&str> = result.imports.iter().map(|i| i.module_path.as_str()).collect();
+        assert_eq!(paths, vec!["UIKit.UIView", "Networking.Request", "App"]);
+    }
+
+    #[test]
+    fn test_swift_protocols_properties_and_extensions() {
+        let source = r#"
+public protocol Repository {}
+public enum Status { case ok }
+public let defaultTimeout = 30
+private var counter = 0
+public typealias Handler = () -> Void
+public extension String {}
+"#;
+        let result = analyze_swift(source).unwrap();
+        for name in ["Repository", "Status", "defaultTimeout", "Handler"] {
+            assert!(result.exports.contains(&name.to_string()), "missing {}", name);
+        }
+        assert!(!result.exports.contains(&"counter".to_string()));
+        assert!(!result.exports.contains(&"String".to_string()));
+    }
+
+    #[test]
+    fn test_grammar_abi_is_supported() {
+        let version = tree_sitter::Language::from(tree_sitter_swift::LANGUAGE).version();
+        assert!(
+            (tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION..=tree_sitter::LANGUAGE_VERSION)
+                .contains(&version),
+            "grammar ABI {} is not supported by tree-sitter",
+            version
+        );
+    }
+}
@@ -0,0 +1,613 @@
+//! Symbol extraction with tree-sitter
+//!
+//! Where the language parsers record what a file imports and exports, this
+//! module records *where* things are: the definitions in a file (with line
+//! ranges and signatures), the identifiers it references, and the
+//! functions it calls. Supported for Rust, TypeScript/JavaScript and
+//! Python. The per-language grammar tables also carry the node kinds
+//! [`crate::metrics`] measures, so both share one language mapping.
+
+use std::path::Path;
+
+use tree_sitter::{Language, Node, Parser};
+
+use super::Visibility;
+
+/// A definition found in a source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Symbol {
+    /// Defined name (methods by their own name)
+    pub name: String,
+    /// Kind of definition (`fn`, `struct`, `class`, ...)
+    pub kind: &'static str,
+    /// First line (1-based)
+    pub start_line: usize,
+    /// Last line (1-based, inclusive)
+    pub end_line: usize,
+    /// Signature for functions, full text for types
+    pub text: String,
+    /// Declared visibility, `None` for private definitions
+    pub visibility: Option<Visibility>,
+}
+
+impl Symbol {
+    /// Whether this is a function or method
+    pub fn is_function(&self) -> bool {
+        matches!(self.kind, "fn" | "function" | "method" | "def")
+    }
+
+    /// Whether the definition spans any of the given lines
+    pub fn overlaps(&self, lines: &std::collections::BTreeSet<usize>) -> bool {
+        lines.range(self.start_line..=self.end_line).next().is_some()
+    }
+}
+
+/// A name used at a line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Reference {
+    pub name: String,
+    /// 1-based line
+    pub line: usize,
+}
+
+/// Definitions, references and calls of one source file
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SourceSymbols {
+    pub definitions: Vec<Symbol>,
+    pub references: Vec<Reference>,
+    /// Calls by callee name (last path segment, e.g. `open` for `db::open()`)
+    pub calls: Vec<Reference>,
+}
+
+/// A source token, for comparing code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Token {
+    /// Token text
+    pub text: String,
+    /// Text with identifiers and literals abstracted (`$id`, `$lit`)
+    pub normalized: String,
+    /// 1-based line
+    pub line: usize,
+}
+
+/// A function or method and its tokens (comments excluded)
+#[derive(Debug, Clone)]
+pub(crate) struct FunctionTokens {
+    pub symbol: Symbol,
+    pub tokens: Vec<Token>,
+}
+
+/// Literal node kinds without a `_literal` suffix, compared as one token
+const LITERAL_KINDS: &[&str] = &[
+    "string",
+    "template_string",
+    "concatenated_string",
+    "number",
+    "integer",
+    "float",
+];
+
+/// Longest type definition kept verbatim; longer ones are cut
+const MAX_DEFINITION_LINES: usize = 30;
+
+/// Node kinds, per language, that make up the symbol tables and the code metrics
+pub(crate) struct SymbolGrammar {
+    pub(crate) language: Language,
+    /// Definition node kinds and the label they are reported with
+    definitions: &'static [(&'static str, &'static str)],
+    /// Labels of definitions shown as a signature only
+    signature_only: &'static [&'static str],
+    /// Identifier node kinds
+    identifiers: &'static [&'static str],
+    /// Call node kinds (callee in the `function` field)
+    calls: &'static [&'static str],
+    /// Visibility of a definition node, `None` if private
+    visibility: fn(Node, &str) -> Option<Visibility>,
+    /// Node kinds the metrics of [`crate::metrics`] are computed from
+    pub(crate) metrics: MetricKinds,
+}
+
+/// Node kinds the code metrics are computed from
+pub(crate) struct MetricKinds {
+    /// Function node kinds (anonymous ones count toward their enclosing function)
+    pub(crate) functions: &'static [&'static str],
+    /// Type definition node kinds
+    pub(crate) types: &'static [&'static str],
+    /// Node kinds that add a decision point
+    pub(crate) branches: &'static [&'static str],
+    /// Multi-way branches whose arms are counted in `branches`; n arms add n - 1
+    pub(crate) multiway: &'static [&'static str],
+    /// Binary node kinds whose operator may short-circuit
+    pub(crate) binary: &'static [&'static str],
+    /// Short-circuit operators
+    pub(crate) logical_operators: &'static [&'static str],
+    /// Node kinds that open a nested block
+    pub(crate) nesting: &'static [&'static str],
+    /// Parameter list children that are not parameters
+    pub(crate) non_params: &'static [&'static str],
+}
+
+#[cfg(feature = "lang-rust")]
+fn rust_grammar() -> SymbolGrammar {
+    SymbolGrammar {
+        language: tree_sitter_rust::LANGUAGE.into(),
+        definitions: &[
+            ("function_item", "fn"),
+            ("function_signature_item", "fn"),
+            ("struct_item", "struct"),
+            ("enum_item", "enum"),
+            ("union_item", "union"),
+            ("trait_item", "trait"),
+            ("type_item", "type"),
+            ("const_item", "const"),
+            ("static_item", "static"),
+            ("macro_definition", "macro"),
+        ],
+        signature_only: &["fn"],
+        identifiers: &["identifier", "type_identifier"],
+        calls: &["call_expression"],
+        visibility: super::rust::item_visibility,
+        metrics: MetricKinds {
+            functions: &["function_item", "closure_expression"],
+            types: &["struct_item", "enum_item", "union_item", "trait_item", "type_item"],
+            branches: &["if_expression", "while_expression", "for_expression", "match_arm"],
+            multiway: &["match_expression"],
+            binary: &["binary_expression"],
+            logical_operators: &["&&", "||"],
+            nesting: &[
+                "if_expression",
+                "match_expression",
+                "for_expression",
+                "while_expression",
+                "loop_expression",
+            ],
+            non_params: &["self_parameter", "attribute_item", "line_comment", "block_comment"],
+        },
+    }
+}
+
+#[cfg(feature = "lang-typescript")]
+fn typescript_grammar(tsx: bool) -> SymbolGrammar {
+    SymbolGrammar {
+        language: if tsx {
+            tree_sitter_typescript::LANGUAGE_TSX.into()
+        } else {
+            tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()
+        },
+        definitions: &[
+            ("function_declaration", "function"),
+            ("generator_function_declaration", "function"),
+            ("variable_declarator", "function"),
+            ("method_definition", "method"),
+            ("method_signature", "method"),
+            ("class_declaration", "class"),
+            ("abstract_class_declaration", "class"),
+            ("interface_declaration", "interface"),
+            ("type_alias_declaration", "type"),
+            ("enum_declaration", "enum"),
+        ],
+        signature_only: &["function", "method", "class"],
+        identifiers: &["identifier", "type_identifier", "property_identifier"],
+        calls: &["call_expression", "new_expression"],
+        visibility: |node, source| {
+            let public = if matches!(node.kind(), "method_definition" | "method_signature") {
+                !node.children(&mut node.walk()).any(|c| {
+                    c.kind() == "accessibility_modifier"
+                        && matches!(&source[c.byte_range()], "private" | "protected")
+                })
+            } else {
+                node.parent().is_some_and(|p| p.kind() == "export_statement")
+                    || (node.kind() == "variable_declarator"
+                        && node
+                            .parent()
+                            .and_then(|p| p.parent())
+                            .is_some_and(|p| p.kind() == "export_statement"))
+            };
+            public.then_some(Visibility::Public)
+        },
+        metrics: MetricKinds {
+            functions: &[
+                "function_declaration",
+                "generator_function_declaration",
+                "method_definition",
+                "arrow_function",
+                "function_expression",
+            ],
+            types: &[
+                "class_declaration",
+                "abstract_class_declaration",
+                "interface_declaration",
+                "type_alias_declaration",
+                "enum_declaration",
+            ],
+            branches: &[
+                "if_statement",
+                "for_statement",
+                "for_in_statement",
+                "while_statement",
+                "do_statement",
+                "switch_case",
+                "catch_clause",
+                "ternary_expression",
+            ],
+            multiway: &[],
+            binary: &["binary_expression"],
+            logical_operators: &["&&", "||", "??"],
+            nesting: &[
+                "if_statement",
+                "for_statement",
+                "for_in_statement",
+                "while_statement",
+                "do_statement",
+                "switch_statement",
+                "try_statement",
+            ],
+            non_params: &["comment"],
+        },
+    }
+}
+
+#[cfg(feature = "lang-python")]
+fn python_grammar() -> SymbolGrammar {
+    SymbolGrammar {
+        language: tree_sitter_python::LANGUAGE.into(),
+        definitions: &[("function_definition", "def"), ("class_definition", "class")],
+        signature_only: &["def"],
+        identifiers: &["identifier"],
+        calls: &["call"],
+        visibility: |node, source| {
+            node.child_by_field_name("name")
+                .is_some_and(|n| !source[n.byte_range()].starts_with('_'))
+                .then_some(Visibility::Public)
+        },
+        metrics: MetricKinds {
+            functions: &["function_definition", "lambda"],
+            types: &["class_definition"],
+            branches: &[
+                "if_statement",
+                "elif_clause",
+                "for_statement",
+                "while_statement",
+                "except_clause",
+                "conditional_expression",
+                "case_clause",
+                "if_clause",
+            ],
+            multiway: &["match_statement"],
+            binary: &["boolean_operator"],
+            logical_operators: &["and", "or"],
+            nesting: &[
+                "if_statement",
+                "for_statement",
+                "while_statement",
+                "try_statement",
+                "with_statement",
+                "match_statement",
+            ],
+            non_params: &["comment", "keyword_separator", "positional_separator"],
+        },
+    }
+}
+
+/// Grammar for a file, by extension
+pub(crate) fn grammar_for(path: &Path) -> Option<SymbolGrammar> {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    match ext {
+        #[cfg(feature = "lang-rust")]
+        "rs" => Some(rust_grammar()),
+        #[cfg(feature = "lang-typescript")]
+        "ts" => Some(typescript_grammar(false)),
+        #[cfg(feature = "lang-typescript")]
+        "tsx" | "js" | "jsx" => Some(typescript_grammar(true)),
+        #[cfg(feature = "lang-python")]
+        "py" => Some(python_grammar()),
+        _ => None,
+    }
+}
+
+/// Whether symbols can be extracted for this file
+pub(crate) fn supports_symbols(path: &Path) -> bool {
+    grammar_for(path).is_some()
+}
+
+/// Extract definitions, references and calls from `source`
+///
+/// Returns `None` for unsupported languages and unparsable input.
+pub(crate) fn parse_symbols(path: &Path, source: &str) -> Option<SourceSymbols> {
+    let grammar = grammar_for(path)?;
+    let mut parser = Parser::new();
+    parser.set_language(&grammar.language).ok()?;
+    let tree = parser.parse(source, None)?;
+
+    let mut symbols = SourceSymbols::default();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        let kind = node.kind();
+        if let Some(symbol) = definition(&grammar, node, source) {
+            symbols.definitions.push(symbol);
+        }
+        if grammar.identifiers.contains(&kind) {
+            symbols.references.push(Reference {
+                name: source[node.byte_range()].to_string(),
+                line: node.start_position().row + 1,
+            });
+        }
+        if grammar.calls.contains(&kind) {
+            let callee = node
+                .child_by_field_name("function")
+                .or_else(|| node.child_by_field_name("constructor"));
+            if let Some(name) = callee.and_then(|c| callee_name(c, source)) {
+                symbols.calls.push(Reference {
+                    name,
+                    line: node.start_position().row + 1,
+                });
+            }
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    symbols.definitions.sort_by_key(|s| s.start_line);
+    symbols.references.sort_by_key(|r| r.line);
+    symbols.calls.sort_by_key(|r| r.line);
+    Some(symbols)
+}
+
+/// Functions and methods of `source` with their token streams
+///
+/// Returns `None` for unsupported languages and unparsable input.
+pub(crate) fn function_tokens(path: &Path, source: &str) -> Option<Vec<FunctionTokens>> {
+    let grammar = grammar_for(path)?;
+    let mut parser = Parser::new();
+    parser.set_language(&grammar.language).ok()?;
+    let tree = parser.parse(source, None)?;
+
+    let mut functions = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if let Some(symbol) = definition(&grammar, node, source).filter(Symbol::is_function) {
+            let mut tokens = Vec::new();
+            collect_tokens(node, source, &mut tokens);
+            functions.push(FunctionTokens { symbol, tokens });
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+    }
+    functions.sort_by_key(|f| f.symbol.start_line);
+    Some(functions)
+}
+
+/// Append the tokens of `node` in source order
+fn collect_tokens(node: Node, source: &str, tokens: &mut Vec<Token>) {
+    let kind = node.kind();
+    if kind.contains("comment") {
+        return;
+    }
+    let literal = kind.ends_with("_literal") || LITERAL_KINDS.contains(&kind);
+    if literal || node.child_count() == 0 {
+        let text = &source[node.byte_range()];
+        if text.is_empty() {
+            return;
+        }
+        let normalized = if literal {
+            "$lit"
+        } else if kind.ends_with("identifier") {
+            "$id"
+        } else {
+            text
+        };
+        tokens.push(Token {
+            text: text.to_string(),
+            normalized: normalized.to_string(),
+            line: node.start_position().row + 1,
+        });
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_tokens(child, source, tokens);
+    }
+}
+
+/// The definition a node introduces, if any
+fn definition(grammar: &SymbolGrammar, node: Node, source: &str) -> Option<Symbol> {
+    let (_, label) = grammar.definitions.iter().find(|(kind, _)| *kind == node.kind())?;
+    // `const f = () => {}` defines a function; other variables are not symbols
+    if node.kind() == "variable_declarator" {
+        let value = node.child_by_field_name("value")?;
+        if !matches!(value.kind(), "arrow_function" | "function_expression" | "function") {
+            return None;
+        }
+    }
+    let name = node.child_by_field_name("name")?;
+
+    // Include `const`, decorators and `export` so the snippet reads like the source
+    let mut outer = node;
+    if node.kind() == "variable_declarator" {
+        outer = node.parent().unwrap_or(node);
+    }
+    if let Some(p) = outer.parent().filter(|p| matches!(p.kind(), "decorated_definition" | "export_statement")) {
+        outer = p;
+    }
+    let text = if grammar.signature_only.contains(label) {
+        signature(node, source, outer)
+    } else {
+        truncate_lines(&source[outer.byte_range()])
+    };
+
+    Some(Symbol {
+        name: source[name.byte_range()].to_string(),
+        kind: label,
+        start_line: outer.start_position().row + 1,
+        end_line: outer.end_position().row + 1,
+        text,
+        visibility: (grammar.visibility)(node, source),
+    })
+}
+
+/// Text of a definition up to its body
+///
+/// Drops the token that opens the body: `{`, Python's `:` or an arrow
+/// function's `=>`. A `>` closing a generic type stays.
+fn signature(node: Node, source: &str, outer: Node) -> String {
+    let body = node
+        .child_by_field_name("body")
+        .or_else(|| node.child_by_field_name("value").and_then(|v| v.child_by_field_name("body")));
+    let end = body.map_or(outer.end_byte(), |b| b.start_byte());
+    let text = source[outer.start_byte()..end].trim_end();
+    text.strip_suffix("=>")
+        .or_else(|| text.strip_suffix(['{', ':']))
+        .unwrap_or(text)
+        .trim_end()
+        .to_string()
+}
+
+/// Keep at most [`MAX_DEFINITION_LINES`] lines
+fn truncate_lines(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.len() <= MAX_DEFINITION_LINES {
+        return text.to_string();
+    }
+    format!("{}\n    // ...", lines[..MAX_DEFINITION_LINES].join("\n"))
+}
+
+/// Last name segment of a callee expression (`a::b::f`, `obj.f`, `f::<T>`)
+fn callee_name(node: Node, source: &str) -> Option<String> {
+    match node.kind() {
+        "identifier" | "property_identifier" | "type_identifier" | "field_identifier" => {
+            Some(source[node.byte_range()].to_string())
+        }
+        "scoped_identifier" => node.child_by_field_name("name").and_then(|n| callee_name(n, source)),
+        "field_expression" => node.child_by_field_name("field").and_then(|n| callee_name(n, source)),
+        "member_expression" => node.child_by_field_name("property").and_then(|n| callee_name(n, source)),
+        "attribute" => node.child_by_field_name("attribute").and_then(|n| callee_name(n, source)),
+        "generic_function" => node.child_by_field_name("function").and_then(|n| callee_name(n, source)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(any(feature = "lang-rust", feature = "lang-typescript", feature = "lang-python"))]
+    use super::*;
+    #[cfg(any(feature = "lang-rust", feature = "lang-typescript", feature = "lang-python"))]
+    use Visibility::Public;
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_rust_symbols() {
+        let source = r#"
+pub struct Config {
+    pub name: String,
+}
+
+impl Config {
+    pub fn load(path: &str) -> Config {
+        parse(path)
+    }
+}
+
+fn parse(path: &str) -> Config {
+    Config { name: path.to_string() }
+}
+
+pub(crate) fn names(configs: &[Config]) -> Vec<Option<String>> {
+    Vec::new()
+}
+"#;
+        let symbols = parse_symbols(Path::new("lib.rs"), source).unwrap();
+        let names: Vec<(&str, &str, Option<Visibility>)> = symbols
+            .definitions
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind, s.visibility))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("Config", "struct", Some(Public)),
+                ("load", "fn", Some(Public)),
+                ("parse", "fn", None),
+                ("names", "fn", Some(Visibility::Crate)),
+            ]
+        );
+        assert_eq!(symbols.definitions[1].text, "pub fn load(path: &str) -> Config");
+        assert_eq!(
+            symbols.definitions[3].text,
+            "pub(crate) fn names(configs: &[Config]) -> Vec<Option<String>>"
+        );
+        assert!(symbols.definitions[0].text.contains("pub name: String"));
+        assert_eq!((symbols.definitions[0].start_line, symbols.definitions[0].end_line), (2, 4));
+
+        let calls: Vec<(&str, usize)> = symbols.calls.iter().map(|c| (c.name.as_str(), c.line)).collect();
+        assert_eq!(calls, vec![("parse", 8), ("to_string", 13), ("new", 17)]);
+        assert!(symbols.references.iter().any(|r| r.name == "Config" && r.line == 13));
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_function_tokens() {
+        let source = "// helper\nfn add(a: u32) -> u32 {\n    a + 1 // one\n}\n";
+        let functions = function_tokens(Path::new("lib.rs"), source).unwrap();
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].symbol.name, "add");
+        let normalized: Vec<&str> = functions[0].tokens.iter().map(|t| t.normalized.as_str()).collect();
+        assert_eq!(
+            normalized,
+            vec!["fn", "$id", "(", "$id", ":", "u32", ")", "->", "u32", "{", "$id", "+", "$lit", "}"]
+        );
+        assert_eq!(functions[0].tokens[10].text, "a");
+        assert_eq!(functions[0].tokens[10].line, 3);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-typescript")]
+    fn test_typescript_symbols() {
+        let source = r#"export interface User { id: number }
+export const load = async (id: number): Promise<User> => {
+  return api.fetch(id);
+};
+class Store {
+  private cache(): void {}
+  save(user: User) { this.cache(); }
+}
+"#;
+        let symbols = parse_symbols(Path::new("user.ts"), source).unwrap();
+        let names: Vec<(&str, &str, Option<Visibility>)> = symbols
+            .definitions
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind, s.visibility))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("User", "interface", Some(Public)),
+                ("load", "function", Some(Public)),
+                ("Store", "class", None),
+                ("cache", "method", None),
+                ("save", "method", Some(Public)),
+            ]
+        );
+        assert_eq!(
+            symbols.definitions[1].text,
+            "export const load = async (id: number): Promise<User>"
+        );
+        let calls: Vec<&str> = symbols.calls.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(calls, vec!["fetch", "cache"]);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-python")]
+    fn test_python_symbols() {
+        let source = "class Repo:\n    def get(self, key):\n        return self._load(key)\n\n@cache\ndef _helper():\n    Repo().get(1)\n";
+        let symbols = parse_symbols(Path::new("repo.py"), source).unwrap();
+        let names: Vec<(&str, &str, Option<Visibility>)> = symbols
+            .definitions
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind, s.visibility))
+            .collect();
+        assert_eq!(names, vec![("Repo", "class", Some(Public)), ("get", "def", Some(Public)), ("_helper", "def", None)]);
+        assert_eq!(symbols.definitions[2].text, "@cache\ndef _helper()");
+        let calls: Vec<&str> = symbols.calls.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(calls, vec!["_load", "get", "Repo"]);
+    }
+}
@@ -6,26 +6,62 @@
 //! - `import A from 'module'` (default import)
 //! - `import * as A from 'module'` (namespace import)
 //! - `import 'module'` (side-effect import)
+//! - `import type { T } from 'module'` (type-only import)
+//! - `import fs = require('fs')` (TypeScript require import)
+//! - `export * from 'module'`, `export { a } from 'module'` (re-exports,
+//!   recorded as imports and in `reexports`)
+//! - `require('module')` and `import('module')` anywhere in the file
+//!
+//! Exports are declarations (`export function/class/const/type/...`),
+//! `export { a, b as c }` clauses, re-exported names and `export default`
+//! (the declared name when there is one, otherwise `default`).
 
-use tree_sitter::Parser;
+use std::collections::HashMap;
 
-use crate::error::{CodeReviewError, Result};
-use super::{FileAnalysis, ImportInfo};
+use tree_sitter::{Language, Parser};
 
-/// Analyze TypeScript/JavaScript source code to extract imports and exports
+use crate::error::{CodeReviewError, Result};
+use super::{FileAnalysis, ImportInfo, ImportKind};
+
+/// Declarations whose `name` field is the exported name
+const NAMED_DECLARATIONS: &[&str] = &[
+    "function_declaration",
+    "generator_function_declaration",
+    "function_signature",
+    "class_declaration",
+    "abstract_class_declaration",
+    "interface_declaration",
+    "type_alias_declaration",
+    "enum_declaration",
+];
+
+/// Analyze TypeScript source code to extract imports and exports
 pub(crate) fn analyze_typescript(source: &str) -> Result<FileAnalysis> {
+    analyze_with_grammar(source, tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(), "TypeScript")
+}
+
+/// Analyze TSX/JSX source code to extract imports and exports
+///
+/// The TSX grammar is a superset of JavaScript with JSX, so it is also used
+/// for `.js` files, which commonly contain JSX in React code.
+pub(crate) fn analyze_tsx(source: &str) -> Result<FileAnalysis> {
+    analyze_with_grammar(source, tree_sitter_typescript::LANGUAGE_TSX.into(), "TSX")
+}
+
+/// Parse with `language`; `name` identifies the grammar in error messages
+fn analyze_with_grammar(source: &str, language: Language, name: &str) -> Result<FileAnalysis> {
     let mut parser = Parser::new();
-    let language = tree_sitter_typescript::LANGUAGE_TYPESCRIPT;
-    parser.set_language(&language.into()).map_err(|e| {
-        CodeReviewError::ParseError(format!("Failed to set TypeScript language: {}", e))
+    parser.set_language(&language).map_err(|e| {
+        CodeReviewError::ParseError(format!("Failed to set {} language: {}", name, e))
     })?;
 
     let tree = parser.parse(source, None).ok_or_else(|| {
-        CodeReviewError::ParseError("Failed to parse TypeScript source".to_string())
+        CodeReviewError::ParseError(format!("Failed to parse {} source", name))
     })?;
 
     let mut imports = Vec::new();
     let mut exports = Vec::new();
+    let mut reexports = Vec::new();
 
     let root = tree.root_node();
     let mut cursor = root.walk();
@@ -38,17 +74,28 @@ pub(crate) fn analyze_typescript(source: &str) -> Result<FileAnalysis> {
                 }
             }
             "export_statement" => {
-                if let Some(name) = extract_ts_export(node, source) {
-                    exports.push(name);
+                // `export ... from 'module'` is a dependency as well as an export
+                if let Some(reexport) = extract_ts_reexport(node, source, &mut exports) {
+                    imports.push(reexport.clone());
+                    reexports.push(reexport);
+                } else {
+                    extract_ts_exports(node, source, &mut exports);
                 }
             }
             _ => {}
         }
     }
 
+    // `require()` and `import()` can appear in any expression
+    collect_call_imports(root, source, &mut imports);
+
     Ok(FileAnalysis {
         imports,
         exports,
+        namespaces: Vec::new(),
+        modules: Vec::new(),
+        reexports,
+        visibility: HashMap::new(),
         language: "typescript".to_string(),
     })
 }
@@ -71,9 +118,13 @@ pub(crate) fn analyze_typescript(source: &str) -> Result<FileAnalysis> {
 ///       - identifier
 ///   - "from" (optional)
 ///   - string (module path)
+///
+/// `import type ...` and imports whose specifiers are all `type` are
+/// type-only. `import fs = require('fs')` is read from its require clause.
 fn extract_ts_import(node: tree_sitter::Node, source: &str) -> Option<ImportInfo> {
     let mut module_path = String::new();
     let mut items = Vec::new();
+    let mut type_only = false;
 
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
@@ -85,7 +136,24 @@ fn extract_ts_import(node: tree_sitter::Node, source: &str) -> Option<ImportInfo
             // Import clause containing the imported items
             "import_clause" => {
                 extract_import_clause_items(child, source, &mut items);
+                type_only |= all_specifiers_type_only(child);
             }
+            // import fs = require('fs')
+            "import_require_clause" => {
+                let mut clause_cursor = child.walk();
+                for part in child.children(&mut clause_cursor) {
+                    match part.kind() {
+                        "identifier" => {
+                            if let Ok(text) = part.utf8_text(source.as_bytes()) {
+                                items.push(text.to_string());
+                            }
+                        }
+                        "string" => module_path = extract_string_content(part, source),
+                        _ => {}
+                    }
+                }
+            }
+            "type" => type_only = true,
             _ => {}
         }
     }
@@ -95,7 +163,35 @@ fn extract_ts_import(node: tree_sitter::Node, source: &str) -> Option<ImportInfo
         return None;
     }
 
-    Some(ImportInfo { module_path, items })
+    let kind = if type_only { ImportKind::TypeOnly } else { ImportKind::Static };
+    Some(ImportInfo { module_path, items, kind })
+}
+
+/// Check for `import { type A, type B }`, where every specifier is type-only
+fn all_specifiers_type_only(clause: tree_sitter::Node) -> bool {
+    let mut cursor = clause.walk();
+    let mut specifiers = Vec::new();
+    for child in clause.children(&mut cursor) {
+        match child.kind() {
+            "named_imports" => {
+                let mut named_cursor = child.walk();
+                specifiers.extend(
+                    child
+                        .children(&mut named_cursor)
+                        .filter(|n| n.kind() == "import_specifier"),
+                );
+            }
+            // A default or namespace import binds a value
+            "identifier" | "namespace_import" => return false,
+            _ => {}
+        }
+    }
+    !specifiers.is_empty() && specifiers.iter().all(|spec| has_type_keyword(*spec))
+}
+
+/// Check for an anonymous `type` keyword child
+fn has_type_keyword(node: tree_sitter::Node) -> bool {
+    node.children(&mut node.walk()).any(|child| child.kind() == "type")
 }
 
 /// Extract items from import_clause node
@@ -195,22 +291,230 @@ fn extract_string_content(node: tree_sitter::Node, source: &str) -> String {
     }
 }
 
-fn extract_ts_export(node: tree_sitter::Node, source: &str) -> Option<String> {
+/// Extract a re-export (`export * from`, `export { x } from`) as an import
+///
+/// Re-exported names are added to `exports`: `export * from` adds none,
+/// `export * as ns from` adds `ns`, and specifiers add their alias or name.
+/// Returns `None` for exports without a `from` clause.
+fn extract_ts_reexport(
+    node: tree_sitter::Node,
+    source: &str,
+    exports: &mut Vec<String>,
+) -> Option<ImportInfo> {
+    let module_path = extract_string_content(node.child_by_field_name("source")?, source);
+    if module_path.is_empty() {
+        return None;
+    }
+
+    let mut items = Vec::new();
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "*" => items.push("*".to_string()),
+            // export * as ns from 'module'
+            "namespace_export" => {
+                if let Some(name) = child
+                    .named_child(0)
+                    .map(|n| extract_string_content(n, source))
+                {
+                    items.push(format!("* as {}", name));
+                    exports.push(name);
+                }
+            }
+            // export { a, b as c } from 'module'
+            "export_clause" => {
+                for (name, exported) in export_clause_names(child, source) {
+                    items.push(name);
+                    exports.push(exported);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let kind = if has_type_keyword(node) { ImportKind::TypeOnly } else { ImportKind::Static };
+    Some(ImportInfo { module_path, items, kind })
+}
+
+/// Extract exported names from an export_statement without a `from` clause
+///
+/// tree-sitter-typescript export_statement structure:
+/// - export_statement
+///   - "export"
+///   - "default" (optional)
+///   - declaration: function/class/interface/type/enum/lexical declaration
+///   - value: expression (`export default <expr>`)
+///   - export_clause (`export { a, b as c }`)
+///
+/// `export default` records the declared or referenced name when there is
+/// one, and `default` otherwise.
+fn extract_ts_exports(node: tree_sitter::Node, source: &str, exports: &mut Vec<String>) {
+    let is_default = node
+        .children(&mut node.walk())
+        .any(|child| child.kind() == "default");
+
+    if let Some(declaration) = node.child_by_field_name("declaration") {
+        let before = exports.len();
+        collect_declaration_names(declaration, source, exports);
+        if is_default && exports.len() == before {
+            exports.push("default".to_string());
+        }
+        return;
+    }
+
+    if let Some(value) = node.child_by_field_name("value") {
+        let name = match value.kind() {
+            "identifier" => value.utf8_text(source.as_bytes()).ok().map(String::from),
+            _ => value
+                .child_by_field_name("name")
+                .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+                .map(String::from),
+        };
+        exports.push(name.unwrap_or_else(|| "default".to_string()));
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.kind() == "export_clause" {
+            exports.extend(export_clause_names(child, source).into_iter().map(|(_, e)| e));
+        }
+    }
+}
+
+/// Names declared by an exported declaration
+fn collect_declaration_names(declaration: tree_sitter::Node, source: &str, exports: &mut Vec<String>) {
+    match declaration.kind() {
+        kind if NAMED_DECLARATIONS.contains(&kind) => {
+            if let Some(name) = declaration
+                .child_by_field_name("name")
+                .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+            {
+                exports.push(name.to_string());
+            }
+        }
+        // export const a = 1, b = 2;
+        "lexical_declaration" | "variable_declaration" => {
+            let mut cursor = declaration.walk();
+            for declarator in declaration.children(&mut cursor) {
+                if let Some(name) = declarator
+                    .child_by_field_name("name")
+                    .filter(|n| declarator.kind() == "variable_declarator" && n.kind() == "identifier")
+                    .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+                {
+                    exports.push(name.to_string());
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `(local name, exported name)` pairs from an export_clause
+fn export_clause_names(node: tree_sitter::Node, source: &str) -> Vec<(String, String)> {
+    let mut names = Vec::new();
+    let mut cursor = node.walk();
+    for specifier in node.children(&mut cursor) {
+        if specifier.kind() != "export_specifier" {
+            continue;
+        }
+        let Some(name) = specifier
+            .child_by_field_name("name")
+            .map(|n| extract_string_content(n, source))
+        else {
+            continue;
+        };
+        let exported = specifier
+            .child_by_field_name("alias")
+            .map(|n| extract_string_content(n, source))
+            .unwrap_or_else(|| name.clone());
+        names.push((name, exported));
+    }
+    names
+}
+
+/// Collect `require('x')` and `import('x')` calls anywhere below `node`
+///
+/// Only calls with a literal module path are recorded. `require()` is a
+/// static dependency; its bound names (`const x = require()` or
+/// `const { a, b } = require()`) become the items. `import()` is dynamic.
+fn collect_call_imports(node: tree_sitter::Node, source: &str, imports: &mut Vec<ImportInfo>) {
+    if node.kind() == "call_expression" {
+        if let Some(import) = extract_call_import(node, source) {
+            imports.push(import);
+        }
+    }
+
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
-        if child.kind() == "function_declaration"
-            || child.kind() == "class_declaration"
-            || child.kind() == "interface_declaration"
-        {
-            let mut inner_cursor = child.walk();
-            for inner_child in child.children(&mut inner_cursor) {
-                if inner_child.kind() == "identifier" || inner_child.kind() == "type_identifier" {
-                    return inner_child.utf8_text(source.as_bytes()).ok().map(String::from);
+        collect_call_imports(child, source, imports);
+    }
+}
+
+fn extract_call_import(node: tree_sitter::Node, source: &str) -> Option<ImportInfo> {
+    let function = node.child_by_field_name("function")?;
+    let kind = match function.kind() {
+        "import" => ImportKind::Dynamic,
+        "identifier" if function.utf8_text(source.as_bytes()) == Ok("require") => ImportKind::Static,
+        _ => return None,
+    };
+
+    let argument = node.child_by_field_name("arguments")?.named_child(0)?;
+    let module_path = match argument.kind() {
+        "string" => extract_string_content(argument, source),
+        // `import(`./pages/${name}`)` cannot be resolved statically
+        "template_string" if argument.named_child_count() == 0 => argument
+            .utf8_text(source.as_bytes())
+            .ok()?
+            .trim_matches('`')
+            .to_string(),
+        _ => return None,
+    };
+    if module_path.is_empty() {
+        return None;
+    }
+
+    let items = if kind == ImportKind::Static {
+        bound_names(node, source)
+    } else {
+        Vec::new()
+    };
+
+    Some(ImportInfo { module_path, items, kind })
+}
+
+/// Names bound by `const x = require()` or `const { a, b: c } = require()`
+fn bound_names(call: tree_sitter::Node, source: &str) -> Vec<String> {
+    let Some(pattern) = call
+        .parent()
+        .filter(|p| p.kind() == "variable_declarator")
+        .and_then(|p| p.child_by_field_name("name"))
+    else {
+        return Vec::new();
+    };
+
+    match pattern.kind() {
+        "identifier" => pattern
+            .utf8_text(source.as_bytes())
+            .map(|text| vec![text.to_string()])
+            .unwrap_or_default(),
+        "object_pattern" => {
+            let mut names = Vec::new();
+            let mut cursor = pattern.walk();
+            for property in pattern.children(&mut cursor) {
+                let name = match property.kind() {
+                    "shorthand_property_identifier_pattern" => Some(property),
+                    "pair_pattern" => property.child_by_field_name("value"),
+                    _ => None,
+                };
+                if let Some(text) = name.and_then(|n| n.utf8_text(source.as_bytes()).ok()) {
+                    names.push(text.to_string());
                 }
             }
+            names
         }
+        _ => Vec::new(),
     }
-    None
 }
 
 #[cfg(test)]
@@ -385,4 +689,103 @@ import 'styles.css';
         assert_eq!(result.imports[0].module_path, "./components/Component");
         assert_eq!(result.imports[0].items, vec!["Component"]);
     }
+
+    #[test]
+    fn test_type_only_imports() {
+        let source = r#"
+import type { User } from './models';
+import { type Role, type Permission } from './auth';
+import { type Config, load } from './config';
+"#;
+        let result = analyze_typescript(source).unwrap();
+        assert_eq!(result.imports.len(), 3);
+        assert_eq!(result.imports[0].kind, ImportKind::TypeOnly);
+        assert_eq!(result.imports[0].items, vec!["User"]);
+        assert_eq!(result.imports[1].kind, ImportKind::TypeOnly);
+        assert_eq!(result.imports[2].kind, ImportKind::Static);
+    }
+
+    #[test]
+    fn test_reexports() {
+        let source = r#"
+export * from './button';
+export * as icons from './icons';
+export { Modal, Dialog as Popup } from './modal';
+export type { Theme } from './theme';
+"#;
+        let result = analyze_typescript(source).unwrap();
+        let paths: Vec<&str> = result.imports.iter().map(|i| i.module_path.as_str()).collect();
+        assert_eq!(paths, vec!["./button", "./icons", "./modal", "./theme"]);
+        assert_eq!(result.reexports.len(), 4);
+        assert_eq!(result.imports[0].items, vec!["*"]);
+        assert_eq!(result.imports[1].items, vec!["* as icons"]);
+        assert_eq!(result.imports[2].items, vec!["Modal", "Dialog"]);
+        assert_eq!(result.imports[3].kind, ImportKind::TypeOnly);
+        assert_eq!(result.exports, vec!["icons", "Modal", "Popup", "Theme"]);
+    }
+
+    #[test]
+    fn test_require_and_dynamic_import() {
+        let source = r#"
+const fs = require('fs');
+const { join, resolve: resolvePath } = require("path");
+import os = require('os');
+
+async function loadPage() {
+    const page = await import('./pages/home');
+    const other = await import(`./pages/${name}`);
+    require('./polyfill');
+}
+"#;
+        let result = analyze_typescript(source).unwrap();
+        let find = |path: &str| result.imports.iter().find(|i| i.module_path == path).unwrap();
+        assert_eq!(find("fs").items, vec!["fs"]);
+        assert_eq!(find("fs").kind, ImportKind::Static);
+        assert_eq!(find("path").items, vec!["join", "resolvePath"]);
+        assert_eq!(find("os").items, vec!["os"]);
+        assert_eq!(find("./pages/home").kind, ImportKind::Dynamic);
+        assert!(find("./polyfill").items.is_empty());
+        assert_eq!(result.imports.len(), 5);
+    }
+
+    #[test]
+    fn test_export_declarations_and_default() {
+        let source = r#"
+export const API_URL = '/api', TIMEOUT = 30;
+export type Props = { id: string };
+export enum Color { Red }
+const helper = 1;
+export { helper, helper as alias };
+export default function App() {}
+"#;
+        let result = analyze_typescript(source).unwrap();
+        for name in ["API_URL", "TIMEOUT", "Props", "Color", "helper", "alias", "App"] {
+            assert!(result.exports.contains(&name.to_string()), "missing {}", name);
+        }
+
+        let anonymous = analyze_typescript("export default { debug: true };").unwrap();
+        assert_eq!(anonymous.exports, vec!["default"]);
+
+        let named = analyze_typescript("const Store = {};\nexport default Store;").unwrap();
+        assert_eq!(named.exports, vec!["Store"]);
+    }
+
+    #[test]
+    fn test_tsx_grammar_parses_jsx() {
+        let source = r#"
+import React from 'react';
+import { Button } from './Button';
+
+export const App = () => <div className="app"><Button label={<b>Go</b>} /></div>;
+export default App;
+"#;
+        let result = analyze_tsx(source).unwrap();
+        assert_eq!(result.imports.len(), 2);
+        assert_eq!(result.imports[1].module_path, "./Button");
+        assert!(result.exports.contains(&"App".to_string()));
+
+        // The plain TypeScript grammar mis-parses generic-looking JSX
+        let generic = "const f = <T,>(x: T) => x;\nexport const el = <Foo bar={1} />;";
+        assert!(analyze_tsx(generic).unwrap().exports.contains(&"el".to_string()));
+    }
 }
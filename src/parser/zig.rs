@@ -0,0 +1,197 @@
+//! Zig source code parsing with tree-sitter
+//!
+//! Supports:
+//! - `const std = @import("std");` (package import)
+//! - `const emit = @import("codegen/emit.zig");` (file import)
+//! - `@import(...)` calls anywhere in the file, e.g. `@import("x.zig").Foo`
+//!
+//! Exports are top-level `pub` functions, constants and variables.
+
+use std::collections::HashMap;
+
+use tree_sitter::Parser;
+
+use crate::error::{CodeReviewError, Result};
+use super::{FileAnalysis, ImportInfo, ImportKind};
+
+/// Analyze Zig source code to extract imports and exports
+pub(crate) fn analyze_zig(source: &str) -> Result<FileAnalysis> {
+    let mut parser = Parser::new();
+    let language = tree_sitter_zig::LANGUAGE;
+    parser
+        .set_language(&language.into())
+        .map_err(|e| CodeReviewError::ParseError(format!("Failed to set Zig language: {}", e)))?;
+
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| CodeReviewError::ParseError("Failed to parse Zig source".to_string()))?;
+
+    let root = tree.root_node();
+
+    let mut imports = Vec::new();
+    collect_imports(root, source, &mut imports);
+
+    let mut exports = Vec::new();
+    let mut cursor = root.walk();
+    for node in root.children(&mut cursor) {
+        if let Some(name) = pub_declaration_name(node, source) {
+            exports.push(name.to_string());
+        }
+    }
+
+    Ok(FileAnalysis {
+        imports,
+        exports,
+        namespaces: Vec::new(),
+        modules: Vec::new(),
+        reexports: Vec::new(),
+        visibility: HashMap::new(),
+        language: "zig".to_string(),
+    })
+}
+
+/// Collect `@import("...")` calls anywhere below `node`
+///
+/// A builtin call starts with its `@import` identifier, so only the call
+/// node itself matches, not the declarations or expressions around it.
+fn collect_imports(node: tree_sitter::Node, source: &str, imports: &mut Vec<ImportInfo>) {
+    let is_import_call = node
+        .child(0)
+        .filter(|first| first.kind() == "builtin_identifier")
+        .and_then(|first| first.utf8_text(source.as_bytes()).ok())
+        == Some("@import");
+
+    if is_import_call {
+        if let Some(module_path) = import_path(node, source) {
+            imports.push(ImportInfo {
+                module_path: module_path.to_string(),
+                items: Vec::new(),
+                kind: ImportKind::Static,
+            });
+        }
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_imports(child, source, imports);
+    }
+}
+
+/// Path argument of an `@import("path")` call
+///
+/// tree-sitter-zig builtin call structure:
+/// - builtin_function
+///   - builtin_identifier (`@import`)
+///   - arguments
+///     - string (`"path"`)
+fn import_path<'a>(call: tree_sitter::Node, source: &'a str) -> Option<&'a str> {
+    let arguments = call.named_child(1)?;
+    let mut cursor = arguments.walk();
+    let string = arguments
+        .named_children(&mut cursor)
+        .find(|arg| arg.kind() == "string")?;
+    let path = string
+        .utf8_text(source.as_bytes())
+        .ok()?
+        .strip_prefix('"')?
+        .strip_suffix('"')?;
+    (!path.is_empty()).then_some(path)
+}
+
+/// Name declared by a top-level `pub` declaration
+///
+/// Function and variable declarations carry `pub` as their own token, before
+/// qualifiers such as `inline` or `extern "c"`, and name the declaration in
+/// their first identifier.
+fn pub_declaration_name<'a>(node: tree_sitter::Node, source: &'a str) -> Option<&'a str> {
+    if !matches!(node.kind(), "function_declaration" | "variable_declaration") {
+        return None;
+    }
+    let mut cursor = node.walk();
+    if !node.children(&mut cursor).any(|child| child.kind() == "pub") {
+        return None;
+    }
+    let name = node.child_by_field_name("name").or_else(|| {
+        let mut cursor = node.walk();
+        let first = node
+            .named_children(&mut cursor)
+            .find(|child| child.kind() == "identifier");
+        first
+    })?;
+    name.utf8_text(source.as_bytes()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_zig() {
+        let source = r#"
+const std = @import("std");
+const emit = @import("codegen/emit.zig");
+
+pub fn generate(allocator: std.mem.Allocator) !void {}
+fn helper() void {}
+pub const Options = struct {};
+"#;
+        let result = analyze_zig(source).unwrap();
+        assert_eq!(result.language, "zig");
+        let paths: Vec<&str> = result.imports.iter().map(|i| i.module_path.as_str()).collect();
+        assert_eq!(paths, vec!["std", "codegen/emit.zig"]);
+        assert!(result.exports.contains(&"generate".to_string()));
+        assert!(result.exports.contains(&"Options".to_string()));
+        assert!(!result.exports.contains(&"helper".to_string()));
+    }
+
+    #[test]
+    fn test_nested_import_calls() {
+        let source = r#"
+const Token = @import("lexer.zig").Token;
+
+test "emit" {
+    _ = @import("emit_test.zig");
+}
+"#;
+        let result = analyze_zig(source).unwrap();
+        let paths: Vec<&str> = result.imports.iter().map(|i| i.module_path.as_str()).collect();
+        assert_eq!(paths, vec!["lexer.zig", "emit_test.zig"]);
+    }
+
+    #[test]
+    fn test_pub_declaration_forms() {
+        let source = r#"
+pub fn init() void {}
+pub const Config = struct {};
+pub var count: u32 = 0;
+pub inline fn fast() void {}
+pub extern "c" fn write(fd: i32) isize;
+const private = 1;
+"#;
+        let result = analyze_zig(source).unwrap();
+        assert_eq!(result.exports, vec!["init", "Config", "count", "fast", "write"]);
+        assert!(result.imports.is_empty());
+    }
+
+    #[test]
+    fn test_empty_and_other_builtins_are_not_imports() {
+        let source = r#"
+const empty = @import("");
+const data = @embedFile("data.bin");
+"#;
+        let result = analyze_zig(source).unwrap();
+        assert!(result.imports.is_empty());
+    }
+
+    #[test]
+    fn test_grammar_abi_is_supported() {
+        let version = tree_sitter::Language::from(tree_sitter_zig::LANGUAGE).version();
+        assert!(
+            (tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION..=tree_sitter::LANGUAGE_VERSION)
+                .contains(&version),
+            "grammar ABI {} is not supported by tree-sitter",
+            version
+        );
+    }
+}
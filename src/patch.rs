@@ -0,0 +1,620 @@
+//! Unified diff patches
+//!
+//! Parses unified diffs (as written by git or by an AI), checks that every
+//! hunk applies to the files on disk and applies them. Hunk headers are
+//! only hints: a hunk is located by its context and removed lines, so wrong
+//! line numbers or counts do not break an otherwise correct patch.
+//! [`Patch::validate`] returns the patch with corrected headers, which
+//! `git apply` accepts as well.
+
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::{CodeReviewError, Result};
+
+/// One line of a hunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkLine {
+    /// Unchanged line (` `)
+    Context(String),
+    /// Removed line (`-`)
+    Remove(String),
+    /// Added line (`+`)
+    Add(String),
+}
+
+/// A hunk: a run of changed lines with their context
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// First old line (1-based; the line before the hunk if it removes nothing)
+    pub old_start: usize,
+    /// First new line (1-based; the line before the hunk if it adds nothing)
+    pub new_start: usize,
+    pub lines: Vec<HunkLine>,
+}
+
+impl Hunk {
+    /// Lines the hunk expects in the file (context and removed lines)
+    pub fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Remove(text) => Some(text.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect()
+    }
+
+    /// Lines the hunk leaves in the file (context and added lines)
+    pub fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Add(text) => Some(text.as_str()),
+                HunkLine::Remove(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Changes to one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatch {
+    /// Path before the change, relative to the base directory (`None` for a new file)
+    pub old_path: Option<String>,
+    /// Path after the change, relative to the base directory (`None` for a deleted file)
+    pub new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FilePatch {
+    /// The path the patch applies to
+    pub fn path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+/// A unified diff over one or more files
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Patch {
+    pub files: Vec<FilePatch>,
+}
+
+/// A file patch checked against the file on disk
+struct ResolvedFile {
+    path: PathBuf,
+    /// Content after the patch (`None` if the file is deleted)
+    content: Option<String>,
+    /// Previous path of a renamed file, removed when the patch is applied
+    renamed_from: Option<PathBuf>,
+    /// The file patch with hunk positions and text taken from the file
+    patch: FilePatch,
+}
+
+impl Patch {
+    /// Parse a unified diff
+    ///
+    /// Text around the diff (explanations, Markdown fences, `diff --git`
+    /// and `index` lines) is ignored. Fails if there is no file header,
+    /// a header has no hunks, a hunk changes nothing, or a path is absolute
+    /// or leaves the base directory.
+    pub fn parse(text: &str) -> Result<Self> {
+        let lines: Vec<&str> = text.lines().collect();
+        let mut files = Vec::new();
+        let mut i = 0;
+        while i < lines.len() {
+            if !is_file_header(&lines, i) {
+                i += 1;
+                continue;
+            }
+            let old_path = parse_path(&lines[i][4..])?;
+            let new_path = parse_path(&lines[i + 1][4..])?;
+            if old_path.is_none() && new_path.is_none() {
+                return Err(patch_error("both sides of a file header are /dev/null"));
+            }
+            i += 2;
+
+            let mut hunks = Vec::new();
+            while i < lines.len() && lines[i].starts_with("@@") {
+                let (hunk, next) = parse_hunk(&lines, i)?;
+                hunks.push(hunk);
+                i = next;
+            }
+            let file = FilePatch {
+                old_path,
+                new_path,
+                hunks,
+            };
+            if file.hunks.is_empty() {
+                return Err(patch_error(format!("no hunks for {}", file.path())));
+            }
+            files.push(file);
+        }
+
+        if files.is_empty() {
+            return Err(patch_error("no unified diff found (missing --- / +++ headers)"));
+        }
+        Ok(Self { files })
+    }
+
+    /// Paths the patch touches, relative to the base directory
+    pub fn paths(&self) -> Vec<&str> {
+        self.files.iter().map(FilePatch::path).collect()
+    }
+
+    /// Check that every hunk applies to the files under `base`
+    ///
+    /// Returns the patch with hunk positions corrected and context taken
+    /// from the files, without writing anything. Sections for the same
+    /// path are merged into one; overlapping hunks are rejected.
+    pub fn validate(&self, base: &Path) -> Result<Patch> {
+        let files = self.resolve(base)?.into_iter().map(|r| r.patch).collect();
+        Ok(Patch { files })
+    }
+
+    /// Apply the patch to the files under `base`
+    ///
+    /// Every file is checked before any is written, so a patch that does
+    /// not apply leaves the tree untouched. Returns the changed files.
+    pub fn apply(&self, base: &Path) -> Result<Vec<PathBuf>> {
+        let resolved = self.resolve(base)?;
+        let mut changed = Vec::new();
+        for file in resolved {
+            match file.content {
+                Some(content) => {
+                    if let Some(parent) = file.path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::write(&file.path, content)?;
+                }
+                None => fs::remove_file(&file.path)?,
+            }
+            changed.push(file.path);
+            if let Some(old) = file.renamed_from {
+                fs::remove_file(&old)?;
+                changed.push(old);
+            }
+        }
+        Ok(changed)
+    }
+
+    fn resolve(&self, base: &Path) -> Result<Vec<ResolvedFile>> {
+        // Sections for one path, in order of first appearance
+        let mut sections: Vec<Vec<&FilePatch>> = Vec::new();
+        for file in &self.files {
+            match sections.iter_mut().find(|group| group[0].path() == file.path()) {
+                Some(group) => {
+                    let first = group[0];
+                    if first.old_path != file.old_path || first.new_path != file.new_path {
+                        return Err(patch_error(format!(
+                            "conflicting file headers for {}",
+                            file.path()
+                        )));
+                    }
+                    group.push(file);
+                }
+                None => sections.push(vec![file]),
+            }
+        }
+        // The source of a rename is removed, so it cannot be patched as well
+        for group in &sections {
+            let file = group[0];
+            if let (Some(old), Some(new)) = (&file.old_path, &file.new_path) {
+                if old != new && sections.iter().any(|other| other[0].path() == old) {
+                    return Err(patch_error(format!(
+                        "{} is renamed to {} and patched separately",
+                        old, new
+                    )));
+                }
+            }
+        }
+        sections.iter().map(|group| resolve_file(base, group)).collect()
+    }
+}
+
+impl fmt::Display for Patch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for file in &self.files {
+            let side = |path: &Option<String>, prefix: &str| match path {
+                Some(path) => format!("{}{}", prefix, path),
+                None => "/dev/null".to_string(),
+            };
+            writeln!(f, "--- {}", side(&file.old_path, "a/"))?;
+            writeln!(f, "+++ {}", side(&file.new_path, "b/"))?;
+            for hunk in &file.hunks {
+                writeln!(
+                    f,
+                    "@@ -{},{} +{},{} @@",
+                    hunk.old_start,
+                    hunk.old_lines().len(),
+                    hunk.new_start,
+                    hunk.new_lines().len()
+                )?;
+                for line in &hunk.lines {
+                    match line {
+                        HunkLine::Context(text) => writeln!(f, " {}", text)?,
+                        HunkLine::Remove(text) => writeln!(f, "-{}", text)?,
+                        HunkLine::Add(text) => writeln!(f, "+{}", text)?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn patch_error(message: impl Into<String>) -> CodeReviewError {
+    CodeReviewError::PatchError(message.into())
+}
+
+/// Whether a `---` / `+++` file header starts at line `i`
+fn is_file_header(lines: &[&str], i: usize) -> bool {
+    lines[i].starts_with("--- ") && lines.get(i + 1).is_some_and(|l| l.starts_with("+++ "))
+}
+
+/// Path of a file header (`None` for `/dev/null`), without `a/` / `b/`
+fn parse_path(raw: &str) -> Result<Option<String>> {
+    // git and diff(1) may append a tab and a timestamp
+    let path = raw.split('\t').next().unwrap_or_default().trim();
+    if path == "/dev/null" {
+        return Ok(None);
+    }
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    let safe = !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if !safe {
+        return Err(patch_error(format!("path outside the base directory: {}", raw.trim())));
+    }
+    Ok(Some(path.to_string()))
+}
+
+/// Parse the hunk starting at line `start`; returns it and the next line
+fn parse_hunk(lines: &[&str], start: usize) -> Result<(Hunk, usize)> {
+    let header = lines[start];
+    let ranges = header
+        .strip_prefix("@@")
+        .and_then(|rest| rest.split("@@").next())
+        .map(str::trim)
+        .unwrap_or_default();
+    let mut parts = ranges.split_whitespace();
+    let (old_start, old_count) = parts
+        .next()
+        .and_then(|p| p.strip_prefix('-'))
+        .and_then(parse_range)
+        .ok_or_else(|| patch_error(format!("malformed hunk header: {}", header)))?;
+    let (new_start, new_count) = parts
+        .next()
+        .and_then(|p| p.strip_prefix('+'))
+        .and_then(parse_range)
+        .ok_or_else(|| patch_error(format!("malformed hunk header: {}", header)))?;
+
+    let mut body = Vec::new();
+    let mut i = start + 1;
+    while i < lines.len() && !lines[i].starts_with("@@") && !is_file_header(lines, i) {
+        let line = lines[i];
+        match line.chars().next() {
+            None => body.push(HunkLine::Context(String::new())),
+            Some(' ') => body.push(HunkLine::Context(line[1..].to_string())),
+            Some('-') => body.push(HunkLine::Remove(line[1..].to_string())),
+            Some('+') => body.push(HunkLine::Add(line[1..].to_string())),
+            // "\ No newline at end of file"
+            Some('\\') => {}
+            Some(_) => break,
+        }
+        i += 1;
+    }
+
+    // Trust the header counts when the body has a prefix matching them;
+    // otherwise the body is authoritative (minus trailing blank lines)
+    match counted_prefix(&body, old_count, new_count) {
+        Some(len) => body.truncate(len),
+        None => {
+            while body.last() == Some(&HunkLine::Context(String::new())) {
+                body.pop();
+            }
+        }
+    }
+    if !body.iter().any(|l| !matches!(l, HunkLine::Context(_))) {
+        return Err(patch_error(format!("hunk without changes: {}", header)));
+    }
+
+    let hunk = Hunk {
+        old_start,
+        new_start,
+        lines: body,
+    };
+    Ok((hunk, i))
+}
+
+/// `start[,count]` of a hunk header (count defaults to 1)
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+/// Length of the shortest body prefix with exactly the given line counts
+fn counted_prefix(body: &[HunkLine], old_count: usize, new_count: usize) -> Option<usize> {
+    let (mut old, mut new) = (0, 0);
+    for (i, line) in body.iter().enumerate() {
+        if old == old_count && new == new_count {
+            return Some(i);
+        }
+        match line {
+            HunkLine::Context(_) => {
+                old += 1;
+                new += 1;
+            }
+            HunkLine::Remove(_) => old += 1,
+            HunkLine::Add(_) => new += 1,
+        }
+    }
+    (old == old_count && new == new_count).then_some(body.len())
+}
+
+/// Apply the sections for one file in memory
+///
+/// Hunks are located in the original file, in order within a section, then
+/// applied in file order; sections may come in any order but must not
+/// overlap.
+fn resolve_file(base: &Path, sections: &[&FilePatch]) -> Result<ResolvedFile> {
+    let file = sections[0];
+    let path = base.join(file.path());
+    let renamed_from = match (&file.old_path, &file.new_path) {
+        (Some(old), Some(new)) if old != new => Some(base.join(old)),
+        _ => None,
+    };
+    let original = match &file.old_path {
+        Some(_) if renamed_from.is_some() && path.exists() => {
+            return Err(patch_error(format!("{} already exists", file.path())));
+        }
+        Some(old) => Some(fs::read_to_string(base.join(old)).map_err(|e| {
+            patch_error(format!("cannot read {}: {}", old, e))
+        })?),
+        None if path.exists() => {
+            return Err(patch_error(format!("{} already exists", file.path())));
+        }
+        None => None,
+    };
+    let text = original.as_deref().unwrap_or_default();
+    let lines: Vec<&str> = text.lines().collect();
+    let crlf = text.contains("\r\n");
+
+    // (position in the original, hunk number within the file, hunk)
+    let mut located: Vec<(usize, usize, &Hunk)> = Vec::new();
+    for section in sections {
+        let mut cursor = 0;
+        for hunk in &section.hunks {
+            let n = located.len();
+            let old = hunk.old_lines();
+            let pos = if old.is_empty() {
+                hunk.old_start.clamp(cursor, lines.len())
+            } else {
+                find_block(&lines, &old, cursor, hunk.old_start.saturating_sub(1)).ok_or_else(|| {
+                    // The first removed line is usually the one that is wrong
+                    let expected = hunk
+                        .lines
+                        .iter()
+                        .find_map(|line| match line {
+                            HunkLine::Remove(text) => Some(text.as_str()),
+                            _ => None,
+                        })
+                        .unwrap_or(old[0]);
+                    patch_error(format!(
+                        "hunk {} of {} does not apply: expected `{}` near line {}",
+                        n + 1,
+                        file.path(),
+                        expected.trim(),
+                        hunk.old_start
+                    ))
+                })?
+            };
+            located.push((pos, n, hunk));
+            cursor = pos + old.len();
+        }
+    }
+    located.sort_by_key(|&(pos, n, _)| (pos, n));
+
+    let mut out: Vec<String> = Vec::new();
+    let mut hunks = Vec::new();
+    let mut cursor = 0;
+    for (pos, n, hunk) in located {
+        if pos < cursor {
+            return Err(patch_error(format!(
+                "hunk {} of {} overlaps another hunk near line {}",
+                n + 1,
+                file.path(),
+                pos + 1
+            )));
+        }
+        let old = hunk.old_lines();
+        out.extend(lines[cursor..pos].iter().map(|l| l.to_string()));
+        let new_start = if hunk.new_lines().is_empty() { out.len() } else { out.len() + 1 };
+
+        // Context and removed lines are taken from the file, so whitespace
+        // differences the search tolerated do not end up in the output
+        let mut file_lines = lines[pos..pos + old.len()].iter();
+        let mut corrected = Vec::new();
+        for line in &hunk.lines {
+            match line {
+                HunkLine::Context(_) => {
+                    let text = file_lines.next().copied().unwrap_or_default().to_string();
+                    out.push(text.clone());
+                    corrected.push(HunkLine::Context(text));
+                }
+                HunkLine::Remove(_) => {
+                    let text = file_lines.next().copied().unwrap_or_default().to_string();
+                    corrected.push(HunkLine::Remove(text));
+                }
+                HunkLine::Add(text) => {
+                    out.push(text.clone());
+                    corrected.push(HunkLine::Add(text.clone()));
+                }
+            }
+        }
+        hunks.push(Hunk {
+            old_start: if old.is_empty() { pos } else { pos + 1 },
+            new_start,
+            lines: corrected,
+        });
+        cursor = pos + old.len();
+    }
+    out.extend(lines[cursor..].iter().map(|l| l.to_string()));
+
+    let content = if file.new_path.is_none() {
+        if out.iter().any(|l| !l.trim().is_empty()) {
+            return Err(patch_error(format!(
+                "{} is deleted but the hunks do not remove all of it",
+                file.path()
+            )));
+        }
+        None
+    } else {
+        let newline = if crlf { "\r\n" } else { "\n" };
+        let mut content = out.join(newline);
+        let trailing = original.as_deref().is_none_or(|t| t.ends_with('\n'));
+        if trailing && !content.is_empty() {
+            content.push_str(newline);
+        }
+        Some(content)
+    };
+
+    Ok(ResolvedFile {
+        path,
+        content,
+        renamed_from,
+        patch: FilePatch {
+            old_path: file.old_path.clone(),
+            new_path: file.new_path.clone(),
+            hunks,
+        },
+    })
+}
+
+/// Index of `block` in `lines` at or after `from`, nearest to `hint`
+///
+/// Exact matches win; otherwise lines are compared without trailing
+/// whitespace.
+fn find_block(lines: &[&str], block: &[&str], from: usize, hint: usize) -> Option<usize> {
+    if block.len() > lines.len() {
+        return None;
+    }
+    let candidates = from..=lines.len() - block.len();
+    let nearest = |matches: &dyn Fn(&str, &str) -> bool| {
+        candidates
+            .clone()
+            .filter(|&start| block.iter().zip(&lines[start..]).all(|(b, l)| matches(b, l)))
+            .min_by_key(|&start| start.abs_diff(hint))
+    };
+    nearest(&|b, l| b == l).or_else(|| nearest(&|b, l| b.trim_end() == l.trim_end()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const SOURCE: &str = "fn main() {\n    let x = 1;\n    println!(\"{}\", x);\n}\n\nfn helper() -> u32 {\n    41\n}\n";
+
+    #[test]
+    fn test_parse_ignores_surrounding_text() {
+        let text = "Here is the fix:\n```diff\ndiff --git a/src/main.rs b/src/main.rs\nindex 123..456 100644\n--- a/src/main.rs\n+++ b/src/main.rs\n@@ -6,3 +6,3 @@ fn main\n fn helper() -> u32 {\n-    41\n+    42\n }\n```\nThis returns 42.\n";
+        let patch = Patch::parse(text).unwrap();
+        assert_eq!(patch.paths(), vec!["src/main.rs"]);
+        let hunk = &patch.files[0].hunks[0];
+        assert_eq!(hunk.old_lines(), vec!["fn helper() -> u32 {", "    41", "}"]);
+        assert_eq!(hunk.new_lines(), vec!["fn helper() -> u32 {", "    42", "}"]);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_patches() {
+        assert!(Patch::parse("no diff here").is_err());
+        assert!(Patch::parse("--- a/../etc/passwd\n+++ b/../etc/passwd\n@@ -1 +1 @@\n-a\n+b\n").is_err());
+        assert!(Patch::parse("--- a/x.rs\n+++ b/x.rs\n@@ -1 +1 @@\n a\n").is_err());
+    }
+
+    #[test]
+    fn test_validate_corrects_line_numbers() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("main.rs"), SOURCE).unwrap();
+
+        // Wrong line numbers and counts, trailing whitespace in the context
+        let patch = Patch::parse("--- a/main.rs\n+++ b/main.rs\n@@ -1,9 +1,9 @@\n fn helper() -> u32 {  \n-    41\n+    42\n }\n").unwrap();
+        let validated = patch.validate(dir.path()).unwrap();
+        assert_eq!(
+            validated.to_string(),
+            "--- a/main.rs\n+++ b/main.rs\n@@ -6,3 +6,3 @@\n fn helper() -> u32 {\n-    41\n+    42\n }\n"
+        );
+        // Validation does not write
+        assert_eq!(fs::read_to_string(dir.path().join("main.rs")).unwrap(), SOURCE);
+    }
+
+    #[test]
+    fn test_apply_is_all_or_nothing() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("main.rs"), SOURCE).unwrap();
+
+        let broken = "--- a/main.rs\n+++ b/main.rs\n@@ -2 +2 @@\n-    let x = 1;\n+    let x = 2;\n--- a/main.rs\n+++ b/main.rs\n@@ -7 +7 @@\n-    40\n+    42\n";
+        let err = Patch::parse(broken).unwrap().apply(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("does not apply"));
+        assert_eq!(fs::read_to_string(dir.path().join("main.rs")).unwrap(), SOURCE);
+
+        let good = "--- a/main.rs\n+++ b/main.rs\n@@ -2 +2 @@\n-    let x = 1;\n+    let x = 2;\n--- /dev/null\n+++ b/src/new.rs\n@@ -0,0 +1 @@\n+pub fn new() {}\n";
+        let changed = Patch::parse(good).unwrap().apply(dir.path()).unwrap();
+        assert_eq!(changed.len(), 2);
+        assert!(fs::read_to_string(dir.path().join("main.rs")).unwrap().contains("let x = 2;"));
+        assert_eq!(
+            fs::read_to_string(dir.path().join("src").join("new.rs")).unwrap(),
+            "pub fn new() {}\n"
+        );
+    }
+
+    #[test]
+    fn test_sections_for_one_path_are_merged() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("main.rs"), SOURCE).unwrap();
+
+        // The second section comes first in the file; neither is lost
+        let text = "--- a/main.rs\n+++ b/main.rs\n@@ -7 +7 @@\n-    41\n+    42\n--- a/main.rs\n+++ b/main.rs\n@@ -2 +2 @@\n-    let x = 1;\n+    let x = 2;\n";
+        let patch = Patch::parse(text).unwrap();
+        assert_eq!(patch.validate(dir.path()).unwrap().files.len(), 1);
+        let changed = patch.apply(dir.path()).unwrap();
+        assert_eq!(changed.len(), 1);
+        let content = fs::read_to_string(dir.path().join("main.rs")).unwrap();
+        assert!(content.contains("let x = 2;") && content.contains("    42\n"));
+
+        let overlapping = "--- a/main.rs\n+++ b/main.rs\n@@ -2 +2 @@\n-    let x = 2;\n+    let x = 3;\n--- a/main.rs\n+++ b/main.rs\n@@ -1,2 +1,2 @@\n fn main() {\n-    let x = 2;\n+    let x = 4;\n";
+        let err = Patch::parse(overlapping).unwrap().apply(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("overlaps"));
+        assert_eq!(fs::read_to_string(dir.path().join("main.rs")).unwrap(), content);
+    }
+
+    #[test]
+    fn test_apply_rename_removes_old_file() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("main.rs"), SOURCE).unwrap();
+
+        let text = "--- a/main.rs\n+++ b/app.rs\n@@ -7 +7 @@\n-    41\n+    42\n";
+        let changed = Patch::parse(text).unwrap().apply(dir.path()).unwrap();
+        assert_eq!(changed, vec![dir.path().join("app.rs"), dir.path().join("main.rs")]);
+        assert!(!dir.path().join("main.rs").exists());
+        assert!(fs::read_to_string(dir.path().join("app.rs")).unwrap().contains("    42\n"));
+
+        // The source of a rename cannot be patched in the same diff
+        fs::write(dir.path().join("main.rs"), SOURCE).unwrap();
+        fs::remove_file(dir.path().join("app.rs")).unwrap();
+        let both = format!("{}--- a/main.rs\n+++ b/main.rs\n@@ -2 +2 @@\n-    let x = 1;\n+    let x = 2;\n", text);
+        assert!(Patch::parse(&both).unwrap().apply(dir.path()).is_err());
+        assert_eq!(fs::read_to_string(dir.path().join("main.rs")).unwrap(), SOURCE);
+    }
+}
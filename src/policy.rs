@@ -0,0 +1,597 @@
+//! Call policy for AI backend requests
+//!
+//! Wraps `cli_ai_analyzer::prompt` with a per-call timeout, retries with
+//! jittered exponential backoff, a fallback backend chain, and a circuit
+//! breaker used by watch mode to stop hammering a failing backend.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use cli_ai_analyzer::{prompt as ai_prompt, AnalyzeOptions, Backend};
+
+use crate::error::{CodeReviewError, Result};
+
+/// Default per-call timeout
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// Default number of retries per backend (in addition to the first attempt)
+const DEFAULT_MAX_RETRIES: u32 = 2;
+
+/// Default consecutive failures before the circuit breaker opens
+const DEFAULT_BREAKER_THRESHOLD: u32 = 5;
+
+/// Default time the circuit breaker stays open
+const DEFAULT_BREAKER_COOLDOWN_SECS: u64 = 60;
+
+/// Retry, timeout and fallback settings for AI calls
+#[derive(Debug, Clone)]
+pub struct CallPolicy {
+    /// Maximum wall-clock time for a single attempt (`None` = wait forever)
+    pub timeout: Option<Duration>,
+    /// Retries per backend for transient errors (rate limit, unavailable)
+    pub max_retries: u32,
+    /// Base delay for exponential backoff
+    pub backoff_base: Duration,
+    /// Upper bound for a single backoff delay
+    pub backoff_max: Duration,
+    /// Backends tried in order after the primary backend fails
+    pub fallback_backends: Vec<Backend>,
+    /// Consecutive failed reviews before the watch-mode circuit breaker opens (0 = disabled)
+    pub breaker_threshold: u32,
+    /// How long the circuit breaker stays open before allowing a trial call
+    pub breaker_cooldown: Duration,
+}
+
+impl Default for CallPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(DEFAULT_TIMEOUT_SECS)),
+            max_retries: DEFAULT_MAX_RETRIES,
+            backoff_base: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(30),
+            fallback_backends: Vec::new(),
+            breaker_threshold: DEFAULT_BREAKER_THRESHOLD,
+            breaker_cooldown: Duration::from_secs(DEFAULT_BREAKER_COOLDOWN_SECS),
+        }
+    }
+}
+
+impl CallPolicy {
+    /// Create a policy with default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the per-attempt timeout (`None` disables it)
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the number of retries per backend
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Set the backoff base and maximum delay
+    pub fn with_backoff(mut self, base: Duration, max: Duration) -> Self {
+        self.backoff_base = base;
+        self.backoff_max = max;
+        self
+    }
+
+    /// Append a fallback backend to the chain
+    pub fn with_fallback(mut self, backend: Backend) -> Self {
+        self.fallback_backends.push(backend);
+        self
+    }
+
+    /// Configure the watch-mode circuit breaker (threshold 0 disables it)
+    pub fn with_circuit_breaker(mut self, threshold: u32, cooldown: Duration) -> Self {
+        self.breaker_threshold = threshold;
+        self.breaker_cooldown = cooldown;
+        self
+    }
+
+    /// Delay before retry number `attempt` (0-based), with equal jitter.
+    ///
+    /// The exponential delay is capped at `backoff_max`; half of it is fixed
+    /// and the other half is random so concurrent callers spread out.
+    fn backoff_delay(&self, attempt: u32) -> Duration {
+        let base_ms = self.backoff_base.as_millis() as u64;
+        let max_ms = self.backoff_max.as_millis() as u64;
+        let exp_ms = base_ms
+            .saturating_mul(1u64.checked_shl(attempt).unwrap_or(u64::MAX))
+            .min(max_ms);
+        let half = exp_ms / 2;
+        let jitter = if half == 0 { 0 } else { random_u64() % (half + 1) };
+        Duration::from_millis(half + jitter)
+    }
+}
+
+/// Successful AI call result
+#[derive(Debug, Clone)]
+pub struct CallOutcome {
+    /// Response text from the backend
+    pub response: String,
+    /// Backend that produced the response (may be a fallback)
+    pub backend: Backend,
+    /// Total attempts made across all backends
+    pub attempts: u32,
+}
+
+/// Send a prompt to the AI backend, applying the call policy.
+///
+/// The model is only passed to the primary backend; fallback backends
+/// use their default model since model names are backend-specific.
+pub fn call_ai(
+    prompt: &str,
+    backend: Backend,
+    model: Option<&str>,
+    policy: &CallPolicy,
+) -> Result<CallOutcome> {
+    call_ai_gated(prompt, backend, model, policy, || Ok(()))
+}
+
+/// Like [`call_ai`], but runs `gate` before every attempt (retries and
+/// fallbacks included) and holds what it returns while the attempt runs.
+///
+/// `UsageLimits` uses this to take one rate-limiter permit per attempt.
+pub(crate) fn call_ai_gated<G>(
+    prompt: &str,
+    backend: Backend,
+    model: Option<&str>,
+    policy: &CallPolicy,
+    gate: impl Fn() -> Result<G>,
+) -> Result<CallOutcome> {
+    let primary_model = model.map(String::from);
+    run_gated(backend, policy, gate, |b| {
+        let options = match primary_model {
+            Some(ref m) if b == backend => AnalyzeOptions::with_model(m).with_backend(b),
+            _ => AnalyzeOptions::default().with_backend(b),
+        };
+        let prompt = prompt.to_string();
+        call_with_timeout(policy.timeout, move || {
+            ai_prompt(&prompt, options).map_err(classify_ai_error)
+        })
+    })
+}
+
+/// Run the retry/fallback loop with `gate` held around every attempt
+fn run_gated<G, F>(
+    backend: Backend,
+    policy: &CallPolicy,
+    gate: impl Fn() -> Result<G>,
+    mut call: F,
+) -> Result<CallOutcome>
+where
+    F: FnMut(Backend) -> Result<String>,
+{
+    run_with_policy(backend, policy, |b| {
+        let _guard = gate()?;
+        call(b)
+    })
+}
+
+/// Core retry/fallback loop, generic over the actual call for testability
+///
+/// A timed-out attempt is never retried on the same backend: its worker
+/// (and the backend CLI it spawned) is still running, so retrying would
+/// pile up hung processes. The next fallback backend is tried instead.
+fn run_with_policy<F>(backend: Backend, policy: &CallPolicy, mut call: F) -> Result<CallOutcome>
+where
+    F: FnMut(Backend) -> Result<String>,
+{
+    let chain = std::iter::once(backend).chain(policy.fallback_backends.iter().copied());
+    let mut attempts = 0;
+    let mut last_err = None;
+
+    for b in chain {
+        for retry in 0..=policy.max_retries {
+            attempts += 1;
+            match call(b) {
+                Ok(response) => {
+                    return Ok(CallOutcome {
+                        response,
+                        backend: b,
+                        attempts,
+                    })
+                }
+                Err(e) => {
+                    let retryable = is_retryable(&e);
+                    log::warn!("AI call via {:?} failed (attempt {}): {}", b, attempts, e);
+                    last_err = Some(e);
+                    if !retryable || retry == policy.max_retries {
+                        break;
+                    }
+                    thread::sleep(policy.backoff_delay(retry));
+                }
+            }
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| CodeReviewError::BackendUnavailable("no backend attempted".into())))
+}
+
+/// Run `f` on a worker thread and give up after `timeout`.
+///
+/// On timeout the worker is detached; the backend CLI process it spawned
+/// is left to finish on its own, which is why timeouts are not retried.
+fn call_with_timeout<F>(timeout: Option<Duration>, f: F) -> Result<String>
+where
+    F: FnOnce() -> Result<String> + Send + 'static,
+{
+    let Some(limit) = timeout else {
+        return f();
+    };
+
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(f());
+    });
+
+    match rx.recv_timeout(limit) {
+        Ok(result) => result,
+        Err(mpsc::RecvTimeoutError::Timeout) => Err(CodeReviewError::Timeout(limit)),
+        Err(mpsc::RecvTimeoutError::Disconnected) => Err(CodeReviewError::BackendUnavailable(
+            "AI worker thread terminated unexpectedly".into(),
+        )),
+    }
+}
+
+/// Map a backend error to a typed error
+///
+/// I/O failures are classified by their `io::ErrorKind`: a backend CLI
+/// that is missing or not executable is `BackendNotInstalled`, a broken
+/// connection is `BackendUnavailable`. The backend CLIs only report quota
+/// errors in their output, so rate limiting is recognized by the HTTP
+/// status or API error code they print.
+fn classify_ai_error(e: cli_ai_analyzer::Error) -> CodeReviewError {
+    let msg = e.to_string();
+    if let Some(kind) = io_error_kind(&e) {
+        match kind {
+            std::io::ErrorKind::NotFound | std::io::ErrorKind::PermissionDenied => {
+                return CodeReviewError::BackendNotInstalled(msg)
+            }
+            std::io::ErrorKind::ConnectionRefused
+            | std::io::ErrorKind::ConnectionReset
+            | std::io::ErrorKind::ConnectionAborted
+            | std::io::ErrorKind::NotConnected
+            | std::io::ErrorKind::TimedOut
+            | std::io::ErrorKind::Interrupted
+            | std::io::ErrorKind::BrokenPipe => return CodeReviewError::BackendUnavailable(msg),
+            _ => {}
+        }
+    }
+    if is_rate_limit_message(&msg) {
+        CodeReviewError::RateLimited(msg)
+    } else {
+        CodeReviewError::AiError(e)
+    }
+}
+
+/// Kind of the first `io::Error` in an error's source chain
+fn io_error_kind(e: &(dyn std::error::Error + 'static)) -> Option<std::io::ErrorKind> {
+    let mut current = Some(e);
+    while let Some(err) = current {
+        if let Some(io) = err.downcast_ref::<std::io::Error>() {
+            return Some(io.kind());
+        }
+        current = err.source();
+    }
+    None
+}
+
+/// Whether backend output reports HTTP 429 or an exhausted quota
+fn is_rate_limit_message(msg: &str) -> bool {
+    const CODES: &[&str] = &["429", "RESOURCE_EXHAUSTED", "rate_limit_error"];
+    msg.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .any(|token| CODES.contains(&token))
+}
+
+/// Whether an error is worth retrying on the same backend
+fn is_retryable(e: &CodeReviewError) -> bool {
+    matches!(
+        e,
+        CodeReviewError::RateLimited(_) | CodeReviewError::BackendUnavailable(_)
+    )
+}
+
+/// Whether an error says the backend is unhealthy (counts toward the breaker)
+///
+/// Unreadable files, parse errors or an exhausted budget say nothing about
+/// the backend and must not open the circuit breaker.
+pub(crate) fn is_backend_failure(e: &CodeReviewError) -> bool {
+    matches!(
+        e,
+        CodeReviewError::Timeout(_)
+            | CodeReviewError::RateLimited(_)
+            | CodeReviewError::BackendUnavailable(_)
+            | CodeReviewError::BackendNotInstalled(_)
+            | CodeReviewError::AiError(_)
+    )
+}
+
+/// Random value from the std hasher seed (avoids a `rand` dependency)
+fn random_u64() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(Instant::now().elapsed().as_nanos());
+    hasher.finish()
+}
+
+/// Internal breaker state
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    /// A half-open trial call has been let through and not yet recorded
+    trial_in_flight: bool,
+}
+
+/// Circuit breaker that pauses AI calls after repeated failures.
+///
+/// After `threshold` consecutive failures the breaker opens for `cooldown`.
+/// Once the cooldown elapses exactly one trial call is let through; success
+/// closes the breaker, failure re-opens it, and other calls are rejected
+/// until the trial is recorded.
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    /// Create a breaker from a call policy's breaker settings
+    pub fn from_policy(policy: &CallPolicy) -> Self {
+        Self {
+            threshold: policy.breaker_threshold,
+            cooldown: policy.breaker_cooldown,
+            state: Mutex::new(BreakerState {
+                consecutive_failures: 0,
+                open_until: None,
+                trial_in_flight: false,
+            }),
+        }
+    }
+
+    /// Check whether a call may proceed.
+    ///
+    /// Returns `CodeReviewError::CircuitOpen` with the remaining cooldown
+    /// while the breaker is open, and with a zero duration while the
+    /// half-open trial call is in flight. A caller that is let through must
+    /// report back with `record_success`, `record_failure` or `release`.
+    pub fn allow(&self) -> Result<()> {
+        if self.threshold == 0 {
+            return Ok(());
+        }
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|e| CodeReviewError::LockError(e.to_string()))?;
+        if let Some(until) = state.open_until {
+            let now = Instant::now();
+            if now < until {
+                return Err(CodeReviewError::CircuitOpen(until - now));
+            }
+            if state.trial_in_flight {
+                return Err(CodeReviewError::CircuitOpen(Duration::ZERO));
+            }
+            // Half-open: let one trial call through
+            state.trial_in_flight = true;
+        }
+        Ok(())
+    }
+
+    /// Record a successful call (closes the breaker)
+    pub fn record_success(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.consecutive_failures = 0;
+            state.open_until = None;
+            state.trial_in_flight = false;
+        }
+    }
+
+    /// Give back a call that was let through but never reached the backend
+    pub fn release(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.trial_in_flight = false;
+        }
+    }
+
+    /// Record a failed call (may open the breaker)
+    pub fn record_failure(&self) {
+        if self.threshold == 0 {
+            return;
+        }
+        if let Ok(mut state) = self.state.lock() {
+            state.consecutive_failures += 1;
+            state.trial_in_flight = false;
+            if state.consecutive_failures >= self.threshold {
+                state.open_until = Some(Instant::now() + self.cooldown);
+                log::warn!(
+                    "Circuit breaker opened after {} consecutive failures; pausing for {:?}",
+                    state.consecutive_failures,
+                    self.cooldown
+                );
+            }
+        }
+    }
+
+    /// Check whether the breaker is currently open
+    pub fn is_open(&self) -> bool {
+        self.state
+            .lock()
+            .map(|s| s.open_until.is_some_and(|until| Instant::now() < until))
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn fast_policy() -> CallPolicy {
+        CallPolicy::new().with_backoff(Duration::from_millis(1), Duration::from_millis(2))
+    }
+
+    #[test]
+    fn test_backoff_delay_is_capped() {
+        let policy = CallPolicy::new().with_backoff(Duration::from_millis(100), Duration::from_millis(400));
+        for attempt in 0..10 {
+            let d = policy.backoff_delay(attempt);
+            assert!(d <= Duration::from_millis(400), "attempt {} gave {:?}", attempt, d);
+        }
+        // First retry: between half and full base delay
+        let d0 = policy.backoff_delay(0);
+        assert!(d0 >= Duration::from_millis(50) && d0 <= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_retries_transient_error_then_succeeds() {
+        let calls = Cell::new(0);
+        let outcome = run_with_policy(Backend::Gemini, &fast_policy(), |_| {
+            calls.set(calls.get() + 1);
+            if calls.get() < 3 {
+                Err(CodeReviewError::RateLimited("429".into()))
+            } else {
+                Ok("✓ OK".into())
+            }
+        })
+        .unwrap();
+        assert_eq!(outcome.response, "✓ OK");
+        assert_eq!(outcome.backend, Backend::Gemini);
+        assert_eq!(outcome.attempts, 3);
+    }
+
+    #[test]
+    fn test_non_retryable_error_skips_retries() {
+        let calls = Cell::new(0);
+        let result = run_with_policy(Backend::Gemini, &fast_policy(), |_| {
+            calls.set(calls.get() + 1);
+            Err(CodeReviewError::ParseError("bad".into()))
+        });
+        assert!(matches!(result, Err(CodeReviewError::ParseError(_))));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_falls_back_to_next_backend() {
+        let policy = fast_policy().with_retries(1).with_fallback(Backend::Claude);
+        let outcome = run_with_policy(Backend::Gemini, &policy, |b| match b {
+            Backend::Gemini => Err(CodeReviewError::BackendUnavailable("down".into())),
+            _ => Ok("from fallback".into()),
+        })
+        .unwrap();
+        assert_eq!(outcome.backend, Backend::Claude);
+        assert_eq!(outcome.attempts, 3);
+    }
+
+    #[test]
+    fn test_gate_runs_per_attempt() {
+        let policy = fast_policy().with_retries(2).with_fallback(Backend::Claude);
+        let gates = Cell::new(0);
+        let outcome = run_gated(
+            Backend::Gemini,
+            &policy,
+            || {
+                gates.set(gates.get() + 1);
+                Ok(())
+            },
+            |b| match b {
+                Backend::Gemini => Err(CodeReviewError::RateLimited("429".into())),
+                _ => Ok("ok".into()),
+            },
+        )
+        .unwrap();
+        assert_eq!(outcome.attempts, 4);
+        assert_eq!(gates.get(), 4);
+    }
+
+    #[test]
+    fn test_timeout_is_not_retried_on_same_backend() {
+        let policy = fast_policy().with_retries(3).with_fallback(Backend::Claude);
+        let calls = Cell::new(0);
+        let outcome = run_with_policy(Backend::Gemini, &policy, |b| {
+            calls.set(calls.get() + 1);
+            match b {
+                Backend::Gemini => Err(CodeReviewError::Timeout(Duration::from_secs(1))),
+                _ => Ok("from fallback".into()),
+            }
+        })
+        .unwrap();
+        assert_eq!(outcome.backend, Backend::Claude);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_classify_ai_error() {
+        let missing = std::io::Error::new(std::io::ErrorKind::NotFound, "gemini");
+        let wrapped = CodeReviewError::IoError(missing);
+        assert_eq!(io_error_kind(&wrapped), Some(std::io::ErrorKind::NotFound));
+        assert!(is_rate_limit_message("API error (429): Too Many Requests"));
+        assert!(is_rate_limit_message("status: RESOURCE_EXHAUSTED"));
+        // Line numbers, model names or "not found" in output are not quota errors
+        assert!(!is_rate_limit_message("model gemini-4290 not found"));
+        assert!(!is_retryable(&CodeReviewError::BackendNotInstalled("gemini".into())));
+    }
+
+    #[test]
+    fn test_call_with_timeout_expires() {
+        let result = call_with_timeout(Some(Duration::from_millis(20)), || {
+            thread::sleep(Duration::from_millis(500));
+            Ok(String::new())
+        });
+        assert!(matches!(result, Err(CodeReviewError::Timeout(_))));
+    }
+
+    #[test]
+    fn test_call_with_timeout_returns_result() {
+        let result = call_with_timeout(Some(Duration::from_secs(5)), || Ok("done".into()));
+        assert_eq!(result.unwrap(), "done");
+    }
+
+    #[test]
+    fn test_circuit_breaker_opens_and_recovers() {
+        let policy = CallPolicy::new().with_circuit_breaker(2, Duration::from_millis(30));
+        let breaker = CircuitBreaker::from_policy(&policy);
+
+        assert!(breaker.allow().is_ok());
+        breaker.record_failure();
+        assert!(breaker.allow().is_ok());
+        breaker.record_failure();
+        assert!(breaker.is_open());
+        assert!(matches!(breaker.allow(), Err(CodeReviewError::CircuitOpen(_))));
+
+        thread::sleep(Duration::from_millis(40));
+        assert!(breaker.allow().is_ok());
+        // Only one trial call while half-open
+        assert!(matches!(breaker.allow(), Err(CodeReviewError::CircuitOpen(_))));
+        breaker.record_success();
+        assert!(breaker.allow().is_ok());
+        assert!(!breaker.is_open());
+    }
+
+    #[test]
+    fn test_backend_failures() {
+        assert!(is_backend_failure(&CodeReviewError::Timeout(Duration::from_secs(1))));
+        assert!(is_backend_failure(&CodeReviewError::BackendNotInstalled("gemini".into())));
+        assert!(!is_backend_failure(&CodeReviewError::ParseError("bad".into())));
+        assert!(!is_backend_failure(&CodeReviewError::IoError(std::io::Error::other("empty"))));
+        assert!(!is_backend_failure(&CodeReviewError::BudgetExhausted("daily".into())));
+    }
+
+    #[test]
+    fn test_circuit_breaker_disabled() {
+        let policy = CallPolicy::new().with_circuit_breaker(0, Duration::from_secs(60));
+        let breaker = CircuitBreaker::from_policy(&policy);
+        for _ in 0..10 {
+            breaker.record_failure();
+        }
+        assert!(breaker.allow().is_ok());
+    }
+}
@@ -3,6 +3,10 @@
 use std::fmt::Write as FmtWrite;
 
 use crate::context::{ProjectContext, RawContext};
+use crate::metrics::{FileMetrics, MetricThresholds};
+use crate::rules::RuleViolation;
+use crate::snapshot::ArchitectureDrift;
+use crate::symbols::SymbolContext;
 
 /// Default code review prompt (Japanese)
 pub const DEFAULT_REVIEW_PROMPT: &str = r#"以下のコード変更をレビューしてください。
@@ -22,7 +26,7 @@ pub const DEFAULT_REVIEW_PROMPT: &str = r#"以下のコード変更をレビュ
    - 変動性の高いコード（コアロジック）ほど厳密な分離が必要。変動しないコードは多少の結合を許容
 
 2. **コード品質**
-   - 関数が長すぎないか（50行超えは要注意）
+   - 関数が長すぎないか（50行超えは要注意。コードメトリクスの表があれば計測値を使う）
    - 重複コードはないか
    - 命名は適切か
 
@@ -460,6 +464,83 @@ pub const INVESTIGATE_PROMPT: &str = r#"以下のコードベースについて
 - 具体的な修正案（あれば）
 "#;
 
+/// Reference investigation prompt - compares a failing file with reference codebases
+pub const REFERENCE_INVESTIGATION_PROMPT: &str = r#"以下のファイルがビルドに失敗しています。参照コードベースでの同じAPIの使い方と比較して、原因と修正を調査してください。
+
+## 対象ファイル: {file_name}
+
+```{language}
+{content}
+```
+
+## ビルドエラー
+
+```
+{errors}
+```
+
+## 参照コードベースの該当箇所（キーワード: {keywords}）
+{references}
+
+## 調査方針
+1. エラーに関係するAPI・型・命名を参照コードでの使い方と比較
+2. 対象ファイルとの差異（名前、引数、戻り値の型、呼び出し規約）を特定
+3. 参照コードに根拠がない推測は推測と明記
+
+## 出力形式
+### 原因
+- エラーごとの根本原因を簡潔に
+### 根拠
+- 参照コードの該当箇所（ファイル:行）
+### 修正案
+- 対象ファイルへの具体的な変更
+"#;
+
+/// Fix prompt - asks for a unified diff that fixes build errors
+pub const FIX_PROMPT: &str = r#"以下のファイルがビルドに失敗しています。ビルドエラーを修正する unified diff を作成してください。
+
+## 対象ファイル
+{files}
+
+## 作業中の変更（git diff）
+
+```diff
+{diff}
+```
+
+## ビルドエラー
+
+```
+{errors}
+```
+
+## 参照コードベースの該当箇所（キーワード: {keywords}）
+{references}
+
+## 修正方針
+1. ビルドエラーの解消に必要な最小限の変更にとどめる（リファクタリングや整形はしない）
+2. API・型・命名は参照コードでの使い方に合わせる
+3. 対象ファイル以外の変更は、エラーの解消に必須な場合のみ
+
+## 出力形式
+unified diff のみを ```diff ブロックで出力してください。説明は不要です。
+- ファイルヘッダは `--- a/<パス>` と `+++ b/<パス>`（パスは上記の対象ファイルと同じ相対パス）
+- 変更の前後に3行のコンテキストを含める
+- コンテキスト行と削除行は対象ファイルの内容と一字一句同じにする（インデントを含む）
+"#;
+
+/// Fix retry prompt - appended when the previous diff did not apply
+pub const FIX_RETRY_PROMPT: &str = r#"
+## 前回の出力の問題
+前回の出力は対象ファイルに適用できませんでした: {error}
+
+```
+{previous}
+```
+
+対象ファイルの内容と一致するコンテキストで、unified diff を出力し直してください。
+"#;
+
 /// QDD (Question-Driven Development) prompt - generates questions, not answers
 pub const QDD_PROMPT: &str = r#"以下のコード変更に対して、「答え」ではなく「問い」を生成してください。
 良い問いは思考の分岐点を可視化し、設計判断の見落としを防ぎます。
@@ -502,16 +583,16 @@ pub const QDD_PROMPT: &str = r#"以下のコード変更に対して、「答え
 "#;
 
 /// Shared code discovery prompt - analyzes cross-project sharing opportunities
-pub const FIND_SHARED_PROMPT: &str = r#"以下は2つのプロジェクト間の共有コード候補の分析結果です。
+pub const FIND_SHARED_PROMPT: &str = r#"以下は複数のプロジェクト間の共有コード候補（クラスタ）の分析結果です。
 
 {context}
 
 ## 分析してほしいこと
 
-各候補について以下を判断してください：
+各クラスタについて以下を判断してください：
 
-1. **共通化すべきか** - 両プロジェクトで同じロジック/データを持つべきでない場合
-   - 共通ライブラリに切り出すべき（変更時に両方更新が必要になるリスク）
+1. **共通化すべきか** - 複数のプロジェクトで同じロジック/データを持つべきでない場合
+   - 共通ライブラリに切り出すべき（変更時にすべてのコピーの更新が必要になるリスク）
    - 設定ファイル（JSON等）として外部化して共有すべき
    - そのまま別々に持つのが適切（偶然の類似に過ぎない）
 
@@ -524,6 +605,10 @@ pub const FIND_SHARED_PROMPT: &str = r#"以下は2つのプロジェクト間の
    - どのファイルをどう統合するか
    - 共通モジュールの配置場所
 
+4. **抽出計画の評価**（抽出計画が含まれる場合）
+   - 提案されたcrate/モジュール名・メンバー・依存するプロジェクトは妥当か
+   - 分割・統合・除外すべきメンバーはないか
+
 ## 出力形式
 
 ### 共通化推奨
@@ -585,6 +670,7 @@ pub const ARCHITECTURE_REVIEW_WITH_CONTEXT_PROMPT: &str = r#"以下のコード
    - 遠い距離（別モジュール・別サービス）への結合はコントラクト結合（公開APIのみ）か → 疎結合＝良い
    - 遠いのに強い結合（他モジュールのDB直接参照、非公開型への依存）がないか → 大域的複雑性＝悪い
    - 変動性の高いコード（頻繁に変わるビジネスロジック）ほど結合を弱くすべき
+   - 「結合バランス」セクションがあれば、その計測値（強度・距離・変更回数）を根拠に指摘する
 3. 関連ファイル（一緒に変更されたファイル）との整合性は取れているか
 4. 依存方向は適切か（循環依存がないか）
 5. このファイルにあるべきコードか、別の場所が適切か
@@ -635,6 +721,101 @@ pub fn build_find_shared_prompt(template: &str, context: &str) -> String {
     template.replace("{context}", context)
 }
 
+/// Build a reference investigation prompt for a failing file
+pub fn build_reference_investigation_prompt(
+    template: &str,
+    file_name: &str,
+    language: &str,
+    content: &str,
+    errors: &str,
+    keywords: &str,
+    references: &str,
+) -> String {
+    let references = if references.trim().is_empty() {
+        "（該当箇所なし）"
+    } else {
+        references
+    };
+    fill_placeholders(
+        template,
+        &[
+            ("file_name", file_name),
+            ("language", language),
+            ("keywords", keywords),
+            ("errors", errors),
+            ("references", references),
+            ("content", content),
+        ],
+    )
+}
+
+/// Substitute `{name}` placeholders in one pass over the template
+///
+/// Values are inserted verbatim, so a build log or snippet that happens to
+/// contain `{content}` is not expanded by a later placeholder.
+fn fill_placeholders(template: &str, values: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            let name = &after[..end];
+            values
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                out.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Build a fix prompt for failing files
+///
+/// `files` are the target files, already formatted with their paths.
+pub fn build_fix_prompt(
+    template: &str,
+    files: &str,
+    diff: &str,
+    errors: &str,
+    keywords: &str,
+    references: &str,
+) -> String {
+    let diff = if diff.trim().is_empty() { "（なし）" } else { diff };
+    let references = if references.trim().is_empty() {
+        "（該当箇所なし）"
+    } else {
+        references
+    };
+    fill_placeholders(
+        template,
+        &[
+            ("diff", diff),
+            ("errors", errors),
+            ("keywords", keywords),
+            ("references", references),
+            ("files", files),
+        ],
+    )
+}
+
+/// Append the reason a previous fix did not apply to a fix prompt
+pub fn build_fix_retry_prompt(prompt: &str, error: &str, previous: &str) -> String {
+    let retry = fill_placeholders(FIX_RETRY_PROMPT, &[("error", error), ("previous", previous)]);
+    format!("{}{}", prompt, retry)
+}
+
 /// Build an investigate prompt with question and codebase context
 pub fn build_investigate_prompt(template: &str, question: &str, context: &str) -> String {
     template
@@ -691,6 +872,24 @@ impl PromptType {
         }
     }
 
+    /// Short lowercase name (matches the CLI `--prompt` value)
+    pub fn name(&self) -> &'static str {
+        match self {
+            PromptType::Default => "default",
+            PromptType::Quick => "quick",
+            PromptType::Security => "security",
+            PromptType::Architecture => "architecture",
+            PromptType::Holistic => "holistic",
+            PromptType::Principles => "principles",
+            PromptType::Discovery => "discovery",
+            PromptType::Analyze => "analyze",
+            PromptType::Investigate => "investigate",
+            PromptType::Multi => "multi",
+            PromptType::Qdd => "qdd",
+            PromptType::Custom => "custom",
+        }
+    }
+
     /// Check if this prompt type requires a goal instead of file content
     pub fn requires_goal(&self) -> bool {
         matches!(self, PromptType::Discovery | PromptType::Investigate)
@@ -747,13 +946,21 @@ pub fn format_project_context(ctx: &ProjectContext) -> String {
     }
 
     // Dependencies
-    if !ctx.dependencies.imports.is_empty() || !ctx.dependencies.imported_by.is_empty() {
+    if !ctx.dependencies.imports.is_empty()
+        || !ctx.dependencies.import_files.is_empty()
+        || !ctx.dependencies.imported_by.is_empty()
+    {
         output.push_str("## 依存関係\n");
         if !ctx.dependencies.imports.is_empty() {
             output.push_str("このファイルが使用: ");
             output.push_str(&ctx.dependencies.imports.join(", "));
             output.push('\n');
         }
+        if !ctx.dependencies.import_files.is_empty() {
+            output.push_str("依存ファイル: ");
+            output.push_str(&ctx.dependencies.import_files.join(", "));
+            output.push('\n');
+        }
         if !ctx.dependencies.imported_by.is_empty() {
             output.push_str("このファイルを使用: ");
             output.push_str(&ctx.dependencies.imported_by.join(", "));
@@ -762,6 +969,22 @@ pub fn format_project_context(ctx: &ProjectContext) -> String {
         output.push('\n');
     }
 
+    // Public API surface
+    if !ctx.dependencies.exports.is_empty() || !ctx.dependencies.modules.is_empty() {
+        output.push_str("## 公開API\n");
+        if !ctx.dependencies.exports.is_empty() {
+            output.push_str("公開API: ");
+            output.push_str(&ctx.dependencies.exports.join(", "));
+            output.push('\n');
+        }
+        if !ctx.dependencies.modules.is_empty() {
+            output.push_str("子モジュール: ");
+            output.push_str(&ctx.dependencies.modules.join(", "));
+            output.push('\n');
+        }
+        output.push('\n');
+    }
+
     // Sibling files
     if !ctx.sibling_files.is_empty() {
         output.push_str("## 同じディレクトリのファイル\n");
@@ -769,6 +992,133 @@ pub fn format_project_context(ctx: &ProjectContext) -> String {
         output.push_str("\n\n");
     }
 
+    // Unbalanced coupling (strength x distance x volatility)
+    if !ctx.coupling.is_empty() {
+        output.push_str("## 結合バランス（不均衡な依存）\n");
+        for edge in &ctx.coupling {
+            let _ = writeln!(
+                output,
+                "- {} → {}: {} / {} / 変更{}回",
+                edge.from,
+                edge.to,
+                edge.strength.label(),
+                edge.distance.label(),
+                edge.churn
+            );
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Format architecture rule violations as known issues for the AI
+pub fn format_rule_violations(violations: &[RuleViolation]) -> String {
+    let mut output = String::from("## 既知のアーキテクチャ違反（ルール検査で検出済み）\n");
+    output.push_str("以下は設定されたアーキテクチャルールへの違反として確定しています。必ず指摘し、修正方針を示してください。\n");
+    for violation in violations {
+        let _ = writeln!(output, "- {}", violation.message());
+    }
+    output
+}
+
+/// Format architecture drift (changes since the base commit) for the AI
+pub fn format_architecture_drift(drift: &ArchitectureDrift) -> String {
+    let mut output = String::from("## アーキテクチャの変化（コミット前との比較）\n");
+    if !drift.new_modules.is_empty() {
+        let _ = writeln!(output, "新しいモジュール: {}", drift.new_modules.join(", "));
+    }
+    if !drift.new_module_edges.is_empty() {
+        output.push_str("新しいモジュール間依存:\n");
+        for edge in &drift.new_module_edges {
+            let _ = writeln!(output, "- {} → {} ({}件)", edge.from, edge.to, edge.imports);
+        }
+    }
+    for cycle in drift.new_cycles.iter().chain(&drift.new_module_cycles) {
+        let _ = writeln!(output, "新しい循環依存: {}", cycle.join(" → "));
+    }
+    for (module, exports) in &drift.new_exports {
+        let _ = writeln!(output, "公開APIの追加 ({}): {}", module, exports.join(", "));
+    }
+    for growth in &drift.grown_modules {
+        let _ = writeln!(
+            output,
+            "肥大化したモジュール: {} ({}→{}行, {}→{}ファイル)",
+            growth.module, growth.lines.0, growth.lines.1, growth.files.0, growth.files.1
+        );
+    }
+    output
+}
+
+/// Maximum number of functions listed in the metrics table
+const MAX_METRIC_ROWS: usize = 10;
+
+/// Format code metrics as a compact table, most complex functions first
+///
+/// Values over a threshold are marked with ⚠.
+pub fn format_metrics(metrics: &FileMetrics, thresholds: &MetricThresholds) -> String {
+    let mark = |value: usize, limit: usize| {
+        if value > limit {
+            format!("{} ⚠", value)
+        } else {
+            value.to_string()
+        }
+    };
+    let mut output = String::from("## コードメトリクス（静的解析で計測済み）\n");
+    let _ = writeln!(
+        output,
+        "ファイル: 関数{} / 型{} / {}行",
+        metrics.function_count, metrics.type_count, metrics.lines
+    );
+    if metrics.functions.is_empty() {
+        return output;
+    }
+    let mut functions: Vec<_> = metrics.functions.iter().collect();
+    functions.sort_by(|a, b| b.complexity.cmp(&a.complexity).then(b.lines.cmp(&a.lines)));
+    output.push_str("| 関数 | 行 | 長さ | ネスト | 複雑度 | 引数 |\n|---|---|---|---|---|---|\n");
+    for f in functions.iter().take(MAX_METRIC_ROWS) {
+        let _ = writeln!(
+            output,
+            "| {} | {} | {} | {} | {} | {} |",
+            f.name,
+            f.line,
+            mark(f.lines, thresholds.function_lines),
+            mark(f.nesting, thresholds.nesting),
+            mark(f.complexity, thresholds.complexity),
+            mark(f.params, thresholds.params)
+        );
+    }
+    if functions.len() > MAX_METRIC_ROWS {
+        let _ = writeln!(output, "（他{}関数）", functions.len() - MAX_METRIC_ROWS);
+    }
+    output
+}
+
+/// Format symbol-level context (referenced definitions and call sites)
+pub fn format_symbol_context(ctx: &SymbolContext) -> String {
+    let mut output = String::new();
+    if !ctx.definitions.is_empty() {
+        output.push_str("## 変更箇所が参照する定義\n");
+        for definition in &ctx.definitions {
+            let _ = writeln!(
+                output,
+                "### {} ({}, {}:{})\n```\n{}\n```",
+                definition.name, definition.kind, definition.file, definition.line, definition.text
+            );
+        }
+        output.push('\n');
+    }
+    if !ctx.call_sites.is_empty() {
+        output.push_str("## 変更された公開関数の呼び出し箇所\n");
+        output.push_str("シグネチャや振る舞いの変更がこれらの呼び出し元と整合しているか確認してください。\n");
+        for call in &ctx.call_sites {
+            let _ = writeln!(output, "- {}: {}:{} `{}`", call.function, call.file, call.line, call.code);
+        }
+        output.push('\n');
+    }
+    if ctx.omitted > 0 {
+        let _ = writeln!(output, "（プロンプト予算のため{}件を省略）", ctx.omitted);
+    }
     output
 }
 
@@ -845,6 +1195,23 @@ mod tests {
         assert!(!PromptType::Principles.template().is_empty());
     }
 
+    #[test]
+    fn test_fill_placeholders_single_pass() {
+        let prompt = build_reference_investigation_prompt(
+            "{file_name}: {errors}\n{content}",
+            "main.rs",
+            "rs",
+            "fn main() {}",
+            "expected `{content}`",
+            "",
+            "",
+        );
+        assert_eq!(prompt, "main.rs: expected `{content}`\nfn main() {}");
+
+        let prompt = build_fix_prompt("{errors}|{files}", "a.rs", "", "missing {files}", "", "");
+        assert_eq!(prompt, "missing {files}|a.rs");
+    }
+
     #[test]
     fn test_build_prompt_with_context() {
         let context = "## プロジェクト構造\nsrc/\n└── main.rs";
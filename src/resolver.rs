@@ -0,0 +1,886 @@
+//! Import-to-file resolution
+//!
+//! Maps an [`ImportInfo`] found in one file to the project files it refers
+//! to, so dependency edges point at concrete files instead of name matches:
+//!
+//! - Rust: `crate::`/`super::`/`self::` paths, child modules and the
+//!   library crate names of the project's packages, walked through the
+//!   `foo.rs`/`foo/mod.rs` module tree
+//! - TypeScript/JavaScript: relative specifiers, plus `baseUrl` and `paths`
+//!   from the project's `tsconfig.json` and the configs it `extends`
+//! - Python: relative imports, and absolute imports below the configured
+//!   package roots
+//! - C/C++: quoted and angle includes against the include directories
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::parser::{ImportInfo, SYSTEM_INCLUDE};
+use crate::utils::fs::walk_source_files;
+
+/// Extensions tried for extensionless TypeScript/JavaScript specifiers
+const TS_EXTENSIONS: &[&str] = &["ts", "tsx", "d.ts", "js", "jsx", "mjs", "cjs"];
+
+/// Extensions of importing files whose imports are resolved to files
+pub(crate) const RESOLVED_EXTENSIONS: &[&str] = &[
+    "rs", "ts", "tsx", "js", "jsx", "mjs", "cjs", "py", "c", "h", "cpp", "hpp",
+];
+
+/// Index files tried when a specifier names a directory
+const TS_INDEX_FILES: &[&str] = &["index.ts", "index.tsx", "index.js", "index.jsx"];
+
+/// Maximum depth of `tsconfig.json` `extends` chains (guards against cycles)
+const TSCONFIG_MAX_EXTENDS: usize = 8;
+
+/// Resolves imports to project files
+///
+/// Created once per project and shared across lookups; `tsconfig.json` is
+/// read when the resolver is created, Rust library names on first use.
+#[derive(Debug, Clone)]
+pub struct ImportResolver {
+    base_path: PathBuf,
+    include_dirs: Vec<PathBuf>,
+    python_roots: Vec<PathBuf>,
+    tsconfig: Option<TsConfig>,
+    /// Library name of each package directory seen so far
+    library_names: Arc<Mutex<HashMap<PathBuf, Option<String>>>>,
+    /// Rust packages with a library below the project root (workspace members)
+    rust_packages: Arc<OnceLock<Vec<PathBuf>>>,
+}
+
+/// `tsconfig.json` `paths` patterns and their substitutions
+type PathMappings = Vec<(String, Vec<String>)>;
+
+/// Module resolution settings from `tsconfig.json`
+#[derive(Debug, Clone)]
+struct TsConfig {
+    /// Directory bare specifiers resolve against (`baseUrl`), if set
+    base_url: Option<PathBuf>,
+    /// Directory `paths` targets are taken from: `baseUrl`, else the
+    /// directory of the config that declared `paths`
+    paths_dir: PathBuf,
+    /// `paths` patterns and their substitutions, exact patterns first, then
+    /// by longest prefix before `*` (the order TypeScript tries them in)
+    paths: PathMappings,
+}
+
+/// `compilerOptions` collected along an `extends` chain
+#[derive(Debug, Default)]
+struct TsCompilerOptions {
+    base_url: Option<PathBuf>,
+    /// `paths` entries and the directory of the config declaring them
+    paths: Option<(PathMappings, PathBuf)>,
+}
+
+impl ImportResolver {
+    /// Create a resolver for the project rooted at `base_path`
+    pub fn new(base_path: &Path) -> Self {
+        Self {
+            base_path: base_path.to_path_buf(),
+            include_dirs: Vec::new(),
+            python_roots: Vec::new(),
+            tsconfig: TsConfig::load(base_path),
+            library_names: Arc::default(),
+            rust_packages: Arc::default(),
+        }
+    }
+
+    /// Set C/C++ include directories (relative ones are taken from the project root)
+    pub fn with_include_dirs(mut self, dirs: &[PathBuf]) -> Self {
+        self.include_dirs = dirs.to_vec();
+        self
+    }
+
+    /// Set Python package roots (relative ones are taken from the project root)
+    ///
+    /// Defaults to the project root and its `src/` directory.
+    pub fn with_python_roots(mut self, roots: &[PathBuf]) -> Self {
+        self.python_roots = roots.to_vec();
+        self
+    }
+
+    /// Project files an import in `importing_file` refers to
+    ///
+    /// Returns an empty list for imports outside the project (standard
+    /// library, external packages), unrecognized layouts, and languages
+    /// without a resolver.
+    pub fn resolve(&self, import: &ImportInfo, importing_file: &Path) -> Vec<PathBuf> {
+        let ext = importing_file
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("");
+        let mut files = match ext {
+            "rs" => self.resolve_rust(import, importing_file),
+            "ts" | "tsx" | "js" | "jsx" | "mjs" | "cjs" => self
+                .resolve_typescript(&import.module_path, importing_file)
+                .into_iter()
+                .collect(),
+            "py" => self.resolve_python(import, importing_file),
+            "c" | "h" | "cpp" | "hpp" => self.resolve_include(import, importing_file).into_iter().collect(),
+            _ => Vec::new(),
+        };
+        let mut seen = HashSet::new();
+        files.retain_mut(|file| {
+            *file = normalize(file);
+            seen.insert(file.clone())
+        });
+        files
+    }
+
+    /// Resolve a C/C++ include to an existing file
+    ///
+    /// Quoted includes are tried relative to the including file first, then
+    /// against each include directory. Angle includes only search the include
+    /// directories.
+    fn resolve_include(&self, include: &ImportInfo, including_file: &Path) -> Option<PathBuf> {
+        let is_system = include.items.iter().any(|i| i == SYSTEM_INCLUDE);
+        let local = including_file
+            .parent()
+            .filter(|_| !is_system)
+            .map(|dir| dir.join(&include.module_path));
+
+        local
+            .into_iter()
+            .chain(
+                self.include_dirs
+                    .iter()
+                    .map(|dir| self.base_path.join(dir).join(&include.module_path)),
+            )
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Resolve a Rust `use` path through the module tree
+    ///
+    /// The longest prefix of the path that names a module file wins, so
+    /// `crate::auth::Token` resolves to `auth.rs` (or `auth/mod.rs`). Items
+    /// of a use list that are themselves modules resolve too.
+    fn resolve_rust(&self, import: &ImportInfo, importing_file: &Path) -> Vec<PathBuf> {
+        let Some(module) = RustModule::locate(importing_file, &self.base_path) else {
+            return Vec::new();
+        };
+        let mut segments = import
+            .module_path
+            .split("::")
+            .filter(|s| !s.is_empty())
+            .peekable();
+
+        let (root, mut path) = match segments.peek().copied() {
+            Some("crate") => {
+                segments.next();
+                (module.root.clone(), Vec::new())
+            }
+            Some("self") => {
+                segments.next();
+                (module.root.clone(), module.path.clone())
+            }
+            Some("super") => {
+                let mut path = module.path.clone();
+                while segments.peek() == Some(&"super") {
+                    segments.next();
+                    path.pop();
+                }
+                (module.root.clone(), path)
+            }
+            Some(first) => {
+                let mut child = module.path.clone();
+                child.push(first.to_string());
+                if rust_module_file(&module.root, &child).is_some() {
+                    (module.root.clone(), module.path.clone())
+                } else if let Some(lib_root) = self.library_root(&module, first) {
+                    segments.next();
+                    (lib_root, Vec::new())
+                } else {
+                    return Vec::new();
+                }
+            }
+            None => return Vec::new(),
+        };
+        path.extend(segments.map(str::to_string));
+
+        let mut files = Vec::new();
+        let mut base_len = 0;
+        if let Some((len, file)) = (0..=path.len())
+            .rev()
+            .find_map(|len| rust_module_file(&root, &path[..len]).map(|file| (len, file)))
+        {
+            base_len = len;
+            files.push(file);
+        }
+
+        for item in &import.items {
+            if item == "*" || item == "self" || item.starts_with("as ") {
+                continue;
+            }
+            let mut item_path = path.clone();
+            item_path.extend(item.split("::").map(str::to_string));
+            if let Some(file) = (base_len + 1..=item_path.len())
+                .rev()
+                .find_map(|len| rust_module_file(&root, &item_path[..len]))
+            {
+                files.push(file);
+            }
+        }
+        files
+    }
+
+    /// Resolve a TypeScript/JavaScript module specifier
+    fn resolve_typescript(&self, specifier: &str, importing_file: &Path) -> Option<PathBuf> {
+        if specifier.starts_with("./") || specifier.starts_with("../") || specifier == "." || specifier == ".." {
+            let dir = importing_file.parent()?;
+            return ts_module_file(&dir.join(specifier));
+        }
+
+        let tsconfig = self.tsconfig.as_ref()?;
+        for (pattern, targets) in &tsconfig.paths {
+            let Some(captured) = match_path_pattern(pattern, specifier) else {
+                continue;
+            };
+            if let Some(file) = targets
+                .iter()
+                .find_map(|target| ts_module_file(&tsconfig.paths_dir.join(target.replacen('*', captured, 1))))
+            {
+                return Some(file);
+            }
+        }
+        tsconfig
+            .base_url
+            .as_ref()
+            .and_then(|base_url| ts_module_file(&base_url.join(specifier)))
+    }
+
+    /// Resolve a Python import to its module file and imported submodules
+    ///
+    /// Relative imports are taken from the importing file's package;
+    /// absolute ones are looked up below each package root in turn.
+    fn resolve_python(&self, import: &ImportInfo, importing_file: &Path) -> Vec<PathBuf> {
+        let dotted = &import.module_path;
+        let level = dotted.chars().take_while(|&c| c == '.').count();
+        let rest: Vec<&str> = dotted[level..].split('.').filter(|s| !s.is_empty()).collect();
+
+        let package_dirs: Vec<PathBuf> = if level > 0 {
+            let mut dir = importing_file.parent().map(Path::to_path_buf);
+            for _ in 1..level {
+                dir = dir.and_then(|d| d.parent().map(Path::to_path_buf));
+            }
+            dir.into_iter().collect()
+        } else {
+            self.python_search_roots()
+        };
+
+        for dir in package_dirs {
+            let module_dir = rest.iter().fold(dir, |d, part| d.join(part));
+            let module = python_module_file(&module_dir);
+            // `from pkg import sub` may import a submodule rather than a name
+            let submodules = import
+                .items
+                .iter()
+                .filter(|item| *item != "*" && !item.starts_with("as "))
+                .filter_map(|item| python_module_file(&module_dir.join(item)));
+
+            let files: Vec<PathBuf> = module.into_iter().chain(submodules).collect();
+            if !files.is_empty() {
+                return files;
+            }
+        }
+        Vec::new()
+    }
+
+    /// Library crate root directory of the package whose library is `name`
+    ///
+    /// The importing file's own package is tried first, then every package
+    /// below the project root, so workspace crates resolve to each other.
+    fn library_root(&self, module: &RustModule, name: &str) -> Option<PathBuf> {
+        let packages = self.rust_packages.get_or_init(|| {
+            walk_source_files(&self.base_path, &["toml"])
+                .into_iter()
+                .filter(|manifest| manifest.file_name().is_some_and(|n| n == "Cargo.toml"))
+                .filter_map(|manifest| manifest.parent().map(Path::to_path_buf))
+                .filter(|package| package.join("src/lib.rs").is_file())
+                .collect()
+        });
+        module
+            .package
+            .iter()
+            .chain(packages)
+            .find(|package| {
+                package.join("src/lib.rs").is_file() && self.library_name(package).as_deref() == Some(name)
+            })
+            .map(|package| package.join("src"))
+    }
+
+    /// Library name of a package, read from its `Cargo.toml` once
+    fn library_name(&self, package_dir: &Path) -> Option<String> {
+        let mut names = self
+            .library_names
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        names
+            .entry(package_dir.to_path_buf())
+            .or_insert_with(|| rust_library_name(package_dir))
+            .clone()
+    }
+
+    /// Directories absolute Python imports are resolved against
+    fn python_search_roots(&self) -> Vec<PathBuf> {
+        if self.python_roots.is_empty() {
+            vec![self.base_path.clone(), self.base_path.join("src")]
+        } else {
+            self.python_roots.iter().map(|root| self.base_path.join(root)).collect()
+        }
+    }
+}
+
+/// Position of a Rust file in its crate's module tree
+struct RustModule {
+    /// Directory holding the crate root file (`src/` for library and binary crates)
+    root: PathBuf,
+    /// Module path of the file below the crate root (empty for the root itself)
+    path: Vec<String>,
+    /// Package directory (the one with `Cargo.toml`), if any
+    package: Option<PathBuf>,
+}
+
+impl RustModule {
+    /// Locate `file` in the module tree of its crate
+    ///
+    /// Files below a package's `src/` (other than `src/bin/`) belong to the
+    /// library or main binary crate. Other files (tests, examples, binaries)
+    /// are treated as crate roots. Without a `Cargo.toml`, `base_path/src`
+    /// or `base_path` is used as the crate root directory.
+    fn locate(file: &Path, base_path: &Path) -> Option<Self> {
+        let package = file
+            .ancestors()
+            .skip(1)
+            .find(|dir| dir.join("Cargo.toml").is_file())
+            .map(Path::to_path_buf);
+
+        let src_root = match &package {
+            Some(pkg) => pkg.join("src"),
+            None if base_path.join("src").is_dir() && file.starts_with(base_path.join("src")) => {
+                base_path.join("src")
+            }
+            None => base_path.to_path_buf(),
+        };
+
+        let in_tree = file.starts_with(&src_root) && !file.starts_with(src_root.join("bin"));
+        if !in_tree {
+            return Some(Self {
+                root: file.parent()?.to_path_buf(),
+                path: Vec::new(),
+                package,
+            });
+        }
+
+        let relative = file.strip_prefix(&src_root).ok()?;
+        let mut path: Vec<String> = relative
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .filter_map(|c| c.as_os_str().to_str().map(str::to_string))
+            .collect();
+        let stem = relative.file_stem()?.to_str()?;
+        let is_crate_root = path.is_empty() && matches!(stem, "lib" | "main");
+        if stem != "mod" && !is_crate_root {
+            path.push(stem.to_string());
+        }
+        Some(Self { root: src_root, path, package })
+    }
+}
+
+/// File for a module path below a crate root directory
+///
+/// The empty path is the crate root file itself (`lib.rs` or `main.rs`).
+fn rust_module_file(root: &Path, path: &[String]) -> Option<PathBuf> {
+    if path.is_empty() {
+        return ["lib.rs", "main.rs"]
+            .iter()
+            .map(|name| root.join(name))
+            .find(|candidate| candidate.is_file());
+    }
+    let dir = path.iter().fold(root.to_path_buf(), |d, part| d.join(part));
+    let file = dir.with_extension("rs");
+    if file.is_file() {
+        return Some(file);
+    }
+    Some(dir.join("mod.rs")).filter(|candidate| candidate.is_file())
+}
+
+/// Library name of a package: `[lib] name`, else the package name with `-` as `_`
+fn rust_library_name(package_dir: &Path) -> Option<String> {
+    let manifest = fs::read_to_string(package_dir.join("Cargo.toml")).ok()?;
+    let mut section = "";
+    let mut package_name = None;
+    let mut lib_name = None;
+    for line in manifest.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            section = line;
+            continue;
+        }
+        let Some(value) = line
+            .strip_prefix("name")
+            .map(str::trim_start)
+            .and_then(|rest| rest.strip_prefix('='))
+        else {
+            continue;
+        };
+        let value = value.trim().trim_matches('"').to_string();
+        match section {
+            "[package]" => package_name = Some(value),
+            "[lib]" => lib_name = Some(value),
+            _ => {}
+        }
+    }
+    lib_name.or(package_name).map(|name| name.replace('-', "_"))
+}
+
+/// File for a TypeScript/JavaScript module path, trying extensions and index files
+fn ts_module_file(path: &Path) -> Option<PathBuf> {
+    let has_source_ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| TS_EXTENSIONS.contains(&ext));
+    if has_source_ext && path.is_file() {
+        return Some(path.to_path_buf());
+    }
+
+    // ESM imports name the emitted `.js` file of a `.ts` source
+    if let Some(ext @ ("js" | "jsx" | "mjs")) = path.extension().and_then(|e| e.to_str()) {
+        let source_exts: &[&str] = if ext == "mjs" { &["mts"] } else { &["ts", "tsx"] };
+        if let Some(file) = source_exts
+            .iter()
+            .map(|source_ext| path.with_extension(source_ext))
+            .find(|candidate| candidate.is_file())
+        {
+            return Some(file);
+        }
+    }
+
+    let file_name = path.file_name()?.to_str()?;
+    TS_EXTENSIONS
+        .iter()
+        .map(|ext| path.with_file_name(format!("{}.{}", file_name, ext)))
+        .chain(TS_INDEX_FILES.iter().map(|index| path.join(index)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Match a `tsconfig` `paths` pattern, returning the text captured by `*`
+fn match_path_pattern<'a>(pattern: &str, specifier: &'a str) -> Option<&'a str> {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => specifier
+            .strip_prefix(prefix)?
+            .strip_suffix(suffix)
+            .filter(|_| specifier.len() >= prefix.len() + suffix.len()),
+        None => (pattern == specifier).then_some(""),
+    }
+}
+
+/// File for a Python module: `name.py` or the package's `name/__init__.py`
+fn python_module_file(module: &Path) -> Option<PathBuf> {
+    let file = module.with_extension("py");
+    if module.file_name().is_some() && file.is_file() {
+        return Some(file);
+    }
+    Some(module.join("__init__.py")).filter(|candidate| candidate.is_file())
+}
+
+impl TsConfig {
+    /// Read `compilerOptions.baseUrl` and `compilerOptions.paths` from
+    /// `tsconfig.json`, following relative `extends`
+    fn load(base_path: &Path) -> Option<Self> {
+        let options = Self::read_options(&base_path.join("tsconfig.json"), 0)?;
+        let (mut paths, declared_in) = options.paths.unwrap_or_default();
+        if options.base_url.is_none() && paths.is_empty() {
+            return None;
+        }
+
+        // Exact patterns first, then the longest prefix before `*`
+        paths.sort_by_key(|(pattern, _)| match pattern.split_once('*') {
+            Some((prefix, _)) => (true, std::cmp::Reverse(prefix.len())),
+            None => (false, std::cmp::Reverse(pattern.len())),
+        });
+        Some(Self {
+            paths_dir: options.base_url.clone().unwrap_or(declared_in),
+            base_url: options.base_url,
+            paths,
+        })
+    }
+
+    /// Options of `config` layered over those of the configs it extends
+    ///
+    /// `baseUrl` and `paths` are relative to the config that sets them; a
+    /// later config replaces them as a whole. Package `extends` (resolved
+    /// from `node_modules`) are skipped.
+    fn read_options(config: &Path, depth: usize) -> Option<TsCompilerOptions> {
+        if depth > TSCONFIG_MAX_EXTENDS {
+            log::warn!("tsconfig extends chain too deep at {}", config.display());
+            return None;
+        }
+        let content = fs::read_to_string(config).ok()?;
+        let json: serde_json::Value = match serde_json::from_str(&strip_jsonc(&content)) {
+            Ok(json) => json,
+            Err(e) => {
+                log::warn!("Failed to parse {}: {}", config.display(), e);
+                return None;
+            }
+        };
+        let dir = config.parent().unwrap_or(Path::new("."));
+
+        let extends = match json.get("extends") {
+            Some(serde_json::Value::String(parent)) => vec![parent.as_str()],
+            Some(serde_json::Value::Array(parents)) => {
+                parents.iter().filter_map(|p| p.as_str()).collect()
+            }
+            _ => Vec::new(),
+        };
+        let mut options = TsCompilerOptions::default();
+        for parent in extends {
+            if !(parent.starts_with("./") || parent.starts_with("../") || Path::new(parent).is_absolute()) {
+                continue;
+            }
+            let mut parent_path = dir.join(parent);
+            if !parent_path.is_file() && parent_path.extension().is_none_or(|ext| ext != "json") {
+                parent_path.as_mut_os_string().push(".json");
+            }
+            if let Some(inherited) = Self::read_options(&parent_path, depth + 1) {
+                options.base_url = inherited.base_url.or(options.base_url);
+                options.paths = inherited.paths.or(options.paths);
+            }
+        }
+
+        if let Some(compiler_options) = json.get("compilerOptions") {
+            if let Some(base_url) = compiler_options.get("baseUrl").and_then(|v| v.as_str()) {
+                options.base_url = Some(normalize(&dir.join(base_url)));
+            }
+            if let Some(paths) = compiler_options.get("paths").and_then(|v| v.as_object()) {
+                let paths = paths
+                    .iter()
+                    .map(|(pattern, targets)| {
+                        let targets = targets
+                            .as_array()
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|t| t.as_str().map(str::to_string))
+                            .collect();
+                        (pattern.clone(), targets)
+                    })
+                    .collect();
+                options.paths = Some((paths, dir.to_path_buf()));
+            }
+        }
+        Some(options)
+    }
+}
+
+/// Remove comments and trailing commas so `tsconfig.json` parses as JSON
+fn strip_jsonc(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+            }
+            (',', _) => {
+                if !matches!(next_significant(chars.clone()), Some('}' | ']')) {
+                    out.push(c);
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Next character that is neither whitespace nor inside a comment
+fn next_significant(mut chars: std::iter::Peekable<std::str::Chars<'_>>) -> Option<char> {
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                chars.find(|&next| next == '\n');
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+            }
+            (c, _) if c.is_whitespace() => {}
+            (c, _) => return Some(c),
+        }
+    }
+    None
+}
+
+/// Lexically remove `.` and `..` components
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ImportKind;
+    use tempfile::tempdir;
+
+    fn import(path: &str, items: &[&str]) -> ImportInfo {
+        ImportInfo {
+            module_path: path.to_string(),
+            items: items.iter().map(|s| s.to_string()).collect(),
+            kind: ImportKind::Static,
+        }
+    }
+
+    fn touch(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_include_quoted_and_angle() {
+        let dir = tempdir().unwrap();
+        let src = dir.path().join("src");
+        touch(&src.join("util.h"), "");
+        touch(&dir.path().join("include/net/socket.h"), "");
+        let including = src.join("main.c");
+
+        let angle = |path: &str| import(path, &[SYSTEM_INCLUDE]);
+        let plain = ImportResolver::new(dir.path());
+        let with_dirs = ImportResolver::new(dir.path()).with_include_dirs(&[PathBuf::from("include")]);
+
+        // Quoted: relative to the including file, then include dirs
+        assert_eq!(
+            plain.resolve_include(&import("util.h", &[]), &including),
+            Some(src.join("util.h"))
+        );
+        assert!(plain.resolve_include(&import("net/socket.h", &[]), &including).is_none());
+        assert!(with_dirs.resolve_include(&import("net/socket.h", &[]), &including).is_some());
+
+        // Angle: include dirs only
+        assert!(with_dirs.resolve_include(&angle("util.h"), &including).is_none());
+        assert!(with_dirs.resolve_include(&angle("net/socket.h"), &including).is_some());
+    }
+
+    #[test]
+    fn test_resolve_rust_module_tree() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        touch(&root.join("Cargo.toml"), "[package]\nname = \"my-app\"\n");
+        let src = root.join("src");
+        touch(&src.join("lib.rs"), "mod auth;\nmod db;\n");
+        touch(&src.join("auth/mod.rs"), "mod token;\n");
+        touch(&src.join("auth/token.rs"), "");
+        touch(&src.join("db.rs"), "");
+        let resolver = ImportResolver::new(root);
+        let token = src.join("auth/token.rs");
+
+        assert_eq!(
+            resolver.resolve(&import("crate::db", &["Pool"]), &token),
+            vec![src.join("db.rs")]
+        );
+        assert_eq!(
+            resolver.resolve(&import("super", &["Session"]), &token),
+            vec![src.join("auth/mod.rs")]
+        );
+        assert_eq!(
+            resolver.resolve(&import("self::token", &["Token"]), &src.join("auth/mod.rs")),
+            vec![src.join("auth/token.rs")]
+        );
+        // Child module named without a prefix, and a module in a use list
+        assert_eq!(
+            resolver.resolve(&import("auth", &["token", "Session"]), &src.join("lib.rs")),
+            vec![src.join("auth/mod.rs"), src.join("auth/token.rs")]
+        );
+        // The library's crate name from an integration test
+        assert_eq!(
+            resolver.resolve(&import("my_app::auth::token", &["Token"]), &root.join("tests/login.rs")),
+            vec![src.join("auth/token.rs")]
+        );
+        assert!(resolver.resolve(&import("std::path", &["Path"]), &token).is_empty());
+        // Another crate of the workspace
+        touch(&root.join("tools/Cargo.toml"), "[package]\nname = \"tools\"\n\n[lib]\nname = \"devtools\"\n");
+        touch(&root.join("tools/src/lib.rs"), "pub mod lint;\n");
+        touch(&root.join("tools/src/lint.rs"), "");
+        let fresh = ImportResolver::new(root);
+        assert_eq!(
+            fresh.resolve(&import("devtools::lint", &["check"]), &token),
+            vec![root.join("tools/src/lint.rs")]
+        );
+        // Duplicates are dropped even when not adjacent
+        assert_eq!(
+            resolver.resolve(&import("crate", &["auth::token", "db", "auth::token"]), &token),
+            vec![src.join("lib.rs"), src.join("auth/token.rs"), src.join("db.rs")]
+        );
+    }
+
+    #[test]
+    fn test_resolve_typescript_relative_and_tsconfig() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        touch(
+            &root.join("tsconfig.json"),
+            r#"{
+  // comments and trailing commas are allowed
+  "compilerOptions": {
+    "baseUrl": "src",
+    "paths": { "@app/*": ["app/*"], },
+  },
+}"#,
+        );
+        touch(&root.join("src/app/auth/index.ts"), "");
+        touch(&root.join("src/app/button.tsx"), "");
+        touch(&root.join("src/lib/format.ts"), "");
+        let resolver = ImportResolver::new(root);
+        let importer = root.join("src/app/page.tsx");
+
+        assert_eq!(
+            resolver.resolve(&import("./auth", &[]), &importer),
+            vec![root.join("src/app/auth/index.ts")]
+        );
+        assert_eq!(
+            resolver.resolve(&import("./button.js", &[]), &importer),
+            vec![root.join("src/app/button.tsx")]
+        );
+        assert_eq!(
+            resolver.resolve(&import("../lib/format", &[]), &importer),
+            vec![root.join("src/lib/format.ts")]
+        );
+        assert_eq!(
+            resolver.resolve(&import("@app/button", &[]), &importer),
+            vec![root.join("src/app/button.tsx")]
+        );
+        assert_eq!(
+            resolver.resolve(&import("lib/format", &[]), &importer),
+            vec![root.join("src/lib/format.ts")]
+        );
+        assert!(resolver.resolve(&import("react", &[]), &importer).is_empty());
+    }
+
+    #[test]
+    fn test_tsconfig_extends_and_pattern_order() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        touch(
+            &root.join("config/tsconfig.base.json"),
+            r#"{ "compilerOptions": { "baseUrl": "../src", "paths": { "*": ["vendor/*"] } } }"#,
+        );
+        touch(
+            &root.join("tsconfig.json"),
+            r#"{
+  "extends": "./config/tsconfig.base",
+  "compilerOptions": {
+    "paths": {
+      "*": ["vendor/*"],
+      "@app/*": ["app/*"], // more specific than "*"
+    },
+  },
+}"#,
+        );
+        touch(&root.join("src/app/button.ts"), "");
+        touch(&root.join("src/vendor/@app/button.ts"), "");
+        touch(&root.join("src/lib/format.ts"), "");
+        let resolver = ImportResolver::new(root);
+        let importer = root.join("src/main.ts");
+
+        // `@app/*` has the longer prefix, so it is tried before `*`
+        assert_eq!(
+            resolver.resolve(&import("@app/button", &[]), &importer),
+            vec![root.join("src/app/button.ts")]
+        );
+        // `baseUrl` comes from the extended config, relative to its directory
+        assert_eq!(
+            resolver.resolve(&import("lib/format", &[]), &importer),
+            vec![root.join("src/lib/format.ts")]
+        );
+    }
+
+    #[test]
+    fn test_strip_jsonc_comment_after_trailing_comma() {
+        let stripped = strip_jsonc("{\n  \"a\": [1, 2,], // done\n  \"b\": 3, /* last */\n}");
+        let json: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(json["a"], serde_json::json!([1, 2]));
+        assert_eq!(json["b"], 3);
+    }
+
+    #[test]
+    fn test_resolve_python_packages() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        touch(&root.join("src/shop/__init__.py"), "");
+        touch(&root.join("src/shop/models.py"), "");
+        touch(&root.join("src/shop/api/views.py"), "");
+        let resolver = ImportResolver::new(root);
+        let views = root.join("src/shop/api/views.py");
+
+        assert_eq!(
+            resolver.resolve(&import("shop.models", &["Order"]), &views),
+            vec![root.join("src/shop/models.py")]
+        );
+        assert_eq!(
+            resolver.resolve(&import("..models", &["Order"]), &views),
+            vec![root.join("src/shop/models.py")]
+        );
+        assert_eq!(
+            resolver.resolve(&import("..", &["models"]), &views),
+            vec![root.join("src/shop/__init__.py"), root.join("src/shop/models.py")]
+        );
+        assert!(resolver.resolve(&import("requests", &[]), &views).is_empty());
+
+        // Configured roots replace the defaults
+        let custom = ImportResolver::new(root).with_python_roots(&[PathBuf::from("lib")]);
+        assert!(custom.resolve(&import("shop.models", &[]), &views).is_empty());
+    }
+
+    #[test]
+    fn test_match_path_pattern() {
+        assert_eq!(match_path_pattern("@app/*", "@app/ui/button"), Some("ui/button"));
+        assert_eq!(match_path_pattern("@config", "@config"), Some(""));
+        assert_eq!(match_path_pattern("@app/*", "@other/x"), None);
+    }
+}
@@ -1,6 +1,7 @@
 //! Review result structures
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// Result of a code review
@@ -27,10 +28,99 @@ pub struct ReviewResult {
     /// The diff or content that was reviewed
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reviewed_content: Option<String>,
+
+    /// Token and latency accounting for the AI call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<ReviewMetrics>,
+
+    /// Deterministic findings from static checks, reported alongside the AI review
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub findings: Vec<Finding>,
+}
+
+/// A finding produced by a deterministic check rather than the AI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    /// Check that produced the finding (e.g. "architecture-rule")
+    pub check: String,
+    /// How serious the finding is
+    pub severity: ReviewSeverity,
+    /// Human-readable description
+    pub message: String,
+    /// 1-based line the finding refers to, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+}
+
+/// Token and latency accounting for a single review
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReviewMetrics {
+    /// Backend that produced the response (after any fallback)
+    pub backend: String,
+    /// Model override, if one was set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Prompt type name (e.g. "default", "quick", "multi")
+    pub prompt_type: String,
+    /// Prompt size in characters
+    pub prompt_chars: usize,
+    /// Estimated prompt tokens
+    pub prompt_tokens: u64,
+    /// Response size in characters
+    pub response_chars: usize,
+    /// Wall-clock latency of the AI call(s) in milliseconds
+    pub latency_ms: u64,
+    /// Whether the reviewed file's analysis came from the parse cache
+    /// (context was built without parsing it again)
+    pub cache_hit: bool,
+    /// Context sections included in the prompt
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context_sections: Vec<String>,
+}
+
+/// Aggregated metrics for one prompt type
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PromptTypeStats {
+    /// Number of reviews with metrics
+    pub reviews: usize,
+    /// Total prompt characters
+    pub prompt_chars: usize,
+    /// Total estimated prompt tokens
+    pub prompt_tokens: u64,
+    /// Total response characters
+    pub response_chars: usize,
+    /// Total latency in milliseconds
+    pub latency_ms: u64,
+    /// Reviews whose file analysis came from the parse cache
+    pub cache_hits: usize,
+}
+
+impl PromptTypeStats {
+    /// Fold one review's metrics into the totals
+    fn add(&mut self, metrics: &ReviewMetrics) {
+        self.reviews += 1;
+        self.prompt_chars += metrics.prompt_chars;
+        self.prompt_tokens += metrics.prompt_tokens;
+        self.response_chars += metrics.response_chars;
+        self.latency_ms += metrics.latency_ms;
+        if metrics.cache_hit {
+            self.cache_hits += 1;
+        }
+    }
+
+    /// Average latency per review in milliseconds
+    pub fn avg_latency_ms(&self) -> u64 {
+        self.latency_ms.checked_div(self.reviews as u64).unwrap_or(0)
+    }
+
+    /// Average estimated prompt tokens per review
+    pub fn avg_prompt_tokens(&self) -> u64 {
+        self.prompt_tokens.checked_div(self.reviews as u64).unwrap_or(0)
+    }
 }
 
 /// Severity level of review findings
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum ReviewSeverity {
     /// No issues found
@@ -64,6 +154,8 @@ impl ReviewResult {
             has_issues,
             severity,
             reviewed_content: None,
+            metrics: None,
+            findings: Vec::new(),
         }
     }
 
@@ -73,6 +165,22 @@ impl ReviewResult {
         self
     }
 
+    /// Attach token and latency metrics
+    pub fn with_metrics(mut self, metrics: ReviewMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Attach deterministic findings, raising the severity to the worst one
+    pub fn with_findings(mut self, findings: impl IntoIterator<Item = Finding>) -> Self {
+        self.findings.extend(findings);
+        let worst = self.findings.iter().map(|f| f.severity).max().unwrap_or_default();
+        if worst > self.severity {
+            self = self.with_severity(worst);
+        }
+        self
+    }
+
     /// Create a result with explicit severity (for testing or manual override)
     ///
     /// This method allows setting the severity directly, bypassing automatic detection.
@@ -185,6 +293,14 @@ pub struct ReviewSummary {
     /// Warning count
     pub warning_count: usize,
 
+    /// Metrics totals across all prompt types
+    #[serde(default)]
+    pub totals: PromptTypeStats,
+
+    /// Metrics aggregated per prompt type
+    #[serde(default)]
+    pub by_prompt_type: BTreeMap<String, PromptTypeStats>,
+
     /// Individual results
     pub results: Vec<ReviewResult>,
 }
@@ -213,6 +329,14 @@ impl ReviewSummary {
             }
         }
 
+        if let Some(ref metrics) = result.metrics {
+            self.totals.add(metrics);
+            self.by_prompt_type
+                .entry(metrics.prompt_type.clone())
+                .or_default()
+                .add(metrics);
+        }
+
         self.results.push(result);
     }
 
@@ -430,4 +554,65 @@ mod tests {
         assert!(result.has_issues);
         assert_eq!(result.severity, ReviewSeverity::Error);
     }
+
+    #[test]
+    fn with_findings_raises_severity() {
+        let finding = |severity| Finding {
+            check: "architecture-rule".into(),
+            severity,
+            message: "ui -> db".into(),
+            line: None,
+        };
+        let result = ReviewResult::new(PathBuf::from("test.rs"), "\u{2713} OK".into())
+            .with_findings([finding(ReviewSeverity::Warning)]);
+        assert!(result.has_issues);
+        assert_eq!(result.severity, ReviewSeverity::Warning);
+        assert_eq!(result.findings.len(), 1);
+
+        // A milder finding never lowers the AI's severity
+        let result = ReviewResult::new(PathBuf::from("test.rs"), "\u{1F6A8} Critical".into())
+            .with_findings([finding(ReviewSeverity::Info)]);
+        assert_eq!(result.severity, ReviewSeverity::Error);
+    }
+
+    // --- Metrics ---
+
+    fn metrics(prompt_type: &str, tokens: u64, latency_ms: u64) -> ReviewMetrics {
+        ReviewMetrics {
+            backend: "gemini".into(),
+            prompt_type: prompt_type.into(),
+            prompt_tokens: tokens,
+            latency_ms,
+            ..ReviewMetrics::default()
+        }
+    }
+
+    #[test]
+    fn metrics_round_trip_through_json() {
+        let result = ReviewResult::new(PathBuf::from("a.rs"), "\u{2713}".into())
+            .with_metrics(metrics("quick", 120, 800));
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.contains("\"prompt_tokens\":120"));
+
+        let back: ReviewResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.metrics, result.metrics);
+    }
+
+    #[test]
+    fn summary_aggregates_metrics_per_prompt_type() {
+        let mut summary = ReviewSummary::new();
+        summary.add(ReviewResult::new(PathBuf::from("a.rs"), "ok".into()).with_metrics(metrics("quick", 100, 1000)));
+        summary.add(ReviewResult::new(PathBuf::from("b.rs"), "ok".into()).with_metrics(metrics("quick", 300, 3000)));
+        summary.add(ReviewResult::new(PathBuf::from("c.rs"), "ok".into()).with_metrics(metrics("multi", 900, 9000)));
+        summary.add(ReviewResult::new(PathBuf::from("d.rs"), "ok".into()));
+
+        assert_eq!(summary.total_files, 4);
+        assert_eq!(summary.totals.reviews, 3);
+        assert_eq!(summary.totals.prompt_tokens, 1300);
+        let quick = &summary.by_prompt_type["quick"];
+        assert_eq!(quick.reviews, 2);
+        assert_eq!(quick.avg_latency_ms(), 2000);
+        assert_eq!(quick.avg_prompt_tokens(), 200);
+        assert_eq!(summary.by_prompt_type["multi"].latency_ms, 9000);
+    }
 }
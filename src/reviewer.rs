@@ -5,20 +5,32 @@ use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 
-use cli_ai_analyzer::{prompt as ai_prompt, AnalyzeOptions, Backend};
+use cli_ai_analyzer::Backend;
 use folder_watcher::FolderWatcher;
 
-use crate::context::gather_context;
+use crate::cache::parse_cache;
+use crate::context::{gather_context_with_graph, gather_context_with_resolver, gather_coupling_context};
 use crate::error::{CodeReviewError, Result};
 use crate::git::get_git_diff;
+use crate::graph::DependencyGraph;
+use crate::limits::{estimate_tokens, BudgetAction, UsageLimits};
+use crate::metrics::{analyze_metrics, FileMetrics, MetricThresholds};
+use crate::parser::symbols::supports_symbols;
+use crate::policy::{call_ai, is_backend_failure, CallOutcome, CallPolicy, CircuitBreaker};
 use crate::prompt::{
-    build_prompt, build_prompt_with_context, PromptType, DEFAULT_REVIEW_PROMPT,
-    SECURITY_REVIEW_PROMPT, ARCHITECTURE_REVIEW_PROMPT, PRINCIPLES_REVIEW_PROMPT,
+    build_prompt, build_prompt_with_context, format_architecture_drift, format_rule_violations,
+    PromptType, DEFAULT_REVIEW_PROMPT, QUICK_REVIEW_PROMPT,
+};
+use crate::resolver::ImportResolver;
+use crate::result::{Finding, ReviewMetrics, ReviewResult};
+use crate::rules::{ArchitectureRules, RuleViolation};
+use crate::snapshot::ArchitectureDrift;
+use crate::symbols::{
+    changed_lines, gather_symbol_context, gather_symbol_context_with_graph, DEFAULT_SYMBOL_BUDGET,
 };
-use crate::result::ReviewResult;
 use crate::utils::fs::SOURCE_EXTENSIONS;
 
 /// Build the review prompt for a file, handling context gathering and prompt construction.
@@ -33,15 +45,18 @@ use crate::utils::fs::SOURCE_EXTENSIONS;
 /// * `content` - The content to review (git diff or file content)
 /// * `config` - Review configuration containing prompt template and context settings
 /// * `base_path` - Optional base path for context gathering (defaults to file's parent)
+/// * `checks` - Results of the deterministic checks, passed to the AI as known issues
 ///
 /// # Returns
-/// The fully constructed prompt string ready to send to the AI
+/// The fully constructed prompt string ready to send to the AI, and the
+/// names of the context sections it includes
 fn build_review_prompt(
     path: &Path,
     content: &str,
     config: &ReviewConfig,
     base_path: Option<&Path>,
-) -> String {
+    checks: &StaticChecks,
+) -> (String, Vec<String>) {
     // Extract file name
     let file_name = path
         .file_name()
@@ -52,12 +67,44 @@ fn build_review_prompt(
     let base = base_path.unwrap_or_else(|| path.parent().unwrap_or(Path::new(".")));
 
     // Gather context if enabled
-    let context_str = config
+    let context = config
         .context_enabled
-        .then(|| gather_context(path, base, config.context_depth).ok())
+        .then(|| match config.dependency_graph.as_ref().and_then(|g| g.read().ok()) {
+            Some(graph) => gather_context_with_graph(path, base, config.context_depth, &graph)
+                .ok()
+                .map(|mut ctx| {
+                    // Coupling balance is an architecture concern
+                    if config.prompt_type == PromptType::Architecture {
+                        ctx.coupling = gather_coupling_context(path, config.context_depth, &graph);
+                    }
+                    ctx
+                }),
+            None => gather_context_with_resolver(path, base, config.context_depth, &config.resolver(base)).ok(),
+        })
         .flatten()
-        .filter(|ctx| !ctx.is_empty())
-        .map(|ctx| ctx.to_prompt_string());
+        .filter(|ctx| !ctx.is_empty());
+    let mut sections: Vec<String> = context
+        .as_ref()
+        .map(|ctx| ctx.section_names().into_iter().map(String::from).collect())
+        .unwrap_or_default();
+    let mut context_str = context.map(|ctx| ctx.to_prompt_string());
+
+    // Definitions the changed lines use and callers of changed functions
+    // (`content` is the diff when there is one)
+    if config.context_enabled && config.symbol_budget > 0 && supports_symbols(path) {
+        let symbols = match config.dependency_graph.as_ref().and_then(|g| g.read().ok()) {
+            Some(graph) => gather_symbol_context_with_graph(path, Some(content), &graph, config.symbol_budget),
+            None => gather_symbol_context(path, base, Some(content), &config.resolver(base), config.symbol_budget),
+        };
+        if !symbols.is_empty() {
+            let ctx = context_str.get_or_insert_with(String::new);
+            if !ctx.is_empty() {
+                ctx.push('\n');
+            }
+            ctx.push_str(&symbols.to_prompt_string());
+            sections.push("symbols".to_string());
+        }
+    }
 
     // Build prompt with or without context
     let mut prompt = match context_str {
@@ -65,25 +112,146 @@ fn build_review_prompt(
         None => build_prompt(&config.prompt_template, &file_name, content),
     };
 
+    // Deterministic rule violations, so the AI does not have to find them
+    if !checks.violations.is_empty() {
+        prompt.push_str("\n\n");
+        prompt.push_str(&format_rule_violations(&checks.violations));
+        sections.push("rule_violations".to_string());
+    }
+
+    // Measured sizes and complexity, so the AI does not have to estimate them
+    if let (Some(metrics), Some(thresholds)) = (&checks.metrics, &config.metric_thresholds) {
+        prompt.push_str("\n\n");
+        prompt.push_str(&metrics.to_prompt_string(thresholds));
+        sections.push("metrics".to_string());
+    }
+
+    // How the change set moves the architecture away from the base commit
+    if let Some(ref drift) = config.architecture_drift {
+        prompt.push_str("\n\n");
+        prompt.push_str(&format_architecture_drift(drift));
+        sections.push("architecture_drift".to_string());
+    }
+
     // Inject extra context from REVIEW_EXTRA_CONTEXT env var (file path)
     if let Ok(extra_path) = std::env::var("REVIEW_EXTRA_CONTEXT") {
         if let Ok(extra) = fs::read_to_string(&extra_path) {
             if !extra.trim().is_empty() {
                 prompt.push_str("\n\n## Additional Review Context\n");
                 prompt.push_str(&extra);
+                sections.push("extra".to_string());
             }
         }
     }
 
-    prompt
+    (prompt, sections)
+}
+
+/// Results of the deterministic checks run before the AI review
+#[derive(Debug, Default)]
+struct StaticChecks {
+    /// Architecture rule violations
+    violations: Vec<RuleViolation>,
+    /// Metrics of the functions under review (the changed ones for a diff)
+    metrics: Option<FileMetrics>,
+}
+
+impl StaticChecks {
+    /// Run the configured checks on a file; `content` is its diff or source
+    fn run(path: &Path, content: &str, config: &ReviewConfig, base_path: Option<&Path>) -> Self {
+        Self {
+            violations: check_rules(path, config, base_path),
+            metrics: config.metric_thresholds.and_then(|_| file_metrics(path, content)),
+        }
+    }
+
+    /// Violations and threshold breaches as findings
+    fn findings(&self, config: &ReviewConfig) -> Vec<Finding> {
+        let mut findings: Vec<Finding> = self.violations.iter().map(RuleViolation::to_finding).collect();
+        if let (Some(metrics), Some(thresholds)) = (&self.metrics, &config.metric_thresholds) {
+            findings.extend(metrics.findings(thresholds));
+        }
+        findings
+    }
+}
+
+/// Architecture rule violations of a file (empty when no rules are configured)
+fn check_rules(path: &Path, config: &ReviewConfig, base_path: Option<&Path>) -> Vec<RuleViolation> {
+    let Some(ref rules) = config.architecture_rules else {
+        return Vec::new();
+    };
+    let base = base_path.unwrap_or_else(|| path.parent().unwrap_or(Path::new(".")));
+    rules.check_file(path, base, &config.resolver(base))
+}
+
+/// Metrics of a file, limited to the changed functions when `content` is a diff
+fn file_metrics(path: &Path, content: &str) -> Option<FileMetrics> {
+    let source = fs::read_to_string(path).ok()?;
+    let metrics = analyze_metrics(path, &source)?;
+    let changed = changed_lines(content);
+    Some(if changed.is_empty() { metrics } else { metrics.touching(&changed) })
+}
+
+/// Build usage metrics for a review from its prompt(s) and response(s)
+fn review_metrics(
+    config: &ReviewConfig,
+    backend: Backend,
+    prompts: &[&str],
+    responses: &[&str],
+    latency: Duration,
+    cache_hit: bool,
+    context_sections: Vec<String>,
+) -> ReviewMetrics {
+    ReviewMetrics {
+        backend: format!("{:?}", backend).to_lowercase(),
+        model: config.model.clone(),
+        prompt_type: config.prompt_type.name().to_string(),
+        prompt_chars: prompts.iter().map(|p| p.chars().count()).sum(),
+        prompt_tokens: prompts.iter().map(|p| estimate_tokens(p)).sum(),
+        response_chars: responses.iter().map(|r| r.chars().count()).sum(),
+        latency_ms: latency.as_millis() as u64,
+        cache_hit,
+        context_sections,
+    }
+}
+
+/// Send a prompt through the configured usage limits, if any
+///
+/// `degraded` marks a prompt that was already downgraded to the quick prompt,
+/// which is allowed past an exhausted budget under `BudgetAction::Quick`.
+fn send_prompt(prompt: &str, config: &ReviewConfig, degraded: bool) -> Result<CallOutcome> {
+    let model = config.model.as_deref();
+    match config.limits {
+        Some(ref limits) if degraded => {
+            limits.call_degraded(prompt, config.backend, model, &config.call_policy)
+        }
+        Some(ref limits) => limits.call(prompt, config.backend, model, &config.call_policy),
+        None => call_ai(prompt, config.backend, model, &config.call_policy),
+    }
+}
+
+/// Check the spend budget before a review
+///
+/// Returns `Ok(true)` when the budget is exhausted and the review should fall
+/// back to the quick prompt, or `BudgetExhausted` when it should be skipped.
+fn check_budget(config: &ReviewConfig) -> Result<bool> {
+    match config.limits {
+        Some(ref limits) if limits.is_exhausted() => match limits.exhausted_action() {
+            BudgetAction::Quick => Ok(true),
+            BudgetAction::Skip => Err(CodeReviewError::BudgetExhausted(
+                "review skipped".to_string(),
+            )),
+        },
+        _ => Ok(false),
+    }
 }
 
 /// Prompt labels and templates for multi-perspective review
-const MULTI_REVIEW_PROMPTS: &[(&str, &str)] = &[
-    ("Security", SECURITY_REVIEW_PROMPT),
-    ("Architecture", ARCHITECTURE_REVIEW_PROMPT),
-    ("Principles", PRINCIPLES_REVIEW_PROMPT),
-    ("Default", DEFAULT_REVIEW_PROMPT),
+const MULTI_REVIEW_PROMPTS: &[(&str, PromptType)] = &[
+    ("Security", PromptType::Security),
+    ("Architecture", PromptType::Architecture),
+    ("Principles", PromptType::Principles),
+    ("Default", PromptType::Default),
 ];
 
 /// Run multiple review perspectives in parallel and merge results.
@@ -97,6 +265,12 @@ pub fn perform_multi_review(
 ) -> Result<ReviewResult> {
     use rayon::prelude::*;
 
+    // Four perspectives are too expensive once the budget is gone
+    if check_budget(config)? {
+        log::info!("Spend budget exhausted, falling back to quick review for {:?}", path);
+        return perform_review(path, config, base_path);
+    }
+
     // Get content once (shared across all perspectives)
     let (content, is_diff) = match get_git_diff(path) {
         Some(diff) => (diff, true),
@@ -121,35 +295,68 @@ pub fn perform_multi_review(
         &labeled_content
     };
 
+    let cache_hit = parse_cache().is_fresh(path);
+    let checks = StaticChecks::run(path, &content, config, base_path);
+
     // Run all perspectives in parallel
-    let results: Vec<(String, std::result::Result<String, String>)> = MULTI_REVIEW_PROMPTS
+    let started = Instant::now();
+    type PerspectiveResult = (String, String, Vec<String>, std::result::Result<CallOutcome, String>);
+    let results: Vec<PerspectiveResult> = MULTI_REVIEW_PROMPTS
         .par_iter()
-        .map(|(label, template)| {
+        .map(|(label, prompt_type)| {
             let mut per_config = config.clone();
-            per_config.prompt_template = template.to_string();
-            let prompt = build_review_prompt(path, prompt_content, &per_config, base_path);
-            let options = if let Some(ref m) = config.model {
-                AnalyzeOptions::with_model(m).with_backend(config.backend)
-            } else {
-                AnalyzeOptions::default().with_backend(config.backend)
-            };
-            let result = ai_prompt(&prompt, options).map_err(|e| e.to_string());
-            (label.to_string(), result)
+            per_config.prompt_type = *prompt_type;
+            per_config.prompt_template = prompt_type.template().to_string();
+            let (prompt, sections) = build_review_prompt(path, prompt_content, &per_config, base_path, &checks);
+            let result = send_prompt(&prompt, config, false).map_err(|e| e.to_string());
+            (label.to_string(), prompt, sections, result)
         })
         .collect();
+    let latency = started.elapsed();
 
     // Merge results
     let mut merged = String::new();
-    for (label, result) in &results {
+    for (label, _, _, result) in &results {
         merged.push_str(&format!("## {} Review\n\n", label));
         match result {
-            Ok(review) => merged.push_str(review),
+            Ok(outcome) => merged.push_str(&outcome.response),
             Err(e) => merged.push_str(&format!("⚠ Review failed: {}", e)),
         }
         merged.push_str("\n\n---\n\n");
     }
 
-    Ok(ReviewResult::new(path.to_path_buf(), merged).with_content(content))
+    // Perspectives share the same context, so any one of them has the sections
+    let context_sections = results
+        .first()
+        .map(|(_, _, sections, _)| sections.clone())
+        .unwrap_or_default();
+    let backend = results
+        .iter()
+        .find_map(|(_, _, _, r)| r.as_ref().ok().map(|o| o.backend))
+        .unwrap_or(config.backend);
+    let prompts: Vec<&str> = results.iter().map(|(_, p, _, _)| p.as_str()).collect();
+    let responses: Vec<&str> = results
+        .iter()
+        .filter_map(|(_, _, _, r)| r.as_ref().ok().map(|o| o.response.as_str()))
+        .collect();
+    let multi_config = ReviewConfig {
+        prompt_type: PromptType::Multi,
+        ..config.clone()
+    };
+    let metrics = review_metrics(
+        &multi_config,
+        backend,
+        &prompts,
+        &responses,
+        latency,
+        cache_hit,
+        context_sections,
+    );
+
+    Ok(ReviewResult::new(path.to_path_buf(), merged)
+        .with_content(content)
+        .with_metrics(metrics)
+        .with_findings(checks.findings(config)))
 }
 
 /// Configuration for review execution
@@ -157,9 +364,63 @@ pub fn perform_multi_review(
 pub struct ReviewConfig {
     pub backend: Backend,
     pub model: Option<String>,
+    /// Prompt type the template came from (`Custom` for caller-supplied templates)
+    pub prompt_type: PromptType,
     pub prompt_template: String,
     pub context_enabled: bool,
     pub context_depth: usize,
+    /// Extra C/C++ include directories used to resolve includers (relative to the project root)
+    pub include_dirs: Vec<PathBuf>,
+    /// Python package roots absolute imports are resolved against (relative to the project root)
+    pub python_roots: Vec<PathBuf>,
+    /// Timeout, retry and fallback policy for AI calls
+    pub call_policy: CallPolicy,
+    /// Shared rate limit and spend budget (unlimited when `None`)
+    pub limits: Option<Arc<UsageLimits>>,
+    /// Project dependency graph used for context instead of re-walking the
+    /// tree per file (kept current by the watcher)
+    pub dependency_graph: Option<Arc<RwLock<DependencyGraph>>>,
+    /// Architecture rules checked on every review (none when `None`)
+    pub architecture_rules: Option<Arc<ArchitectureRules>>,
+    /// Architecture drift of the change set under review, added to every prompt
+    pub architecture_drift: Option<Arc<ArchitectureDrift>>,
+    /// Prompt budget (characters) for referenced definitions and call
+    /// sites when context is enabled; 0 disables symbol context
+    pub symbol_budget: usize,
+    /// Limits for per-function metrics; breaches become findings (not
+    /// measured when `None`, the default)
+    pub metric_thresholds: Option<MetricThresholds>,
+}
+
+impl ReviewConfig {
+    /// Import resolver for the project at `base_path` with these settings
+    fn resolver(&self, base_path: &Path) -> ImportResolver {
+        ImportResolver::new(base_path)
+            .with_include_dirs(&self.include_dirs)
+            .with_python_roots(&self.python_roots)
+    }
+}
+
+impl Default for ReviewConfig {
+    fn default() -> Self {
+        Self {
+            backend: Backend::default(),
+            model: None,
+            prompt_type: PromptType::Default,
+            prompt_template: DEFAULT_REVIEW_PROMPT.to_string(),
+            context_enabled: false,
+            context_depth: 50,
+            include_dirs: Vec::new(),
+            python_roots: Vec::new(),
+            call_policy: CallPolicy::default(),
+            limits: None,
+            dependency_graph: None,
+            architecture_rules: None,
+            architecture_drift: None,
+            symbol_budget: DEFAULT_SYMBOL_BUDGET,
+            metric_thresholds: None,
+        }
+    }
 }
 
 /// Core review logic - performs AI-powered code review on a file
@@ -170,6 +431,22 @@ pub(crate) fn perform_review(
     config: &ReviewConfig,
     base_path: Option<&Path>,
 ) -> Result<ReviewResult> {
+    // Degrade to a context-free quick review when the budget is exhausted
+    let degraded = check_budget(config)?;
+    let quick_config;
+    let config = if degraded {
+        log::info!("Spend budget exhausted, using quick review for {:?}", path);
+        quick_config = ReviewConfig {
+            prompt_type: PromptType::Quick,
+            prompt_template: QUICK_REVIEW_PROMPT.to_string(),
+            context_enabled: false,
+            ..config.clone()
+        };
+        &quick_config
+    } else {
+        config
+    };
+
     // Get content (git diff or file content), tracking which source it came from
     let (content, is_diff) = match get_git_diff(path) {
         Some(diff) => (diff, true),
@@ -197,17 +474,26 @@ pub(crate) fn perform_review(
     };
 
     // Build the prompt using the helper function
-    let prompt = build_review_prompt(path, prompt_content, config, base_path);
+    let cache_hit = parse_cache().is_fresh(path);
+    let checks = StaticChecks::run(path, &content, config, base_path);
+    let (prompt, context_sections) = build_review_prompt(path, prompt_content, config, base_path, &checks);
 
     // Run the review
-    let options = if let Some(ref m) = config.model {
-        AnalyzeOptions::with_model(m).with_backend(config.backend)
-    } else {
-        AnalyzeOptions::default().with_backend(config.backend)
-    };
-
-    let review = ai_prompt(&prompt, options)?;
-    Ok(ReviewResult::new(path.to_path_buf(), review).with_content(content))
+    let started = Instant::now();
+    let outcome = send_prompt(&prompt, config, degraded)?;
+    let metrics = review_metrics(
+        config,
+        outcome.backend,
+        &[&prompt],
+        &[&outcome.response],
+        started.elapsed(),
+        cache_hit,
+        context_sections,
+    );
+    Ok(ReviewResult::new(path.to_path_buf(), outcome.response)
+        .with_content(content)
+        .with_metrics(metrics)
+        .with_findings(checks.findings(config)))
 }
 
 /// Default debounce duration in milliseconds
@@ -243,6 +529,8 @@ pub struct CodeReviewer {
     running: Arc<AtomicBool>,
     /// Consolidated shared state for debouncing and logging
     shared_state: Arc<Mutex<SharedState>>,
+    /// Circuit breaker for watch mode (created on start)
+    breaker: Option<Arc<CircuitBreaker>>,
 }
 
 impl CodeReviewer {
@@ -257,13 +545,7 @@ impl CodeReviewer {
 
         Ok(Self {
             path: path.to_path_buf(),
-            config: Arc::new(ReviewConfig {
-                backend: Backend::default(),
-                model: None,
-                prompt_template: DEFAULT_REVIEW_PROMPT.to_string(),
-                context_enabled: false,
-                context_depth: 50,
-            }),
+            config: Arc::new(ReviewConfig::default()),
             extensions: SOURCE_EXTENSIONS.iter().map(|s| s.to_string()).collect(),
             multi_mode: false,
             debounce_ms: DEFAULT_DEBOUNCE_MS,
@@ -274,6 +556,7 @@ impl CodeReviewer {
                 last_review: HashMap::new(),
                 log_path: None,
             })),
+            breaker: None,
         })
     }
 
@@ -297,15 +580,19 @@ impl CodeReviewer {
 
     /// Set a custom prompt template
     pub fn with_prompt(mut self, template: impl Into<String>) -> Self {
-        Arc::make_mut(&mut self.config).prompt_template = template.into();
+        let config = Arc::make_mut(&mut self.config);
+        config.prompt_type = PromptType::Custom;
+        config.prompt_template = template.into();
         self
     }
 
     /// Set the prompt type
     pub fn with_prompt_type(mut self, prompt_type: PromptType) -> Self {
         self.multi_mode = prompt_type == PromptType::Multi;
+        let config = Arc::make_mut(&mut self.config);
+        config.prompt_type = prompt_type;
         if prompt_type != PromptType::Custom {
-            Arc::make_mut(&mut self.config).prompt_template = prompt_type.template().to_string();
+            config.prompt_template = prompt_type.template().to_string();
         }
         self
     }
@@ -328,6 +615,76 @@ impl CodeReviewer {
         self
     }
 
+    /// Set the prompt budget (characters) for symbol-level context
+    ///
+    /// Only used when context is enabled; 0 disables it.
+    pub fn with_symbol_budget(mut self, budget: usize) -> Self {
+        Arc::make_mut(&mut self.config).symbol_budget = budget;
+        self
+    }
+
+    /// Set C/C++ include directories used when listing a header's includers
+    pub fn with_include_dirs(mut self, dirs: &[&str]) -> Self {
+        Arc::make_mut(&mut self.config).include_dirs = dirs.iter().map(PathBuf::from).collect();
+        self
+    }
+
+    /// Set Python package roots used to resolve absolute imports
+    ///
+    /// Defaults to the project root and its `src/` directory.
+    pub fn with_python_roots(mut self, roots: &[&str]) -> Self {
+        Arc::make_mut(&mut self.config).python_roots = roots.iter().map(PathBuf::from).collect();
+        self
+    }
+
+    /// Use a prebuilt dependency graph for context gathering
+    ///
+    /// Without one, watch mode builds the graph on start when context is
+    /// enabled, and one-off reviews resolve dependencies per file.
+    pub fn with_dependency_graph(mut self, graph: DependencyGraph) -> Self {
+        Arc::make_mut(&mut self.config).dependency_graph = Some(Arc::new(RwLock::new(graph)));
+        self
+    }
+
+    /// Check every reviewed file against architecture rules
+    ///
+    /// Violations are reported as findings on the result and passed to the
+    /// AI as known violations.
+    pub fn with_architecture_rules(mut self, rules: ArchitectureRules) -> Self {
+        Arc::make_mut(&mut self.config).architecture_rules = (!rules.is_empty()).then(|| Arc::new(rules));
+        self
+    }
+
+    /// Set the limits for per-function metrics, or `None` to skip measuring
+    /// (the default)
+    ///
+    /// Breaches are reported as findings and the measured values are shown
+    /// to the AI as a table.
+    pub fn with_metric_thresholds(mut self, thresholds: Option<MetricThresholds>) -> Self {
+        Arc::make_mut(&mut self.config).metric_thresholds = thresholds;
+        self
+    }
+
+    /// Show the AI how the change set under review drifts the architecture
+    ///
+    /// Empty drift is ignored.
+    pub fn with_architecture_drift(mut self, drift: ArchitectureDrift) -> Self {
+        Arc::make_mut(&mut self.config).architecture_drift = (!drift.is_empty()).then(|| Arc::new(drift));
+        self
+    }
+
+    /// Set the timeout, retry and fallback policy for AI calls
+    pub fn with_call_policy(mut self, policy: CallPolicy) -> Self {
+        Arc::make_mut(&mut self.config).call_policy = policy;
+        self
+    }
+
+    /// Share a rate limiter and spend budget with other reviewers
+    pub fn with_limits(mut self, limits: Arc<UsageLimits>) -> Self {
+        Arc::make_mut(&mut self.config).limits = Some(limits);
+        self
+    }
+
     /// Set a log file path for review results
     pub fn with_log_file(self, path: impl Into<PathBuf>) -> Self {
         let log_path = path.into();
@@ -357,12 +714,30 @@ impl CodeReviewer {
         &self.path
     }
 
+    /// Check if the watch-mode circuit breaker is currently open
+    pub fn is_circuit_open(&self) -> bool {
+        self.breaker.as_ref().is_some_and(|b| b.is_open())
+    }
+
     /// Start the code reviewer
     pub fn start(&mut self) -> Result<()> {
         if self.running.load(Ordering::SeqCst) {
             return Err(CodeReviewError::AlreadyRunning);
         }
 
+        // Reuse files parsed by earlier runs
+        match parse_cache().load(&self.path) {
+            Ok(loaded) => log::debug!("Parse cache: {} files loaded", loaded),
+            Err(e) => log::warn!("Failed to load parse cache: {}", e),
+        }
+
+        if self.config.context_enabled && self.config.dependency_graph.is_none() {
+            let graph = DependencyGraph::build_with_resolver(&self.path, self.config.resolver(&self.path));
+            log::info!("Dependency graph built: {} files", graph.len());
+            Arc::make_mut(&mut self.config).dependency_graph = Some(Arc::new(RwLock::new(graph)));
+            save_parse_cache(&self.path);
+        }
+
         let extensions = self.extensions.clone();
         let review_config = Arc::clone(&self.config);
         let debounce_ms = self.debounce_ms;
@@ -370,6 +745,8 @@ impl CodeReviewer {
         let on_review = self.on_review.clone();
         let shared_state = Arc::clone(&self.shared_state);
         let running = Arc::clone(&self.running);
+        let breaker = Arc::new(CircuitBreaker::from_policy(&self.config.call_policy));
+        let watch_breaker = Arc::clone(&breaker);
 
         // Create the folder watcher
         let ext_refs: Vec<&str> = extensions.iter().map(|s| s.as_str()).collect();
@@ -377,17 +754,31 @@ impl CodeReviewer {
         let watcher = FolderWatcher::new(&self.path)?
             .with_filter(&ext_refs)
             .on_modify(move |path| {
+                // Drop the stale analysis and keep the graph current even
+                // for edits that are not reviewed
+                parse_cache().invalidate(path);
+                if let Some(mut graph) = review_config.dependency_graph.as_ref().and_then(|g| g.write().ok()) {
+                    graph.update_file(path);
+                }
                 if !check_debounce(path, &shared_state, debounce_ms) {
                     return;
                 }
                 if !running.load(Ordering::SeqCst) {
                     return;
                 }
-                handle_file_change(path, &review_config, &base_path, &shared_state, &on_review);
+                handle_file_change(
+                    path,
+                    &review_config,
+                    &base_path,
+                    &shared_state,
+                    &on_review,
+                    &watch_breaker,
+                );
             });
 
         watcher.start()?;
         self.watcher = Some(watcher);
+        self.breaker = Some(breaker);
         self.running.store(true, Ordering::SeqCst);
 
         log::info!("Code reviewer started for {:?}", self.path);
@@ -405,6 +796,7 @@ impl CodeReviewer {
         }
 
         self.running.store(false, Ordering::SeqCst);
+        save_parse_cache(&self.path);
         log::info!("Code reviewer stopped");
         Ok(())
     }
@@ -427,6 +819,13 @@ impl Drop for CodeReviewer {
     }
 }
 
+/// Persist the shared parse cache of the project at `base_path`
+fn save_parse_cache(base_path: &Path) {
+    if let Err(e) = parse_cache().save(base_path) {
+        log::warn!("Failed to save parse cache: {}", e);
+    }
+}
+
 /// Append a review result to the log file (JSON Lines format)
 fn append_review_log(log_path: &Path, result: &ReviewResult) -> Result<()> {
     let json = serde_json::to_string(result)?;
@@ -488,18 +887,39 @@ fn process_review_result(
 }
 
 /// Handle file modification event - perform review and process result
+///
+/// Skips the review while the circuit breaker is open, and feeds the
+/// outcome back into the breaker.
 fn handle_file_change(
     path: &Path,
     config: &Arc<ReviewConfig>,
     base_path: &Path,
     shared_state: &Arc<Mutex<SharedState>>,
     on_review: &Option<Arc<ReviewCallback>>,
+    breaker: &CircuitBreaker,
 ) {
+    if let Err(e) = breaker.allow() {
+        log::warn!("Skipping review for {:?}: {}", path, e);
+        return;
+    }
+
     match perform_review(path, config.as_ref(), Some(base_path)) {
         Ok(result) => {
+            breaker.record_success();
             process_review_result(result, shared_state, on_review);
         }
+        Err(e) if is_backend_failure(&e) => {
+            breaker.record_failure();
+            log::error!("Review error for {:?}: {}", path, e);
+        }
+        // Running out of budget says nothing about backend health
+        Err(e @ CodeReviewError::BudgetExhausted(_)) => {
+            breaker.release();
+            log::info!("Skipping review for {:?}: {}", path, e);
+        }
+        // Neither does a file that cannot be read or parsed
         Err(e) => {
+            breaker.release();
             log::error!("Review error for {:?}: {}", path, e);
         }
     }
@@ -541,16 +961,13 @@ mod tests {
     #[test]
     fn test_build_review_prompt_without_context() {
         let config = ReviewConfig {
-            backend: Backend::default(),
-            model: None,
             prompt_template: "Review {file_name}: {content}".to_string(),
-            context_enabled: false,
-            context_depth: 50,
+            ..ReviewConfig::default()
         };
         let path = Path::new("/test/example.rs");
         let content = "fn main() {}";
 
-        let prompt = build_review_prompt(path, content, &config, None);
+        let (prompt, _) = build_review_prompt(path, content, &config, None, &StaticChecks::default());
 
         assert!(prompt.contains("example.rs"));
         assert!(prompt.contains("fn main() {}"));
@@ -559,18 +976,161 @@ mod tests {
     #[test]
     fn test_build_review_prompt_unknown_filename() {
         let config = ReviewConfig {
-            backend: Backend::default(),
-            model: None,
             prompt_template: "Review {file_name}: {content}".to_string(),
-            context_enabled: false,
-            context_depth: 50,
+            ..ReviewConfig::default()
         };
         // Path with no file name component
         let path = Path::new("/");
         let content = "test content";
 
-        let prompt = build_review_prompt(path, content, &config, None);
+        let (prompt, _) = build_review_prompt(path, content, &config, None, &StaticChecks::default());
 
         assert!(prompt.contains("unknown"));
     }
+
+    #[test]
+    fn test_build_review_prompt_with_rule_violations() {
+        let config = ReviewConfig {
+            prompt_template: "Review {file_name}: {content}".to_string(),
+            ..ReviewConfig::default()
+        };
+        let violation = RuleViolation {
+            file: "src/ui/view.rs".to_string(),
+            import: "crate::db::conn".to_string(),
+            rule: "ui/** -/-> db/**".to_string(),
+            reason: None,
+            severity: crate::result::ReviewSeverity::Warning,
+        };
+
+        let (prompt, sections) = build_review_prompt(
+            Path::new("/test/view.rs"),
+            "fn f() {}",
+            &config,
+            None,
+            &StaticChecks {
+                violations: vec![violation],
+                metrics: None,
+            },
+        );
+
+        assert!(prompt.contains("既知のアーキテクチャ違反"));
+        assert!(prompt.contains("src/ui/view.rs imports crate::db::conn (ui/** -/-> db/**)"));
+        assert_eq!(sections, vec!["rule_violations"]);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_static_checks_metrics() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, "fn small() {}\n\nfn wide(a: u8, b: u8, c: u8, d: u8, e: u8, f: u8) {}\n").unwrap();
+        let config = ReviewConfig {
+            prompt_template: "Review {file_name}: {content}".to_string(),
+            metric_thresholds: Some(MetricThresholds::default()),
+            ..ReviewConfig::default()
+        };
+
+        // A diff touching only `small` measures only `small`
+        let checks = StaticChecks::run(&path, "@@ -1 +1 @@\n-fn small() { }\n+fn small() {}\n", &config, None);
+        assert_eq!(checks.metrics.as_ref().unwrap().functions.len(), 1);
+        assert!(checks.findings(&config).is_empty());
+
+        // The whole file reports the parameter count of `wide`
+        let checks = StaticChecks::run(&path, "fn small() {}", &config, None);
+        let findings = checks.findings(&config);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].check, crate::metrics::METRICS_CHECK);
+        assert_eq!(findings[0].line, Some(3));
+
+        let (prompt, sections) = build_review_prompt(&path, "fn small() {}", &config, None, &checks);
+        assert!(prompt.contains("| wide | 3 | 1 | 0 | 1 | 6 ⚠ |"));
+        assert_eq!(sections, vec!["metrics"]);
+
+        // Not measured unless enabled, so the default never raises the severity
+        let disabled = ReviewConfig { metric_thresholds: None, ..config };
+        assert!(StaticChecks::run(&path, "fn small() {}", &disabled, None).metrics.is_none());
+        assert!(ReviewConfig::default().metric_thresholds.is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_build_review_prompt_with_symbols() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("lib.rs"), "mod model;\nmod store;\n").unwrap();
+        fs::write(dir.path().join("model.rs"), "pub struct Item {\n    pub id: u32,\n}\n").unwrap();
+        let store = dir.path().join("store.rs");
+        fs::write(&store, "use crate::model::Item;\n\npub fn first() -> Item {\n    Item { id: 1 }\n}\n").unwrap();
+
+        let config = ReviewConfig {
+            prompt_template: "{context}\nReview {file_name}: {content}".to_string(),
+            context_enabled: true,
+            ..ReviewConfig::default()
+        };
+        let diff = "@@ -4 +4 @@\n-    Item { id: 0 }\n+    Item { id: 1 }\n";
+        let (prompt, sections) = build_review_prompt(&store, diff, &config, Some(dir.path()), &StaticChecks::default());
+
+        assert!(prompt.contains("## 変更箇所が参照する定義"));
+        assert!(prompt.contains("### Item (struct, model.rs:1)"));
+        assert!(sections.contains(&"symbols".to_string()));
+
+        let disabled = ReviewConfig { symbol_budget: 0, ..config };
+        let (_, sections) = build_review_prompt(&store, diff, &disabled, Some(dir.path()), &StaticChecks::default());
+        assert!(!sections.contains(&"symbols".to_string()));
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_coupling_only_in_architecture_prompt() {
+        let dir = tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(src.join("io/net")).unwrap();
+        fs::write(src.join("lib.rs"), "mod io;\nmod store;\n").unwrap();
+        fs::write(src.join("store.rs"), "pub(super) fn hidden() {}\n").unwrap();
+        let socket = src.join("io/net/socket.rs");
+        fs::write(&socket, "use crate::store::hidden;\n").unwrap();
+        let graph = DependencyGraph::build(dir.path());
+
+        let config = ReviewConfig {
+            prompt_template: "{context}\nReview {file_name}: {content}".to_string(),
+            context_enabled: true,
+            dependency_graph: Some(Arc::new(RwLock::new(graph))),
+            ..ReviewConfig::default()
+        };
+        let (_, sections) = build_review_prompt(&socket, "x", &config, Some(dir.path()), &StaticChecks::default());
+        assert!(!sections.contains(&"coupling".to_string()));
+
+        let architecture = ReviewConfig { prompt_type: PromptType::Architecture, ..config };
+        let (prompt, sections) =
+            build_review_prompt(&socket, "x", &architecture, Some(dir.path()), &StaticChecks::default());
+        assert!(sections.contains(&"coupling".to_string()));
+        assert!(prompt.contains("src/io/net/socket.rs → src/store.rs"));
+    }
+
+    #[test]
+    fn test_check_budget_actions() {
+        use crate::limits::SpendBudget;
+
+        let exhausted = |action| {
+            Arc::new(
+                UsageLimits::new()
+                    .with_budget(SpendBudget::new(Some(0), None, None).unwrap())
+                    .on_exhausted(action),
+            )
+        };
+        let quick = ReviewConfig {
+            limits: Some(exhausted(BudgetAction::Quick)),
+            ..ReviewConfig::default()
+        };
+        let skip = ReviewConfig {
+            limits: Some(exhausted(BudgetAction::Skip)),
+            ..ReviewConfig::default()
+        };
+
+        assert!(!check_budget(&ReviewConfig::default()).unwrap());
+        assert!(check_budget(&quick).unwrap());
+        assert!(matches!(
+            check_budget(&skip),
+            Err(CodeReviewError::BudgetExhausted(_))
+        ));
+    }
 }
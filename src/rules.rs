@@ -0,0 +1,491 @@
+//! Declarative architecture rules
+//!
+//! Teams describe their intended design in a JSON file and every review
+//! checks the reviewed file's imports against it:
+//!
+//! ```json
+//! {
+//!   "rules": [
+//!     { "kind": "forbid", "from": "ui/**", "to": "db/**" },
+//!     { "kind": "only", "target": "crypto", "allowed": ["services::auth"],
+//!       "reason": "key handling stays in one place", "severity": "error" },
+//!     { "kind": "layers", "layers": ["ui", "services", "db"] }
+//!   ]
+//! }
+//! ```
+//!
+//! Patterns are matched segment by segment against both file paths relative
+//! to the project root (`src/ui/button.rs`) and module paths (`ui::button`,
+//! `crate::db::conn`, `app.db`). `/`, `::` and `.` all separate segments,
+//! `*` matches one segment (or part of one, as in `*_test`), `**` any number
+//! of segments, and a pattern also matches everything below it.
+//!
+//! Violations are deterministic: they are attached to the review result as
+//! [`Finding`]s and handed to the AI as known violations.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cache::analyze_cached;
+use crate::error::Result;
+use crate::parser::{analyze_source, FileAnalysis, ImportInfo, SYSTEM_INCLUDE};
+use crate::resolver::{normalize, ImportResolver};
+use crate::result::{Finding, ReviewSeverity};
+
+/// File name looked up in the project root when no rules file is given
+pub const RULES_FILE_NAME: &str = ".review-rules.json";
+
+/// Name of the check reported in findings
+const RULES_CHECK: &str = "architecture-rule";
+
+/// Leading module path segments that stand for the project root
+const ROOT_SEGMENTS: &[&str] = &["crate", "@", "~"];
+
+/// File stems that stand for their directory (`mod.rs`, `index.ts`, ...)
+const DIRECTORY_STEMS: &[&str] = &["mod", "index", "__init__"];
+
+/// A set of architecture rules
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArchitectureRules {
+    /// Rules in declaration order
+    #[serde(default)]
+    pub rules: Vec<ArchitectureRule>,
+}
+
+/// One architecture rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchitectureRule {
+    /// What the rule checks
+    #[serde(flatten)]
+    pub kind: RuleKind,
+    /// Why the rule exists (shown with violations)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Severity of violations
+    #[serde(default = "default_severity")]
+    pub severity: ReviewSeverity,
+}
+
+/// The constraint a rule expresses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum RuleKind {
+    /// Files matching `from` must not import anything matching `to`
+    Forbid { from: String, to: String },
+    /// Only files matching one of `allowed` (or inside `target` itself) may
+    /// import anything matching `target`
+    Only { target: String, allowed: Vec<String> },
+    /// Layers from top to bottom; a layer may import itself and the layers
+    /// below it, never the ones above
+    Layers { layers: Vec<String> },
+}
+
+/// An import that breaks an architecture rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleViolation {
+    /// Importing file (relative to the project root)
+    pub file: String,
+    /// The offending import as written
+    pub import: String,
+    /// Short description of the broken rule
+    pub rule: String,
+    /// Why the rule exists
+    pub reason: Option<String>,
+    /// Severity configured for the rule
+    pub severity: ReviewSeverity,
+}
+
+fn default_severity() -> ReviewSeverity {
+    ReviewSeverity::Warning
+}
+
+impl ArchitectureRules {
+    /// Parse rules from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Load rules from a JSON file
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Load [`RULES_FILE_NAME`] from `dir`, if present
+    pub fn discover(dir: &Path) -> Result<Option<Self>> {
+        let path = dir.join(RULES_FILE_NAME);
+        if path.is_file() {
+            Self::load(&path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Whether there are no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Check the imports of one file
+    ///
+    /// Files that cannot be parsed have no imports to check.
+    pub fn check_file(&self, file: &Path, base_path: &Path, resolver: &ImportResolver) -> Vec<RuleViolation> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        match analyze_cached(file) {
+            Ok(analysis) => self.check_analysis(&analysis, file, base_path, resolver),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Check the imports of `source`, read from somewhere other than `file`
+    ///
+    /// Used for staged content, which may differ from the working tree.
+    /// `file` picks the language and locates the importer.
+    pub fn check_source(
+        &self,
+        file: &Path,
+        source: &str,
+        base_path: &Path,
+        resolver: &ImportResolver,
+    ) -> Vec<RuleViolation> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        match analyze_source(file, source) {
+            Ok(analysis) => self.check_analysis(&analysis, file, base_path, resolver),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn check_analysis(
+        &self,
+        analysis: &FileAnalysis,
+        file: &Path,
+        base_path: &Path,
+        resolver: &ImportResolver,
+    ) -> Vec<RuleViolation> {
+        let importer = Subject::file(&relative_to(base_path, file));
+
+        let mut violations = Vec::new();
+        for import in &analysis.imports {
+            let target = Subject::import(import, file, base_path, resolver);
+            for rule in &self.rules {
+                if rule.kind.is_violated(&importer, &target) {
+                    violations.push(RuleViolation {
+                        file: importer.display.clone(),
+                        import: import.module_path.clone(),
+                        rule: rule.kind.describe(),
+                        reason: rule.reason.clone(),
+                        severity: rule.severity,
+                    });
+                }
+            }
+        }
+        violations
+    }
+}
+
+impl RuleKind {
+    /// Short description of the rule (e.g. `ui/** -/-> db/**`)
+    pub fn describe(&self) -> String {
+        match self {
+            RuleKind::Forbid { from, to } => format!("{} -/-> {}", from, to),
+            RuleKind::Only { target, allowed } => format!("only {} -> {}", allowed.join(", "), target),
+            RuleKind::Layers { layers } => format!("layers {}", layers.join(" > ")),
+        }
+    }
+
+    fn is_violated(&self, importer: &Subject, target: &Subject) -> bool {
+        match self {
+            RuleKind::Forbid { from, to } => importer.matches(from) && target.matches(to),
+            RuleKind::Only { target: pattern, allowed } => {
+                target.matches(pattern)
+                    && !importer.matches(pattern)
+                    && !allowed.iter().any(|a| importer.matches(a))
+            }
+            RuleKind::Layers { layers } => {
+                let layer_of = |subject: &Subject| layers.iter().position(|l| subject.matches(l));
+                match (layer_of(importer), layer_of(target)) {
+                    (Some(from), Some(to)) => to < from,
+                    _ => false,
+                }
+            }
+        }
+    }
+}
+
+impl RuleViolation {
+    /// One-line description (e.g. `src/ui/view.rs imports crate::db::conn (ui/** -/-> db/**)`)
+    pub fn message(&self) -> String {
+        let mut message = format!("{} imports {} ({})", self.file, self.import, self.rule);
+        if let Some(ref reason) = self.reason {
+            message.push_str(": ");
+            message.push_str(reason);
+        }
+        message
+    }
+
+    /// The violation as a review finding
+    pub fn to_finding(&self) -> Finding {
+        Finding {
+            check: RULES_CHECK.to_string(),
+            severity: self.severity,
+            message: self.message(),
+            line: None,
+        }
+    }
+}
+
+/// Segment lists a pattern can match: a file path and/or module paths
+struct Subject {
+    display: String,
+    paths: Vec<Vec<String>>,
+}
+
+impl Subject {
+    /// A project file, by its path relative to the project root
+    fn file(relative: &Path) -> Self {
+        let display = relative.to_string_lossy().replace('\\', "/");
+        let mut paths = Vec::new();
+        add_file_paths(&display, &mut paths);
+        Self { display, paths }
+    }
+
+    /// The target of an import: its module path and the files it resolves to
+    fn import(import: &ImportInfo, file: &Path, base_path: &Path, resolver: &ImportResolver) -> Self {
+        let mut paths = Vec::new();
+        // Paths relative to the importing file only make sense once resolved
+        let relative = ["self::", "super::", "."].iter().any(|p| import.module_path.starts_with(p));
+        if !relative && !import.items.iter().any(|item| item == SYSTEM_INCLUDE) {
+            let module: Vec<String> = segments(&import.module_path)
+                .skip_while(|s| ROOT_SEGMENTS.contains(s))
+                .map(String::from)
+                .collect();
+            if !module.is_empty() {
+                paths.push(module);
+            }
+        }
+        for resolved in resolver.resolve(import, file) {
+            let relative = relative_to(base_path, &resolved);
+            add_file_paths(&relative.to_string_lossy().replace('\\', "/"), &mut paths);
+        }
+        Self {
+            display: import.module_path.clone(),
+            paths,
+        }
+    }
+
+    fn matches(&self, pattern: &str) -> bool {
+        let pattern: Vec<&str> = segments(pattern).collect();
+        self.paths.iter().any(|path| {
+            let path: Vec<&str> = path.iter().map(String::as_str).collect();
+            matches_prefix(&pattern, &path)
+        })
+    }
+}
+
+/// Add the segments of a relative file path, and of the module path it
+/// stands for (without `src/`, the extension, and `mod`/`index`/`__init__`)
+fn add_file_paths(relative: &str, paths: &mut Vec<Vec<String>>) {
+    let without_ext = Path::new(relative).with_extension("");
+    let file: Vec<String> = segments(&without_ext.to_string_lossy().replace('\\', "/"))
+        .map(String::from)
+        .collect();
+
+    let mut module = file.clone();
+    if module.first().is_some_and(|s| s == "src") {
+        module.remove(0);
+    }
+    if module.last().is_some_and(|s| DIRECTORY_STEMS.contains(&s.as_str())) {
+        module.pop();
+    }
+
+    paths.push(file);
+    if !module.is_empty() {
+        paths.push(module);
+    }
+}
+
+/// Split a path or pattern on `/`, `\`, `::` and `.`
+fn segments(s: &str) -> impl Iterator<Item = &str> {
+    s.split(['/', '\\', ':', '.']).filter(|segment| !segment.is_empty())
+}
+
+/// Whether a glob matches a `/`-separated path relative to a project root
+///
+/// Unlike [`path_matches`], `.` is part of a segment, so `*.rs` is a file
+/// name pattern. `*` matches within one segment and `**` any number of
+/// segments. A pattern without `/` matches at any depth (`*.rs`,
+/// `generated`), and a pattern matching a directory matches every file
+/// below it.
+pub(crate) fn glob_matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_start_matches("./");
+    let mut segments: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    if !pattern.trim_end_matches('/').contains('/') {
+        segments.insert(0, "**");
+    }
+    let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    matches_prefix(&segments, &path)
+}
+
+/// Whether `pattern` matches `path` or one of its ancestors
+fn matches_prefix(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => true,
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| matches_prefix(rest, &path[skip..])),
+        Some((segment, rest)) => path
+            .split_first()
+            .is_some_and(|(first, tail)| wildcard_match(segment, first) && matches_prefix(rest, tail)),
+    }
+}
+
+/// Match one segment against a pattern where `*` matches any characters
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == text,
+        Some((head, tail)) => {
+            let Some(rest) = text.strip_prefix(head) else {
+                return false;
+            };
+            rest.char_indices()
+                .map(|(i, _)| i)
+                .chain(std::iter::once(rest.len()))
+                .any(|i| wildcard_match(tail, &rest[i..]))
+        }
+    }
+}
+
+/// `path` relative to `base_path`, as far as it lies inside it
+fn relative_to(base_path: &Path, path: &Path) -> PathBuf {
+    if let (Ok(base), Ok(file)) = (base_path.canonicalize(), path.canonicalize()) {
+        if let Ok(relative) = file.strip_prefix(&base) {
+            return relative.to_path_buf();
+        }
+    }
+    let file = normalize(path);
+    file.strip_prefix(normalize(base_path))
+        .map(Path::to_path_buf)
+        .unwrap_or(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const RULES: &str = r#"{
+        "rules": [
+            { "kind": "forbid", "from": "ui/**", "to": "db/**", "reason": "go through services" },
+            { "kind": "only", "target": "crypto", "allowed": ["services::auth"], "severity": "error" },
+            { "kind": "layers", "layers": ["ui", "services", "db"] }
+        ]
+    }"#;
+
+    fn write(root: &Path, path: &str, content: &str) -> PathBuf {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let rules = ArchitectureRules::from_json(RULES).unwrap();
+        assert_eq!(rules.rules.len(), 3);
+        assert_eq!(rules.rules[0].severity, ReviewSeverity::Warning);
+        assert_eq!(rules.rules[1].severity, ReviewSeverity::Error);
+        assert_eq!(rules.rules[0].kind.describe(), "ui/** -/-> db/**");
+        assert!(ArchitectureRules::from_json(r#"{"rules":[{"kind":"bogus"}]}"#).is_err());
+    }
+
+    #[test]
+    fn test_pattern_matching() {
+        assert!(matches_prefix(&["ui", "**"], &["ui", "widgets", "button"]));
+        assert!(matches_prefix(&["ui"], &["ui", "button"]));
+        assert!(matches_prefix(&["**", "db"], &["src", "db", "conn"]));
+        assert!(!matches_prefix(&["ui"], &["uikit"]));
+        assert!(wildcard_match("*_test", "parser_test"));
+        assert!(!wildcard_match("*_test", "parser"));
+    }
+
+    #[test]
+    fn test_glob_matching() {
+        assert!(glob_matches("*.rs", "src/db/conn.rs"));
+        assert!(!glob_matches("*.rs", "src/db/conn.ts"));
+        assert!(glob_matches("tests/**", "tests/fixtures/a.rs"));
+        assert!(glob_matches("generated", "src/generated/api.ts"));
+        assert!(glob_matches("src/*.ts", "src/main.ts"));
+        assert!(!glob_matches("src/*.ts", "lib/src/main.ts"));
+        assert!(!glob_matches("src/*.ts", "src/main.rs"));
+    }
+
+    #[test]
+    fn test_check_file_reports_violations() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, "src/lib.rs", "pub mod ui;\npub mod db;\npub mod services;\n");
+        write(root, "src/db/mod.rs", "pub mod conn;\n");
+        let conn = write(root, "src/db/conn.rs", "use crate::ui::view;\npub fn open() {}\n");
+        write(root, "src/services/auth.rs", "use crypto::Hasher;\n");
+        write(root, "src/services/billing.rs", "use crypto::Hasher;\nuse crate::db::conn;\n");
+        let view = write(
+            root,
+            "src/ui/view.rs",
+            "use crate::db::conn::open;\nuse crate::services::billing;\n",
+        );
+
+        let rules = ArchitectureRules::from_json(RULES).unwrap();
+        let resolver = ImportResolver::new(root);
+
+        let violations = rules.check_file(&view, root, &resolver);
+        let broken: Vec<&str> = violations.iter().map(|v| v.rule.as_str()).collect();
+        assert_eq!(broken, vec!["ui/** -/-> db/**"]);
+        assert_eq!(violations[0].file, "src/ui/view.rs");
+        assert_eq!(violations[0].import, "crate::db::conn");
+        assert!(violations[0].message().ends_with(": go through services"));
+
+        // db is the bottom layer and must not reach back up to ui
+        let violations = rules.check_file(&conn, root, &resolver);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "layers ui > services > db");
+
+        let auth = root.join("src/services/auth.rs");
+        assert!(rules.check_file(&auth, root, &resolver).is_empty());
+
+        let billing = root.join("src/services/billing.rs");
+        let violations = rules.check_file(&billing, root, &resolver);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "only services::auth -> crypto");
+        assert_eq!(violations[0].to_finding().severity, ReviewSeverity::Error);
+    }
+
+    #[test]
+    fn test_check_source_ignores_file_on_disk() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, "src/lib.rs", "pub mod ui;\npub mod db;\n");
+        write(root, "src/db/conn.rs", "pub fn open() {}\n");
+        let view = write(root, "src/ui/view.rs", "pub fn render() {}\n");
+
+        let rules = ArchitectureRules::from_json(RULES).unwrap();
+        let resolver = ImportResolver::new(root);
+        assert!(rules.check_file(&view, root, &resolver).is_empty());
+
+        let staged = "use crate::db::conn::open;\n";
+        let violations = rules.check_source(&view, staged, root, &resolver);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].file, "src/ui/view.rs");
+    }
+
+    #[test]
+    fn test_discover_rules_file() {
+        let dir = tempdir().unwrap();
+        assert!(ArchitectureRules::discover(dir.path()).unwrap().is_none());
+        write(dir.path(), RULES_FILE_NAME, RULES);
+        let rules = ArchitectureRules::discover(dir.path()).unwrap().unwrap();
+        assert_eq!(rules.rules.len(), 3);
+    }
+}
@@ -1,15 +1,142 @@
 //! Cross-project shared code discovery
 //!
 //! Scans two project directories and identifies potential shared/duplicated code.
+//! Reports serialize to JSON and render as Markdown or CSV for other tooling;
+//! [`SharedOptions`] filters what is reported.
 
 use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::parser::analyze_file;
+use serde::{Deserialize, Serialize};
+
+use crate::cache::analyze_cached;
+use crate::clones::{collect_functions, match_clones, CloneMatch, CloneType, FunctionUnit};
+use crate::error::Result;
+use crate::rules::glob_matches;
 use crate::utils::fs::{walk_source_files, CONFIG_EXTENSIONS, SOURCE_EXTENSIONS};
 
+/// Minimum content similarity for same-name files to join a cluster
+const MIN_CLUSTER_FILE_SIMILARITY: f64 = 0.3;
+
+/// Base similarity for same-name exports
+const SAME_EXPORT_SIMILARITY: f64 = 0.7;
+
+/// Similarity assigned to identifiers that match across naming conventions
+const CROSS_LANGUAGE_SIMILARITY: f64 = 0.6;
+
+/// Clusters listed in the prompt; the rest are only counted
+const MAX_PROMPT_CLUSTERS: usize = 30;
+
+/// Exported symbol names too common to suggest sharing
+pub const DEFAULT_COMMON_SYMBOLS: &[&str] = &[
+    "main", "new", "default", "init", "run", "start", "stop", "get", "set", "test", "setup",
+    "teardown", "build", "create", "delete", "update", "Default", "Display", "Debug", "Clone",
+    "Error", "Result", "App", "Config", "Options", "Settings", "Context", "State",
+];
+
+/// Normalized identifiers (`snake_case`) too common to suggest sharing
+pub const DEFAULT_COMMON_IDENTIFIERS: &[&str] = &[
+    "get_value", "set_value", "to_string", "from_string",
+    "file_path", "file_name", "base_path", "is_empty",
+    "new_error", "parse_error", "read_file", "write_file",
+];
+
+/// Filters and thresholds for shared code discovery
+///
+/// Loadable from JSON; missing fields keep their defaults:
+///
+/// ```json
+/// { "min_similarity": 0.5, "kinds": ["similar_content"], "ignore": ["tests/**"] }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SharedOptions {
+    /// Candidates (and cluster links) below this similarity are dropped
+    pub min_similarity: f64,
+    /// Kinds to report; empty reports every kind
+    pub kinds: Vec<SharedKind>,
+    /// Keep at most this many candidates (clusters for several projects)
+    pub max_results: Option<usize>,
+    /// Globs of paths (relative to each project root) that are not scanned:
+    /// `tests/**`, `*.generated.ts`; a pattern without `/` matches at any depth
+    pub ignore: Vec<String>,
+    /// Exported names never reported as the same export
+    pub common_symbols: Vec<String>,
+    /// Normalized identifiers never reported as the same constant
+    pub common_identifiers: Vec<String>,
+}
+
+impl Default for SharedOptions {
+    fn default() -> Self {
+        Self {
+            min_similarity: 0.0,
+            kinds: Vec::new(),
+            max_results: None,
+            ignore: Vec::new(),
+            common_symbols: DEFAULT_COMMON_SYMBOLS.iter().map(|s| s.to_string()).collect(),
+            common_identifiers: DEFAULT_COMMON_IDENTIFIERS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl SharedOptions {
+    /// Parse options from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Load options from a JSON file
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Whether candidates of `kind` are reported
+    pub fn includes(&self, kind: &SharedKind) -> bool {
+        self.kinds.is_empty() || self.kinds.contains(kind)
+    }
+
+    /// Whether a file (relative to its project root) is skipped
+    pub fn is_ignored(&self, relative: &str) -> bool {
+        self.ignore.iter().any(|pattern| glob_matches(pattern, relative))
+    }
+
+    /// Check if a symbol name is too common to be meaningful
+    fn is_common_symbol(&self, name: &str) -> bool {
+        self.common_symbols.iter().any(|s| s == name)
+    }
+
+    /// Check if a normalized identifier is too common to be meaningful
+    fn is_common_identifier(&self, norm: &str) -> bool {
+        self.common_identifiers.iter().any(|s| s == norm)
+    }
+
+    /// Files under `root` to scan
+    fn scan(&self, root: &Path) -> Vec<PathBuf> {
+        walk_source_files(root, &scan_extensions())
+            .into_iter()
+            .filter(|file| {
+                let relative = file.strip_prefix(root).unwrap_or(file);
+                !self.is_ignored(&relative.to_string_lossy().replace('\\', "/"))
+            })
+            .collect()
+    }
+}
+
+/// Output format of a shared code report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SharedFormat {
+    /// Markdown tables
+    #[default]
+    Markdown,
+    /// The report serialized as JSON
+    Json,
+    /// One row per candidate (per cluster member for several projects)
+    Csv,
+}
+
 /// A candidate pair of files/symbols that may be shared between two projects
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SharedCandidate {
     /// Category of sharing opportunity
     pub kind: SharedKind,
@@ -21,9 +148,13 @@ pub struct SharedCandidate {
     pub description: String,
     /// Similarity score 0.0-1.0 (1.0 = identical)
     pub similarity: f64,
+    /// Function-level clone with line ranges (`SimilarContent` only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clone_match: Option<CloneMatch>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SharedKind {
     /// Same filename exists in both projects
     SameFileName,
@@ -31,7 +162,7 @@ pub enum SharedKind {
     SameExport,
     /// Same constant or type name appears in both
     SameConstant,
-    /// Files have similar content (high line overlap)
+    /// Functions are clones of each other (Type-1/2/3)
     SimilarContent,
 }
 
@@ -46,8 +177,20 @@ impl std::fmt::Display for SharedKind {
     }
 }
 
+impl SharedKind {
+    /// Short snake_case name (matches the JSON value and CLI `--kinds`)
+    pub fn name(&self) -> &'static str {
+        match self {
+            SharedKind::SameFileName => "same_file_name",
+            SharedKind::SameExport => "same_export",
+            SharedKind::SameConstant => "same_constant",
+            SharedKind::SimilarContent => "similar_content",
+        }
+    }
+}
+
 /// Result of cross-project shared code analysis
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SharedReport {
     pub project_a: String,
     pub project_b: String,
@@ -73,41 +216,355 @@ impl SharedReport {
         out.push_str(&format!("### 共有候補: {} 件\n\n", self.candidates.len()));
 
         for (i, c) in self.candidates.iter().enumerate() {
+            let (loc_a, loc_b) = match &c.clone_match {
+                Some(m) => (
+                    format!("{}:{}-{}", c.path_a, m.lines_a.0, m.lines_a.1),
+                    format!("{}:{}-{}", c.path_b, m.lines_b.0, m.lines_b.1),
+                ),
+                None => (c.path_a.clone(), c.path_b.clone()),
+            };
             out.push_str(&format!(
                 "{}. [{}] (類似度: {:.0}%)\n   A: {}\n   B: {}\n   {}\n\n",
                 i + 1,
                 c.kind,
                 c.similarity * 100.0,
-                c.path_a,
-                c.path_b,
+                loc_a,
+                loc_b,
                 c.description,
             ));
         }
 
         out
     }
+
+    /// Render the report for people or other tools
+    pub fn render(&self, format: SharedFormat) -> String {
+        match format {
+            SharedFormat::Markdown => self.to_markdown(),
+            SharedFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+            SharedFormat::Csv => self.to_csv(),
+        }
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# Shared code: {} ↔ {}\n\nScanned {} + {} files, {} candidates.\n\n",
+            self.project_a,
+            self.project_b,
+            self.files_scanned_a,
+            self.files_scanned_b,
+            self.candidates.len()
+        );
+        if self.candidates.is_empty() {
+            return out;
+        }
+        out.push_str("| # | Kind | Similarity | A | B | Description |\n|---|---|---|---|---|---|\n");
+        for (i, c) in self.candidates.iter().enumerate() {
+            let (lines_a, lines_b) = clone_ranges(c);
+            out.push_str(&format!(
+                "| {} | {} | {:.0}% | {} | {} | {} |\n",
+                i + 1,
+                c.kind.name(),
+                c.similarity * 100.0,
+                markdown_cell(&format!("{}{}", c.path_a, lines_a)),
+                markdown_cell(&format!("{}{}", c.path_b, lines_b)),
+                markdown_cell(&c.description)
+            ));
+        }
+        out
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = String::from("kind,similarity,path_a,start_a,end_a,path_b,start_b,end_b,description\n");
+        for c in &self.candidates {
+            let (start_a, end_a, start_b, end_b) = match &c.clone_match {
+                Some(m) => (
+                    m.lines_a.0.to_string(),
+                    m.lines_a.1.to_string(),
+                    m.lines_b.0.to_string(),
+                    m.lines_b.1.to_string(),
+                ),
+                None => Default::default(),
+            };
+            let row = [
+                c.kind.name().to_string(),
+                format!("{:.3}", c.similarity),
+                c.path_a.clone(),
+                start_a,
+                end_a,
+                c.path_b.clone(),
+                start_b,
+                end_b,
+                c.description.clone(),
+            ];
+            push_csv_row(&mut out, &row);
+        }
+        out
+    }
 }
 
-/// Find shared code candidates between two project directories
-pub fn find_shared_candidates(path_a: &Path, path_b: &Path) -> SharedReport {
-    let scan_ext: Vec<&str> = SOURCE_EXTENSIONS
+/// A file, function or symbol of one project that belongs to a cluster
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ClusterMember {
+    /// Project root
+    pub project: String,
+    /// Path relative to the project root
+    pub path: String,
+    /// Function, file or symbol name
+    pub name: String,
+    /// Line range of a cloned function (1-based, inclusive)
+    pub lines: Option<(usize, usize)>,
+}
+
+/// Code duplicated across two or more projects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedCluster {
+    pub kind: SharedKind,
+    /// Most common member name
+    pub name: String,
+    pub members: Vec<ClusterMember>,
+    /// Projects the cluster appears in (in scan order)
+    pub projects: Vec<String>,
+    /// Lines removed by keeping a single copy (0 for symbols)
+    pub duplicated_lines: usize,
+}
+
+/// A shared crate/module proposed by the extraction plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionTarget {
+    /// Proposed crate/module name
+    pub name: String,
+    /// Indices into `MultiSharedReport::clusters`
+    pub clusters: Vec<usize>,
+    /// Projects that would depend on the new crate/module
+    pub dependents: Vec<String>,
+    pub duplicated_lines: usize,
+}
+
+/// Result of shared code analysis across several projects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiSharedReport {
+    pub projects: Vec<String>,
+    /// Files scanned per project (same order as `projects`)
+    pub files_scanned: Vec<usize>,
+    /// Clusters, most duplicated lines first
+    pub clusters: Vec<SharedCluster>,
+    /// Extraction plan, most duplicated lines first
+    pub plan: Vec<ExtractionTarget>,
+}
+
+impl MultiSharedReport {
+    /// Format as a prompt-friendly string for AI analysis
+    pub fn to_prompt_string(&self) -> String {
+        let mut out = String::from("## Cross-Project Shared Code Analysis\n\n");
+        for (project, files) in self.projects.iter().zip(&self.files_scanned) {
+            out.push_str(&format!("- {} ({} files)\n", project, files));
+        }
+        out.push('\n');
+
+        if self.clusters.is_empty() {
+            out.push_str("共有候補は見つかりませんでした。\n");
+            return out;
+        }
+
+        out.push_str(&format!(
+            "### 共有クラスタ: {} 件（重複行数の多い順）\n\n",
+            self.clusters.len()
+        ));
+        for (i, cluster) in self.clusters.iter().take(MAX_PROMPT_CLUSTERS).enumerate() {
+            out.push_str(&format!(
+                "{}. [{}] {} — 重複 {} 行 / {} プロジェクト\n",
+                i + 1,
+                cluster.kind,
+                cluster.name,
+                cluster.duplicated_lines,
+                cluster.projects.len()
+            ));
+            for m in &cluster.members {
+                let location = match m.lines {
+                    Some((start, end)) => format!("{}:{}-{}", m.path, start, end),
+                    None => m.path.clone(),
+                };
+                out.push_str(&format!("   - {}: {} ({})\n", m.project, location, m.name));
+            }
+            out.push('\n');
+        }
+        if self.clusters.len() > MAX_PROMPT_CLUSTERS {
+            out.push_str(&format!(
+                "（他 {} 件は省略）\n\n",
+                self.clusters.len() - MAX_PROMPT_CLUSTERS
+            ));
+        }
+
+        if !self.plan.is_empty() {
+            out.push_str("### 共通ライブラリ抽出計画\n\n");
+            for (i, target) in self.plan.iter().enumerate() {
+                let members: Vec<String> = target
+                    .clusters
+                    .iter()
+                    .map(|&c| format!("{} ({})", self.clusters[c].name, self.clusters[c].kind))
+                    .collect();
+                out.push_str(&format!(
+                    "{}. `{}`（重複 {} 行）\n   メンバー: {}\n   依存するプロジェクト: {}\n\n",
+                    i + 1,
+                    target.name,
+                    target.duplicated_lines,
+                    members.join(", "),
+                    target.dependents.join(", ")
+                ));
+            }
+        }
+
+        out
+    }
+
+    /// Render the report for people or other tools
+    pub fn render(&self, format: SharedFormat) -> String {
+        match format {
+            SharedFormat::Markdown => self.to_markdown(),
+            SharedFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+            SharedFormat::Csv => self.to_csv(),
+        }
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut out = String::from("# Shared code\n\n| Project | Files |\n|---|---|\n");
+        for (project, files) in self.projects.iter().zip(&self.files_scanned) {
+            out.push_str(&format!("| {} | {} |\n", markdown_cell(project), files));
+        }
+        if !self.clusters.is_empty() {
+            out.push_str("\n## Clusters\n\n| # | Kind | Name | Duplicated lines | Members |\n|---|---|---|---|---|\n");
+            for (i, cluster) in self.clusters.iter().enumerate() {
+                let members: Vec<String> = cluster
+                    .members
+                    .iter()
+                    .map(|m| format!("{}: {}{}", m.project, m.path, range_suffix(m.lines)))
+                    .collect();
+                out.push_str(&format!(
+                    "| {} | {} | {} | {} | {} |\n",
+                    i + 1,
+                    cluster.kind.name(),
+                    markdown_cell(&cluster.name),
+                    cluster.duplicated_lines,
+                    markdown_cell(&members.join("<br>"))
+                ));
+            }
+        }
+        if !self.plan.is_empty() {
+            out.push_str("\n## Extraction plan\n\n| Name | Duplicated lines | Members | Dependents |\n|---|---|---|---|\n");
+            for target in &self.plan {
+                let members: Vec<&str> = target
+                    .clusters
+                    .iter()
+                    .map(|&c| self.clusters[c].name.as_str())
+                    .collect();
+                out.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    markdown_cell(&target.name),
+                    target.duplicated_lines,
+                    markdown_cell(&members.join(", ")),
+                    markdown_cell(&target.dependents.join(", "))
+                ));
+            }
+        }
+        out
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = String::from("cluster,kind,name,duplicated_lines,project,path,start,end,member\n");
+        for (i, cluster) in self.clusters.iter().enumerate() {
+            for m in &cluster.members {
+                let (start, end) = m
+                    .lines
+                    .map(|(s, e)| (s.to_string(), e.to_string()))
+                    .unwrap_or_default();
+                let row = [
+                    (i + 1).to_string(),
+                    cluster.kind.name().to_string(),
+                    cluster.name.clone(),
+                    cluster.duplicated_lines.to_string(),
+                    m.project.clone(),
+                    m.path.clone(),
+                    start,
+                    end,
+                    m.name.clone(),
+                ];
+                push_csv_row(&mut out, &row);
+            }
+        }
+        out
+    }
+}
+
+/// `:start-end` of a clone (empty for files and symbols)
+fn range_suffix(lines: Option<(usize, usize)>) -> String {
+    lines.map_or_else(String::new, |(start, end)| format!(":{}-{}", start, end))
+}
+
+/// Line range suffixes of a candidate's two sides
+fn clone_ranges(c: &SharedCandidate) -> (String, String) {
+    let m = c.clone_match.as_ref();
+    (range_suffix(m.map(|m| m.lines_a)), range_suffix(m.map(|m| m.lines_b)))
+}
+
+/// Escape a value for a Markdown table cell
+fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Append one CSV row, quoting fields that need it (RFC 4180)
+fn push_csv_row(out: &mut String, fields: &[String]) {
+    let fields: Vec<String> = fields
         .iter()
-        .chain(CONFIG_EXTENSIONS.iter())
-        .copied()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
         .collect();
-    let files_a = walk_source_files(path_a, &scan_ext);
-    let files_b = walk_source_files(path_b, &scan_ext);
+    out.push_str(&fields.join(","));
+    out.push('\n');
+}
+
+/// Find shared code candidates between two project directories
+pub fn find_shared_candidates(path_a: &Path, path_b: &Path) -> SharedReport {
+    find_shared_candidates_with_options(path_a, path_b, &SharedOptions::default())
+}
+
+/// Find shared code candidates between two project directories, filtered by `options`
+pub fn find_shared_candidates_with_options(
+    path_a: &Path,
+    path_b: &Path,
+    options: &SharedOptions,
+) -> SharedReport {
+    let files_a = options.scan(path_a);
+    let files_b = options.scan(path_b);
 
     let mut candidates = Vec::new();
 
     // 1. Same-name files
-    find_same_name_files(path_a, path_b, &files_a, &files_b, &mut candidates);
+    if options.includes(&SharedKind::SameFileName) {
+        find_same_name_files(path_a, path_b, &files_a, &files_b, &mut candidates);
+    }
 
     // 2. Same exports (via AST parsing)
-    find_same_exports(path_a, path_b, &files_a, &files_b, &mut candidates);
+    if options.includes(&SharedKind::SameExport) {
+        find_same_exports(path_a, path_b, &files_a, &files_b, options, &mut candidates);
+    }
 
     // 3. Cross-language identifier matching (snake_case ↔ camelCase)
-    find_cross_language_symbols(path_a, path_b, &files_a, &files_b, &mut candidates);
+    if options.includes(&SharedKind::SameConstant) {
+        find_cross_language_symbols(path_a, path_b, &files_a, &files_b, options, &mut candidates);
+    }
+
+    // 4. Function clones (normalized token fingerprints)
+    if options.includes(&SharedKind::SimilarContent) {
+        find_function_clones(path_a, path_b, &files_a, &files_b, &mut candidates);
+    }
+
+    candidates.retain(|c| c.similarity >= options.min_similarity);
 
     // Sort by similarity descending
     candidates.sort_by(|a, b| {
@@ -117,7 +574,11 @@ pub fn find_shared_candidates(path_a: &Path, path_b: &Path) -> SharedReport {
     });
 
     // Deduplicate: if same pair appears in multiple categories, keep highest similarity
+    // (clones are keyed by their line ranges, so each cloned function is kept)
     dedup_candidates(&mut candidates);
+    if let Some(max) = options.max_results {
+        candidates.truncate(max);
+    }
 
     SharedReport {
         project_a: path_a.display().to_string(),
@@ -128,6 +589,375 @@ pub fn find_shared_candidates(path_a: &Path, path_b: &Path) -> SharedReport {
     }
 }
 
+/// Find code shared by two or more of the given project directories
+///
+/// Candidates of every project pair are merged into clusters (a function
+/// cloned in three projects becomes one cluster), ranked by duplicated
+/// lines, and grouped into an extraction plan: one proposed crate/module
+/// per set of projects that share code.
+pub fn find_shared_candidates_many(roots: &[&Path]) -> MultiSharedReport {
+    find_shared_candidates_many_with_options(roots, &SharedOptions::default())
+}
+
+/// Find code shared by two or more projects, filtered by `options`
+///
+/// `max_results` limits the number of clusters; the plan covers only the
+/// clusters that are kept.
+pub fn find_shared_candidates_many_with_options(
+    roots: &[&Path],
+    options: &SharedOptions,
+) -> MultiSharedReport {
+    let scans: Vec<ProjectScan> = roots
+        .iter()
+        .map(|root| {
+            let files = options.scan(root);
+            ProjectScan {
+                root: root.to_path_buf(),
+                label: root.display().to_string(),
+                functions: collect_functions(root, &files),
+                exports: collect_exports(root, &files),
+                identifiers: if options.includes(&SharedKind::SameConstant) {
+                    collect_normalized_identifiers(root, &files)
+                } else {
+                    HashMap::new()
+                },
+                files,
+            }
+        })
+        .collect();
+
+    let mut builder = ClusterBuilder::default();
+    for (i, a) in scans.iter().enumerate() {
+        for (j, b) in scans.iter().enumerate().skip(i + 1) {
+            if options.includes(&SharedKind::SimilarContent) {
+                link_clones(&mut builder, (i, a), (j, b), options);
+            }
+            if options.includes(&SharedKind::SameFileName) {
+                link_same_name_files(&mut builder, (i, a), (j, b), options);
+            }
+            if options.includes(&SharedKind::SameExport) {
+                link_same_exports(&mut builder, (i, a), (j, b), options);
+            }
+            if options.includes(&SharedKind::SameConstant) {
+                link_same_constants(&mut builder, (i, a), (j, b), options);
+            }
+        }
+    }
+
+    let mut clusters = builder.into_clusters(&scans);
+    if let Some(max) = options.max_results {
+        clusters.truncate(max);
+    }
+    let plan = extraction_plan(&clusters);
+    MultiSharedReport {
+        projects: scans.iter().map(|s| s.label.clone()).collect(),
+        files_scanned: scans.iter().map(|s| s.files.len()).collect(),
+        clusters,
+        plan,
+    }
+}
+
+/// Files, functions and exports of one project
+struct ProjectScan {
+    root: PathBuf,
+    label: String,
+    files: Vec<PathBuf>,
+    functions: Vec<FunctionUnit>,
+    exports: HashMap<String, Vec<String>>,
+    /// Normalized identifier -> (original name, relative path)
+    identifiers: HashMap<String, Vec<(String, String)>>,
+}
+
+impl ProjectScan {
+    fn member(&self, path: &str, name: &str, lines: Option<(usize, usize)>) -> ClusterMember {
+        ClusterMember {
+            project: self.label.clone(),
+            path: path.to_string(),
+            name: name.to_string(),
+            lines,
+        }
+    }
+}
+
+/// A cluster node: kind, member, project index and line count
+type ClusterNode = (SharedKind, ClusterMember, usize, usize);
+
+/// Union-find over the members linked by pairwise candidates
+#[derive(Default)]
+struct ClusterBuilder {
+    nodes: Vec<ClusterNode>,
+    index: HashMap<(SharedKind, ClusterMember), usize>,
+    parent: Vec<usize>,
+}
+
+impl ClusterBuilder {
+    fn node(&mut self, kind: &SharedKind, member: ClusterMember, project: usize, lines: usize) -> usize {
+        let key = (kind.clone(), member);
+        if let Some(&i) = self.index.get(&key) {
+            return i;
+        }
+        let i = self.nodes.len();
+        self.nodes.push((key.0.clone(), key.1.clone(), project, lines));
+        self.parent.push(i);
+        self.index.insert(key, i);
+        i
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn link(&mut self, kind: SharedKind, a: (ClusterMember, usize, usize), b: (ClusterMember, usize, usize)) {
+        let a = self.node(&kind, a.0, a.1, a.2);
+        let b = self.node(&kind, b.0, b.1, b.2);
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        self.parent[root_b] = root_a;
+    }
+
+    /// Connected components spanning two or more projects, most duplicated first
+    fn into_clusters(mut self, scans: &[ProjectScan]) -> Vec<SharedCluster> {
+        let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+        for i in 0..self.nodes.len() {
+            let root = self.find(i);
+            groups.entry(root).or_default().push(i);
+        }
+
+        // Lines of cloned functions per file: a file cluster counts only the
+        // rest of the file, so no line is counted by two clusters
+        let mut cloned: HashMap<(usize, &str), usize> = HashMap::new();
+        for (kind, member, project, lines) in &self.nodes {
+            if *kind == SharedKind::SimilarContent {
+                *cloned.entry((*project, member.path.as_str())).or_default() += lines;
+            }
+        }
+        let lines = |n: &ClusterNode| match n.0 {
+            SharedKind::SameFileName => n.3.saturating_sub(cloned.get(&(n.2, n.1.path.as_str())).copied().unwrap_or(0)),
+            _ => n.3,
+        };
+
+        let mut clusters: Vec<SharedCluster> = groups
+            .into_values()
+            .filter_map(|group| {
+                let mut nodes: Vec<&ClusterNode> = group.iter().map(|&i| &self.nodes[i]).collect();
+                nodes.sort_by(|a, b| (a.2, &a.1.path, a.1.lines).cmp(&(b.2, &b.1.path, b.1.lines)));
+
+                let mut projects: Vec<usize> = nodes.iter().map(|n| n.2).collect();
+                projects.dedup();
+                if projects.len() < 2 {
+                    return None;
+                }
+                let total: usize = nodes.iter().map(|n| lines(n)).sum();
+                let largest = nodes.iter().map(|n| lines(n)).max().unwrap_or(0);
+
+                let mut names: HashMap<&str, usize> = HashMap::new();
+                for n in &nodes {
+                    *names.entry(n.1.name.as_str()).or_default() += 1;
+                }
+                let name = names
+                    .into_iter()
+                    .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0)))
+                    .map(|(name, _)| name.to_string())
+                    .unwrap_or_default();
+
+                Some(SharedCluster {
+                    kind: nodes[0].0.clone(),
+                    name,
+                    members: nodes.iter().map(|n| n.1.clone()).collect(),
+                    projects: projects.iter().map(|&p| scans[p].label.clone()).collect(),
+                    duplicated_lines: total - largest,
+                })
+            })
+            .collect();
+
+        clusters.sort_by(|a, b| {
+            b.duplicated_lines
+                .cmp(&a.duplicated_lines)
+                .then(b.projects.len().cmp(&a.projects.len()))
+                .then(a.name.cmp(&b.name))
+        });
+        clusters
+    }
+}
+
+/// Link functions cloned between two projects
+fn link_clones(
+    builder: &mut ClusterBuilder,
+    (i, a): (usize, &ProjectScan),
+    (j, b): (usize, &ProjectScan),
+    options: &SharedOptions,
+) {
+    for pair in match_clones(&a.functions, &b.functions) {
+        let m = pair.clone;
+        if m.similarity < options.min_similarity {
+            continue;
+        }
+        let lines_a = m.lines_a.1 + 1 - m.lines_a.0;
+        let lines_b = m.lines_b.1 + 1 - m.lines_b.0;
+        builder.link(
+            SharedKind::SimilarContent,
+            (a.member(&pair.path_a, &m.name_a, Some(m.lines_a)), i, lines_a),
+            (b.member(&pair.path_b, &m.name_b, Some(m.lines_b)), j, lines_b),
+        );
+    }
+}
+
+/// Link same-name files whose content overlaps
+fn link_same_name_files(
+    builder: &mut ClusterBuilder,
+    (i, a): (usize, &ProjectScan),
+    (j, b): (usize, &ProjectScan),
+    options: &SharedOptions,
+) {
+    let mut candidates = Vec::new();
+    find_same_name_files(&a.root, &b.root, &a.files, &b.files, &mut candidates);
+    for c in candidates {
+        if c.similarity < MIN_CLUSTER_FILE_SIMILARITY.max(options.min_similarity) {
+            continue;
+        }
+        let name = Path::new(&c.path_a)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        builder.link(
+            SharedKind::SameFileName,
+            (a.member(&c.path_a, &name, None), i, line_count(&a.root.join(&c.path_a))),
+            (b.member(&c.path_b, &name, None), j, line_count(&b.root.join(&c.path_b))),
+        );
+    }
+}
+
+/// Link files exporting the same (uncommon) symbol
+fn link_same_exports(
+    builder: &mut ClusterBuilder,
+    (i, a): (usize, &ProjectScan),
+    (j, b): (usize, &ProjectScan),
+    options: &SharedOptions,
+) {
+    if SAME_EXPORT_SIMILARITY < options.min_similarity {
+        return;
+    }
+    for (symbol, locs_a) in &a.exports {
+        let Some(locs_b) = b.exports.get(symbol) else {
+            continue;
+        };
+        if options.is_common_symbol(symbol) {
+            continue;
+        }
+        for loc_a in locs_a {
+            for loc_b in locs_b {
+                builder.link(
+                    SharedKind::SameExport,
+                    (a.member(loc_a, symbol, None), i, 0),
+                    (b.member(loc_b, symbol, None), j, 0),
+                );
+            }
+        }
+    }
+}
+
+/// Link identifiers that match only after normalization (`max_retries` ↔ `maxRetries`)
+fn link_same_constants(
+    builder: &mut ClusterBuilder,
+    (i, a): (usize, &ProjectScan),
+    (j, b): (usize, &ProjectScan),
+    options: &SharedOptions,
+) {
+    if CROSS_LANGUAGE_SIMILARITY < options.min_similarity {
+        return;
+    }
+    for (norm, locs_a) in &a.identifiers {
+        let Some(locs_b) = b.identifiers.get(norm) else {
+            continue;
+        };
+        if norm.len() < 6 || options.is_common_identifier(norm) {
+            continue;
+        }
+        // Same as find_cross_language_symbols: first location each, exact matches are exports
+        let ((name_a, path_a), (name_b, path_b)) = (&locs_a[0], &locs_b[0]);
+        if name_a == name_b {
+            continue;
+        }
+        builder.link(
+            SharedKind::SameConstant,
+            (a.member(path_a, name_a, None), i, 0),
+            (b.member(path_b, name_b, None), j, 0),
+        );
+    }
+}
+
+/// Group clusters with duplicated lines by the projects they span
+fn extraction_plan(clusters: &[SharedCluster]) -> Vec<ExtractionTarget> {
+    let mut by_projects: Vec<(Vec<String>, Vec<usize>)> = Vec::new();
+    for (i, cluster) in clusters.iter().enumerate() {
+        if cluster.duplicated_lines == 0 {
+            continue;
+        }
+        match by_projects.iter_mut().find(|(projects, _)| *projects == cluster.projects) {
+            Some((_, members)) => members.push(i),
+            None => by_projects.push((cluster.projects.clone(), vec![i])),
+        }
+    }
+
+    let mut plan: Vec<ExtractionTarget> = Vec::new();
+    for (dependents, members) in by_projects {
+        let mut name = proposed_module_name(members.iter().map(|&i| &clusters[i]));
+        let base = name.clone();
+        let mut n = 2;
+        while plan.iter().any(|t| t.name == name) {
+            name = format!("{}-{}", base, n);
+            n += 1;
+        }
+        plan.push(ExtractionTarget {
+            name,
+            duplicated_lines: members.iter().map(|&i| clusters[i].duplicated_lines).sum(),
+            clusters: members,
+            dependents,
+        });
+    }
+    plan.sort_by_key(|t| std::cmp::Reverse(t.duplicated_lines));
+    plan
+}
+
+/// Name a shared crate/module after the file its members live in most
+fn proposed_module_name<'a>(clusters: impl Iterator<Item = &'a SharedCluster>) -> String {
+    const GENERIC_STEMS: &[&str] = &["lib", "mod", "main", "index", "__init__", "app"];
+    let mut stems: HashMap<String, usize> = HashMap::new();
+    for cluster in clusters {
+        for m in &cluster.members {
+            let Some(stem) = Path::new(&m.path).file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if !GENERIC_STEMS.contains(&stem) {
+                *stems.entry(stem.to_string()).or_default() += m.lines.map_or(1, |(s, e)| e + 1 - s);
+            }
+        }
+    }
+    let stem = stems
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(stem, _)| normalize_identifier(&stem).replace('_', "-"))
+        .unwrap_or_else(|| "common".to_string());
+    format!("shared-{}", stem)
+}
+
+/// Number of lines in a file (0 if unreadable)
+fn line_count(path: &Path) -> usize {
+    std::fs::read_to_string(path).map_or(0, |c| c.lines().count())
+}
+
+/// Extensions scanned for shared code
+fn scan_extensions() -> Vec<&'static str> {
+    SOURCE_EXTENSIONS
+        .iter()
+        .chain(CONFIG_EXTENSIONS.iter())
+        .copied()
+        .collect()
+}
+
 /// Find files with the same name in both projects, then compare content similarity
 fn find_same_name_files(
     root_a: &Path,
@@ -179,6 +1009,7 @@ fn find_same_name_files(
                     path_b: rel_b,
                     description: desc,
                     similarity,
+                    clone_match: None,
                 });
             }
         }
@@ -191,6 +1022,7 @@ fn find_same_exports(
     root_b: &Path,
     files_a: &[PathBuf],
     files_b: &[PathBuf],
+    options: &SharedOptions,
     candidates: &mut Vec<SharedCandidate>,
 ) {
     // Parse exports from both projects
@@ -201,7 +1033,7 @@ fn find_same_exports(
     for (symbol, locs_a) in &exports_a {
         if let Some(locs_b) = exports_b.get(symbol) {
             // Skip very common names that are likely coincidental
-            if is_common_symbol(symbol) {
+            if options.is_common_symbol(symbol) {
                 continue;
             }
 
@@ -215,7 +1047,8 @@ fn find_same_exports(
                             "同名シンボル「{}」が両プロジェクトでエクスポート",
                             symbol
                         ),
-                        similarity: 0.7, // Base similarity for same-name exports
+                        similarity: SAME_EXPORT_SIMILARITY,
+                        clone_match: None,
                     });
                 }
             }
@@ -223,13 +1056,42 @@ fn find_same_exports(
     }
 }
 
+/// Find functions that were copied between the projects
+fn find_function_clones(
+    root_a: &Path,
+    root_b: &Path,
+    files_a: &[PathBuf],
+    files_b: &[PathBuf],
+    candidates: &mut Vec<SharedCandidate>,
+) {
+    let units_a = collect_functions(root_a, files_a);
+    let units_b = collect_functions(root_b, files_b);
+
+    for pair in match_clones(&units_a, &units_b) {
+        let clone = pair.clone;
+        let description = if clone.clone_type == CloneType::Gapped || clone.name_a != clone.name_b {
+            format!("{}: 「{}」(A) ↔ 「{}」(B)", clone.clone_type, clone.name_a, clone.name_b)
+        } else {
+            format!("{}: 「{}」", clone.clone_type, clone.name_a)
+        };
+        candidates.push(SharedCandidate {
+            kind: SharedKind::SimilarContent,
+            path_a: pair.path_a,
+            path_b: pair.path_b,
+            description,
+            similarity: clone.similarity,
+            clone_match: Some(clone),
+        });
+    }
+}
+
 /// Collect all exported symbols from a set of files
 /// Returns: symbol_name -> Vec<relative_path>
 fn collect_exports(root: &Path, files: &[PathBuf]) -> HashMap<String, Vec<String>> {
     let mut exports: HashMap<String, Vec<String>> = HashMap::new();
 
     for file in files {
-        if let Ok(analysis) = analyze_file(file) {
+        if let Ok(analysis) = analyze_cached(file) {
             let rel = file
                 .strip_prefix(root)
                 .unwrap_or(file)
@@ -315,6 +1177,7 @@ fn find_cross_language_symbols(
     root_b: &Path,
     files_a: &[PathBuf],
     files_b: &[PathBuf],
+    options: &SharedOptions,
     candidates: &mut Vec<SharedCandidate>,
 ) {
     // Collect normalized identifiers from each project
@@ -329,7 +1192,7 @@ fn find_cross_language_symbols(
                 continue;
             }
             // Skip common patterns
-            if is_common_normalized(norm) {
+            if options.is_common_identifier(norm) {
                 continue;
             }
 
@@ -350,7 +1213,8 @@ fn find_cross_language_symbols(
                     "Cross-language同名: 「{}」(A) ↔ 「{}」(B) [正規化: {}]",
                     orig_a, orig_b, norm
                 ),
-                similarity: 0.6,
+                similarity: CROSS_LANGUAGE_SIMILARITY,
+                clone_match: None,
             });
         }
     }
@@ -391,25 +1255,6 @@ fn collect_normalized_identifiers(
     result
 }
 
-fn is_common_normalized(norm: &str) -> bool {
-    const COMMON_NORMS: &[&str] = &[
-        "get_value", "set_value", "to_string", "from_string",
-        "file_path", "file_name", "base_path", "is_empty",
-        "new_error", "parse_error", "read_file", "write_file",
-    ];
-    COMMON_NORMS.contains(&norm)
-}
-
-/// Check if a symbol name is too common to be meaningful
-fn is_common_symbol(name: &str) -> bool {
-    const COMMON: &[&str] = &[
-        "main", "new", "default", "init", "run", "start", "stop", "get", "set", "test", "setup",
-        "teardown", "build", "create", "delete", "update", "Default", "Display", "Debug", "Clone",
-        "Error", "Result", "App", "Config", "Options", "Settings", "Context", "State",
-    ];
-    COMMON.contains(&name)
-}
-
 /// Calculate content similarity between two files (0.0-1.0)
 /// Uses line-based Jaccard similarity
 fn content_similarity(path_a: &Path, path_b: &Path) -> f64 {
@@ -454,10 +1299,12 @@ fn content_similarity(path_a: &Path, path_b: &Path) -> f64 {
 
 /// Remove duplicate candidates (same file pair, keep highest similarity)
 fn dedup_candidates(candidates: &mut Vec<SharedCandidate>) {
-    // First pass: find the best index for each (path_a, path_b) pair
-    let mut best: HashMap<(String, String), (usize, f64)> = HashMap::new();
+    // First pass: find the best index for each (path_a, path_b, clone ranges) key
+    type Key = (String, String, Option<((usize, usize), (usize, usize))>);
+    let mut best: HashMap<Key, (usize, f64)> = HashMap::new();
     for (i, c) in candidates.iter().enumerate() {
-        let key = (c.path_a.clone(), c.path_b.clone());
+        let ranges = c.clone_match.as_ref().map(|m| (m.lines_a, m.lines_b));
+        let key = (c.path_a.clone(), c.path_b.clone(), ranges);
         match best.entry(key) {
             std::collections::hash_map::Entry::Vacant(e) => {
                 e.insert((i, c.similarity));
@@ -515,10 +1362,11 @@ mod tests {
 
     #[test]
     fn test_is_common_symbol() {
-        assert!(is_common_symbol("main"));
-        assert!(is_common_symbol("Default"));
-        assert!(!is_common_symbol("calculateTonnage"));
-        assert!(!is_common_symbol("TRUCK_SPECS"));
+        let options = SharedOptions::default();
+        assert!(options.is_common_symbol("main"));
+        assert!(options.is_common_symbol("Default"));
+        assert!(!options.is_common_symbol("calculateTonnage"));
+        assert!(!options.is_common_symbol("TRUCK_SPECS"));
     }
 
     #[test]
@@ -556,6 +1404,7 @@ mod tests {
                 path_b: "b.rs".to_string(),
                 description: "test1".to_string(),
                 similarity: 0.5,
+                clone_match: None,
             },
             SharedCandidate {
                 kind: SharedKind::SameExport,
@@ -563,6 +1412,7 @@ mod tests {
                 path_b: "b.rs".to_string(),
                 description: "test2".to_string(),
                 similarity: 0.8,
+                clone_match: None,
             },
         ];
         dedup_candidates(&mut candidates);
@@ -615,6 +1465,10 @@ mod tests {
 
         let report = find_shared_candidates(dir_a.path(), dir_b.path());
         assert!(report.candidates.iter().any(|c| c.kind == SharedKind::SameConstant));
+
+        // Two projects through the cluster report keep the same candidates
+        let report = find_shared_candidates_many(&[dir_a.path(), dir_b.path()]);
+        assert!(report.clusters.iter().any(|c| c.kind == SharedKind::SameConstant));
     }
 
     #[test]
@@ -628,6 +1482,7 @@ mod tests {
                 path_b: "config.rs".to_string(),
                 description: "同名ファイル".to_string(),
                 similarity: 0.95,
+                clone_match: None,
             }],
             files_scanned_a: 10,
             files_scanned_b: 8,
@@ -636,4 +1491,179 @@ mod tests {
         assert!(prompt.contains("config.rs"));
         assert!(prompt.contains("95%"));
     }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_find_function_clones() {
+        let dir_a = tempdir().unwrap();
+        let dir_b = tempdir().unwrap();
+
+        let original = "pub fn load_rows(path: &str) -> Vec<String> {\n    let text = std::fs::read_to_string(path).unwrap_or_default();\n    let mut rows = Vec::new();\n    for line in text.lines() {\n        if !line.trim().is_empty() {\n            rows.push(line.trim().to_string());\n        }\n    }\n    rows\n}\n";
+        let renamed = original.replace("load_rows", "read_lines").replace("rows", "out");
+        std::fs::write(dir_a.path().join("io.rs"), original).unwrap();
+        std::fs::write(dir_b.path().join("input.rs"), format!("\n\n{}", renamed)).unwrap();
+
+        let report = find_shared_candidates(dir_a.path(), dir_b.path());
+        let clone = report
+            .candidates
+            .iter()
+            .find(|c| c.kind == SharedKind::SimilarContent)
+            .expect("clone candidate");
+        let m = clone.clone_match.as_ref().unwrap();
+        assert_eq!(m.clone_type, CloneType::Renamed);
+        assert_eq!((m.lines_a, m.lines_b), ((1, 10), (3, 12)));
+        assert!(report.to_prompt_string().contains("input.rs:3-12"));
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_find_shared_candidates_many() {
+        let dirs: Vec<_> = (0..3).map(|_| tempdir().unwrap()).collect();
+        let parse = "pub fn parse_rows(text: &str) -> Vec<String> {\n    let mut rows = Vec::new();\n    for line in text.lines() {\n        let line = line.trim();\n        if !line.is_empty() && !line.starts_with('#') {\n            rows.push(line.to_string());\n        }\n    }\n    rows\n}\n";
+        let score = "pub fn score(values: &[f64], weight: f64) -> f64 {\n    let mut total = 0.0;\n    for value in values {\n        if *value > 1.0 {\n            total += value * weight;\n        } else {\n            total += value;\n        }\n    }\n    total\n}\n";
+        // parse_rows is copied into all three projects, score only into the first two
+        std::fs::write(dirs[0].path().join("rows.rs"), parse).unwrap();
+        std::fs::write(dirs[1].path().join("rows.rs"), parse.replace("parse_rows", "split_rows")).unwrap();
+        std::fs::write(dirs[2].path().join("input.rs"), parse).unwrap();
+        std::fs::write(dirs[0].path().join("score.rs"), score).unwrap();
+        std::fs::write(dirs[1].path().join("rank.rs"), score).unwrap();
+
+        let roots: Vec<&Path> = dirs.iter().map(|d| d.path()).collect();
+        let report = find_shared_candidates_many(&roots);
+        assert_eq!(report.files_scanned, vec![2, 2, 1]);
+
+        let clone = report
+            .clusters
+            .iter()
+            .find(|c| c.kind == SharedKind::SimilarContent && c.name == "parse_rows")
+            .expect("parse_rows cluster");
+        assert_eq!(clone.projects.len(), 3);
+        assert_eq!(clone.members.len(), 3);
+        assert_eq!(clone.duplicated_lines, 20);
+
+        // One target per set of projects, named after the files
+        assert_eq!(report.plan.len(), 2);
+        let all = report.plan.iter().find(|t| t.dependents.len() == 3).unwrap();
+        assert_eq!(all.name, "shared-rows");
+        let pair = report.plan.iter().find(|t| t.dependents.len() == 2).unwrap();
+        assert!(pair.clusters.iter().any(|&c| report.clusters[c].name == "score"));
+
+        // rows.rs is all cloned function, so its file cluster adds no lines
+        let file = report
+            .clusters
+            .iter()
+            .find(|c| c.kind == SharedKind::SameFileName && c.name == "rows.rs")
+            .expect("rows.rs cluster");
+        assert_eq!(file.duplicated_lines, 0);
+        let cloned: usize = report
+            .clusters
+            .iter()
+            .filter(|c| c.kind == SharedKind::SimilarContent)
+            .map(|c| c.duplicated_lines)
+            .sum();
+        assert_eq!(report.plan.iter().map(|t| t.duplicated_lines).sum::<usize>(), cloned);
+
+        let prompt = report.to_prompt_string();
+        assert!(prompt.contains("### 共通ライブラリ抽出計画"));
+        assert!(prompt.contains("`shared-rows`"));
+    }
+
+    #[test]
+    fn test_extraction_plan_skips_symbol_only_clusters() {
+        let member = |project: &str| ClusterMember {
+            project: project.to_string(),
+            path: "api.ts".to_string(),
+            name: "fetchUser".to_string(),
+            lines: None,
+        };
+        let clusters = vec![SharedCluster {
+            kind: SharedKind::SameExport,
+            name: "fetchUser".to_string(),
+            members: vec![member("a"), member("b")],
+            projects: vec!["a".to_string(), "b".to_string()],
+            duplicated_lines: 0,
+        }];
+        assert!(extraction_plan(&clusters).is_empty());
+    }
+
+    #[test]
+    fn test_shared_options_filters() {
+        let dir_a = tempdir().unwrap();
+        let dir_b = tempdir().unwrap();
+        for dir in [&dir_a, &dir_b] {
+            std::fs::create_dir_all(dir.path().join("tests")).unwrap();
+            std::fs::write(dir.path().join("tests").join("fixture.rs"), "const X: i32 = 1;").unwrap();
+            std::fs::write(dir.path().join("config.rs"), "const X: i32 = 1;").unwrap();
+        }
+        std::fs::write(dir_a.path().join("specs.ts"), "export const truckSpecs = {};").unwrap();
+        std::fs::write(dir_b.path().join("specs.rs"), "pub const TRUCK_SPECS: u8 = 1;").unwrap();
+
+        let options = SharedOptions::from_json(r#"{ "ignore": ["tests/**"], "kinds": ["same_file_name"] }"#).unwrap();
+        let report = find_shared_candidates_with_options(dir_a.path(), dir_b.path(), &options);
+        assert_eq!(report.files_scanned_a, 2);
+        assert_eq!(report.candidates.len(), 1);
+        assert_eq!(report.candidates[0].path_a, "config.rs");
+
+        // A pattern without `/` is a glob on the file name at any depth
+        let options = SharedOptions::from_json(r#"{ "ignore": ["*.rs"] }"#).unwrap();
+        let report = find_shared_candidates_with_options(dir_a.path(), dir_b.path(), &options);
+        assert_eq!((report.files_scanned_a, report.files_scanned_b), (1, 0));
+
+        // The common identifier list replaces the built-in one
+        let options = SharedOptions {
+            common_identifiers: vec!["truck_specs".to_string()],
+            ..SharedOptions::default()
+        };
+        let report = find_shared_candidates_with_options(dir_a.path(), dir_b.path(), &options);
+        assert!(!report.candidates.iter().any(|c| c.kind == SharedKind::SameConstant));
+
+        let options = SharedOptions {
+            min_similarity: 0.99,
+            max_results: Some(1),
+            ..SharedOptions::default()
+        };
+        let report = find_shared_candidates_with_options(dir_a.path(), dir_b.path(), &options);
+        assert_eq!(report.candidates.len(), 1);
+        assert!(report.candidates[0].similarity >= 0.99);
+    }
+
+    #[test]
+    fn test_shared_report_render() {
+        let report = SharedReport {
+            project_a: "/path/a".to_string(),
+            project_b: "/path/b".to_string(),
+            candidates: vec![SharedCandidate {
+                kind: SharedKind::SimilarContent,
+                path_a: "calc.rs".to_string(),
+                path_b: "math.rs".to_string(),
+                description: "clone, \"renamed\"".to_string(),
+                similarity: 1.0,
+                clone_match: Some(CloneMatch {
+                    clone_type: CloneType::Renamed,
+                    name_a: "total".to_string(),
+                    name_b: "sum".to_string(),
+                    lines_a: (3, 12),
+                    lines_b: (7, 16),
+                    similarity: 1.0,
+                }),
+            }],
+            files_scanned_a: 1,
+            files_scanned_b: 1,
+        };
+
+        let json = report.render(SharedFormat::Json);
+        assert!(json.contains("\"kind\": \"similar_content\""));
+        assert!(json.contains("\"clone_type\": \"renamed\""));
+        let parsed: SharedReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.candidates[0].clone_match, report.candidates[0].clone_match);
+
+        let csv = report.render(SharedFormat::Csv);
+        assert_eq!(
+            csv.lines().nth(1).unwrap(),
+            r#"similar_content,1.000,calc.rs,3,12,math.rs,7,16,"clone, ""renamed""""#
+        );
+
+        let markdown = report.render(SharedFormat::Markdown);
+        assert!(markdown.contains("| 1 | similar_content | 100% | calc.rs:3-12 | math.rs:7-16 |"));
+    }
 }
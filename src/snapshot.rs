@@ -0,0 +1,462 @@
+//! Architecture snapshots and drift
+//!
+//! A snapshot records the shape of a project at one point in time: the
+//! module tree, module sizes, the public exports of every module, the
+//! dependencies between modules and the import cycles. Comparing two
+//! snapshots shows how the architecture drifted between them — new
+//! cross-module dependencies, new cycles, a growing public API and modules
+//! that grew past a size threshold — so gradual erosion becomes visible
+//! per commit instead of only in hindsight.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CodeReviewError, Result};
+use crate::git::{resolve_commit, TreeExport, TreeSource};
+use crate::graph::{DependencyGraph, ModuleEdge};
+use crate::modules::generate_module_tree;
+use crate::parser::Visibility;
+
+/// Size and public surface of one module (directory)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleSnapshot {
+    /// Number of source files
+    pub files: usize,
+    /// Total lines across those files
+    pub lines: usize,
+    /// Public exports as `file::name`
+    pub exports: BTreeSet<String>,
+}
+
+/// Architecture of a project at one point in time
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArchitectureSnapshot {
+    /// Commit the snapshot was taken at (`None` for the working tree)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// ASCII module tree (see `generate_module_tree`)
+    pub module_tree: String,
+    /// Modules keyed by directory relative to the project root
+    pub modules: BTreeMap<String, ModuleSnapshot>,
+    /// Dependencies between modules
+    pub module_edges: Vec<ModuleEdge>,
+    /// File-level import cycles (paths relative to the project root)
+    pub cycles: Vec<Vec<String>>,
+    /// Module-level import cycles
+    pub module_cycles: Vec<Vec<String>>,
+}
+
+/// Size limits a module should stay under
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DriftThresholds {
+    /// Maximum lines per module
+    pub max_module_lines: usize,
+    /// Maximum source files per module
+    pub max_module_files: usize,
+}
+
+impl Default for DriftThresholds {
+    fn default() -> Self {
+        Self {
+            max_module_lines: 3000,
+            max_module_files: 25,
+        }
+    }
+}
+
+/// A module that crossed a size threshold
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModuleGrowth {
+    /// Module (directory relative to the project root)
+    pub module: String,
+    /// Lines before and after
+    pub lines: (usize, usize),
+    /// Files before and after
+    pub files: (usize, usize),
+}
+
+/// Architectural changes between two snapshots
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ArchitectureDrift {
+    /// Modules that did not exist before
+    pub new_modules: Vec<String>,
+    /// Module dependencies that did not exist before
+    pub new_module_edges: Vec<ModuleEdge>,
+    /// File-level cycles that did not exist before
+    pub new_cycles: Vec<Vec<String>>,
+    /// Module-level cycles that did not exist before
+    pub new_module_cycles: Vec<Vec<String>>,
+    /// Exports added to each module's public API
+    pub new_exports: BTreeMap<String, Vec<String>>,
+    /// Modules that grew past a size threshold
+    pub grown_modules: Vec<ModuleGrowth>,
+}
+
+impl ArchitectureSnapshot {
+    /// Snapshot of the working tree at `base_path`
+    pub fn capture(base_path: &Path) -> Self {
+        Self::from_graph(&DependencyGraph::build(base_path))
+    }
+
+    /// Snapshot of an already built dependency graph
+    pub fn from_graph(graph: &DependencyGraph) -> Self {
+        let mut modules: BTreeMap<String, ModuleSnapshot> = BTreeMap::new();
+        for file in graph.files() {
+            let module = modules.entry(graph.module_of(file)).or_default();
+            module.files += 1;
+            module.lines += fs::read_to_string(file).map_or(0, |c| c.lines().count());
+            if let Some(analysis) = graph.analysis(file) {
+                let file_name = file.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+                module.exports.extend(
+                    analysis
+                        .exports
+                        .iter()
+                        .filter(|name| analysis.export_visibility(name) == Visibility::Public)
+                        .map(|name| format!("{}::{}", file_name, name)),
+                );
+            }
+        }
+
+        let base = graph.base_path();
+        let src = base.join("src");
+        let module_tree = generate_module_tree(if src.is_dir() { &src } else { base }, Path::new(""));
+
+        Self {
+            commit: None,
+            module_tree,
+            modules,
+            module_edges: graph.module_edges(),
+            cycles: graph
+                .cycles()
+                .iter()
+                .map(|cycle| cycle.iter().map(|f| graph.display_path(f)).collect())
+                .collect(),
+            module_cycles: graph.module_cycles(),
+        }
+    }
+
+    /// Snapshot of `dir` as of commit `rev`
+    ///
+    /// The files of the commit are exported into a temporary directory, so
+    /// the working tree and the index of `dir` are left alone.
+    pub fn capture_at(dir: &Path, rev: &str) -> Result<Self> {
+        let commit = resolve_commit(dir, rev)
+            .ok_or_else(|| CodeReviewError::GitError(format!("unknown revision: {}", rev)))?;
+        let mut snapshot = Self::capture_export(dir, &TreeExport::export(dir, TreeSource::Commit(&commit))?);
+        snapshot.commit = Some(commit);
+        Ok(snapshot)
+    }
+
+    /// Snapshot of the staged files of `dir`, i.e. of the commit being made
+    ///
+    /// Inside a pre-commit hook this reads the index git is committing,
+    /// including the temporary one of `git commit -a`.
+    pub fn capture_staged(dir: &Path) -> Result<Self> {
+        Ok(Self::capture_export(dir, &TreeExport::export(dir, TreeSource::Index)?))
+    }
+
+    fn capture_export(dir: &Path, export: &TreeExport) -> Self {
+        let mut snapshot = Self::capture(&export.path());
+
+        // Without src/ the tree header names the export directory; use the real name
+        if !export.path().join("src").is_dir() {
+            let name = dir
+                .canonicalize()
+                .ok()
+                .and_then(|d| d.file_name().map(|n| n.to_string_lossy().to_string()))
+                .unwrap_or_else(|| ".".to_string());
+            if let Some((_, rest)) = snapshot.module_tree.split_once('\n') {
+                snapshot.module_tree = format!("{}/\n{}", name, rest);
+            }
+        }
+        snapshot
+    }
+
+    /// Load a snapshot saved with [`ArchitectureSnapshot::save`]
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Save the snapshot as JSON
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// The snapshot as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Changes from this snapshot to `after`
+    pub fn drift_to(&self, after: &ArchitectureSnapshot, thresholds: &DriftThresholds) -> ArchitectureDrift {
+        let new_modules = after
+            .modules
+            .keys()
+            .filter(|module| !self.modules.contains_key(*module))
+            .cloned()
+            .collect();
+
+        let old_edges: BTreeSet<(&str, &str)> = self
+            .module_edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str()))
+            .collect();
+        let new_module_edges = after
+            .module_edges
+            .iter()
+            .filter(|e| !old_edges.contains(&(e.from.as_str(), e.to.as_str())))
+            .cloned()
+            .collect();
+
+        let new_exports = after
+            .modules
+            .iter()
+            .filter_map(|(name, module)| {
+                let before = self.modules.get(name).map(|m| &m.exports);
+                let added: Vec<String> = module
+                    .exports
+                    .iter()
+                    .filter(|export| !before.is_some_and(|b| b.contains(*export)))
+                    .cloned()
+                    .collect();
+                (!added.is_empty()).then(|| (name.clone(), added))
+            })
+            .collect();
+
+        let grown_modules = after
+            .modules
+            .iter()
+            .filter_map(|(name, module)| {
+                let before = self.modules.get(name).cloned().unwrap_or_default();
+                let crossed = |limit: usize, old: usize, new: usize| old <= limit && new > limit;
+                (crossed(thresholds.max_module_lines, before.lines, module.lines)
+                    || crossed(thresholds.max_module_files, before.files, module.files))
+                .then(|| ModuleGrowth {
+                    module: name.clone(),
+                    lines: (before.lines, module.lines),
+                    files: (before.files, module.files),
+                })
+            })
+            .collect();
+
+        ArchitectureDrift {
+            new_modules,
+            new_module_edges,
+            new_cycles: new_cycles(&self.cycles, &after.cycles),
+            new_module_cycles: new_cycles(&self.module_cycles, &after.module_cycles),
+            new_exports,
+            grown_modules,
+        }
+    }
+}
+
+impl ArchitectureDrift {
+    /// Changes from the commit `rev` to the working tree of `dir`
+    pub fn since(dir: &Path, rev: &str, thresholds: &DriftThresholds) -> Result<Self> {
+        let before = ArchitectureSnapshot::capture_at(dir, rev)?;
+        Ok(before.drift_to(&ArchitectureSnapshot::capture(dir), thresholds))
+    }
+
+    /// Changes from HEAD to the staged files of `dir`
+    ///
+    /// Unlike [`ArchitectureDrift::since`] this ignores unstaged edits, so a
+    /// pre-commit hook sees exactly the drift of the commit being made.
+    pub fn staged(dir: &Path, thresholds: &DriftThresholds) -> Result<Self> {
+        let before = ArchitectureSnapshot::capture_at(dir, "HEAD")?;
+        Ok(before.drift_to(&ArchitectureSnapshot::capture_staged(dir)?, thresholds))
+    }
+
+    /// Check whether nothing drifted
+    pub fn is_empty(&self) -> bool {
+        self.new_modules.is_empty()
+            && self.new_module_edges.is_empty()
+            && self.new_cycles.is_empty()
+            && self.new_module_cycles.is_empty()
+            && self.new_exports.is_empty()
+            && self.grown_modules.is_empty()
+    }
+
+    /// Format the drift as a prompt-friendly string
+    ///
+    /// Delegates to [`crate::prompt::format_architecture_drift`].
+    pub fn to_prompt_string(&self) -> String {
+        crate::prompt::format_architecture_drift(self)
+    }
+}
+
+/// Cycles in `after` whose members did not already form a cycle in `before`
+fn new_cycles(before: &[Vec<String>], after: &[Vec<String>]) -> Vec<Vec<String>> {
+    let members = |cycle: &Vec<String>| cycle.iter().cloned().collect::<BTreeSet<String>>();
+    let old: BTreeSet<BTreeSet<String>> = before.iter().map(members).collect();
+    after
+        .iter()
+        .filter(|cycle| !old.contains(&members(cycle)))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::tempdir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn module(lines: usize, files: usize, exports: &[&str]) -> ModuleSnapshot {
+        ModuleSnapshot {
+            files,
+            lines,
+            exports: exports.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_drift_between_snapshots() {
+        let before = ArchitectureSnapshot {
+            modules: BTreeMap::from([
+                ("src".to_string(), module(100, 2, &["lib.rs::run"])),
+                ("src/api".to_string(), module(2900, 3, &[])),
+            ]),
+            module_edges: vec![ModuleEdge {
+                from: "src".into(),
+                to: "src/api".into(),
+                imports: 1,
+            }],
+            cycles: vec![vec!["src/a.rs".into(), "src/b.rs".into()]],
+            ..ArchitectureSnapshot::default()
+        };
+        let after = ArchitectureSnapshot {
+            modules: BTreeMap::from([
+                ("src".to_string(), module(120, 2, &["lib.rs::run", "lib.rs::stop"])),
+                ("src/api".to_string(), module(3100, 3, &[])),
+                ("src/db".to_string(), module(50, 1, &["conn.rs::open"])),
+            ]),
+            module_edges: vec![
+                ModuleEdge {
+                    from: "src".into(),
+                    to: "src/api".into(),
+                    imports: 2,
+                },
+                ModuleEdge {
+                    from: "src/api".into(),
+                    to: "src/db".into(),
+                    imports: 1,
+                },
+            ],
+            cycles: vec![
+                vec!["src/a.rs".into(), "src/b.rs".into()],
+                vec!["src/c.rs".into(), "src/d.rs".into()],
+            ],
+            ..ArchitectureSnapshot::default()
+        };
+
+        let drift = before.drift_to(&after, &DriftThresholds::default());
+        assert_eq!(drift.new_modules, vec!["src/db"]);
+        assert_eq!(drift.new_module_edges.len(), 1);
+        assert_eq!(drift.new_module_edges[0].to, "src/db");
+        assert_eq!(drift.new_cycles, vec![vec!["src/c.rs".to_string(), "src/d.rs".to_string()]]);
+        assert_eq!(drift.new_exports["src"], vec!["lib.rs::stop"]);
+        assert_eq!(drift.new_exports["src/db"], vec!["conn.rs::open"]);
+        assert_eq!(drift.grown_modules.len(), 1);
+        assert_eq!(drift.grown_modules[0].module, "src/api");
+        assert_eq!(drift.grown_modules[0].lines, (2900, 3100));
+
+        let prompt = drift.to_prompt_string();
+        assert!(prompt.contains("- src/api → src/db (1件)"));
+        assert!(prompt.contains("新しい循環依存: src/c.rs → src/d.rs"));
+        assert!(prompt.contains("肥大化したモジュール: src/api (2900→3100行, 3→3ファイル)"));
+
+        assert!(after.drift_to(&after, &DriftThresholds::default()).is_empty());
+    }
+
+    #[test]
+    fn test_capture_at_commit() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        git(root, &["init", "--quiet"]);
+        write(root, "src/lib.rs", "pub mod api;\npub mod db;\n");
+        write(root, "src/api/mod.rs", "pub fn handle() {}\n");
+        write(root, "src/db/mod.rs", "pub fn open() {}\n");
+        git(root, &["add", "-A"]);
+        git(root, &["commit", "--quiet", "-m", "initial"]);
+
+        // Working tree: api now depends on db, and db exports more
+        write(root, "src/api/mod.rs", "use crate::db::open;\npub fn handle() {}\n");
+        write(root, "src/db/mod.rs", "pub fn open() {}\npub fn close() {}\n");
+
+        let before = ArchitectureSnapshot::capture_at(root, "HEAD").unwrap();
+        assert!(before.commit.is_some());
+        assert!(before.module_edges.is_empty());
+        assert!(before.module_tree.starts_with("src/"));
+
+        let drift = ArchitectureDrift::since(root, "HEAD", &DriftThresholds::default()).unwrap();
+        assert_eq!(drift.new_module_edges.len(), 1);
+        assert_eq!(drift.new_module_edges[0].from, "src/api");
+        assert_eq!(drift.new_module_edges[0].to, "src/db");
+        assert_eq!(drift.new_exports["src/db"], vec!["mod.rs::close"]);
+
+        // No worktree was added
+        let worktrees = Command::new("git")
+            .args(["worktree", "list"])
+            .current_dir(root)
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&worktrees.stdout).lines().count(), 1);
+    }
+
+    #[test]
+    fn test_staged_drift_ignores_unstaged_edits() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        git(root, &["init", "--quiet"]);
+        write(root, "svc/src/lib.rs", "pub mod api;\npub mod db;\n");
+        write(root, "svc/src/api/mod.rs", "pub fn handle() {}\n");
+        write(root, "svc/src/db/mod.rs", "pub fn open() {}\n");
+        git(root, &["add", "-A"]);
+        git(root, &["commit", "--quiet", "-m", "initial"]);
+
+        // Staged: api depends on db. Unstaged: db exports more
+        write(root, "svc/src/api/mod.rs", "use crate::db::open;\npub fn handle() {}\n");
+        git(root, &["add", "-A"]);
+        write(root, "svc/src/db/mod.rs", "pub fn open() {}\npub fn close() {}\n");
+
+        let svc = root.join("svc");
+        let drift = ArchitectureDrift::staged(&svc, &DriftThresholds::default()).unwrap();
+        assert_eq!(drift.new_module_edges.len(), 1);
+        assert_eq!(drift.new_module_edges[0].from, "src/api");
+        assert!(drift.new_exports.is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_json_roundtrip() {
+        let dir = tempdir().unwrap();
+        let snapshot = ArchitectureSnapshot {
+            commit: Some("abc123".into()),
+            modules: BTreeMap::from([("src".to_string(), module(10, 1, &["lib.rs::run"]))]),
+            ..ArchitectureSnapshot::default()
+        };
+        let path = dir.path().join("snapshot.json");
+        snapshot.save(&path).unwrap();
+        assert_eq!(ArchitectureSnapshot::load(&path).unwrap(), snapshot);
+    }
+}
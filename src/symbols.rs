@@ -0,0 +1,376 @@
+//! Symbol-level context for code review
+//!
+//! `ProjectContext` tells the reviewer which files a file depends on. This
+//! module goes one level deeper: it collects the definitions the changed
+//! lines actually use and the places that call the public functions being
+//! changed, so the contracts a change affects fit in the prompt without
+//! including whole files.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::analyzer::find_importers_with_resolver;
+use crate::cache::analyze_cached;
+use crate::graph::DependencyGraph;
+use crate::parser::symbols::{parse_symbols, SourceSymbols, Symbol};
+use crate::prompt::format_symbol_context;
+use crate::resolver::ImportResolver;
+
+/// Default prompt budget for symbol context, in characters
+pub const DEFAULT_SYMBOL_BUDGET: usize = 4000;
+
+/// Maximum number of call sites listed per changed function
+const MAX_CALL_SITES_PER_FUNCTION: usize = 5;
+
+/// A definition referenced by the code under review
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolDefinition {
+    /// Defined name
+    pub name: String,
+    /// Kind of definition (`fn`, `struct`, `class`, ...)
+    pub kind: String,
+    /// File containing the definition (relative to the project root)
+    pub file: String,
+    /// First line of the definition (1-based)
+    pub line: usize,
+    /// Signature for functions, definition text for types
+    pub text: String,
+}
+
+/// A call of a changed public function from another file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallSite {
+    /// Called function
+    pub function: String,
+    /// Calling file (relative to the project root)
+    pub file: String,
+    /// Line of the call (1-based)
+    pub line: usize,
+    /// Source line of the call, trimmed
+    pub code: String,
+}
+
+/// Definitions and call sites relevant to a change
+#[derive(Debug, Clone, Default)]
+pub struct SymbolContext {
+    /// Definitions referenced from the changed lines, in order of first use
+    pub definitions: Vec<SymbolDefinition>,
+    /// Calls of changed public functions from other files
+    pub call_sites: Vec<CallSite>,
+    /// Definitions and call sites left out to stay within the budget
+    pub omitted: usize,
+}
+
+impl SymbolContext {
+    /// Check if nothing was found
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty() && self.call_sites.is_empty()
+    }
+
+    /// Format for inclusion in a review prompt
+    pub fn to_prompt_string(&self) -> String {
+        format_symbol_context(self)
+    }
+}
+
+/// Lines of the new file touched by a unified diff (1-based)
+///
+/// Added lines count as changed; a hunk that only deletes marks the line
+/// the deletion happened at, so the enclosing definition counts as changed.
+pub fn changed_lines(diff: &str) -> BTreeSet<usize> {
+    let mut lines = BTreeSet::new();
+    let mut current: Option<usize> = None;
+    for line in diff.lines() {
+        // A new file section: its `---`/`+++` headers are not changes
+        if line.starts_with("diff --git ") {
+            current = None;
+            continue;
+        }
+        if let Some(header) = line.strip_prefix("@@ ") {
+            current = parse_hunk_start(header);
+            continue;
+        }
+        let Some(ref mut next) = current else {
+            continue;
+        };
+        if line.starts_with('+') {
+            lines.insert(*next);
+            *next += 1;
+        } else if line.starts_with('-') {
+            lines.insert((*next).max(1));
+        } else if line.starts_with(' ') || line.is_empty() {
+            *next += 1;
+        }
+    }
+    lines
+}
+
+/// New-file start line of a hunk header body (`-a,b +c,d @@ ...`)
+fn parse_hunk_start(header: &str) -> Option<usize> {
+    let new_range = header.split_whitespace().find(|part| part.starts_with('+'))?;
+    new_range[1..].split(',').next()?.parse().ok()
+}
+
+/// Gather symbol context for a file, resolving imports with `resolver`
+///
+/// `diff` is the file's unified diff; without one the whole file counts as
+/// changed and no call sites are collected. `budget` caps the characters
+/// of definition text and call-site code included.
+pub fn gather_symbol_context(
+    file_path: &Path,
+    base_path: &Path,
+    diff: Option<&str>,
+    resolver: &ImportResolver,
+    budget: usize,
+) -> SymbolContext {
+    let dependencies: Vec<PathBuf> = analyze_cached(file_path)
+        .map(|analysis| {
+            analysis
+                .imports
+                .iter()
+                .flat_map(|import| resolver.resolve(import, file_path))
+                .collect()
+        })
+        .unwrap_or_default();
+    let importers: Vec<PathBuf> = find_importers_with_resolver(file_path, base_path, resolver)
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+    collect(file_path, base_path, diff, &dependencies, &importers, budget)
+}
+
+/// Gather symbol context for a file using a prebuilt dependency graph
+pub fn gather_symbol_context_with_graph(
+    file_path: &Path,
+    diff: Option<&str>,
+    graph: &DependencyGraph,
+    budget: usize,
+) -> SymbolContext {
+    collect(
+        file_path,
+        graph.base_path(),
+        diff,
+        &graph.dependencies(file_path),
+        &graph.importers(file_path),
+        budget,
+    )
+}
+
+fn collect(
+    file_path: &Path,
+    base_path: &Path,
+    diff: Option<&str>,
+    dependencies: &[PathBuf],
+    importers: &[PathBuf],
+    budget: usize,
+) -> SymbolContext {
+    let Some(own) = read_symbols(file_path) else {
+        return SymbolContext::default();
+    };
+    let changed = diff.map(changed_lines).filter(|lines| !lines.is_empty());
+
+    // Names used by the changed lines, in order of first use
+    let mut wanted: Vec<&str> = Vec::new();
+    for reference in &own.references {
+        let in_change = changed.as_ref().is_none_or(|lines| lines.contains(&reference.line));
+        if in_change && !wanted.contains(&reference.name.as_str()) {
+            wanted.push(&reference.name);
+        }
+    }
+
+    let mut definitions: Vec<(usize, SymbolDefinition)> = Vec::new();
+    let mut add_definitions = |file: &Path, symbols: &SourceSymbols, skip: &dyn Fn(&Symbol) -> bool| {
+        for symbol in &symbols.definitions {
+            let Some(rank) = wanted.iter().position(|name| *name == symbol.name) else {
+                continue;
+            };
+            if !skip(symbol) {
+                definitions.push((rank, definition(symbol, file, base_path)));
+            }
+        }
+    };
+    // Definitions elsewhere in the same file, unless they are the change itself
+    if let Some(ref lines) = changed {
+        add_definitions(file_path, &own, &|symbol| symbol.overlaps(lines));
+    }
+    let mut seen: HashSet<&Path> = HashSet::from([file_path]);
+    for dependency in dependencies {
+        if !seen.insert(dependency.as_path()) {
+            continue;
+        }
+        if let Some(symbols) = read_symbols(dependency) {
+            add_definitions(dependency, &symbols, &|_| false);
+        }
+    }
+    definitions.sort_by_key(|(rank, _)| *rank);
+
+    // Callers of the public functions the change touches
+    let mut call_sites = Vec::new();
+    if let Some(ref lines) = changed {
+        let changed_functions: Vec<&str> = own
+            .definitions
+            .iter()
+            // Any visibility: `pub(crate)` functions have callers in other files too
+            .filter(|symbol| symbol.is_function() && symbol.visibility.is_some() && symbol.overlaps(lines))
+            .map(|symbol| symbol.name.as_str())
+            .collect();
+        if !changed_functions.is_empty() {
+            call_sites = find_call_sites(&changed_functions, file_path, importers, base_path);
+        }
+    }
+
+    let mut context = SymbolContext::default();
+    let mut remaining = budget;
+    for (_, definition) in definitions {
+        if definition.text.len() <= remaining {
+            remaining -= definition.text.len();
+            context.definitions.push(definition);
+        } else {
+            context.omitted += 1;
+        }
+    }
+    for call_site in call_sites {
+        if call_site.code.len() <= remaining {
+            remaining -= call_site.code.len();
+            context.call_sites.push(call_site);
+        } else {
+            context.omitted += 1;
+        }
+    }
+    context
+}
+
+/// Calls of `functions` from `importers`, capped per function
+fn find_call_sites(functions: &[&str], file_path: &Path, importers: &[PathBuf], base_path: &Path) -> Vec<CallSite> {
+    let mut call_sites: Vec<CallSite> = Vec::new();
+    for importer in importers {
+        if importer == file_path {
+            continue;
+        }
+        let Ok(source) = fs::read_to_string(importer) else {
+            continue;
+        };
+        let Some(symbols) = parse_symbols(importer, &source) else {
+            continue;
+        };
+        let lines: Vec<&str> = source.lines().collect();
+        for call in &symbols.calls {
+            if !functions.contains(&call.name.as_str()) {
+                continue;
+            }
+            let listed = call_sites.iter().filter(|site| site.function == call.name).count();
+            if listed >= MAX_CALL_SITES_PER_FUNCTION {
+                continue;
+            }
+            call_sites.push(CallSite {
+                function: call.name.clone(),
+                file: display_path(importer, base_path),
+                line: call.line,
+                code: lines.get(call.line - 1).map(|l| l.trim().to_string()).unwrap_or_default(),
+            });
+        }
+    }
+    call_sites
+}
+
+fn read_symbols(path: &Path) -> Option<SourceSymbols> {
+    let source = fs::read_to_string(path).ok()?;
+    parse_symbols(path, &source)
+}
+
+fn definition(symbol: &Symbol, file: &Path, base_path: &Path) -> SymbolDefinition {
+    SymbolDefinition {
+        name: symbol.name.clone(),
+        kind: symbol.kind.to_string(),
+        file: display_path(file, base_path),
+        line: symbol.start_line,
+        text: symbol.text.clone(),
+    }
+}
+
+fn display_path(path: &Path, base_path: &Path) -> String {
+    path.strip_prefix(base_path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "lang-rust")]
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_changed_lines() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -3,4 +3,5 @@ fn main() {
+ let a = 1;
+-let b = 2;
++let b = 3;
++let c = 4;
+ let d = 5;
+@@ -20,2 +21,1 @@
+ keep();
+-drop();
+";
+        let lines: Vec<usize> = changed_lines(diff).into_iter().collect();
+        assert_eq!(lines, vec![4, 5, 22]);
+        assert!(changed_lines("no hunks here").is_empty());
+
+        // Headers of a following file section are not changed lines
+        let two_files = format!("{}diff --git a/src/b.rs b/src/b.rs\n--- a/src/b.rs\n+++ b/src/b.rs\n", diff);
+        let lines: Vec<usize> = changed_lines(&two_files).into_iter().collect();
+        assert_eq!(lines, vec![4, 5, 22]);
+    }
+
+    #[test]
+    #[cfg(feature = "lang-rust")]
+    fn test_gather_symbol_context() {
+        let dir = tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("lib.rs"), "mod config;\nmod app;\n").unwrap();
+        fs::write(
+            src.join("config.rs"),
+            "pub struct Config {\n    pub name: String,\n}\n\npub fn default_name() -> String {\n    String::from(\"app\")\n}\n",
+        )
+        .unwrap();
+        let store = "use crate::config::{default_name, Config};\n\npub fn load(path: &str) -> Config {\n    Config { name: path.to_string() }\n}\n\npub fn fallback() -> Config {\n    Config { name: default_name() }\n}\n";
+        fs::write(src.join("store.rs"), store).unwrap();
+        fs::write(
+            src.join("app.rs"),
+            "use crate::store::load;\n\nfn run() {\n    let config = load(\"x\");\n}\n",
+        )
+        .unwrap();
+
+        let resolver = ImportResolver::new(dir.path());
+        let diff = "@@ -3,2 +3,2 @@\n-pub fn load(path: &str) -> Config {\n-    Config { name: path.into() }\n+pub fn load(path: &str) -> Config {\n+    Config { name: path.to_string() }\n";
+        let context = gather_symbol_context(&src.join("store.rs"), dir.path(), Some(diff), &resolver, 4000);
+
+        let names: Vec<&str> = context.definitions.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["Config"]);
+        assert_eq!(context.definitions[0].file, "src/config.rs");
+        assert!(context.definitions[0].text.contains("pub name: String"));
+
+        assert_eq!(context.call_sites.len(), 1);
+        let call = &context.call_sites[0];
+        assert_eq!((call.function.as_str(), call.file.as_str(), call.line), ("load", "src/app.rs", 4));
+        assert_eq!(call.code, "let config = load(\"x\");");
+
+        // Without a diff every reference counts, but callers are not listed
+        let whole = gather_symbol_context(&src.join("store.rs"), dir.path(), None, &resolver, 4000);
+        let names: Vec<&str> = whole.definitions.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["default_name", "Config"]);
+        assert!(whole.call_sites.is_empty());
+
+        // A tight budget drops what does not fit
+        let tight = gather_symbol_context(&src.join("store.rs"), dir.path(), Some(diff), &resolver, 10);
+        assert!(tight.definitions.is_empty());
+        assert_eq!(tight.omitted, 2);
+    }
+}
@@ -8,18 +8,62 @@ use std::path::{Path, PathBuf};
 /// Source code extensions (without dot)
 pub const SOURCE_EXTENSIONS: &[&str] = &[
     "rs", "ts", "tsx", "js", "jsx", "py", "go", "java", "cpp", "c", "h", "hpp", "cs",
-    "rb", "swift", "kt",
+    "rb", "swift", "kt", "zig",
 ];
 
 /// Config file extensions (without dot)
 pub const CONFIG_EXTENSIONS: &[&str] = &["json", "toml", "yaml", "yml"];
 
 /// Directories to skip during traversal
-const SKIP_DIRS: &[&str] = &["target", "node_modules", "__pycache__"];
+const SKIP_DIRS: &[&str] = &["target", "node_modules", "__pycache__", "zig-cache", "zig-out"];
+
+/// Per-user data directory (`~/.ai-code-review`) for usage records and caches
+pub(crate) fn data_dir() -> PathBuf {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join(".ai-code-review")
+}
+
+/// Write a file atomically: write a temporary file next to it, then rename
+///
+/// Readers see either the old or the new content, never a partial write.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".tmp.{}", std::process::id()));
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+/// Take an exclusive lock on `<path>.lock`, held until the file is dropped
+///
+/// Serializes read-modify-write cycles on `path` across processes.
+pub(crate) fn lock_exclusive(path: &Path) -> std::io::Result<fs::File> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(PathBuf::from(lock_path))?;
+    file.lock()?;
+    Ok(file)
+}
 
 /// Check if a directory should be skipped during traversal
 ///
-/// Skips hidden directories (starting with '.'), target, node_modules, __pycache__
+/// Skips hidden directories (starting with '.'), target, node_modules, __pycache__,
+/// and Zig build output (zig-cache, zig-out)
 ///
 /// # Arguments
 /// * `name` - The directory name to check
@@ -118,6 +162,8 @@ mod tests {
         assert!(should_skip_dir("target"));
         assert!(should_skip_dir("node_modules"));
         assert!(should_skip_dir("__pycache__"));
+        assert!(should_skip_dir("zig-cache"));
+        assert!(should_skip_dir("zig-out"));
         assert!(!should_skip_dir("src"));
         assert!(!should_skip_dir("lib"));
     }
@@ -128,6 +174,7 @@ mod tests {
         assert!(is_source_file(Path::new("app.tsx")));
         assert!(is_source_file(Path::new("script.py")));
         assert!(is_source_file(Path::new("/path/to/file.go")));
+        assert!(is_source_file(Path::new("src/codegen/emit.zig")));
         assert!(!is_source_file(Path::new("Cargo.toml")));
         assert!(!is_source_file(Path::new("README.md")));
     }
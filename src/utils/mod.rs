@@ -3,3 +3,4 @@
 //! This module contains shared utilities used across the crate.
 
 pub mod fs;
+pub mod hash;
@@ -34,3 +34,69 @@ fn test_review_file_in_git_repo() {
     // Verify path is set correctly
     assert_eq!(reviewer.path(), repo_path);
 }
+
+#[cfg(unix)]
+fn git(dir: &std::path::Path, args: &[&str]) -> std::process::Output {
+    std::process::Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_drift_from_pre_commit_hook() {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    assert!(git(root, &["init", "--quiet"]).status.success());
+    fs::create_dir_all(root.join("src/api")).unwrap();
+    fs::create_dir_all(root.join("src/db")).unwrap();
+    fs::write(root.join("src/lib.rs"), "pub mod api;\npub mod db;\n").unwrap();
+    fs::write(root.join("src/api/mod.rs"), "pub fn handle() {}\n").unwrap();
+    fs::write(root.join("src/db/mod.rs"), "pub fn open() {}\n").unwrap();
+    assert!(git(root, &["add", "-A"]).status.success());
+    assert!(git(root, &["commit", "--quiet", "-m", "initial"]).status.success());
+
+    // The hook runs the drift against the index git is about to commit
+    let out = root.join("drift.txt");
+    let hook = root.join(".git/hooks/pre-commit");
+    fs::write(
+        &hook,
+        format!(
+            "#!/bin/sh\nexec '{}' drift --target . --staged > '{}' 2>&1\n",
+            env!("CARGO_BIN_EXE_review"),
+            out.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+
+    // `commit -a` hands the hook a temporary index (index.lock)
+    fs::write(root.join("src/api/mod.rs"), "use crate::db::open;\npub fn handle() {}\n").unwrap();
+    let commit = git(root, &["commit", "--quiet", "-a", "-m", "api uses db"]);
+    assert!(commit.status.success(), "{}", String::from_utf8_lossy(&commit.stderr));
+    let drift = fs::read_to_string(&out).unwrap();
+    assert!(drift.contains("src/api → src/db"), "{}", drift);
+
+    // The commit contains the change and the index is intact
+    let status = git(root, &["status", "--porcelain", "--untracked-files=no"]);
+    assert!(String::from_utf8_lossy(&status.stdout).trim().is_empty());
+    let shown = git(root, &["show", "HEAD:src/api/mod.rs"]);
+    assert!(String::from_utf8_lossy(&shown.stdout).starts_with("use crate::db::open;"));
+
+    // A plain commit of staged changes sees them too, and unstaged edits are left out
+    fs::write(root.join("src/db/mod.rs"), "pub fn open() {}\npub fn close() {}\n").unwrap();
+    assert!(git(root, &["add", "src/db/mod.rs"]).status.success());
+    fs::write(root.join("src/lib.rs"), "pub mod api;\npub mod db;\npub mod cache;\n").unwrap();
+    let commit = git(root, &["commit", "--quiet", "-m", "db closes"]);
+    assert!(commit.status.success(), "{}", String::from_utf8_lossy(&commit.stderr));
+    let drift = fs::read_to_string(&out).unwrap();
+    assert!(drift.contains("mod.rs::close"), "{}", drift);
+    let status = git(root, &["status", "--porcelain", "--untracked-files=no"]);
+    assert_eq!(String::from_utf8_lossy(&status.stdout).trim(), "M src/lib.rs");
+}